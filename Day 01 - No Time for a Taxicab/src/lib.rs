@@ -0,0 +1,425 @@
+//! Day 1: No Time for a Taxicab, following the Easter Bunny Recruiting Document on the
+//! city grid.
+
+extern crate aoc_core;
+#[cfg(feature = "proptest")]
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate report;
+#[macro_use]
+extern crate serde_json;
+
+pub mod no_time_for_a_taxicab {
+    use ::std::fmt;
+    use ::std::str::FromStr;
+    use ::std::collections::HashSet;
+    #[cfg(feature = "proptest")]
+    use ::proptest::collection::vec;
+    #[cfg(feature = "proptest")]
+    use ::proptest::prelude::{any, any_with, Arbitrary, BoxedStrategy, Just, Strategy};
+    use ::rand::Rng;
+    use ::report::{AocError, FromInput, Input, InputError};
+    #[cfg(feature = "proptest")]
+    use ::report::Sampler;
+
+    /// Represent a position on the city grid, `y` growing northward.
+    pub use ::aoc_core::Point;
+
+    /// Used to represent a Cardinal direction.
+    #[derive(Copy, Clone, Debug)]
+    enum Direction {
+        North,
+        East,
+        South,
+        West,
+    }
+
+    impl Direction {
+        /// Returns the direction after having turned as told by `instruction`, the same direction
+        /// when it is not a turn.
+        fn turn(self, instruction: Instruction) -> Direction {
+            match (instruction, self) {
+                (Instruction::TurnRight, Direction::North) => Direction::East,
+                (Instruction::TurnRight, Direction::East)  => Direction::South,
+                (Instruction::TurnRight, Direction::South) => Direction::West,
+                (Instruction::TurnRight, Direction::West)  => Direction::North,
+                (Instruction::TurnLeft,  Direction::North) => Direction::West,
+                (Instruction::TurnLeft,  Direction::East)  => Direction::North,
+                (Instruction::TurnLeft,  Direction::South) => Direction::East,
+                (Instruction::TurnLeft,  Direction::West)  => Direction::South,
+                (Instruction::Walk(_), direction) => direction,
+            }
+        }
+
+        /// Returns the `Point` reached walking one block from `position` in this direction, `None`
+        /// when it is off the grid (i.e. out of the `i32` coordinates).
+        fn step(self, position: Point) -> Option<Point> {
+            match self {
+                Direction::North => position.y.checked_add(1).map(|y| Point { y, ..position }),
+                Direction::East  => position.x.checked_add(1).map(|x| Point { x, ..position }),
+                Direction::South => position.y.checked_sub(1).map(|y| Point { y, ..position }),
+                Direction::West  => position.x.checked_sub(1).map(|x| Point { x, ..position }),
+            }
+        }
+    }
+
+    /// Generate a new random `Point` on the city grid.
+    fn random_point() -> Point {
+        let mut rng = ::rand::thread_rng();
+        // take our random coordinates from the "small" set of i16 in order to generate a
+        // "central" random point "far from the edges" of our Point representation (i.e. i32).
+        Point::new(rng.gen::<i16>() as i32, rng.gen::<i16>() as i32)
+    }
+
+    /// Represent an instruction from the Easter Bunny Recruiting Document.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Instruction {
+        TurnRight,
+        TurnLeft,
+        Walk(u32),
+    }
+
+    impl FromStr for Instruction {
+        type Err = String;
+
+        /// Parse a string into an `Instruction`.
+        ///
+        /// Expect `s` to be either "R", "L", or a number. Walking backward (i.e. a negative
+        /// number) is not allowed.
+        fn from_str(s: &str) -> Result<Instruction, String> {
+            match s {
+                "R" => Ok(Instruction::TurnRight),
+                "L" => Ok(Instruction::TurnLeft),
+                _ => {
+                    if let Ok(stepcount) = s.parse::<u32>() {
+                        Ok(Instruction::Walk(stepcount))
+                    } else {
+                        Err(format!("{}: unrecognized walking step count", s))
+                    }
+                }
+            }
+        }
+    }
+
+    impl fmt::Display for Instruction {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Instruction::TurnRight => write!(f, "R"),
+                Instruction::TurnLeft => write!(f, "L"),
+                Instruction::Walk(stepcount) => write!(f, "{}", stepcount),
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for Instruction {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Instruction>;
+
+        /// Any instruction.
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            prop_oneof![
+                Just(Instruction::TurnRight),
+                Just(Instruction::TurnLeft),
+                any::<u32>().prop_map(Instruction::Walk),
+            ].boxed()
+        }
+    }
+
+    /// represent an Easter Bunny Recruiting Document.
+    #[derive(Debug)]
+    pub struct RecruitingDocument {
+        starting_point: Point,
+        initial_direction: Direction,
+        instructions: Vec<Instruction>,
+    }
+
+    impl FromStr for RecruitingDocument {
+        type Err = String;
+
+        /// parse a string into a `RecruitingDocument`.
+        ///
+        /// Expect `s` to look like [the puzzle input](input.txt) or examples. Only the
+        /// `instructions` are parsed, `initial_direction` is always `Direction::North` and
+        /// `starting_point` is generated randomly.
+        ///
+        /// Hand-annotated documents are accepted too: the instructions may be separated by
+        /// commas and/or whitespace and spread over many lines, and the lines starting with `#`
+        /// are comments.
+        fn from_str(s: &str) -> Result<RecruitingDocument, String> {
+            let mut instructions = Vec::new();
+            for (_, _, token) in tokens(s) {
+                if token.len() < 2 {
+                    return Err(format!("{}: unrecognized instruction", token));
+                }
+                // NOTE: this implementation is actually more permissive than documented:
+                // - token == "12"  would be parsed as (Walk(1), Walk(2))
+                // - token == "1L"  would be parsed as (Walk(1), TurnLeft)
+                // - token == "LR"  would be parsed as (TurnLeft, TurnRight)
+                // - token == "R+1" would be parsed as (TurnRight, Walk(1))
+                // NOTE: the first character may be more than one byte long.
+                let split = token.chars().next().map_or(0, char::len_utf8);
+                let direction: Instruction = token[..split].parse()?;
+                let stepcount: Instruction = token[split..].parse()?;
+                instructions.push(direction);
+                instructions.push(stepcount);
+            }
+            if instructions.is_empty() {
+                return Err("no instruction".to_string());
+            }
+            Ok(RecruitingDocument {
+                starting_point: random_point(),
+                initial_direction: Direction::North,
+                instructions,
+            })
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for RecruitingDocument {
+        /// The number of turns, 1 to 100 when `None`.
+        type Parameters = Option<usize>;
+        type Strategy = BoxedStrategy<RecruitingDocument>;
+
+        /// A document like the puzzle input, every turn followed by a walk of 1 to 199 blocks.
+        fn arbitrary_with(size: Option<usize>) -> Self::Strategy {
+            let turn = prop_oneof![Just(Instruction::TurnRight), Just(Instruction::TurnLeft)];
+            let steps = vec((turn, (1..200u32).prop_map(Instruction::Walk)), size.map_or(1..=100, |n| n..=n));
+            (any::<(i16, i16)>(), steps).prop_map(|((x, y), steps)| RecruitingDocument {
+                starting_point: Point::new(x as i32, y as i32),
+                initial_direction: Direction::North,
+                instructions: steps.into_iter().flat_map(|(turn, walk)| vec![turn, walk]).collect(),
+            }).boxed()
+        }
+    }
+
+    impl FromInput for RecruitingDocument {
+        /// Build a `RecruitingDocument` from the whole `input`, see `from_str()`.
+        fn from_input(input: &Input) -> Result<RecruitingDocument, AocError> {
+            input.text().parse().map_err(|e: String| InputError::new(e).into())
+        }
+    }
+
+    impl RecruitingDocument {
+        /// Borrow a reference to the document's `starting_point`.
+        pub fn starting_point(&self) -> &Point {
+            &self.starting_point
+        }
+
+        /// Returns the line number (starting at 1) and a description of every instruction from
+        /// `s` not strictly matching the documented format, i.e. `L` or `R` followed by a step
+        /// count. The instructions are split like `from_str()` does, which is more permissive
+        /// about the instructions themselves, see its notes.
+        pub fn strict_errors(s: &str) -> Vec<(usize, String)> {
+            tokens(s).into_iter().filter_map(|(line_no, column, token)| {
+                let mut chars = token.chars();
+                let turn = matches!(chars.next(), Some('L') | Some('R'));
+                let stepcount = chars.as_str();
+                if !turn || stepcount.is_empty() || !stepcount.bytes().all(|b| b.is_ascii_digit()) {
+                    Some((line_no, format!("column {}: {:?}: expected L or R followed by a step count", column, token)))
+                } else {
+                    None
+                }
+            }).collect()
+        }
+    }
+
+    /// Returns the instruction tokens of a document along with their line number and column
+    /// (both starting at 1): the tokens are separated by commas and/or whitespace, and the lines
+    /// starting with `#` are comments.
+    fn tokens(s: &str) -> Vec<(usize, usize, &str)> {
+        let mut tokens = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            // the column and byte offset of the token being read, if any.
+            let mut start = None;
+            let chars = line.char_indices().chain(::std::iter::once((line.len(), ',')));
+            for (column, (offset, c)) in chars.enumerate() {
+                if c == ',' || c.is_whitespace() {
+                    if let Some((column, from)) = start.take() {
+                        tokens.push((i + 1, column + 1, &line[from..offset]));
+                    }
+                } else if start.is_none() {
+                    start = Some((column, offset));
+                }
+            }
+        }
+        tokens
+    }
+
+    /// Returns a random document of `size` instructions, formatted like the puzzle input. It is
+    /// drawn from the `RecruitingDocument` strategy.
+    #[cfg(feature = "proptest")]
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let document = Sampler::new(rng.gen()).draw(&any_with::<RecruitingDocument>(Some(size)));
+        let instructions: Vec<String> = document.instructions.chunks(2)
+            .map(|pair| format!("{}{}", pair[0], pair[1]))
+            .collect();
+        instructions.join(", ") + "\n"
+    }
+
+    /// How the city grid is mapped onto geographic coordinates, see `Traveler::geojson()`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct GeoScale {
+        /// The longitude and latitude of the `Traveler` landing point, in degrees.
+        pub origin: (f64, f64),
+        /// The length of a block, in degrees. East is toward the growing longitudes and North
+        /// toward the growing latitudes.
+        pub block: f64,
+    }
+
+    impl Default for GeoScale {
+        /// The landing point at (0, 0), a block spanning a thousandth of a degree (about 110
+        /// meters near the equator).
+        fn default() -> GeoScale {
+            GeoScale { origin: (0.0, 0.0), block: 0.001 }
+        }
+    }
+
+    impl GeoScale {
+        /// Returns the GeoJSON position (i.e. longitude then latitude) of `point`, `landing`
+        /// being at `origin`.
+        fn project(&self, landing: &Point, point: Point) -> [f64; 2] {
+            let (dx, dy) = (point.x as f64 - landing.x as f64, point.y as f64 - landing.y as f64);
+            [self.origin.0 + dx * self.block, self.origin.1 + dy * self.block]
+        }
+    }
+
+    /// Represent someone able to follow the Easter Bunny Recruiting Document instructions.
+    #[derive(Debug)]
+    pub struct Traveler {
+        position: Point,
+    }
+
+    impl Traveler {
+        /// Create a new `Traveler` at the given `landing_point`.
+        pub fn airdrop_at(landing_point: Point) -> Traveler {
+            Traveler { position: landing_point }
+        }
+
+        /// Compute the final point and the first point visited twice after having completely
+        /// followed the given `RecruitingDocument` instructions.
+        ///
+        /// return a tuple `t` with two values: `t.0` is the final `Point` and `t.1` the optional
+        /// first `Point` visited twice. Returns an error if the path goes off the grid.
+        // NOTE: This method does not update the state of self, the puzzle description clearly
+        // state that we don't have the time to actually _perform_ the instructions: we only need
+        // to _find_ the Easter Bunny Headquarters position(s) in order to compute the distance(s).
+        pub fn follow(&self, document: &RecruitingDocument) -> Result<(Point, Option<Point>), String> {
+            let walk = self.walk(document)?;
+            Ok((walk.corners[walk.corners.len() - 1], walk.first_revisited))
+        }
+
+        /// Returns the corners of the path followed from the `Traveler` position: the position
+        /// itself and then the `Point` reached after each walk of the given `RecruitingDocument`
+        /// instructions. Returns an error if the path goes off the grid.
+        pub fn path(&self, document: &RecruitingDocument) -> Result<Vec<Point>, String> {
+            Ok(self.walk(document)?.corners)
+        }
+
+        /// Follow the given `RecruitingDocument` instructions block by block from the `Traveler`
+        /// position, see `follow()` and `path()`.
+        fn walk(&self, document: &RecruitingDocument) -> Result<Walk, String> {
+            let (mut position, mut direction) = (self.position, document.initial_direction);
+            let mut visited = HashSet::new();
+            visited.insert(position);
+            let mut walk = Walk { corners: vec![position], first_revisited: None };
+            for &instruction in &document.instructions {
+                direction = direction.turn(instruction);
+                if let Instruction::Walk(count) = instruction {
+                    for _ in 0..count {
+                        position = direction.step(position).ok_or_else(|| format!("{}: walked off the grid", position))?;
+                        if walk.first_revisited.is_none() && !visited.insert(position) {
+                            walk.first_revisited = Some(position);
+                        }
+                    }
+                    walk.corners.push(position);
+                }
+            }
+            Ok(walk)
+        }
+
+        /// Compute the area enclosed by the path followed from the `Traveler` position with the
+        /// [shoelace formula](https://en.wikipedia.org/wiki/Shoelace_formula) over its corners.
+        ///
+        /// Returns `None` unless the path ends where it started, see `closed_area()` to close it
+        /// explicitly. The path should not cross itself, the areas of the loops of a crossing
+        /// path being added or cancelled depending on the way they are walked.
+        pub fn enclosed_area(&self, document: &RecruitingDocument) -> Result<Option<f64>, String> {
+            let corners = self.path(document)?;
+            if corners.last() == Some(&self.position) {
+                Ok(Some(shoelace(&corners)))
+            } else {
+                Ok(None)
+            }
+        }
+
+        /// Like `enclosed_area()`, closing the path with a straight line from its end back to
+        /// the `Traveler` position.
+        pub fn closed_area(&self, document: &RecruitingDocument) -> Result<f64, String> {
+            Ok(shoelace(&self.path(document)?))
+        }
+
+        /// Returns the path followed from the `Traveler` position as a (pretty-printed) GeoJSON
+        /// `FeatureCollection`, the grid being mapped onto geographic coordinates by `scale`
+        /// (the `Traveler` position being at its origin).
+        ///
+        /// The collection holds a "path" `LineString` feature through the corners of `path()`
+        /// and, if any, a "first revisited" `Point` feature, see `follow()`. Both come from the
+        /// same walk, so that the point is always on the line. The `scale` numbers should be
+        /// finite, as GeoJSON can't represent the others. Returns an error if the path goes off
+        /// the grid.
+        pub fn geojson(&self, document: &RecruitingDocument, scale: &GeoScale) -> Result<String, String> {
+            let walk = self.walk(document)?;
+            let mut coordinates: Vec<[f64; 2]> = walk.corners.into_iter()
+                .map(|p| scale.project(&self.position, p))
+                .collect();
+            // NOTE: a LineString has at least two positions, even when nothing was walked.
+            if coordinates.len() == 1 {
+                coordinates.push(coordinates[0]);
+            }
+            let mut features = vec![json!({
+                "type": "Feature",
+                "properties": { "name": "path" },
+                "geometry": { "type": "LineString", "coordinates": coordinates },
+            })];
+            if let Some(revisited) = walk.first_revisited {
+                features.push(json!({
+                    "type": "Feature",
+                    "properties": { "name": "first revisited" },
+                    "geometry": { "type": "Point", "coordinates": scale.project(&self.position, revisited) },
+                }));
+            }
+            let collection = json!({ "type": "FeatureCollection", "features": features });
+            // NOTE: there are only objects, arrays, strings and numbers in there, so this can't
+            // fail.
+            Ok(::serde_json::to_string_pretty(&collection).unwrap())
+        }
+
+        /// Borrow a reference to the Traveler current position.
+        pub fn position(&self) -> &Point {
+            &self.position
+        }
+    }
+
+    /// A path followed by a `Traveler`, see `Traveler::walk()`.
+    struct Walk {
+        /// The starting point and then the `Point` reached after each walk instruction.
+        corners: Vec<Point>,
+        /// The first `Point` visited twice, if any.
+        first_revisited: Option<Point>,
+    }
+
+    /// Returns the area of the polygon made of the given `corners`, the last one being linked
+    /// to the first.
+    fn shoelace(corners: &[Point]) -> f64 {
+        let edges = corners.iter().zip(corners.iter().cycle().skip(1));
+        let twice: i128 = edges.map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128).sum();
+        twice.abs() as f64 / 2.0
+    }
+}
+
+pub use no_time_for_a_taxicab::*;
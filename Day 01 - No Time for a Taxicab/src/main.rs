@@ -1,238 +1,94 @@
-extern crate rand;
+extern crate aoc_cli;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+extern crate no_time_for_a_taxicab;
+#[macro_use]
+extern crate profiling;
+extern crate report;
 
-mod no_time_for_a_taxicab {
-    use ::std::str::FromStr;
-    use ::std::collections::HashSet;
-    use ::rand::Rng;
 
-    /// Used to represent a Cardinal direction.
-    #[derive(Copy, Clone, Debug)]
-    enum Direction {
-        North,
-        East,
-        South,
-        West,
-    }
+use aoc_cli::Cli;
+use no_time_for_a_taxicab::*;
+use report::{Input, InputError};
 
-    /// Represent a position on the city grid.
-    #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
-    pub struct Point {
-        x: i32,
-        y: i32,
-    }
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
 
-    impl Point {
-        /// Generate a new random `Point`.
-        pub fn random() -> Point {
-            let mut rng = ::rand::thread_rng();
-            // take our random coordinates from the "small" set of i16 in order to generate a
-            // "central" random point "far from the edges" of our Point representation (i.e. i32).
-            Point {
-                x: rng.gen::<i16>() as i32,
-                y: rng.gen::<i16>() as i32,
-            }
-        }
+// Returns the walk result, reporting its error as an input error since it depends on the
+// document (and the random starting point).
+fn walked<T>(input: &Input, result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| input.fail(Some(InputError::new(e))))
+}
 
-        /// Compute the "snake distance" from a given other `Point`.
-        /// see [Taxicab geometry](https://en.wikipedia.org/wiki/Taxicab_geometry)
-        pub fn snake_distance(&self, other: &Self) -> u32 {
-            (self.x - other.x).abs() as u32 + (self.y - other.y).abs() as u32
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    #[cfg(feature = "proptest")]
+    let cli = Cli::start_with_generator(generate_input);
+    #[cfg(not(feature = "proptest"))]
+    let cli = Cli::start();
+    // when asked, print the corners of the walked path, one per line (e.g. to draw it), and
+    // export it as GeoJSON, mapped onto geographic coordinates by --geo-origin and --geo-block.
+    let mut show_path = false;
+    let mut geojson: Option<String> = None;
+    let mut scale = GeoScale::default();
+    let mut args = cli.args().iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| report::exit(format!("{}: missing value", arg)));
+        let degrees = |s: &str| match s.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() => n,
+            _ => report::exit(format!("{}: expected a number of degrees, got {}", arg, s)),
+        };
+        match arg.as_str() {
+            "--path" => show_path = true,
+            "--geojson" => geojson = Some(value()),
+            "--geo-origin" => {
+                let origin = value();
+                scale.origin = match origin.split_once(',') {
+                    Some((lon, lat)) => (degrees(lon), degrees(lat)),
+                    None => report::exit(format!("{}: expected LONGITUDE,LATITUDE, got {}", arg, origin)),
+                };
+            },
+            "--geo-block" => scale.block = degrees(&value()),
+            _ => report::exit(format!("unrecognized argument: {}", arg)),
         }
     }
-
-    /// Represent an instruction from the Easter Bunny Recruiting Document.
-    #[derive(Copy, Clone, Debug)]
-    enum Instruction {
-        TurnRight,
-        TurnLeft,
-        Walk(i32), // NOTE: i32 allow us walk backward
-    }
-
-    impl FromStr for Instruction {
-        type Err = String;
-
-        /// Parse a string into an `Instruction`.
-        ///
-        /// Expect `s` to be either "R", "L", or a number.
-        fn from_str(s: &str) -> Result<Instruction, String> {
-            match s {
-                "R" => Ok(Instruction::TurnRight),
-                "L" => Ok(Instruction::TurnLeft),
-                _ => {
-                    if let Ok(stepcount) = s.parse::<i32>() {
-                        Ok(Instruction::Walk(stepcount))
-                    } else {
-                        Err(format!("{}: unrecognized walking step count", s))
-                    }
-                }
-            }
+    // acquire the puzzle input.
+    let input = cli.input();
+    if cli.validating() {
+        let lines: Vec<&str> = input.text().lines().collect();
+        let mut errors: Vec<InputError> = RecruitingDocument::strict_errors(input.text()).into_iter()
+            .map(|(line_no, e)| InputError::at(line_no, lines[line_no - 1], e))
+            .collect();
+        // every malformed instruction is a strict error, but the document may have none.
+        if errors.is_empty() {
+            errors = input.build_errors::<RecruitingDocument>();
         }
+        input.validate(errors);
     }
 
-    /// represent an Easter Bunny Recruiting Document.
-    #[derive(Debug)]
-    pub struct RecruitingDocument {
-        starting_point: Point,
-        initial_direction: Direction,
-        instructions: Vec<Instruction>,
-    }
-
-    impl FromStr for RecruitingDocument {
-        type Err = String;
+    // parse the document instructions, which may span many lines when annotated.
+    let document: RecruitingDocument = phase!("parse").in_scope(|| input.build());
 
-        /// parse a string into a `RecruitingDocument`.
-        ///
-        /// Expect `s` to look like [the puzzle input](input.txt) or examples. Only the
-        /// `instructions` are parsed, `initial_direction` is always `Direction::North` and
-        /// `starting_point` is generated randomly.
-        fn from_str(s: &str) -> Result<RecruitingDocument, String> {
-            let tokens: Vec<&str> = s.split(',').map(|s| s.trim()).collect();
-            let mut instructions = Vec::new();
-            for token in tokens.into_iter() {
-                if token.len() < 2 {
-                    return Err(format!("{}: unrecognized instruction", token));
-                }
-                // NOTE: this implementation is actually more permissive than documented:
-                // - token == "12"  would be parsed as (Walk(1), Walk(2))
-                // - token == "1L"  would be parsed as (Walk(1), TurnLeft)
-                // - token == "LR"  would be parsed as (TurnLeft, TurnRight)
-                // - token == "R-1" would be parsed as (TurnRight, Walk(-1))
-                // Also negative numbers for Walk(_) could be accepted.
-                let direction: Instruction = token[..1].parse()?;
-                let stepcount: Instruction = token[1..].parse()?;
-                instructions.push(direction);
-                instructions.push(stepcount);
-            }
-            Ok(RecruitingDocument {
-                starting_point: Point::random(),
-                initial_direction: Direction::North,
-                instructions: instructions,
-            })
-        }
-    }
-
-    impl RecruitingDocument {
-        /// Borrow a reference to the document's `starting_point`.
-        pub fn starting_point(&self) -> &Point {
-            &self.starting_point
-        }
+    // dive into action and compute.
+    let me = Traveler::airdrop_at(*document.starting_point());
+    let easter_bunny_hq_positions = phase!("follow").in_scope(|| walked(&input, me.follow(&document)));
+    let distance = easter_bunny_hq_positions.0.snake_distance(me.position());
+    cli.answer(1, distance, format_args!("Easter Bunny Headquarters distance: {}", distance));
+    if let Some(real_hq_position) = easter_bunny_hq_positions.1 {
+        let distance = real_hq_position.snake_distance(me.position());
+        cli.answer(2, distance, format_args!("Easter Bunny Headquarters distance (after careful read): {}", distance));
     }
-
-    /// Represent someone able to follow the Easter Bunny Recruiting Document instructions.
-    #[derive(Debug)]
-    pub struct Traveler {
-        position: Point,
+    if let Some(area) = walked(&input, me.enclosed_area(&document)) {
+        cli.note(format_args!("The walked loop encloses an area of {} blocks", area));
     }
-
-    impl Traveler {
-        /// Create a new `Traveler` at the given `landing_point`.
-        pub fn airdrop_at(landing_point: Point) -> Traveler {
-            Traveler { position: landing_point }
-        }
-
-        /// Compute the final point and the first point visited twice after having completely
-        /// followed the given `RecruitingDocument` instructions.
-        ///
-        /// return a tuple `t` with two values: `t.0` is the final `Point` and `t.1` the optional
-        /// first `Point` visited twice.
-        // NOTE: This method does not update the state of self, the puzzle description clearly
-        // state that we don't have the time to actually _perform_ the instructions: we only need
-        // to _find_ the Easter Bunny Headquarters position(s) in order to compute the distance(s).
-        pub fn follow(&self, document: &RecruitingDocument) -> (Point, Option<Point>) {
-            let (mut position, mut direction) = (self.position, document.initial_direction);
-            let mut visited = HashSet::new();
-            visited.insert(position);
-            let mut first_position_visited_twice = None;
-            for instruction in &document.instructions {
-                match *instruction {
-                    Instruction::TurnRight => {
-                        direction = match direction {
-                            Direction::North => Direction::East,
-                            Direction::East  => Direction::South,
-                            Direction::South => Direction::West,
-                            Direction::West  => Direction::North,
-                        }
-                    }
-                    Instruction::TurnLeft => {
-                        direction = match direction {
-                            Direction::North => Direction::West,
-                            Direction::East  => Direction::North,
-                            Direction::South => Direction::East,
-                            Direction::West  => Direction::South,
-                        }
-                    }
-                    Instruction::Walk(count) => {
-                        for _ in 0..count {
-                            position = match direction {
-                                Direction::North => Point { y: position.y + 1, ..position },
-                                Direction::East  => Point { x: position.x + 1, ..position },
-                                Direction::South => Point { y: position.y - 1, ..position },
-                                Direction::West  => Point { x: position.x - 1, ..position },
-                            };
-                            if first_position_visited_twice.is_none() && !visited.insert(position) {
-                                first_position_visited_twice = Some(position);
-                            }
-                        }
-                    }
-                }
-            }
-            (position, first_position_visited_twice)
-        }
-
-        /// Borrow a reference to the Traveler current position.
-        pub fn position(&self) -> &Point {
-            &self.position
+    if show_path {
+        for corner in walked(&input, me.path(&document)) {
+            cli.note(format_args!("{}", corner));
         }
     }
-}
-
-
-use no_time_for_a_taxicab::*;
-
-fn main() {
-    // acquire data from stdin, we only need the first line.
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).expect("no input given");
-
-    // parse the document instructions.
-    let document: RecruitingDocument = input.parse().expect("bad input");
-
-    // dive into action and compute.
-    let me = Traveler::airdrop_at(*document.starting_point());
-    let easter_bunny_hq_positions = me.follow(&document);
-    println!("Easter Bunny Headquarters distance: {}",
-             easter_bunny_hq_positions.0.snake_distance(me.position()));
-    if let Some(real_hq_position) = easter_bunny_hq_positions.1 {
-        println!("Easter Bunny Headquarters distance (after careful read): {}",
-                 real_hq_position.snake_distance(me.position()));
+    if let Some(path) = geojson {
+        std::fs::write(&path, walked(&input, me.geojson(&document, &scale))).unwrap_or_else(|e| report::exit(format!("{}: {}", path, e)));
     }
 }
-
-
-#[test]
-fn part1_first_example() {
-    let document: RecruitingDocument = "R2, L3".parse().unwrap();
-    let me = Traveler::airdrop_at(*document.starting_point());
-    assert_eq!(me.follow(&document).0.snake_distance(me.position()), 5);
-}
-
-#[test]
-fn part1_second_example() {
-    let document: RecruitingDocument = "R2, R2, R2".parse().unwrap();
-    let me = Traveler::airdrop_at(*document.starting_point());
-    assert_eq!(me.follow(&document).0.snake_distance(me.position()), 2);
-}
-
-#[test]
-fn part1_third_example() {
-    let document: RecruitingDocument = "R5, L5, R5, R3".parse().unwrap();
-    let me = Traveler::airdrop_at(*document.starting_point());
-    assert_eq!(me.follow(&document).0.snake_distance(me.position()), 12);
-}
-
-#[test]
-fn part2_single_example() {
-    let document: RecruitingDocument = "R8, R4, R4, R8".parse().unwrap();
-    let me = Traveler::airdrop_at(*document.starting_point());
-    assert_eq!(me.follow(&document).1.unwrap().snake_distance(&me.position()), 4);
-}
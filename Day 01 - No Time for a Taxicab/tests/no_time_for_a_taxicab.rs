@@ -0,0 +1,199 @@
+extern crate no_time_for_a_taxicab;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate report;
+extern crate serde_json;
+
+use no_time_for_a_taxicab::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use report::Input;
+
+#[test]
+fn part1_first_example() {
+    let document: RecruitingDocument = "R2, L3".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.follow(&document).unwrap().0.snake_distance(me.position()), 5);
+}
+
+#[test]
+fn part1_second_example() {
+    let document: RecruitingDocument = "R2, R2, R2".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.follow(&document).unwrap().0.snake_distance(me.position()), 2);
+}
+
+#[test]
+fn part1_third_example() {
+    let document: RecruitingDocument = "R5, L5, R5, R3".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.follow(&document).unwrap().0.snake_distance(me.position()), 12);
+}
+
+#[test]
+fn part2_single_example() {
+    let document: RecruitingDocument = "R8, R4, R4, R8".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.follow(&document).unwrap().1.unwrap().snake_distance(me.position()), 4);
+}
+
+#[test]
+fn path_corners() {
+    let document: RecruitingDocument = "R2, L3, L0, R1".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    let start = *me.position();
+    let corners: Vec<Point> = me.path(&document).unwrap();
+    assert_eq!(corners.len(), 5);
+    assert_eq!(corners[0], start);
+    assert_eq!(corners[2], corners[3]);
+    assert_eq!(corners.last(), Some(&me.follow(&document).unwrap().0));
+    assert_eq!(corners[4].snake_distance(&start), 6);
+}
+
+#[test]
+fn geojson() {
+    let document: RecruitingDocument = "R8, R4, R4, R8".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    let scale = GeoScale { origin: (6.5, 46.5), block: 0.25 };
+    let json: serde_json::Value = serde_json::from_str(&me.geojson(&document, &scale).unwrap()).unwrap();
+    assert_eq!(json["type"], "FeatureCollection");
+    assert_eq!(json["features"][0]["properties"]["name"], "path");
+    assert_eq!(json["features"][0]["geometry"], serde_json::json!({
+        "type": "LineString",
+        "coordinates": [[6.5, 46.5], [8.5, 46.5], [8.5, 45.5], [7.5, 45.5], [7.5, 47.5]],
+    }));
+    assert_eq!(json["features"][1]["properties"]["name"], "first revisited");
+    assert_eq!(json["features"][1]["geometry"], serde_json::json!({ "type": "Point", "coordinates": [7.5, 46.5] }));
+    // without any block walked, hence no revisited point.
+    let document: RecruitingDocument = "R0, L0".parse().unwrap();
+    let json: serde_json::Value = serde_json::from_str(&me.geojson(&document, &GeoScale::default()).unwrap()).unwrap();
+    assert_eq!(json["features"].as_array().unwrap().len(), 1);
+    assert_eq!(json["features"][0]["geometry"]["coordinates"], serde_json::json!([[0.0, 0.0], [0.0, 0.0], [0.0, 0.0]]));
+}
+
+#[test]
+fn enclosed_area() {
+    // a 3x2 rectangle walked clockwise, then counter-clockwise.
+    let document: RecruitingDocument = "R3, R2, R3, R2".parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.enclosed_area(&document).unwrap(), Some(6.0));
+    let document: RecruitingDocument = "L3, L2, L3, L2".parse().unwrap();
+    assert_eq!(me.enclosed_area(&document).unwrap(), Some(6.0));
+    // an L-shaped loop.
+    let document: RecruitingDocument = "R4, R2, R2, L2, R2, R4".parse().unwrap();
+    assert_eq!(me.enclosed_area(&document).unwrap(), Some(12.0));
+    // an open path, closed by a diagonal back to the start.
+    let document: RecruitingDocument = "R3, L1".parse().unwrap();
+    assert_eq!(me.enclosed_area(&document).unwrap(), None);
+    assert_eq!(me.closed_area(&document).unwrap(), 1.5);
+    assert_eq!(me.closed_area(&"R8".parse().unwrap()).unwrap(), 0.0);
+}
+
+#[test]
+fn from_input() {
+    let document: RecruitingDocument = Input::new("<test>", "R5, L5,\nR5, R3".to_string()).build();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.follow(&document).unwrap().0.snake_distance(me.position()), 12);
+    let errors = Input::new("<test>", "R5, X".to_string()).build_errors::<RecruitingDocument>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "X: unrecognized instruction");
+}
+
+proptest! {
+    #[test]
+    fn instruction_round_trip(s in "R|L|[1-9][0-9]{0,8}|0") {
+        let instruction: Instruction = s.parse().unwrap();
+        prop_assert_eq!(instruction.to_string(), s);
+    }
+
+    #[test]
+    fn arbitrary_instruction_round_trip(instruction: Instruction) {
+        prop_assert_eq!(instruction.to_string().parse::<Instruction>(), Ok(instruction));
+    }
+
+    #[test]
+    fn instruction_parse_never_panics(s in "[RL0-9+-]{0,12}|\\PC*") {
+        if let Ok(instruction) = s.parse::<Instruction>() {
+            prop_assert_eq!(instruction.to_string().parse::<Instruction>(), Ok(instruction));
+        }
+    }
+
+    #[test]
+    fn geojson_revisited_on_path(document: RecruitingDocument) {
+        let me = Traveler::airdrop_at(*document.starting_point());
+        let json: serde_json::Value = serde_json::from_str(&me.geojson(&document, &GeoScale::default()).unwrap()).unwrap();
+        let position = |p: &serde_json::Value| (p[0].as_f64().unwrap(), p[1].as_f64().unwrap());
+        let corners: Vec<(f64, f64)> = json["features"][0]["geometry"]["coordinates"].as_array().unwrap()
+            .iter().map(position).collect();
+        if let Some(point) = json["features"].get(1) {
+            let (x, y) = position(&point["geometry"]["coordinates"]);
+            // the segments are either horizontal or vertical.
+            let between = |v: f64, a: f64, b: f64| a.min(b) - 1e-9 <= v && v <= a.max(b) + 1e-9;
+            prop_assert!(corners.windows(2).any(|w| between(x, w[0].0, w[1].0) && between(y, w[0].1, w[1].1)));
+        }
+    }
+
+    #[test]
+    fn document_parse_never_panics(s in "([RL0-9]{0,4}, ){0,4}[RL0-9]{0,4}|\\PC*") {
+        let _ = s.parse::<RecruitingDocument>();
+    }
+}
+
+#[test]
+fn multibyte_instruction() {
+    assert!("R2, é2".parse::<RecruitingDocument>().is_err());
+}
+
+#[test]
+fn annotated_document() {
+    let text = "# the part 2 example\nR8, R4,\n\n  # the way back\n  R4 R8\n";
+    let document: RecruitingDocument = text.parse().unwrap();
+    let me = Traveler::airdrop_at(*document.starting_point());
+    assert_eq!(me.follow(&document).unwrap().1.unwrap().snake_distance(me.position()), 4);
+    assert!("R8 # not a comment".parse::<RecruitingDocument>().is_err());
+    assert!("# nothing but comments\n\n".parse::<RecruitingDocument>().is_err());
+}
+
+#[test]
+fn walking_backward() {
+    assert_eq!("-3".parse::<Instruction>(), Err("-3: unrecognized walking step count".to_string()));
+    assert!("R-3, L2".parse::<RecruitingDocument>().is_err());
+}
+
+#[test]
+fn walking_off_the_grid() {
+    let document: RecruitingDocument = "R5, L1".parse().unwrap();
+    let me = Traveler::airdrop_at(Point::new(i32::MAX - 2, 0));
+    assert!(me.follow(&document).is_err());
+    assert!(me.path(&document).is_err());
+    assert!(me.geojson(&document, &GeoScale::default()).is_err());
+    let me = Traveler::airdrop_at(Point::new(i32::MAX - 5, 0));
+    assert_eq!(me.follow(&document).unwrap().0, Point::new(i32::MAX, 1));
+}
+
+#[test]
+fn strict_errors() {
+    assert!(RecruitingDocument::strict_errors("R2, L3, R12").is_empty());
+    // accepted by from_str(), but not by the strict check.
+    assert!("R2, 1L, R+1".parse::<RecruitingDocument>().is_ok());
+    assert_eq!(RecruitingDocument::strict_errors("R2, 1L, R-1, L"), vec![
+        (1, "column 5: \"1L\": expected L or R followed by a step count".to_string()),
+        (1, "column 9: \"R-1\": expected L or R followed by a step count".to_string()),
+        (1, "column 14: \"L\": expected L or R followed by a step count".to_string()),
+    ]);
+    // annotated documents are split like by from_str(), the errors being on their own line.
+    assert!(RecruitingDocument::strict_errors("# the part 2 example\nR8 R4,\n\n  R4 R8\n").is_empty());
+    assert_eq!(RecruitingDocument::strict_errors("R8, R4\n# R4\n  R4 8R,\tL3"), vec![
+        (3, "column 6: \"8R\": expected L or R followed by a step count".to_string()),
+    ]);
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 100);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 100));
+    let line = input.trim_end();
+    assert_eq!(line.split(", ").count(), 100);
+    assert!(RecruitingDocument::strict_errors(line).is_empty());
+}
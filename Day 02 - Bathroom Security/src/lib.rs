@@ -0,0 +1,545 @@
+//! Day 2: Bathroom Security, guessing the bathroom code by following the instructions on
+//! the expected and actual keypads.
+//!
+//! Walking the keypads only needs `core` and `alloc`: without the default `std` feature the
+//! crate is `#![no_std]` and only provides the `keypad` module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+extern crate aoc_core;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "std")]
+extern crate proptest;
+#[cfg(feature = "std")]
+extern crate rand;
+#[cfg(feature = "std")]
+extern crate report;
+
+pub mod keypad {
+    use ::alloc::collections::{BTreeMap, VecDeque};
+    use ::alloc::format;
+    use ::alloc::string::String;
+    use ::alloc::vec::Vec;
+    use ::aoc_core::Point;
+    use ::core::fmt::{self, Display};
+    use ::core::ops::{Deref, DerefMut};
+    use ::core::str::FromStr;
+
+    /// Represent a direction on they keypad.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Direction {
+        Up,
+        Right,
+        Down,
+        Left,
+    }
+
+    impl Direction {
+        /// Every `Direction`, in the order the shortest moves prefer them.
+        const ALL: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+    }
+
+    impl Display for Direction {
+        /// Format the `Direction` like the documents instructions, e.g. "U" for `Direction::Up`.
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let c = match *self {
+                Direction::Up => 'U',
+                Direction::Right => 'R',
+                Direction::Down => 'D',
+                Direction::Left => 'L',
+            };
+            write!(f, "{}", c)
+        }
+    }
+
+    // NOTE: don't impl From<char> because it can not fail, TryFrom not ready yet.
+    impl FromStr for Direction {
+        type Err = String;
+
+        /// Parse a string into a `Direction`.
+        ///
+        /// Expect `s` to be either "U", "R", "D" or "L".
+        fn from_str(s: &str) -> Result<Direction, String> {
+            match s {
+                "U" => Ok(Direction::Up),
+                "R" => Ok(Direction::Right),
+                "D" => Ok(Direction::Down),
+                "L" => Ok(Direction::Left),
+                _ => Err(format!("{}: unrecognized direction", s)),
+            }
+        }
+    }
+
+    /// Represent a keypad button, storing its "label" as `char`.
+    #[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+    pub struct KeypadButton(char);
+
+    impl Deref for KeypadButton {
+        type Target = char;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    /// Represent an input sequence of `KeypadButton`
+    ///
+    /// Newtype'd so we can to_string() and impl Deref and DerefMut to the underlying Vec.
+    #[derive(Debug)]
+    pub struct KeypadButtonSequence(Vec<KeypadButton>);
+
+    impl Deref for KeypadButtonSequence {
+        type Target = Vec<KeypadButton>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    impl DerefMut for KeypadButtonSequence {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.0
+        }
+    }
+
+    impl Display for KeypadButtonSequence {
+        /// Basically join each `KeypadButton` characters in self into a `String`.
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let s: String = self.iter().map(|&button| *button).collect();
+            write!(f, "{}", s)
+        }
+    }
+
+    /// Represent a bathroom Keypad.
+    #[derive(Debug)]
+    pub struct Keypad {
+        // NOTE: Point { x: 0, y: 0 } on the keypad is the top-left corner.
+        positions_to_buttons: BTreeMap<Point, KeypadButton>,
+        buttons_to_positions: BTreeMap<KeypadButton, Point>,
+        pressed: KeypadButtonSequence,
+    }
+
+    impl Keypad {
+        /// Returns true if the given `KeypadButton` exist in self, false otherwise.
+        fn has_button(&self, button: KeypadButton) -> bool {
+            self.buttons_to_positions.contains_key(&button)
+        }
+
+        /// Find the button near the given target KeypadButton.
+        ///
+        /// Returns None if target is not in self or there is no button in the given `Direction`
+        /// from target, `Some` button otherwise.
+        fn neighbour_of(&self, target: KeypadButton, direction: Direction) -> Option<KeypadButton> {
+            self.buttons_to_positions.get(&target).and_then(|&position| {
+                let next_position = match direction {
+                    Direction::Up => position.checked_offset(0, -1),
+                    Direction::Right => position.checked_offset(1, 0),
+                    Direction::Down => position.checked_offset(0, 1),
+                    Direction::Left => position.checked_offset(-1, 0),
+                }?;
+                self.positions_to_buttons.get(&next_position).copied()
+            })
+        }
+
+        /// Press the given `KeypadButton` on self.
+        ///
+        /// Returns true if the button could be pressed, false otherwise (the button doesn't
+        /// belongs in self).
+        fn press(&mut self, target: KeypadButton) -> bool {
+            if !self.has_button(target) {
+                false
+            } else {
+                self.pressed.push(target);
+                true
+            }
+        }
+
+        /// Borrow a reference to the `Keypad` pressed buttons.
+        pub fn input_sequence(&self) -> &KeypadButtonSequence {
+            &self.pressed
+        }
+
+        /// Returns the button labelled `label` if it exists in self, `None` otherwise.
+        pub fn button(&self, label: char) -> Option<KeypadButton> {
+            Some(KeypadButton(label)).filter(|&button| self.has_button(button))
+        }
+
+        /// Find the fewest moves leading from the `from` button to the `to` button, using a
+        /// breadth-first search over the buttons and their neighbours. The holes are walked
+        /// around, a `Finger` moving toward a hole or off the keypad staying where it is.
+        ///
+        /// Returns `None` if either button is not in self or `to` can't be reached from `from`,
+        /// `Some` moves otherwise (none when `from` and `to` are the same button).
+        pub fn shortest_moves(&self, from: KeypadButton, to: KeypadButton) -> Option<Vec<Direction>> {
+            if !self.has_button(from) || !self.has_button(to) {
+                return None;
+            }
+            // every button found so far, along with the button and move it was reached from.
+            let mut reached: BTreeMap<KeypadButton, Option<(KeypadButton, Direction)>> = BTreeMap::new();
+            reached.insert(from, None);
+            let mut queue = VecDeque::new();
+            queue.push_back(from);
+            while let Some(button) = queue.pop_front() {
+                if button == to {
+                    let mut moves = Vec::new();
+                    let mut current = to;
+                    while let Some(&Some((previous, direction))) = reached.get(&current) {
+                        moves.push(direction);
+                        current = previous;
+                    }
+                    moves.reverse();
+                    return Some(moves);
+                }
+                for &direction in &Direction::ALL {
+                    if let Some(neighbour) = self.neighbour_of(button, direction) {
+                        reached.entry(neighbour).or_insert_with(|| {
+                            queue.push_back(neighbour);
+                            Some((button, direction))
+                        });
+                    }
+                }
+            }
+            None
+        }
+
+        /// Draw self as text, one line per keypad row and three characters per button: the
+        /// `current` (i.e. hovered) button is drawn in square brackets, e.g. `[5]`, the pressed
+        /// ones in parenthesis, e.g. `(5)`, and the button both hovered and pressed in braces,
+        /// e.g. `{5}`. The holes are drawn as blanks and the trailing ones are left out.
+        pub fn render(&self, current: Option<KeypadButton>) -> String {
+            // NOTE: the keypad representation may start with blank lines or columns.
+            let positions = || self.positions_to_buttons.keys();
+            let (left, right) = (positions().map(|p| p.x).min(), positions().map(|p| p.x).max());
+            let (top, bottom) = (positions().map(|p| p.y).min(), positions().map(|p| p.y).max());
+            let (left, right, top, bottom) = match (left, right, top, bottom) {
+                (Some(left), Some(right), Some(top), Some(bottom)) => (left, right, top, bottom),
+                _ => return String::new(),
+            };
+            let mut lines: Vec<String> = Vec::new();
+            for y in top..=bottom {
+                let mut line = String::new();
+                for x in left..=right {
+                    let (left, label, right) = match self.positions_to_buttons.get(&Point::new(x, y)) {
+                        None => (' ', ' ', ' '),
+                        Some(&button) => match (Some(button) == current, self.pressed.contains(&button)) {
+                            (true, true) => ('{', *button, '}'),
+                            (true, false) => ('[', *button, ']'),
+                            (false, true) => ('(', *button, ')'),
+                            (false, false) => (' ', *button, ' '),
+                        },
+                    };
+                    line.extend([left, label, right]);
+                }
+                lines.push(String::from(line.trim_end()));
+            }
+            lines.join("\n")
+        }
+    }
+
+    impl FromStr for Keypad {
+        type Err = String;
+
+        /// Parse a string into a `Keypad`.
+        ///
+        /// Expect `s` to be a keypad grid representation where ASCII spaces (0x20) are skipped
+        /// (but not ignored) zones of the size of a button and all other characters are buttons.
+        /// All non-space characters must be unique through the representation.
+        ///
+        /// # Examples
+        ///
+        /// A classic keypad (with buttons from 1 to 9 as any sane person would picture)
+        /// representation look like this:
+        ///
+        /// ```text
+        /// 123
+        /// 456
+        /// 789
+        /// ```
+        ///
+        /// A keypad from hell resulting of hundreds of man-hours of bathroom-keypad-design
+        /// meetings representation look like this:
+        ///
+        /// ```text
+        ///   1
+        ///  234
+        /// 56789
+        ///  ABC
+        ///   D
+        /// ```
+        fn from_str(s: &str) -> Result<Keypad, String> {
+            let mut buttons_to_positions: BTreeMap<KeypadButton, Point> = BTreeMap::new();
+            let mut positions_to_buttons: BTreeMap<Point, KeypadButton> = BTreeMap::new();
+            for point in ::aoc_core::parse::char_points(s) {
+                let (position, c) = point?;
+                // skip if we're on a blank space, it is a non-button position.
+                if c == ' ' {
+                    continue;
+                }
+                let button = KeypadButton(c);
+                if buttons_to_positions.insert(button, position).is_some() {
+                    return Err(format!("{:?}: already exist", button));
+                }
+                positions_to_buttons.insert(position, button);
+            }
+            Ok(Keypad {
+                positions_to_buttons,
+                buttons_to_positions,
+                pressed: KeypadButtonSequence(Vec::new()),
+            })
+        }
+    }
+
+    /// Represent an action that can be performed on a keypad.
+    #[derive(Copy, Clone, Debug)]
+    enum KeypadAction {
+        Move(Direction),
+        Press,
+    }
+
+    /// Represent a bathroom code document found in Easter Bunny Headquarters.
+    #[derive(Debug)]
+    pub struct BathroomDocument {
+        initial_button: KeypadButton,
+        instructions: Vec<KeypadAction>,
+    }
+
+    impl Default for BathroomDocument {
+        /// An empty `BathroomDocument`. The starting button is always '5'.
+        fn default() -> BathroomDocument {
+            BathroomDocument {
+                initial_button: KeypadButton('5'),
+                instructions: Vec::new(),
+            }
+        }
+    }
+
+    impl BathroomDocument {
+        /// Append the instructions of `line` to self, pressing the button reached at its end.
+        ///
+        /// Expect `line` to match `/[URDL]*/`, self is left unchanged otherwise.
+        pub fn push_line(&mut self, line: &str) -> Result<(), String> {
+            // NOTE: loop through the line characters index and not .chars() so we can slice it,
+            // because `Direction` are parsed `FromStr`.
+            let moves = line.char_indices().enumerate()
+                .map(|(column, (i, c))| {
+                    line[i..i + c.len_utf8()].parse()
+                        .map(KeypadAction::Move)
+                        .map_err(|e| format!("column {}: {}", column + 1, e))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            self.instructions.extend(moves);
+            self.instructions.push(KeypadAction::Press);
+            Ok(())
+        }
+
+        /// Create the `BathroomDocument` typing `code` on `keypad` in the fewest moves, one line
+        /// per button.
+        ///
+        /// Returns an error if a button from `code` is not on the keypad or can't be reached.
+        pub fn typing(keypad: &Keypad, code: &str) -> Result<BathroomDocument, String> {
+            let mut document = BathroomDocument::default();
+            let mut hovering = document.initial_button;
+            for label in code.chars() {
+                let button = keypad.button(label).ok_or_else(|| format!("{:?}: no such button", label))?;
+                let moves = keypad.shortest_moves(hovering, button)
+                    .ok_or_else(|| format!("{:?}: can't be reached from {:?}", label, *hovering))?;
+                document.instructions.extend(moves.into_iter().map(KeypadAction::Move));
+                document.instructions.push(KeypadAction::Press);
+                hovering = button;
+            }
+            Ok(document)
+        }
+
+        /// Count the moves from self that were not needed to type the same code on `keypad`,
+        /// i.e. all its moves but the fewest ones between each pressed button.
+        ///
+        /// Returns `None` if the starting button is not on the keypad.
+        pub fn wasted_moves(&self, keypad: &Keypad) -> Option<usize> {
+            let mut hovering = keypad.button(*self.initial_button)?;
+            let (mut pressed, mut moves, mut needed) = (hovering, 0, 0);
+            for &action in &self.instructions {
+                match action {
+                    KeypadAction::Move(direction) => {
+                        moves += 1;
+                        hovering = keypad.neighbour_of(hovering, direction).unwrap_or(hovering);
+                    }
+                    KeypadAction::Press => {
+                        // NOTE: the hovering button was reached from the pressed one.
+                        needed += keypad.shortest_moves(pressed, hovering).map_or(0, |moves| moves.len());
+                        pressed = hovering;
+                    }
+                }
+            }
+            Some(moves - needed)
+        }
+    }
+
+    impl Display for BathroomDocument {
+        /// Format the `BathroomDocument` like the puzzle input, one line of moves per press.
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for &action in &self.instructions {
+                match action {
+                    KeypadAction::Move(direction) => write!(f, "{}", direction)?,
+                    KeypadAction::Press => writeln!(f)?,
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Represent someone (or something) able to follow the Bathroom Document instructions.
+    #[derive(Debug)]
+    pub struct Finger<'a> {
+        keypad: &'a mut Keypad,
+        hovering: KeypadButton,
+    }
+
+    impl<'a> Finger<'a> {
+        /// Create a new `Finger` hovering the given button on the provided `Keypad`.
+        ///
+        /// Returns `None` if `button` doesn't exist in the keypad, `Some` new `Finger` object
+        /// otherwise.
+        fn new(keypad: &'a mut Keypad, button: KeypadButton) -> Option<Finger<'a>> {
+            if !keypad.has_button(button) {
+                return None;
+            }
+            Some(Finger {
+                keypad,
+                hovering: button,
+            })
+        }
+
+        /// Follow every instructions from the `BathroomDocument` on the given `Keypad`.
+        pub fn follow(document: &BathroomDocument, keypad: &'a mut Keypad) {
+            Finger::follow_with(document, keypad, |_, _| ());
+        }
+
+        /// Like `follow()`, calling `each` with the keypad and the hovered button once at the
+        /// start and after every move or press (e.g. to `Keypad::render()` it).
+        pub fn follow_with<F>(document: &BathroomDocument, keypad: &'a mut Keypad, mut each: F)
+            where F: FnMut(&Keypad, KeypadButton)
+        {
+            if let Some(mut finger) = Finger::new(keypad, document.initial_button) {
+                each(finger.keypad, finger.hovering);
+                for &action in &document.instructions {
+                    finger.perform(action);
+                    each(finger.keypad, finger.hovering);
+                }
+            }
+        }
+
+        /// Perform the given `KeypadAction` on our keypad.
+        ///
+        /// Returns the hovering button after the action has resolved.
+        fn perform(&mut self, action: KeypadAction) {
+            match action {
+                KeypadAction::Press => {
+                    if !self.keypad.press(self.hovering) {
+                        // NOTE: if self.hovering is not in the keypad it is a Finger impl bug.
+                        panic!("buggy hovering button handling in Finger");
+                    }
+                }
+                KeypadAction::Move(direction) => {
+                    let neighbour = self.keypad.neighbour_of(self.hovering, direction);
+                    if let Some(button) = neighbour {
+                        self.hovering = button;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The keypad we first imagine, from the puzzle part one.
+    pub fn expected_bathroom_keypad() -> Keypad {
+        "
+123
+456
+789
+"
+            .parse()
+            .unwrap()
+    }
+
+    /// The keypad actually found in the bathroom, from the puzzle part two.
+    pub fn actual_bathroom_keypad() -> Keypad {
+        "
+  1
+ 234
+56789
+ ABC
+  D
+"
+            .parse()
+            .unwrap()
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod bathroom_security {
+    use ::std::str::FromStr;
+    use ::keypad::{BathroomDocument, Keypad};
+    use ::proptest::collection::vec;
+    use ::proptest::prelude::{any, Arbitrary, BoxedStrategy, Just, Strategy};
+    use ::rand::Rng;
+    use ::report::InputError;
+
+    impl FromStr for BathroomDocument {
+        type Err = InputError;
+
+        /// Parse a string into a `BathroomDocument`.
+        ///
+        /// Expect each line from `s` to match `/[URDL]*/`. Only the instructions are parsed, the
+        /// starting button is always '5'.
+        fn from_str(s: &str) -> Result<BathroomDocument, InputError> {
+            let mut document = BathroomDocument::default();
+            for (line_no, line) in s.lines().enumerate() {
+                document.push_line(line).map_err(|e| InputError::at(line_no + 1, line, e))?;
+            }
+            Ok(document)
+        }
+    }
+
+    impl Arbitrary for Keypad {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Keypad>;
+
+        /// Any keypad layout of at most 5x5 buttons, with holes. The buttons are labelled in
+        /// reading order starting at '1', and there are always at least five so that the
+        /// documents starting button '5' is on the keypad.
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            const LABELS: &[u8; 25] = b"123456789ABCDEFGHIJKLMNOP";
+            (1..=5usize, 1..=5usize)
+                .prop_flat_map(|(width, height)| (Just(width), vec(any::<bool>(), width * height)))
+                .prop_filter("less than five buttons", |(_, cells)| cells.iter().filter(|&&b| b).count() >= 5)
+                .prop_map(|(width, cells)| {
+                    let mut labels = LABELS.iter().map(|&label| label as char);
+                    let mut layout = String::new();
+                    for row in cells.chunks(width) {
+                        layout.extend(row.iter().map(|&button| if button { labels.next().unwrap() } else { ' ' }));
+                        layout.push('\n');
+                    }
+                    // the labels are unique, so that the layout is always valid.
+                    layout.parse().unwrap()
+                })
+                .boxed()
+        }
+    }
+
+    /// Returns a random document of `size` lines, formatted like the puzzle input. Any such
+    /// document is solvable as the moves leading off the keypad are ignored.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            let len = rng.gen_range(300, 600);
+            input.extend((0..len).map(|_| b"URDL"[rng.gen_range(0, 4)] as char));
+            input.push('\n');
+        }
+        input
+    }
+}
+
+pub use keypad::*;
+#[cfg(feature = "std")]
+pub use bathroom_security::*;
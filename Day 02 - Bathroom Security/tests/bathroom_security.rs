@@ -0,0 +1,124 @@
+extern crate bathroom_security;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+
+use bathroom_security::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn part1_example() {
+    let document: BathroomDocument = "ULL\nRRDDD\nLURDL\nUUUUD".parse().unwrap();
+    let mut keypad = expected_bathroom_keypad();
+    Finger::follow(&document, &mut keypad);
+    assert_eq!(keypad.input_sequence().to_string(), "1985".to_string());
+}
+
+#[test]
+fn part2_example() {
+    let document: BathroomDocument = "ULL\nRRDDD\nLURDL\nUUUUD".parse().unwrap();
+    let mut keypad = actual_bathroom_keypad();
+    Finger::follow(&document, &mut keypad);
+    assert_eq!(keypad.input_sequence().to_string(), "5DB3".to_string());
+}
+
+#[test]
+fn render() {
+    let mut keypad = actual_bathroom_keypad();
+    assert_eq!(keypad.render(None), "       1\n    2  3  4\n 5  6  7  8  9\n    A  B  C\n       D");
+    let document: BathroomDocument = "ULL\nRRDDD\nLURDL".parse().unwrap();
+    let mut frames = Vec::new();
+    Finger::follow_with(&document, &mut keypad, |keypad, hovering| frames.push(keypad.render(Some(hovering))));
+    // the starting frame, then one per move or press.
+    assert_eq!(frames.len(), 1 + 3 + 1 + 5 + 1 + 5 + 1);
+    assert_eq!(frames[0], "       1\n    2  3  4\n[5] 6  7  8  9\n    A  B  C\n       D");
+    assert_eq!(frames.last().unwrap(), "       1\n    2  3  4\n(5) 6  7  8  9\n    A {B} C\n      (D)");
+    let expected = expected_bathroom_keypad();
+    assert_eq!(expected.render(expected.button('9')), " 1  2  3\n 4  5  6\n 7  8 [9]");
+}
+
+#[test]
+fn push_line() {
+    let mut document = BathroomDocument::default();
+    for line in &["ULL", "RRDDD", "LURDL", "UUUUD"] {
+        document.push_line(line).unwrap();
+    }
+    assert!(document.push_line("UX").is_err());
+    let mut keypad = expected_bathroom_keypad();
+    Finger::follow(&document, &mut keypad);
+    // the rejected line was not appended.
+    assert_eq!(keypad.input_sequence().to_string(), "1985".to_string());
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 5);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 5));
+    assert_eq!(input.lines().count(), 5);
+    let document: BathroomDocument = input.parse().unwrap();
+    let mut keypad = actual_bathroom_keypad();
+    Finger::follow(&document, &mut keypad);
+    assert_eq!(keypad.input_sequence().to_string().len(), 5);
+}
+
+#[test]
+fn shortest_moves() {
+    let keypad = actual_bathroom_keypad();
+    let button = |label| keypad.button(label).unwrap();
+    assert_eq!(keypad.button('0'), None);
+    assert_eq!(keypad.shortest_moves(button('5'), button('5')), Some(vec![]));
+    // around the holes of the keypad.
+    assert_eq!(keypad.shortest_moves(button('5'), button('1')).unwrap().len(), 4);
+    assert_eq!(keypad.shortest_moves(button('1'), button('D')).unwrap().len(), 4);
+    assert_eq!(keypad.shortest_moves(button('5'), button('9')).unwrap().len(), 4);
+    let disconnected: Keypad = "1 2\n 5 ".parse().unwrap();
+    let button = |label| disconnected.button(label).unwrap();
+    assert_eq!(disconnected.shortest_moves(button('1'), button('2')), None);
+}
+
+#[test]
+fn typing_a_code() {
+    let keypad = actual_bathroom_keypad();
+    let document = BathroomDocument::typing(&keypad, "5DB3").unwrap();
+    assert_eq!(document.to_string(), "\nRRDD\nU\nUU\n");
+    assert_eq!(document.wasted_moves(&keypad), Some(0));
+    let mut typed = actual_bathroom_keypad();
+    Finger::follow(&document.to_string().parse().unwrap(), &mut typed);
+    assert_eq!(typed.input_sequence().to_string(), "5DB3");
+    assert!(BathroomDocument::typing(&keypad, "5E").is_err());
+}
+
+#[test]
+fn wasted_moves() {
+    let document: BathroomDocument = "ULL\nRRDDD\nLURDL\nUUUUD".parse().unwrap();
+    // 1985 only needs 2 + 4 + 1 + 1 moves out of the 18.
+    assert_eq!(document.wasted_moves(&expected_bathroom_keypad()), Some(10));
+    // 5DB3 only needs 0 + 4 + 1 + 2 moves.
+    assert_eq!(document.wasted_moves(&actual_bathroom_keypad()), Some(11));
+    assert_eq!(document.wasted_moves(&"123\n4 6".parse().unwrap()), None);
+}
+
+proptest! {
+    #[test]
+    fn typing_any_pressed_code(mut keypad: Keypad, lines in proptest::collection::vec("[URDL]{1,20}", 1..10)) {
+        let document: BathroomDocument = lines.join("\n").parse().unwrap();
+        Finger::follow(&document, &mut keypad);
+        let code = keypad.input_sequence().to_string();
+        let minimal = BathroomDocument::typing(&keypad, &code).unwrap();
+        let moves = |document: &BathroomDocument| document.to_string().chars().filter(|&c| c != '\n').count();
+        prop_assert_eq!(minimal.wasted_moves(&keypad), Some(0));
+        prop_assert_eq!(document.wasted_moves(&keypad), Some(moves(&document) - moves(&minimal)));
+        // typing the code again on the same keypad.
+        Finger::follow(&minimal, &mut keypad);
+        prop_assert_eq!(keypad.input_sequence().to_string(), code.repeat(2));
+    }
+
+
+    #[test]
+    fn one_button_per_line_on_any_keypad(mut keypad: Keypad, lines in proptest::collection::vec("[URDL]{1,20}", 1..10)) {
+        let document: BathroomDocument = lines.join("\n").parse().unwrap();
+        Finger::follow(&document, &mut keypad);
+        prop_assert_eq!(keypad.input_sequence().len(), lines.len());
+    }
+}
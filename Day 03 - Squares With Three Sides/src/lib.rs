@@ -0,0 +1,344 @@
+//! Day 3: Squares With Three Sides, telling apart the possible triangles specifications read
+//! by rows or by columns.
+
+#[cfg(feature = "proptest")]
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+#[cfg(feature = "proptest")]
+extern crate report;
+extern crate serde;
+extern crate serde_json;
+
+pub mod squares_with_three_sides {
+    use ::std::collections::BTreeMap;
+    use ::std::fmt;
+    use ::std::str::FromStr;
+    #[cfg(feature = "proptest")]
+    use ::proptest::collection::vec;
+    #[cfg(feature = "proptest")]
+    use ::proptest::prelude::{any_with, Arbitrary, BoxedStrategy, Strategy};
+    #[cfg(feature = "proptest")]
+    use ::rand::Rng;
+    #[cfg(feature = "proptest")]
+    use ::report::Sampler;
+    use ::serde::Serialize;
+
+    /// Represent a triangle with three sides length.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub struct Triangle(u32, u32, u32);
+
+    impl Triangle {
+        /// Create a new triangle given its sides.
+        ///
+        /// Returns `None` when the sides combination is invalid according to the puzzle
+        /// definition:
+        /// > In a valid triangle, the sum of any two sides must be larger than
+        /// > the remaining side.
+        pub fn new(sides: (u32, u32, u32)) -> Option<Triangle> {
+            // NOTE: summed as u64 so that any u32 sides are handled.
+            let xs = [u64::from(sides.0), u64::from(sides.1), u64::from(sides.2)];
+            let max = *xs.iter().max().unwrap();
+            let sum: u64 = xs.iter().sum();
+            if (sum - max) > max {
+                Some(Triangle(sides.0, sides.1, sides.2))
+            } else {
+                None
+            }
+        }
+
+        /// Returns the three sides of self, in the order they were given to `new()`.
+        pub fn sides(&self) -> (u32, u32, u32) {
+            (self.0, self.1, self.2)
+        }
+
+        /// Returns the sum of the three sides of self.
+        pub fn perimeter(&self) -> u64 {
+            u64::from(self.0) + u64::from(self.1) + u64::from(self.2)
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for Triangle {
+        /// The longest side allowed, any side goes when `None`.
+        type Parameters = Option<u32>;
+        type Strategy = BoxedStrategy<Triangle>;
+
+        /// Any valid triangle.
+        fn arbitrary_with(max_side: Option<u32>) -> Self::Strategy {
+            let max_side = max_side.unwrap_or(u32::MAX).max(1);
+            (1..=max_side, 1..=max_side).prop_flat_map(move |(a, b)| {
+                // the third side is bounded by the two others, see `new()`.
+                let end = (u64::from(a) + u64::from(b)).min(u64::from(max_side) + 1);
+                (a.abs_diff(b) + 1..=(end - 1) as u32).prop_map(move |c| Triangle(a, b, c))
+            }).boxed()
+        }
+    }
+
+    /// How the sides are laid out in a listing.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub enum Listing {
+        /// Sides separated by whitespaces, like the puzzle input.
+        Columns,
+        /// Three comma-separated sides per line, e.g. exported from a spreadsheet.
+        Csv,
+        /// Three tab-separated sides per line.
+        Tsv,
+    }
+
+    impl FromStr for Listing {
+        type Err = String;
+
+        /// Parse a string into a `Listing`, one of "columns", "csv" or "tsv".
+        fn from_str(s: &str) -> Result<Listing, String> {
+            match s {
+                "columns" => Ok(Listing::Columns),
+                "csv" => Ok(Listing::Csv),
+                "tsv" => Ok(Listing::Tsv),
+                _ => Err(format!("unknown listing: {}", s)),
+            }
+        }
+    }
+
+    impl Listing {
+        /// Guess the listing of `s` from its first non-blank line: `Tsv` when it holds a tab,
+        /// `Csv` when it holds a comma and `Columns` otherwise.
+        pub fn detect(s: &str) -> Listing {
+            match s.lines().find(|line| !line.trim().is_empty()) {
+                Some(line) if line.contains('\t') => Listing::Tsv,
+                Some(line) if line.contains(',') => Listing::Csv,
+                _ => Listing::Columns,
+            }
+        }
+
+        /// Parse the sides listed in `s` laid out as self, to be read as triangles
+        /// specifications by `triangles_by_rows()` or `triangles_by_columns()`.
+        ///
+        /// The first non-blank line of a `Csv` or `Tsv` listing may be a header naming the columns `a`, `b`
+        /// and `c` (in any order and case), the sides of each line being then given in that
+        /// order. Their blank lines are skipped and their fields may be quoted.
+        ///
+        /// Returns the line number (from 1) and description of every error otherwise.
+        pub fn parse(self, s: &str) -> Result<Vec<u32>, Vec<(usize, String)>> {
+            let mut sides = Vec::new();
+            let mut errors = Vec::new();
+            // for each side, the index of the field holding it.
+            let mut order = [0, 1, 2];
+            // whether the first non-blank line, which may be a header, is yet to be seen.
+            let mut first = true;
+            for (i, line) in s.lines().enumerate() {
+                let fields: Vec<&str> = match self {
+                    Listing::Columns => line.split_whitespace().collect(),
+                    Listing::Csv => line.split(',').map(unquote).collect(),
+                    Listing::Tsv => line.split('\t').map(unquote).collect(),
+                };
+                if self != Listing::Columns {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if first {
+                        first = false;
+                        if let Some(header) = header(&fields) {
+                            order = header;
+                            continue;
+                        }
+                    }
+                    if fields.len() != 3 {
+                        errors.push((i + 1, format!("expected 3 fields, got {}", fields.len())));
+                        continue;
+                    }
+                }
+                let mut parsed: Vec<u32> = Vec::with_capacity(fields.len());
+                for field in &fields {
+                    match field.parse() {
+                        Ok(n) => parsed.push(n),
+                        Err(e) => errors.push((i + 1, format!("{}: {}", field, e))),
+                    }
+                }
+                match self {
+                    _ if parsed.len() != fields.len() => (),
+                    Listing::Columns => sides.extend(parsed),
+                    Listing::Csv | Listing::Tsv => sides.extend(order.iter().map(|&j| parsed[j])),
+                }
+            }
+            if errors.is_empty() {
+                Ok(sides)
+            } else {
+                Err(errors)
+            }
+        }
+    }
+
+    /// Returns `field` without its surrounding whitespaces and double quotes.
+    fn unquote(field: &str) -> &str {
+        let field = field.trim();
+        field.strip_prefix('"').and_then(|f| f.strip_suffix('"')).unwrap_or(field)
+    }
+
+    /// Returns the index of the `a`, `b` and `c` columns if `fields` is a header naming them,
+    /// `None` otherwise.
+    fn header(fields: &[&str]) -> Option<[usize; 3]> {
+        if fields.len() != 3 {
+            return None;
+        }
+        let mut order = [0; 3];
+        for (side, name) in order.iter_mut().zip(&["a", "b", "c"]) {
+            *side = fields.iter().position(|field| field.eq_ignore_ascii_case(name))?;
+        }
+        Some(order)
+    }
+
+    /// Read the triangles specifications horizontally: every three sides make a triangle.
+    ///
+    /// Returns `None` for each invalid triangle, trailing sides not making a whole triangle are
+    /// ignored.
+    pub fn triangles_by_rows(sides: &[u32]) -> Vec<Option<Triangle>> {
+        rows(sides).map(Triangle::new).collect()
+    }
+
+    /// Read the triangles specifications vertically: in each group of three rows, every column
+    /// makes a triangle.
+    ///
+    /// Returns `None` for each invalid triangle, trailing rows not making a whole group are
+    /// ignored.
+    pub fn triangles_by_columns(sides: &[u32]) -> Vec<Option<Triangle>> {
+        columns(sides).map(Triangle::new).collect()
+    }
+
+    /// The triangles specifications read horizontally, see `triangles_by_rows()`.
+    fn rows(sides: &[u32]) -> impl Iterator<Item = (u32, u32, u32)> + '_ {
+        sides.chunks_exact(3).map(|s| (s[0], s[1], s[2]))
+    }
+
+    /// The triangles specifications read vertically, see `triangles_by_columns()`.
+    fn columns(sides: &[u32]) -> impl Iterator<Item = (u32, u32, u32)> + '_ {
+        sides.chunks_exact(9).flat_map(|s| (0..3).map(move |i| (s[i], s[i + 3], s[i + 6])))
+    }
+
+    /// A triangle specification given more than once, see `OrientationSummary`.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct Duplicate {
+        /// The sides, from the shortest to the longest.
+        pub sides: (u32, u32, u32),
+        pub count: usize,
+    }
+
+    /// How many valid triangles have a given perimeter, see `OrientationSummary`.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct PerimeterCount {
+        pub perimeter: u64,
+        pub count: usize,
+    }
+
+    /// The statistics of the triangles specifications read in one orientation.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct OrientationSummary {
+        pub valid: usize,
+        pub invalid: usize,
+        /// The specifications given more than once, whatever the order of their sides, sorted
+        /// by sides.
+        pub duplicates: Vec<Duplicate>,
+        /// The distribution of the valid triangles perimeters, sorted by perimeter.
+        pub perimeters: Vec<PerimeterCount>,
+    }
+
+    impl OrientationSummary {
+        /// Compute the statistics of the given triangles `specifications`.
+        fn new<I: Iterator<Item = (u32, u32, u32)>>(specifications: I) -> OrientationSummary {
+            let (mut valid, mut invalid) = (0, 0);
+            let mut specified: BTreeMap<(u32, u32, u32), usize> = BTreeMap::new();
+            let mut perimeters: BTreeMap<u64, usize> = BTreeMap::new();
+            for sides in specifications {
+                match Triangle::new(sides) {
+                    Some(triangle) => {
+                        valid += 1;
+                        *perimeters.entry(triangle.perimeter()).or_insert(0) += 1;
+                    },
+                    None => invalid += 1,
+                }
+                let mut sorted = [sides.0, sides.1, sides.2];
+                sorted.sort_unstable();
+                *specified.entry((sorted[0], sorted[1], sorted[2])).or_insert(0) += 1;
+            }
+            OrientationSummary {
+                valid,
+                invalid,
+                duplicates: specified.into_iter()
+                    .filter(|&(_, count)| count > 1)
+                    .map(|(sides, count)| Duplicate { sides, count })
+                    .collect(),
+                perimeters: perimeters.into_iter()
+                    .map(|(perimeter, count)| PerimeterCount { perimeter, count })
+                    .collect(),
+            }
+        }
+    }
+
+    impl fmt::Display for OrientationSummary {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} valid, {} invalid, {} duplicated", self.valid, self.invalid, self.duplicates.len())?;
+            if let (Some(min), Some(max)) = (self.perimeters.first(), self.perimeters.last()) {
+                // NOTE: the first most common perimeter, as max_by_key() would pick the last.
+                let mode = self.perimeters.iter().rev().max_by_key(|p| p.count).unwrap_or(min);
+                write!(f, ", perimeters from {} to {} (most common {}, for {} triangle{})",
+                       min.perimeter, max.perimeter, mode.perimeter, mode.count, if mode.count == 1 { "" } else { "s" })?;
+            }
+            for duplicate in &self.duplicates {
+                let (a, b, c) = duplicate.sides;
+                write!(f, "\n  {} {} {} given {} times", a, b, c, duplicate.count)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// The statistics of the triangles specifications read both horizontally and vertically.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct Summary {
+        pub rows: OrientationSummary,
+        pub columns: OrientationSummary,
+    }
+
+    impl Summary {
+        /// Summarize the triangles specifications from `sides`, read like `triangles_by_rows()`
+        /// and `triangles_by_columns()` do.
+        pub fn new(sides: &[u32]) -> Summary {
+            Summary {
+                rows: OrientationSummary::new(rows(sides)),
+                columns: OrientationSummary::new(columns(sides)),
+            }
+        }
+
+        /// Returns this summary as (pretty-printed) JSON.
+        pub fn to_json(&self) -> String {
+            // NOTE: there are only structs, sequences and integers in there, so this can't fail.
+            ::serde_json::to_string_pretty(self).unwrap()
+        }
+    }
+
+    impl fmt::Display for Summary {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "rows: {}\ncolumns: {}", self.rows, self.columns)
+        }
+    }
+
+    /// Returns `size` random rows of three sides, formatted like the puzzle input. `size` is
+    /// rounded up to a multiple of three so that the rows can be read vertically too.
+    ///
+    /// Like in the puzzle input the sides are below 1000, half of the rows being drawn from the
+    /// `Triangle` strategy and the others being any three sides.
+    #[cfg(feature = "proptest")]
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let rows = size.div_ceil(3) * 3;
+        let row = prop_oneof![
+            any_with::<Triangle>(Some(999)).prop_map(|triangle| triangle.sides()),
+            (1..1000u32, 1..1000u32, 1..1000u32),
+        ];
+        let mut input = String::new();
+        for (a, b, c) in Sampler::new(rng.gen()).draw(&vec(row, rows)) {
+            input.push_str(&format!("  {:3}  {:3}  {:3}\n", a, b, c));
+        }
+        input
+    }
+}
+
+pub use squares_with_three_sides::*;
@@ -1,73 +1,86 @@
-mod squares_with_three_sides {
+extern crate aoc_cli;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+extern crate report;
+extern crate squares_with_three_sides;
 
-    /// Represent a triangle with three sides length.
-    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
-    pub struct Triangle(u32, u32, u32);
 
-    impl Triangle {
-        /// Create a new triangle given its sides.
-        ///
-        /// Returns `None` when the sides combination is invalid according to the puzzle
-        /// definition:
-        /// > In a valid triangle, the sum of any two sides must be larger than
-        /// > the remaining side.
-        pub fn new(sides: (u32, u32, u32)) -> Option<Triangle> {
-            let xs = [sides.0, sides.1, sides.2];
-            let max = *xs.iter().max().unwrap();
-            let sum: u32 = xs.iter().sum();
-            if (sum - max) > max {
-                Some(Triangle(sides.0, sides.1, sides.2))
-            } else {
-                None
-            }
-        }
-    }
-}
-
-
-use std::io::Read;
+use aoc_cli::Cli;
+use report::InputError;
 use squares_with_three_sides::*;
 
-fn main() {
-    // acquire data from stdin.
-    let mut input = String::new();
-    let stdin = std::io::stdin();
-    stdin.lock().read_to_string(&mut input).expect("no input given");
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
 
-    // parse the input as a vector of u32.
-    let mut numbers: Vec<u32> = Vec::new();
-    for line in input.lines() {
-        for part in line.split_whitespace() {
-            numbers.push(part.parse().expect("bad input"));
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    #[cfg(feature = "proptest")]
+    let cli = Cli::start_with_generator(generate_input);
+    #[cfg(not(feature = "proptest"))]
+    let cli = Cli::start();
+    // parse the command line: when asked, report the statistics of the specifications (as text
+    // or JSON) instead. The listing is guessed from the input unless given.
+    let (mut summary, mut json) = (false, false);
+    let mut listing: Option<Listing> = None;
+    let mut args = cli.args().iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--summary" => summary = true,
+            "--json" => json = true,
+            "--listing" => {
+                let value = args.next().unwrap_or_else(|| report::exit(format!("{}: missing value", arg)));
+                listing = Some(value.parse().unwrap_or_else(|e| report::exit(format!("{}: {}", arg, e))));
+            },
+            _ => report::exit(format!("unrecognized argument: {}", arg)),
         }
     }
+    // acquire the puzzle input.
+    let input = cli.input();
 
-    // build vectors of triangle for each puzzle parts; rows is for part1, cols for part2.
-    let mut rows: Vec<Option<Triangle>> = Vec::new();
-    let mut cols: Vec<Option<Triangle>> = Vec::new();
-    for chunk in numbers.chunks(9) {
-        if chunk.len() != 9 {
-            panic!("bad input");
-        }
-        rows.push(Triangle::new((chunk[0], chunk[1], chunk[2])));
-        rows.push(Triangle::new((chunk[3], chunk[4], chunk[5])));
-        rows.push(Triangle::new((chunk[6], chunk[7], chunk[8])));
-        cols.push(Triangle::new((chunk[0], chunk[3], chunk[6])));
-        cols.push(Triangle::new((chunk[1], chunk[4], chunk[7])));
-        cols.push(Triangle::new((chunk[2], chunk[5], chunk[8])));
+    // parse the input as a vector of u32, reporting all the bad lines at once.
+    let listing = listing.unwrap_or_else(|| Listing::detect(input.text()));
+    let lines: Vec<&str> = input.text().lines().collect();
+    let (numbers, mut errors) = match phase!("parse").in_scope(|| listing.parse(input.text())) {
+        Ok(numbers) => (numbers, Vec::new()),
+        Err(errors) => {
+            let errors = errors.into_iter().map(|(line_no, e)| InputError::at(line_no, lines[line_no - 1], e));
+            (Vec::new(), errors.collect())
+        },
+    };
+    // both parts need complete groups of three lines with three sides each.
+    if errors.is_empty() && !numbers.len().is_multiple_of(9) {
+        errors.push(InputError::new(format!("expected a multiple of 9 sides, got {}", numbers.len())));
+    }
+    if cli.validating() {
+        input.validate(errors);
+    }
+    if !errors.is_empty() {
+        input.fail(errors);
     }
 
-    // report.
-    println!("found {} valid triangles specifications on the graphic design department walls \
-              horizontally",
-             rows.iter().filter_map(|&x| x).count());
-    println!("found {} valid triangles specifications on the graphic design department walls \
-              vertically",
-             cols.iter().filter_map(|&x| x).count());
-}
+    if summary || json {
+        let summary = phase!("summary").in_scope(|| Summary::new(&numbers));
+        if json {
+            println!("{}", summary.to_json());
+        } else {
+            println!("{}", summary);
+        }
+        return;
+    }
 
+    // build vectors of triangle for each puzzle parts; rows is for part1, cols for part2.
+    let rows = phase!("part1").in_scope(|| triangles_by_rows(&numbers));
+    let cols = phase!("part2").in_scope(|| triangles_by_columns(&numbers));
 
-#[test]
-fn part1_example() {
-    assert_eq!(Triangle::new((5, 10, 25)), None);
+    // report.
+    let valid = rows.iter().filter_map(|&x| x).count();
+    cli.answer(1, valid, format_args!("found {} valid triangles specifications on the graphic design \
+                                       department walls horizontally", valid));
+    let valid = cols.iter().filter_map(|&x| x).count();
+    cli.answer(2, valid, format_args!("found {} valid triangles specifications on the graphic design \
+                                       department walls vertically", valid));
 }
@@ -0,0 +1,114 @@
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate serde_json;
+extern crate squares_with_three_sides;
+
+use proptest::prelude::any_with;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use squares_with_three_sides::*;
+
+#[test]
+fn part1_example() {
+    assert_eq!(Triangle::new((5, 10, 25)), None);
+}
+
+#[test]
+fn huge_sides() {
+    let max = u32::MAX;
+    assert!(Triangle::new((max, max, max)).is_some());
+    assert!(Triangle::new((max, max - 1, 1)).is_none());
+    assert!(Triangle::new((max, max - 1, 2)).is_some());
+    assert!(Triangle::new((max / 2, max / 2, max)).is_none());
+    let sides = [4_000_000_000; 9];
+    assert_eq!(triangles_by_rows(&sides), [Triangle::new((4_000_000_000, 4_000_000_000, 4_000_000_000)); 3]);
+    assert_eq!(Summary::new(&sides).columns.valid, 3);
+}
+
+#[test]
+fn part2_example() {
+    let sides = [101, 301, 501, 102, 302, 502, 103, 303, 503, 201, 401, 601, 202, 402, 602, 203, 403, 603];
+    assert_eq!(triangles_by_columns(&sides).iter().filter(|t| t.is_some()).count(), 6);
+    assert_eq!(triangles_by_rows(&sides).iter().filter(|t| t.is_some()).count(), 3);
+}
+
+#[test]
+fn summary() {
+    let sides = [3, 4, 5, 5, 3, 4, 1, 1, 9, 4, 5, 3, 2, 2, 3, 1, 9, 1];
+    let summary = Summary::new(&sides);
+    assert_eq!(summary.rows, OrientationSummary {
+        valid: 4,
+        invalid: 2,
+        duplicates: vec![
+            Duplicate { sides: (1, 1, 9), count: 2 },
+            Duplicate { sides: (3, 4, 5), count: 3 },
+        ],
+        perimeters: vec![
+            PerimeterCount { perimeter: 7, count: 1 },
+            PerimeterCount { perimeter: 12, count: 3 },
+        ],
+    });
+    // the columns are (3, 5, 1), (4, 3, 1), (5, 4, 9), (4, 2, 1), (5, 2, 9), (3, 3, 1).
+    assert_eq!((summary.columns.valid, summary.columns.invalid), (1, 5));
+    assert!(summary.columns.duplicates.is_empty());
+    assert_eq!(summary.to_string(), "\
+rows: 4 valid, 2 invalid, 2 duplicated, perimeters from 7 to 12 (most common 12, for 3 triangles)
+  1 1 9 given 2 times
+  3 4 5 given 3 times
+columns: 1 valid, 5 invalid, 0 duplicated, perimeters from 7 to 7 (most common 7, for 1 triangle)");
+    let json: serde_json::Value = serde_json::from_str(&summary.to_json()).unwrap();
+    assert_eq!(json["rows"]["duplicates"][1], serde_json::json!({ "sides": [3, 4, 5], "count": 3 }));
+    assert_eq!(json["columns"]["valid"], 1);
+}
+
+#[test]
+fn listings() {
+    assert_eq!(Listing::detect("  101  301  501\n"), Listing::Columns);
+    assert_eq!(Listing::detect("a,b,c\n101,301,501\n"), Listing::Csv);
+    assert_eq!(Listing::detect("101\t301\t501\n"), Listing::Tsv);
+    assert_eq!(Listing::detect("\n  \na,b,c\n101,301,501\n"), Listing::Csv);
+    assert_eq!("tsv".parse(), Ok(Listing::Tsv));
+    assert!("xlsx".parse::<Listing>().is_err());
+
+    let columns = "  101  301  501\n  102  302  502\n";
+    assert_eq!(Listing::Columns.parse(columns), Ok(vec![101, 301, 501, 102, 302, 502]));
+    // a header naming the columns out of order, quoted fields and a blank line.
+    let csv = "C, \"A\", b\n5,3,4\n\n\"25\",5,10\n";
+    let sides = Listing::Csv.parse(csv).unwrap();
+    assert_eq!(sides, [3, 4, 5, 5, 10, 25]);
+    assert_eq!(triangles_by_rows(&sides), [Triangle::new((3, 4, 5)), None]);
+    assert_eq!(Listing::Tsv.parse("3\t4\t5\n"), Ok(vec![3, 4, 5]));
+    // a header is only expected on the first line.
+    assert_eq!(Listing::Tsv.parse("3\t4\t5\na\tb\tc\n").unwrap_err()[0].0, 2);
+    assert_eq!(Listing::Csv.parse("\n \nb,c,a\n4,5,3\n"), Ok(vec![3, 4, 5]));
+    assert_eq!(Listing::Csv.parse("a,b,c\n3,4\n3,x,5\n"), Err(vec![
+        (2, "expected 3 fields, got 2".to_string()),
+        (3, "x: invalid digit found in string".to_string()),
+    ]));
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 100);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 100));
+    let sides: Vec<u32> = input.split_whitespace().map(|side| side.parse().unwrap()).collect();
+    assert_eq!(sides.len(), 102 * 3);
+    assert_eq!(triangles_by_columns(&sides).len(), 102);
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_triangles_are_valid(triangle: Triangle) {
+        let (a, b, c) = triangle.sides();
+        prop_assert_eq!(Triangle::new((a, b, c)), Some(triangle));
+        prop_assert_eq!(triangles_by_rows(&[a, b, c]), vec![Some(triangle)]);
+    }
+
+    #[test]
+    fn bounded_arbitrary_triangles(triangle in any_with::<Triangle>(Some(9))) {
+        let (a, b, c) = triangle.sides();
+        prop_assert!(a <= 9 && b <= 9 && c <= 9);
+        prop_assert_eq!(Triangle::new((a, b, c)), Some(triangle));
+    }
+}
@@ -0,0 +1,24 @@
+//! Allocations made by the Day 4 room parsing and checking, see `memory::PeakAllocator`. Unlike
+//! the timings these are exact, so they are simply printed:
+//!
+//!     cargo bench --bench allocations
+
+extern crate memory;
+extern crate security_through_obscurity;
+
+use security_through_obscurity::*;
+
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+const INPUT: &str = include_str!("../input.txt");
+
+fn main() {
+    let lines = INPUT.lines().count();
+    let (rooms, count) = ALLOCATOR.count_allocations(|| {
+        INPUT.lines().map(|line| line.parse().unwrap()).collect::<Vec<Room>>()
+    });
+    println!("day04/parse: {} allocations for {} rooms", count, lines);
+    let (_, count) = ALLOCATOR.count_allocations(|| rooms.iter().filter(|room| room.is_real()).count());
+    println!("day04/is_real: {} allocations for {} rooms", count, lines);
+}
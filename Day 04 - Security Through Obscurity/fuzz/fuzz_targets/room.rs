@@ -0,0 +1,17 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate security_through_obscurity;
+
+use security_through_obscurity::Room;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = ::std::str::from_utf8(data) {
+        if let Ok(room) = s.parse::<Room>() {
+            let _ = (room.is_real(), room.name());
+            // whatever was accepted is displayed as a valid room.
+            let again: Room = room.to_string().parse().unwrap();
+            assert_eq!(again.to_string(), room.to_string());
+        }
+    }
+});
@@ -0,0 +1,406 @@
+//! Day 4: Security Through Obscurity, parsing the kiosk rooms, checking them and decrypting
+//! their names.
+//!
+//! The shift cipher only needs `core` and `alloc`: without the default `std` feature the crate
+//! is `#![no_std]` and only provides the `shift_cipher` module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate aoc_core;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "std")]
+extern crate rand;
+#[cfg(feature = "std")]
+extern crate report;
+
+pub mod shift_cipher {
+    use ::alloc::string::String;
+
+    /// Returns the given lowercase letter shifted `key` times forward through the alphabet,
+    /// wrapping around from `z` to `a`.
+    ///
+    /// Dashes become spaces, and any other character is replaced by `?` (i.e. 0x3f).
+    pub fn shift(ch: char, key: u32) -> char {
+        // NOTE: % is the reminder operator in Rust, no modulus operator in the stdlib.
+        let mod26 = |x| (x % 26) as u8;
+        match ch {
+            '-' => ' ',
+            'a'..='z' => char::from(b'a' + mod26(ch as u32 - 'a' as u32 + mod26(key) as u32)),
+            _ => '?', // unexpected
+        }
+    }
+
+    /// Decrypt `name` by shifting each of its characters `key` times, see `shift()`.
+    pub fn decrypt(name: &str, key: u32) -> String {
+        name.chars().map(|ch| shift(ch, key)).collect()
+    }
+
+    /// Encrypt `plaintext` so that `decrypt()` with the same `key` gives it back: spaces become
+    /// dashes and each lowercase letter is shifted `key` times backward.
+    ///
+    /// Like `shift()`, any other character is replaced by `?` (i.e. 0x3f).
+    pub fn encrypt(plaintext: &str, key: u32) -> String {
+        let backward = 26 - key % 26;
+        plaintext.chars().map(|ch| match ch {
+            ' ' => '-',
+            'a'..='z' => shift(ch, backward),
+            _ => '?', // unexpected
+        }).collect()
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod security_through_obscurity {
+    use ::aoc_core::{Event, Sink, Spanned};
+    use ::std::fmt::Display;
+    use ::std::iter::FromIterator;
+    use ::std::ops::{Deref, RangeBounds};
+    use ::std::str::FromStr;
+    use ::rand::Rng;
+    use ::report::{AocError, FromInput, Input};
+
+    // some Room parsing / filtering related helpers
+
+    /// Returns true if the given character is a dash (0x2d), false otherwise.
+    fn is_dash(ch: char) -> bool {
+        ch == '-'
+    }
+
+    /// Returns true if the given character is a letter as defined by the puzzle — i.e. matching
+    /// [a-z], false otherwise.
+    fn is_ascii_lower(ch: char) -> bool {
+        ch.is_ascii_lowercase()
+    }
+
+    /// Returns true if the given character is numeric as defined by the puzzle — i.e. matching
+    /// [0-9], false otherwise.
+    fn is_ascii_digit(ch: char) -> bool {
+        ch.is_ascii_digit()
+    }
+
+    /// Returns true if the given character is a left square bracket (0x5b), false otherwise.
+    fn is_left_square_bracket(ch: char) -> bool {
+        ch == '['
+    }
+
+    /// Returns true if the given character is a right square bracket (0x5d), false otherwise.
+    fn is_right_square_bracket(ch: char) -> bool {
+        ch == ']'
+    }
+
+    /// Represent a `Room` encrypted name, implement the encryption, decryption and checksum
+    /// logic.
+    #[derive(Debug)]
+    pub struct RoomEncryptedName(String);
+
+    impl RoomEncryptedName {
+        /// Encrypt `plaintext` using the given key, see `shift_cipher::encrypt()`.
+        ///
+        /// Returns None unless `plaintext` is made of lowercase words separated by a single
+        /// space, as the decrypted `Room` names are.
+        pub fn encrypt(plaintext: &str, key: u32) -> Option<RoomEncryptedName> {
+            let valid = |word: &str| !word.is_empty() && word.chars().all(is_ascii_lower);
+            if plaintext.split(' ').all(valid) {
+                Some(RoomEncryptedName(::shift_cipher::encrypt(plaintext, key)))
+            } else {
+                None
+            }
+        }
+
+        /// Compute the checksum according to the puzzle definition.
+        ///
+        /// > [...] the checksum is the five most common letters in the encrypted name, in order,
+        /// > with ties broken by alphabetization.
+        pub fn checksum(&self) -> impl Iterator<Item = char> {
+            // compute the frequency of each letter in our encrypted_name, indexed from `a`.
+            let mut freqs = [0usize; 26];
+            for ch in self.0.chars().filter(|&ch| is_ascii_lower(ch)) {
+                freqs[(ch as u8 - b'a') as usize] += 1;
+            }
+            // sort the letters by frequency in the descending order (i.e. the most frequent
+            // first), hence Reverse. The sort is stable and the letters start in alphabetic
+            // order, so ties are already broken.
+            let mut letters: [u8; 26] = ::std::array::from_fn(|i| b'a' + i as u8);
+            letters.sort_by_key(|&ch| ::std::cmp::Reverse(freqs[(ch - b'a') as usize]));
+
+            // NOTE: the 2015 edition `letters.into_iter()` would iterate over references.
+            IntoIterator::into_iter(letters)
+                .take_while(move |&ch| freqs[(ch - b'a') as usize] > 0) // only the letters used
+                .map(char::from)
+                .take(5) // the checksum is *the five* most common letters
+        }
+
+        /// Decrypt self using the given key.
+        ///
+        /// Returns a decrypted representation of self.
+        // NOTE: Only dash and lower letters will be decrypted, other characters will be replaced
+        // by `?` (i.e. 0x3f). The puzzle `Room` encrypted names only contains dash and lower
+        // letters but this invariant is enforced at the `Room` level.
+        pub fn decrypt(&self, key: u32) -> String {
+            ::shift_cipher::decrypt(&self.0, key)
+        }
+    }
+
+    impl Display for RoomEncryptedName {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    /// Represent a room from the list at the information kiosk
+    #[derive(Debug)]
+    pub struct Room {
+        encrypted_name: RoomEncryptedName,
+        sector_id: u32,
+        checksum: String,
+    }
+
+    impl Room {
+        /// Returns the real `Room` named `name` once decrypted in the given sector, i.e. its name
+        /// is encrypted using `sector_id` as key and its checksum is the correct one.
+        ///
+        /// Returns None unless `name` is made of lowercase words separated by a single space,
+        /// see `RoomEncryptedName::encrypt()`.
+        pub fn forge(name: &str, sector_id: u32) -> Option<Room> {
+            let encrypted_name = RoomEncryptedName::encrypt(name, sector_id)?;
+            let checksum = encrypted_name.checksum().collect();
+            Some(Room { encrypted_name, sector_id, checksum })
+        }
+
+        /// Returns true if a room is real (i.e. if its checksum is correct), false otherwise.
+        pub fn is_real(&self) -> bool {
+            self.encrypted_name.checksum().eq(self.checksum.chars())
+        }
+
+        /// Returns true if a room is not real (i.e. if its checksum is incorrect), false
+        /// otherwise.
+        pub fn is_decoy(&self) -> bool {
+            !self.is_real()
+        }
+
+        /// Explain why self is a decoy, if it is: its checksum is not the one expected from its
+        /// encrypted name.
+        pub fn explain(&self, sink: &mut dyn Sink) {
+            let expected: String = self.encrypted_name.checksum().collect();
+            if expected != self.checksum {
+                sink.emit(&Event::new("room_rejected", &[
+                    ("room", self.to_string().as_str().into()),
+                    ("sector_id", self.sector_id.into()),
+                    ("checksum", self.checksum.as_str().into()),
+                    ("expected", expected.as_str().into()),
+                ]));
+            }
+        }
+
+        /// Returns the `Room` sector_id.
+        pub fn sector_id(&self) -> u32 {
+            self.sector_id
+        }
+
+        /// Returns the decrypted `Room` name.
+        pub fn name(&self) -> String {
+            self.encrypted_name.decrypt(self.sector_id)
+        }
+    }
+
+    impl FromStr for Room {
+        type Err = Spanned<String>;
+
+        /// Parse a string into a `Room`.
+        ///
+        /// > Each room consists of an encrypted name (lowercase letters separated by dashes)
+        /// > followed by a dash, a sector ID, and a checksum in square brackets.
+        ///
+        /// # Examples
+        ///
+        /// `aaaaa-bbb-z-y-x-123[abxyz]`
+        /// `a-b-c-d-e-f-g-h-987[abcde]`
+        /// `not-a-real-room-404[oarel]`
+        /// `totally-real-room-200[decoy]`
+        // We could just /^([a-z]+(?:-[a-z]+)*)-(\d+)\[[a-z]+\]$/ but meh
+        fn from_str(s: &str) -> Result<Room, Spanned<String>> {
+            // NOTE: the error is about the unexpected character, or the end of `s`.
+            let parse_error_for = |part, x: Option<(usize, char)>| {
+                match x {
+                    Some((i, ch)) => Err(Spanned::of(s, i..i + ch.len_utf8(), format!("unexpected `{}` while parsing {}", ch, part))),
+                    None          => Err(Spanned::of(s, s.len()..s.len(), format!("parsing {} failed", part))),
+                }
+            };
+            // NOTE: only the encrypted name is copied as we go, as a stray dash is skipped. The
+            // sector ID and checksum are sliced out of `s` once their end is found.
+            let mut iter = s.char_indices().peekable();
+            let mut encrypted_name = String::with_capacity(s.len());
+            // parse the encrypted name
+            loop {
+                match iter.next() {
+                    Some((_, ch)) if is_ascii_lower(ch) => encrypted_name.push(ch),
+                    Some((i, ch)) if is_dash(ch) => match iter.peek().map(|&(_, next)| next) {
+                        // we don't accept encrypted name beginning with a dash
+                        _ if encrypted_name.is_empty() => return parse_error_for("encrypted name", Some((i, ch))),
+                        // if the next character is numeric then this dash (ch) is the delimiter
+                        // between the encrypted name and sector ID.
+                        Some(next) if is_ascii_digit(next) => break,
+                        // the encrypted name may contains dash but then we require the next
+                        // character to be a letter
+                        Some(next) if is_ascii_lower(next) => encrypted_name.push(ch),
+                        // this is unexpected, but we'll handle it at the next iteration.
+                        _ => continue,
+                    },
+                    x => return parse_error_for("encrypted name", x),
+                }
+            }
+            // parse the sector ID
+            let start = iter.peek().map_or(s.len(), |&(i, _)| i);
+            let sector_id = loop {
+                match iter.next() {
+                    Some((_, ch)) if is_ascii_digit(ch) => continue,
+                    Some((i, ch)) if is_left_square_bracket(ch) => break start..i,
+                    x => return parse_error_for("sector ID", x),
+                }
+            };
+            // parse the checksum
+            let start = iter.peek().map_or(s.len(), |&(i, _)| i);
+            let checksum = loop {
+                match iter.next() {
+                    Some((_, ch)) if is_ascii_lower(ch) => continue,
+                    Some((i, ch)) if is_right_square_bracket(ch) => break &s[start..i],
+                    x => return parse_error_for("checksum", x),
+                }
+            };
+            // we're done parsing, don't allow the input to have more characters.
+            if let Some(x) = iter.next() {
+                return parse_error_for("room", Some(x));
+            }
+            // NOTE: sector_id is made of digits only, but it may still be too large.
+            let sector_id = match s[sector_id.clone()].parse() {
+                Ok(id) => id,
+                Err(_) => return Err(Spanned::of(s, sector_id.clone(), format!("sector ID out of range: {}", &s[sector_id]))),
+            };
+            Ok(Room {
+                encrypted_name: RoomEncryptedName(encrypted_name),
+                sector_id,
+                checksum: checksum.to_string(),
+            })
+        }
+    }
+
+    impl Display for Room {
+        /// Reconstruct a string from `Room`
+        ///
+        /// see from_str() for the format.
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "{}-{}[{}]", self.encrypted_name, self.sector_id, self.checksum)
+        }
+    }
+
+    /// Represent the list of rooms at the information kiosk.
+    ///
+    /// Newtype'd so that queries can start from the list, e.g.
+    /// `rooms.in_sector_range(500..=600).real().name_contains("pole")`, see `RoomFilters`.
+    #[derive(Debug, Default)]
+    pub struct RoomList(Vec<Room>);
+
+    impl RoomList {
+        /// Returns the real rooms of self, see `RoomFilters::real()`.
+        pub fn real(&self) -> impl Iterator<Item = &Room> {
+            self.iter().real()
+        }
+
+        /// Returns the rooms of self in the given sector ID `range`, see
+        /// `RoomFilters::in_sector_range()`.
+        pub fn in_sector_range<R: RangeBounds<u32>>(&self, range: R) -> impl Iterator<Item = &Room> {
+            self.iter().in_sector_range(range)
+        }
+
+        /// Returns the rooms of self with `pattern` in their decrypted name, see
+        /// `RoomFilters::name_contains()`.
+        pub fn name_contains<'a>(&'a self, pattern: &'a str) -> impl Iterator<Item = &'a Room> {
+            self.iter().name_contains(pattern)
+        }
+    }
+
+    impl Deref for RoomList {
+        type Target = [Room];
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    impl From<Vec<Room>> for RoomList {
+        fn from(rooms: Vec<Room>) -> RoomList {
+            RoomList(rooms)
+        }
+    }
+
+    impl FromIterator<Room> for RoomList {
+        fn from_iter<I: IntoIterator<Item = Room>>(iter: I) -> RoomList {
+            RoomList(iter.into_iter().collect())
+        }
+    }
+
+    impl FromInput for RoomList {
+        /// Parse one `Room` per line of `input`, reporting every malformed line.
+        fn from_input(input: &Input) -> Result<RoomList, AocError> {
+            Ok(::report::parse_spanned_lines::<Room, _>(input.text())?.into())
+        }
+    }
+
+    /// Lazy and chainable filters over any iterator of rooms, e.g. from `RoomList`.
+    pub trait RoomFilters<'a>: Iterator<Item = &'a Room> + Sized {
+        /// Keep only the real rooms, see `Room::is_real()`.
+        fn real(self) -> impl Iterator<Item = &'a Room> {
+            self.filter(|room| room.is_real())
+        }
+
+        /// Keep only the rooms whose sector ID is in `range`, e.g. `500..=600`.
+        fn in_sector_range<R: RangeBounds<u32>>(self, range: R) -> impl Iterator<Item = &'a Room> {
+            self.filter(move |room| range.contains(&room.sector_id()))
+        }
+
+        /// Keep only the rooms with `pattern` in their decrypted name, see `Room::name()`.
+        fn name_contains(self, pattern: &str) -> impl Iterator<Item = &'a Room> {
+            self.filter(move |room| room.name().contains(pattern))
+        }
+    }
+
+    impl<'a, I: Iterator<Item = &'a Room>> RoomFilters<'a> for I {}
+
+    /// Returns a random list of `size` rooms, formatted like the puzzle input.
+    ///
+    /// About a quarter of the rooms are decoys, and one real room is where the North Pole
+    /// objects are stored.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let storage = rng.gen_range(0, size.max(1));
+        let mut input = String::new();
+        for i in 0..size {
+            let sector_id = rng.gen_range(100, 1000);
+            let name = if i == storage {
+                "northpole object storage".to_string()
+            } else {
+                let words: Vec<String> = (0..rng.gen_range(2, 6)).map(|_| {
+                    (0..rng.gen_range(3, 11)).map(|_| char::from(rng.gen_range(b'a', b'z' + 1))).collect()
+                }).collect();
+                words.join(" ")
+            };
+            // the name is made of lowercase words, so forging can't fail.
+            let mut room = Room::forge(&name, sector_id).unwrap();
+            if i != storage && rng.gen_range(0, 4) == 0 {
+                room.checksum = loop {
+                    let decoy: String = (0..5).map(|_| char::from(rng.gen_range(b'a', b'z' + 1))).collect();
+                    if decoy != room.checksum {
+                        break decoy;
+                    }
+                };
+            }
+            input.push_str(&format!("{}\n", room));
+        }
+        input
+    }
+}
+
+#[cfg(feature = "std")]
+pub use security_through_obscurity::*;
@@ -0,0 +1,186 @@
+extern crate aoc_core;
+extern crate security_through_obscurity;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate report;
+#[macro_use]
+extern crate snapshot;
+
+use aoc_core::Span;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use report::Input;
+use security_through_obscurity::*;
+
+#[test]
+fn part1_first_example() {
+    let room: Room = "aaaaa-bbb-z-y-x-123[abxyz]".parse().unwrap();
+    println!("{:?}", room);
+    assert!(room.is_real());
+}
+
+#[test]
+fn part1_second_example() {
+    let room: Room = "a-b-c-d-e-f-g-h-987[abcde]".parse().unwrap();
+    println!("{:?}", room);
+    assert!(room.is_real());
+}
+
+#[test]
+fn part1_third_example() {
+    let room: Room = "not-a-real-room-404[oarel]".parse().unwrap();
+    println!("{:?}", room);
+    assert!(room.is_real());
+}
+
+#[test]
+fn part1_fourth_example() {
+    let room: Room = "totally-real-room-200[decoy]".parse().unwrap();
+    println!("{:?}", room);
+    assert!(room.is_decoy());
+}
+
+#[test]
+fn part2_example() {
+    let room: Room = "qzmt-zixmtkozy-ivhz-343[incomplete]".parse().unwrap();
+    assert_eq!(room.name(), "very encrypted name");
+}
+
+#[test]
+fn shift_cipher_wraps_around() {
+    assert_eq!(shift_cipher::shift('z', 1), 'a');
+    assert_eq!(shift_cipher::shift('a', 26 * 1000 + 3), 'd');
+    assert_eq!(shift_cipher::decrypt("ab-C", 1), "bc ?");
+}
+
+#[test]
+fn shift_cipher_encrypt() {
+    assert_eq!(shift_cipher::encrypt("very encrypted name", 343), "qzmt-zixmtkozy-ivhz");
+    assert_eq!(shift_cipher::encrypt("a-B", 1), "z??");
+}
+
+#[test]
+fn room_forge() {
+    let room = Room::forge("very encrypted name", 343).unwrap();
+    assert_eq!(room.to_string(), "qzmt-zixmtkozy-ivhz-343[zimth]");
+    assert!(room.is_real());
+    assert_eq!(room.name(), "very encrypted name");
+    let encrypted = RoomEncryptedName::encrypt("northpole object storage", 0).unwrap();
+    assert_eq!(encrypted.to_string(), "northpole-object-storage");
+    assert_eq!(encrypted.checksum().collect::<String>(), "oetra");
+    for name in &["", "two  spaces", " leading", "trailing ", "Upper", "dash-ed"] {
+        assert!(Room::forge(name, 42).is_none(), "{:?}", name);
+    }
+}
+
+proptest! {
+    #[test]
+    fn room_round_trip(s in "[a-z]{1,8}(-[a-z]{1,8}){0,4}-[1-9][0-9]{0,8}\\[[a-z]{5}\\]") {
+        let room: Room = s.parse().unwrap();
+        prop_assert_eq!(room.to_string(), s);
+    }
+
+    #[test]
+    fn forged_room_is_real(name in "[a-z]{1,8}( [a-z]{1,8}){0,4}", sector_id: u32) {
+        let room: Room = Room::forge(&name, sector_id).unwrap().to_string().parse().unwrap();
+        prop_assert!(room.is_real());
+        prop_assert_eq!(room.sector_id(), sector_id);
+        prop_assert_eq!(room.name(), name);
+    }
+
+    #[test]
+    fn room_parse_never_panics(s in "[a-z0-9\\[\\]-]{0,30}|\\PC*") {
+        if let Ok(room) = s.parse::<Room>() {
+            // whatever was accepted is displayed as a valid room.
+            let again: Room = room.to_string().parse().unwrap();
+            prop_assert_eq!(again.to_string(), room.to_string());
+        }
+    }
+}
+
+#[test]
+fn room_sector_id_out_of_range() {
+    assert!("a-99999999999[a]".parse::<Room>().is_err());
+}
+
+#[test]
+fn room_parse_error_spans() {
+    let span = |s: &str| s.parse::<Room>().unwrap_err().span;
+    assert_eq!(span("a-99999999999[a]"), Span::new(1, 3, 11));
+    assert_eq!(span("aaa-bb_b-1[ab]"), Span::new(1, 7, 1));
+    assert_eq!(span("aaa-1[ab"), Span::new(1, 9, 0));
+    assert_eq!(span("aaa-1[ab]é"), Span::new(1, 10, 1));
+}
+
+#[test]
+fn room_snapshots() {
+    let rooms = [
+        "aaaaa-bbb-z-y-x-123[abxyz]",
+        "a-b-c-d-e-f-g-h-987[abcde]",
+        "not-a-real-room-404[oarel]",
+        "totally-real-room-200[decoy]",
+        "qzmt-zixmtkozy-ivhz-343[zimth]",
+    ];
+    let mut out = String::new();
+    for s in &rooms {
+        let room: Room = s.parse().unwrap();
+        let kind = if room.is_real() { "real" } else { "decoy" };
+        out += &format!("{} ({}): {}\n", room, kind, room.name());
+    }
+    assert_snapshot!("rooms", out);
+}
+
+#[test]
+fn room_filters() {
+    let rooms: RoomList = [
+        "aaaaa-bbb-z-y-x-123[abxyz]",
+        "qzmt-zixmtkozy-ivhz-343[zimth]",
+        "not-a-real-room-404[oarel]",
+        "totally-real-room-200[decoy]",
+        "sbov-mlib-523[bilmo]",
+    ].iter().map(|line| line.parse().unwrap()).collect();
+    let ids = |rooms: Vec<&Room>| rooms.iter().map(|room| room.sector_id()).collect::<Vec<_>>();
+    assert_eq!(ids(rooms.real().collect()), vec![123, 343, 404, 523]);
+    assert_eq!(ids(rooms.in_sector_range(200..=404).collect()), vec![343, 404, 200]);
+    assert_eq!(ids(rooms.in_sector_range(..343).real().collect()), vec![123]);
+    // "qzmt-zixmtkozy-ivhz" is "very encrypted name" and "sbov-mlib" is "very pole".
+    assert_eq!(ids(rooms.name_contains("very").collect()), vec![343, 523]);
+    assert_eq!(ids(rooms.in_sector_range(300..=600).real().name_contains("pole").collect()), vec![523]);
+    assert!(rooms.iter().real().name_contains("storage").next().is_none());
+}
+
+#[test]
+fn room_list_from_input() {
+    let input = Input::new("<test>", "aaaaa-bbb-z-y-x-123[abxyz]\n\ntotally-real-room-200[decoy]\n".to_string());
+    let rooms: RoomList = input.build();
+    assert_eq!(rooms.len(), 2);
+    assert_eq!(rooms.real().count(), 1);
+    let input = Input::new("<test>", "aaaaa-bbb-z-y-x-123[abxyz]\naaa-bb_b-1[ab]\nabc-x[abc]\n".to_string());
+    let errors = input.build_errors::<RoomList>();
+    assert_eq!(errors.iter().map(|e| e.line_no).collect::<Vec<_>>(), [Some(2), Some(3)]);
+}
+
+#[test]
+fn room_explain() {
+    let mut events: Vec<String> = Vec::new();
+    for line in &["not-a-real-room-404[oarel]", "totally-real-room-200[decoy]"] {
+        line.parse::<Room>().unwrap().explain(&mut events);
+    }
+    assert_eq!(events, [
+        r#"{"event":"room_rejected","room":"totally-real-room-200[decoy]","sector_id":200,"checksum":"decoy","expected":"loart"}"#,
+    ]);
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 1000);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 1000));
+    let rooms: Vec<Room> = input.lines().map(|line| line.parse().unwrap()).collect();
+    assert_eq!(rooms.len(), 1000);
+    let decoys = rooms.iter().filter(|room| room.is_decoy()).count();
+    assert!(decoys > 200 && decoys < 300, "{} decoys", decoys);
+    let storage: Vec<&Room> = rooms.iter().filter(|room| room.name() == "northpole object storage").collect();
+    assert_eq!(storage.len(), 1);
+    assert!(storage[0].is_real());
+}
@@ -0,0 +1,216 @@
+//! Day 5: How About a Nice Game of Chess?, cracking both security doors passwords from the
+//! interesting MD5 hashes of the door ID.
+
+extern crate checked;
+extern crate hashing;
+extern crate rand;
+
+pub mod how_about_a_nice_game_of_chess {
+    use ::rand::Rng;
+
+    /// The engine scanning the door ID suffixes for interesting hashes, configurable for other
+    /// searches (starting index, step, nonce).
+    pub use ::hashing::InterestingHashFinder;
+
+    /// The password character count.
+    pub const PASSWORD_LEN: usize = 8;
+    const UNKNOWN_CHAR: char = '_';
+
+    /// Represent a `SecurityDoor` password
+    #[derive(Debug)]
+    pub struct Password {
+        characters: [char; PASSWORD_LEN],
+    }
+
+    impl Password {
+        /// Create a new (completely unknown) password
+        fn new() -> Password {
+            Password {
+                characters: [UNKNOWN_CHAR; PASSWORD_LEN],
+            }
+        }
+
+        /// Returns true if all characters are known in self, false otherwise.
+        pub fn is_known(&self) -> bool {
+            self.characters.iter().all(|&ch| ch != UNKNOWN_CHAR)
+        }
+
+        /// Returns the count of characters known in self.
+        pub fn known_count(&self) -> usize {
+            self.characters.iter().filter(|&&ch| ch != UNKNOWN_CHAR).count()
+        }
+
+    }
+
+    impl ::std::fmt::Display for Password {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            let characters: String = self.characters.iter().collect();
+            write!(f, "{}", characters)
+        }
+    }
+
+    /// An interesting hash ignored by the second door, either because its position is invalid
+    /// or because the character at that position is already known.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub struct Decoy {
+        /// The index of the hash.
+        pub index: u64,
+        /// The sixth character of the hash, i.e. the position.
+        pub position: char,
+        /// The seventh character of the hash, i.e. the character for that position.
+        pub character: char,
+    }
+
+    impl Decoy {
+        /// Returns true if the position of self is not a valid one (`0` to `7`), false if it
+        /// was ignored because the character at this position was already known.
+        pub fn is_invalid_position(&self) -> bool {
+            self.position.to_digit(16).and_then(|digit| ::checked::index(digit, PASSWORD_LEN)).is_none()
+        }
+    }
+
+    impl ::std::fmt::Display for Decoy {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            let reason = if self.is_invalid_position() { "invalid position" } else { "position already known" };
+            write!(f, "decoy hash #{}: {} at position {} ({})", self.index, self.character, self.position, reason)
+        }
+    }
+
+    /// How many interesting hashes were ignored by the second door, per position.
+    #[derive(Clone, Debug, Default, Eq, PartialEq)]
+    pub struct DecoyCounts {
+        /// Indexed by the position hexadecimal digit.
+        counts: [u64; 16],
+    }
+
+    impl DecoyCounts {
+        fn record(&mut self, decoy: &Decoy) {
+            if let Some(digit) = decoy.position.to_digit(16) {
+                self.counts[digit as usize] += 1;
+            }
+        }
+
+        /// Returns the count of hashes ignored with the given `position`, a hexadecimal digit.
+        /// For a valid position (`0` to `7`) these hashes came after its character was known.
+        pub fn at(&self, position: char) -> u64 {
+            position.to_digit(16).map_or(0, |digit| self.counts[digit as usize])
+        }
+
+        /// Returns the count of hashes ignored because their position was invalid.
+        pub fn invalid_positions(&self) -> u64 {
+            self.counts[PASSWORD_LEN..].iter().sum()
+        }
+
+        /// Returns the count of hashes ignored because their position was already known.
+        pub fn known_positions(&self) -> u64 {
+            self.counts[..PASSWORD_LEN].iter().sum()
+        }
+    }
+
+    impl ::std::fmt::Display for DecoyCounts {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "{} decoys, {} at known positions (", self.known_positions() + self.invalid_positions(),
+                   self.known_positions())?;
+            for (digit, count) in self.counts.iter().enumerate() {
+                if digit == PASSWORD_LEN {
+                    write!(f, "), {} at invalid positions (", self.invalid_positions())?;
+                } else if digit > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{:x}: {}", digit, count)?;
+            }
+            write!(f, ")")
+        }
+    }
+
+    /// Represent a security door designed by Easter Bunny engineers.
+    #[derive(Debug)]
+    pub struct SecurityDoor {
+        door_id: String,
+    }
+
+    impl SecurityDoor {
+        /// Create a new `SecurityDoor` given a door ID.
+        pub fn new(door_id: &str) -> SecurityDoor {
+            SecurityDoor { door_id: door_id.to_string() }
+        }
+
+        /// Generate both passwords (for the first and the second door) according to the Easter
+        /// Bunny engineers questionable algorithm.
+        ///
+        /// The cracking process will continue as long as the given `progress` function return
+        /// `true`.
+        ///
+        /// # Errors
+        ///
+        /// When the password generation failed.
+        pub fn crack<T>(&self, progress: T) -> Result<(Password, Password), String>
+                where T: Fn(&Password, &Password) -> bool {
+            self.crack_indexed(|_, first, second| progress(first, second))
+        }
+
+        /// Like `crack()`, the given `progress` function also being given the index of the next
+        /// hash to be searched.
+        ///
+        /// # Errors
+        ///
+        /// When the password generation failed.
+        pub fn crack_indexed<T>(&self, progress: T) -> Result<(Password, Password), String>
+                where T: Fn(u64, &Password, &Password) -> bool {
+            self.crack_with_decoys(progress, |_| ()).map(|(first, second, _)| (first, second))
+        }
+
+        /// Like `crack_indexed()`, the given `decoy` function being called with every
+        /// interesting hash ignored by the second door. Also returns how many of them were
+        /// ignored per position.
+        ///
+        /// # Errors
+        ///
+        /// When the password generation failed.
+        pub fn crack_with_decoys<T, D>(&self, progress: T, mut decoy: D)
+                -> Result<(Password, Password, DecoyCounts), String>
+                where T: Fn(u64, &Password, &Password) -> bool, D: FnMut(&Decoy) {
+            let mut passwords = (Password::new(), Password::new());
+            let mut decoys = DecoyCounts::default();
+            let mut generator = InterestingHashFinder::new(&self.door_id).ok_or("OpenSSL error")?;
+            while progress(generator.index(), &passwords.0, &passwords.1) {
+                let hash_str = generator.next().ok_or("Password generation failure")?;
+                let sixth    = hash_str.chars().nth(5).ok_or("Password generation error")?;
+                let seventh  = hash_str.chars().nth(6).ok_or("Password generation error")?;
+                // First door password:
+                // > […] the sixth character in the hash is the next character of the password.
+                let position = passwords.0.characters.iter().position(|&ch| ch == UNKNOWN_CHAR);
+                if let Some(index) = position {
+                    passwords.0.characters[index] = sixth;
+                }
+                // Second door password:
+                // > […] the sixth character represents the position (0-7), and the seventh
+                // > character is the character to put in that position.
+                // > […] Use only the first result for each position, and ignore invalid positions.
+                let index = sixth.to_digit(16).and_then(|digit| ::checked::index(digit, PASSWORD_LEN));
+                match index.filter(|&i| passwords.1.characters[i] == UNKNOWN_CHAR) {
+                    Some(index) => passwords.1.characters[index] = seventh,
+                    None => {
+                        // NOTE: the generator index is the one of the next hash to be searched.
+                        let ignored = Decoy { index: generator.index() - 1, position: sixth, character: seventh };
+                        decoys.record(&ignored);
+                        decoy(&ignored);
+                    },
+                }
+            }
+            Ok((passwords.0, passwords.1, decoys))
+        }
+    }
+
+    /// Returns a random door ID, formatted like the puzzle input.
+    ///
+    /// The door ID is always eight letters long like the puzzle input ones, `size` is ignored as
+    /// the time spent cracking the passwords doesn't depend on it.
+    pub fn generate_input<R: Rng>(rng: &mut R, _size: usize) -> String {
+        let mut input: String = (0..8).map(|_| char::from(rng.gen_range(b'a', b'z' + 1))).collect();
+        input.push('\n');
+        input
+    }
+}
+
+pub use how_about_a_nice_game_of_chess::*;
@@ -0,0 +1,48 @@
+extern crate how_about_a_nice_game_of_chess;
+extern crate rand;
+
+use how_about_a_nice_game_of_chess::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn part1_example() {
+    let door = SecurityDoor::new("abc");
+    let password = door.crack(|first, _| !first.is_known()).unwrap().0;
+    assert_eq!(password.to_string(), "18f47a30".to_string());
+}
+
+#[test]
+fn part2_example() {
+    let door = SecurityDoor::new("abc");
+    let password = door.crack(|_, second| !second.is_known()).unwrap().1;
+    assert_eq!(password.to_string(), "05ace8e3".to_string());
+}
+
+#[test]
+fn part2_example_decoys() {
+    let door = SecurityDoor::new("abc");
+    let decoys = ::std::cell::RefCell::new(Vec::new());
+    let (_, second, counts) = door.crack_with_decoys(|_, _, second| !second.is_known(), |decoy| {
+        decoys.borrow_mut().push(*decoy);
+    }).unwrap();
+    assert_eq!(second.to_string(), "05ace8e3".to_string());
+    let decoys = decoys.into_inner();
+    // > The first interesting hash is from abc3231929, which produces 0000015...; so, 5 goes in
+    // > position 1: _5______.
+    // > In the previous method, 5017308 produced an interesting hash; however, it is ignored,
+    // > because it specifies an invalid position (8).
+    assert_eq!(decoys[0], Decoy { index: 5017308, position: '8', character: 'f' });
+    assert!(decoys[0].is_invalid_position());
+    assert_eq!(counts.invalid_positions() + counts.known_positions(), decoys.len() as u64);
+    assert_eq!(counts.at('8'), decoys.iter().filter(|decoy| decoy.position == '8').count() as u64);
+    assert_eq!(counts.invalid_positions(), decoys.iter().filter(|decoy| decoy.is_invalid_position()).count() as u64);
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 0);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 0));
+    assert_eq!(input.len(), 9);
+    assert!(input.trim_end().bytes().all(|b| b.is_ascii_lowercase()));
+}
@@ -0,0 +1,240 @@
+//! Day 6: Signals and Noise, error-correcting the repeated messages using the most and least
+//! frequent character of every position.
+
+extern crate rand;
+
+pub mod signals_and_noise {
+    use ::rand::Rng;
+    use ::std::collections::HashMap;
+    use ::std::ops::{Deref, DerefMut};
+    use ::std::str::FromStr;
+
+    /// Represent characters frequency counters for a given message position.
+    #[derive(Debug)]
+    struct CharFreq(HashMap<char, u32>);
+
+    impl CharFreq {
+        /// Create a new `CharFreq`
+        fn new() -> CharFreq {
+            CharFreq(HashMap::new())
+        }
+
+        /// Returns the character having the maximum frequency.
+        ///
+        /// If many characters are tied for the maximum frequency, the return value is one of them
+        /// chosen arbitrarily. If self is empty, return `None`.
+        fn most_frequent_character(&self) -> Option<char> {
+            // compare by the frequency (value) in the descending order (i.e. the most frequent
+            // first), hence "b cmp a".
+            self.first_char_sort_by_freq(|a, b| b.cmp(a))
+        }
+
+        /// Returns the character having the minimum frequency.
+        ///
+        /// If many characters are tied for the minimum frequency, the return value is one of them
+        /// chosen arbitrarily. If self is empty, return `None`.
+        fn least_frequent_character(&self) -> Option<char> {
+            // compare by the frequency (value) in the ascending order (i.e. the least frequent
+            // first), hence "a cmp b".
+            self.first_char_sort_by_freq(|a, b| a.cmp(b))
+        }
+
+        /// Returns the first character of self sorted by a given `cmp` comparison function on the
+        /// frequency.
+        fn first_char_sort_by_freq<F>(&self, cmp: F) -> Option<char>
+            where F: FnMut(&u32, &u32) -> ::std::cmp::Ordering
+        {
+            // map to the char, we don't need the frequency anymore
+            self.confidence(cmp).map(|confidence| confidence.character)
+        }
+
+        /// Returns the `Confidence` in the first character of self sorted by a given `cmp`
+        /// comparison function on the frequency, compared to the second one.
+        fn confidence<F>(&self, mut cmp: F) -> Option<Confidence>
+            where F: FnMut(&u32, &u32) -> ::std::cmp::Ordering
+        {
+            // build a vector of tuple (char, frequency) from the hash (key, value) so we can sort
+            // our results.
+            let mut vec: Vec<_> = self.iter().collect();
+            vec.sort_by(|&(_, freqa), &(_, freqb)| cmp(freqa, freqb));
+            let (&character, &count) = *vec.first()?;
+            let margin = vec.get(1).map(|&(_, &runner_up)| count.abs_diff(runner_up));
+            Some(Confidence { character, count, margin })
+        }
+    }
+
+    impl Deref for CharFreq {
+        type Target = HashMap<char, u32>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    impl DerefMut for CharFreq {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.0
+        }
+    }
+
+    /// How confident the decoding of a message character is, see
+    /// `ErrorCorrector::src_confidence()`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Confidence {
+        /// The decoded character.
+        pub character: char,
+        /// How many times the character was seen at its position.
+        pub count: u32,
+        /// The difference between `count` and the frequency of the runner-up character, or
+        /// `None` if no other character was seen at this position. Zero means a tie, the
+        /// character being one of the tied ones chosen arbitrarily.
+        pub margin: Option<u32>,
+    }
+
+    /// How the messages are laid out in a capture.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Capture {
+        /// One message per line, like the puzzle input.
+        Messages,
+        /// One message position per line, holding the samples of every message for it (i.e. the
+        /// transpose of `Messages`).
+        Positions,
+    }
+
+    impl Capture {
+        /// Returns the messages of the capture `s` laid out as self, one per line of `s` for
+        /// `Messages` or one per column for `Positions`. Blank lines are skipped, like in
+        /// `ErrorCorrector::from_capture()`.
+        pub fn messages(self, s: &str) -> Vec<String> {
+            match self {
+                Capture::Messages => capture_lines(s).map(str::to_string).collect(),
+                Capture::Positions => {
+                    let mut messages: Vec<String> = Vec::new();
+                    for line in capture_lines(s) {
+                        for (index, ch) in line.chars().enumerate() {
+                            if messages.len() <= index {
+                                messages.push(String::new());
+                            }
+                            messages[index].push(ch);
+                        }
+                    }
+                    messages
+                },
+            }
+        }
+    }
+
+    /// Returns the lines of the capture `s`, skipping the blank ones.
+    fn capture_lines(s: &str) -> impl Iterator<Item = &str> {
+        s.lines().filter(|line| !line.trim().is_empty())
+    }
+
+    /// Returns the Hamming distance between `a` and `b`, i.e. the count of positions at which
+    /// their characters differ. When their lengths differ, every character of the longest one
+    /// past the end of the other counts as a difference.
+    pub fn hamming_distance(a: &str, b: &str) -> usize {
+        let (alen, blen) = (a.chars().count(), b.chars().count());
+        let differences = a.chars().zip(b.chars()).filter(|(x, y)| x != y).count();
+        differences + alen.abs_diff(blen)
+    }
+
+    /// Represents an error corrector device used to communicate with Santa when the signal is poor
+    /// or jammed.
+    #[derive(Debug, Default)]
+    pub struct ErrorCorrector(Vec<CharFreq>);
+
+    impl ErrorCorrector {
+        /// Create a new `ErrorCorrector`
+        pub fn new() -> ErrorCorrector {
+            ErrorCorrector(Vec::new())
+        }
+
+        /// Register a given message into the `ErrorCorrector`.
+        pub fn register(&mut self, message: &str) {
+            let vec = &mut self.0;
+            for (index, ch) in message.chars().enumerate() {
+                // ensure to have a CharFreq at self.0[index]
+                while vec.len() <= index {
+                    vec.push(CharFreq::new());
+                }
+                *vec[index].entry(ch).or_insert(0) += 1;
+            }
+        }
+
+        /// Register the samples of every message for the given message `position`, i.e. a column
+        /// of the messages.
+        pub fn register_samples(&mut self, position: usize, samples: &str) {
+            let vec = &mut self.0;
+            while vec.len() <= position {
+                vec.push(CharFreq::new());
+            }
+            for ch in samples.chars() {
+                *vec[position].entry(ch).or_insert(0) += 1;
+            }
+        }
+
+        /// Create a new `ErrorCorrector` from a capture laid out as `capture`, one message or one
+        /// message position per line. Blank lines are skipped.
+        pub fn from_capture(s: &str, capture: Capture) -> ErrorCorrector {
+            let mut ec = ErrorCorrector::new();
+            for (index, line) in capture_lines(s).enumerate() {
+                match capture {
+                    Capture::Messages => ec.register(line),
+                    Capture::Positions => ec.register_samples(index, line),
+                }
+            }
+            ec
+        }
+
+        /// Compute and return the error-corrected message version using the simple repetition code
+        /// protocol.
+        pub fn src_message(&self) -> String {
+            self.0.iter().filter_map(|cfreq| cfreq.most_frequent_character()).collect()
+        }
+
+        /// Compute and return the original message using the modified repetition code protocol.
+        pub fn mrc_message(&self) -> String {
+            self.0.iter().filter_map(|cfreq| cfreq.least_frequent_character()).collect()
+        }
+
+        /// Returns the `Confidence` in each character of `src_message()`, the margin being how
+        /// many times it was seen more than the runner-up.
+        pub fn src_confidence(&self) -> Vec<Confidence> {
+            self.0.iter().filter_map(|cfreq| cfreq.confidence(|a, b| b.cmp(a))).collect()
+        }
+
+        /// Returns the `Confidence` in each character of `mrc_message()`, the margin being how
+        /// many times it was seen less than the runner-up.
+        pub fn mrc_confidence(&self) -> Vec<Confidence> {
+            self.0.iter().filter_map(|cfreq| cfreq.confidence(|a, b| a.cmp(b))).collect()
+        }
+    }
+
+    impl FromStr for ErrorCorrector {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<ErrorCorrector, Self::Err> {
+            Ok(ErrorCorrector::from_capture(s, Capture::Messages))
+        }
+    }
+
+    /// Returns `size` random messages of eight letters, formatted like the puzzle input.
+    ///
+    /// Every message is a noisy copy of a hidden message: each of its letters is random, but is
+    /// more often the hidden message letter at the same position.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let mut letter = || char::from(rng.gen_range(b'a', b'z' + 1));
+        let hidden: Vec<char> = (0..8).map(|_| letter()).collect();
+        let mut input = String::new();
+        for _ in 0..size {
+            for &ch in &hidden {
+                let noisy = letter();
+                input.push(if noisy < 'g' { ch } else { noisy });
+            }
+            input.push('\n');
+        }
+        input
+    }
+}
+
+pub use signals_and_noise::*;
@@ -1,177 +1,84 @@
-mod signals_and_noise {
-    use ::std::collections::HashMap;
-    use ::std::ops::{Deref, DerefMut};
-    use ::std::str::FromStr;
+extern crate aoc_cli;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+extern crate report;
+extern crate signals_and_noise;
 
-    /// Represent characters frequency counters for a given message position.
-    #[derive(Debug)]
-    struct CharFreq(HashMap<char, u32>);
 
-    impl CharFreq {
-        /// Create a new `CharFreq`
-        fn new() -> CharFreq {
-            CharFreq(HashMap::new())
-        }
-
-        /// Returns the character having the maximum frequency.
-        ///
-        /// If many characters are tied for the maximum frequency, the return value is one of them
-        /// choosen arbitrarily. If self is empty, return `None`.
-        fn most_frequent_character(&self) -> Option<char> {
-            // compare by the frequency (value) in the descending order (i.e. the most frequent
-            // first), hence "b cmp a".
-            self.first_char_sort_by_freq(|a, b| b.cmp(&a))
-        }
+use aoc_cli::Cli;
+use std::collections::BTreeMap;
+use signals_and_noise::*;
 
-        /// Returns the character having the minimum frequency.
-        ///
-        /// If many characters are tied for the minimum frequency, the return value is one of them
-        /// choosen arbitrarily. If self is empty, return `None`.
-        fn least_frequent_character(&self) -> Option<char> {
-            // compare by the frequency (value) in the ascending order (i.e. the least frequent
-            // first), hence "a cmp b".
-            self.first_char_sort_by_freq(|a, b| a.cmp(&b))
-        }
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
 
-        /// Returns the first character of self sorted by a given `cmp` comparison function on the
-        /// frequency.
-        fn first_char_sort_by_freq<F>(&self, mut cmp: F) -> Option<char>
-            where F: FnMut(&u32, &u32) -> ::std::cmp::Ordering
-        {
-            // build a vector of tuple (char, frequency) from the hash (key, value) so we can sort
-            // our results.
-            let mut vec: Vec<_> = self.iter().collect();
-            vec.sort_by(|&(_, freqa), &(_, freqb)| cmp(freqa, freqb));
-            // map to the char, we don't need the frequency anymore
-            vec.into_iter().map(|(&ch, _)| ch).next()
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    let cli = Cli::start_with_generator(generate_input);
+    // given --transposed, every line holds the samples of one message position. Given
+    // --confidence, report how noisy the capture was for each decoded message.
+    let mut capture = Capture::Messages;
+    let mut confidence = false;
+    for arg in cli.args() {
+        match arg.as_str() {
+            "--transposed" => capture = Capture::Positions,
+            "--confidence" => confidence = true,
+            _ => report::exit(format!("unrecognized argument: {}", arg)),
         }
     }
+    // acquire the puzzle input.
+    let input = cli.input();
+    if cli.validating() {
+        // every message (or position) is expected to be made of the same number of lowercase
+        // letters.
+        let len = input.first_line().chars().count();
+        input.validate(input.check_lines(|message| {
+            let count = message.chars().count();
+            match message.chars().position(|c| !c.is_ascii_lowercase()) {
+                Some(i) => Err(format!("column {}: not a lowercase letter", i + 1)),
+                None if count != len => Err(format!("expected {} characters, got {}", len, count)),
+                None => Ok(()),
+            }
+        }));
+    }
 
-    impl Deref for CharFreq {
-        type Target = HashMap<char, u32>;
+    let ec = phase!("register").in_scope(|| ErrorCorrector::from_capture(input.text(), capture));
 
-        fn deref(&self) -> &Self::Target {
-            &self.0
-        }
+    let message = ec.src_message();
+    cli.answer(1, &message, format_args!("The error-corrected version of the message is: {}", message));
+    if confidence {
+        report_confidence(&cli, &ec.src_confidence(), "more", &message, &capture.messages(input.text()));
     }
-
-    impl DerefMut for CharFreq {
-        fn deref_mut<'a>(&'a mut self) -> &'a mut Self::Target {
-            &mut self.0
-        }
+    let message = ec.mrc_message();
+    cli.answer(2, &message, format_args!("The original message is: {}", message));
+    if confidence {
+        report_confidence(&cli, &ec.mrc_confidence(), "less", &message, &capture.messages(input.text()));
     }
+}
 
-    /// Represents an error corrector device used to communicate with Santa when the signal is poor
-    /// or jammed.
-    #[derive(Debug)]
-    pub struct ErrorCorrector(Vec<CharFreq>);
-
-    impl ErrorCorrector {
-        /// Create a new `ErrorCorrector`
-        pub fn new() -> ErrorCorrector {
-            ErrorCorrector(Vec::new())
-        }
-
-        /// Register a given message into the `ErrorCorrector`.
-        pub fn register(&mut self, message: &str) {
-            let ref mut vec = self.0;
-            for (index, ch) in message.chars().enumerate() {
-                // ensure to have a CharFreq at self.0[index]
-                while vec.len() <= index {
-                    vec.push(CharFreq::new());
-                }
-                *vec[index].entry(ch).or_insert(0) += 1;
-            }
-        }
-
-        /// Compute and return the error-corrected message version using the simple repetition code
-        /// protocol.
-        pub fn src_message(&self) -> String {
-            self.0.iter().filter_map(|cfreq| cfreq.most_frequent_character()).collect()
-        }
-
-        /// Compute and return the original message using the modified repetition code protocol.
-        pub fn mrc_message(&self) -> String {
-            self.0.iter().filter_map(|cfreq| cfreq.least_frequent_character()).collect()
+/// Print the confidence in each character of `message`, seen `than` the runner-up, and how many
+/// of the `messages` are at each Hamming distance from it.
+fn report_confidence(cli: &Cli, confidence: &[Confidence], than: &str, message: &str, messages: &[String]) {
+    for (position, c) in confidence.iter().enumerate() {
+        match c.margin {
+            Some(margin) => cli.note(format_args!(
+                "  position {}: {} seen {} times, {} {} than the runner-up",
+                position + 1, c.character, c.count, margin, than,
+            )),
+            None => cli.note(format_args!(
+                "  position {}: {} seen {} times, the only character", position + 1, c.character, c.count,
+            )),
         }
     }
-
-    impl FromStr for ErrorCorrector {
-        type Err = ();
-
-        fn from_str(s: &str) -> Result<ErrorCorrector, Self::Err> {
-            let mut ec = ErrorCorrector::new();
-            for message in s.lines() {
-                ec.register(message);
-            }
-            Ok(ec)
-        }
+    let mut distances: BTreeMap<usize, usize> = BTreeMap::new();
+    for other in messages {
+        *distances.entry(hamming_distance(other, message)).or_insert(0) += 1;
     }
-}
-
-
-use std::io::Read;
-use signals_and_noise::*;
-
-fn main() {
-    // acquire data from stdin.
-    let mut input = String::new();
-    let stdin = std::io::stdin();
-    stdin.lock().read_to_string(&mut input).expect("no input given");
-
-    let mut ec: ErrorCorrector = ErrorCorrector::new();
-    for message in input.lines() {
-        ec.register(message);
+    for (distance, count) in distances {
+        cli.note(format_args!("  {} message(s) at distance {}", count, distance));
     }
-
-    println!("The error-corrected version of the message is: {}",
-             ec.src_message());
-    println!("The original message is: {}", ec.mrc_message());
-}
-
-
-#[test]
-fn part1_example() {
-    let messages = "\
-eedadn
-drvtee
-eandsr
-raavrd
-atevrs
-tsrnev
-sdttsa
-rasrtv
-nssdts
-ntnada
-svetve
-tesnvt
-vntsnd
-vrdear
-dvrsen
-enarar";
-    let ec: ErrorCorrector = messages.parse().unwrap();
-    assert_eq!(ec.src_message(), "easter".to_string());
-}
-
-#[test]
-fn part2_example() {
-    let messages = "\
-eedadn
-drvtee
-eandsr
-raavrd
-atevrs
-tsrnev
-sdttsa
-rasrtv
-nssdts
-ntnada
-svetve
-tesnvt
-vntsnd
-vrdear
-dvrsen
-enarar";
-    let ec: ErrorCorrector = messages.parse().unwrap();
-    assert_eq!(ec.mrc_message(), "advent".to_string());
 }
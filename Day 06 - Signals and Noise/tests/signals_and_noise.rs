@@ -0,0 +1,129 @@
+extern crate rand;
+extern crate signals_and_noise;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use signals_and_noise::*;
+
+#[test]
+fn part1_example() {
+    let messages = "\
+eedadn
+drvtee
+eandsr
+raavrd
+atevrs
+tsrnev
+sdttsa
+rasrtv
+nssdts
+ntnada
+svetve
+tesnvt
+vntsnd
+vrdear
+dvrsen
+enarar";
+    let ec: ErrorCorrector = messages.parse().unwrap();
+    assert_eq!(ec.src_message(), "easter".to_string());
+}
+
+#[test]
+fn part2_example() {
+    let messages = "\
+eedadn
+drvtee
+eandsr
+raavrd
+atevrs
+tsrnev
+sdttsa
+rasrtv
+nssdts
+ntnada
+svetve
+tesnvt
+vntsnd
+vrdear
+dvrsen
+enarar";
+    let ec: ErrorCorrector = messages.parse().unwrap();
+    assert_eq!(ec.mrc_message(), "advent".to_string());
+}
+
+#[test]
+fn transposed_example() {
+    // the example messages, one position per line.
+    let positions = "\
+ederatsrnnstvvde
+eraatsdastvenrvn
+dvnaertssnestdra
+atdvvntrdatnsesr
+desrresttdvvnaea
+nerdsvavsaetdrnr";
+    let ec = ErrorCorrector::from_capture(positions, Capture::Positions);
+    assert_eq!(ec.src_message(), "easter".to_string());
+    assert_eq!(ec.mrc_message(), "advent".to_string());
+}
+
+#[test]
+fn confidence() {
+    let messages = "\
+eedadn
+drvtee
+eandsr
+raavrd
+atevrs
+tsrnev
+sdttsa
+rasrtv
+nssdts
+ntnada
+svetve
+tesnvt
+vntsnd
+vrdear
+dvrsen
+enarar";
+    let ec: ErrorCorrector = messages.parse().unwrap();
+    let src = ec.src_confidence();
+    assert_eq!(src.iter().map(|c| c.character).collect::<String>(), ec.src_message());
+    assert!(src.iter().all(|c| c.count == 3 && c.margin == Some(1)));
+    let mrc = ec.mrc_confidence();
+    assert_eq!(mrc.iter().map(|c| c.character).collect::<String>(), ec.mrc_message());
+    assert!(mrc.iter().all(|c| c.count == 1 && c.margin == Some(1)));
+    // a single character, and a tie.
+    let ec: ErrorCorrector = "ab\nab\nac".parse().unwrap();
+    assert_eq!(ec.src_confidence(), [
+        Confidence { character: 'a', count: 3, margin: None },
+        Confidence { character: 'b', count: 2, margin: Some(1) },
+    ]);
+    let ec: ErrorCorrector = "a\nb".parse().unwrap();
+    assert_eq!(ec.src_confidence()[0].margin, Some(0));
+}
+
+#[test]
+fn distances() {
+    assert_eq!(hamming_distance("easter", "easter"), 0);
+    assert_eq!(hamming_distance("eedadn", "easter"), 5);
+    assert_eq!(hamming_distance("east", "easter"), 2);
+    assert_eq!(hamming_distance("", "abc"), 3);
+    let positions = "ace\nbdf";
+    assert_eq!(Capture::Positions.messages(positions), ["ab", "cd", "ef"]);
+    assert_eq!(Capture::Messages.messages(positions), ["ace", "bdf"]);
+    // blank lines are skipped, like when registering the capture.
+    let positions = "ace\n\n  \nbdf\n";
+    assert_eq!(Capture::Positions.messages(positions), ["ab", "cd", "ef"]);
+    assert_eq!(Capture::Messages.messages(positions), ["ace", "bdf"]);
+    assert_eq!(ErrorCorrector::from_capture("aab\n\nccd\n", Capture::Positions).src_message(), "ac");
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 600);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 600));
+    assert_eq!(input.lines().count(), 600);
+    let ec: ErrorCorrector = input.parse().unwrap();
+    assert_eq!(ec.src_message().len(), 8);
+    assert_eq!(ec.mrc_message().len(), 8);
+}
@@ -0,0 +1,26 @@
+//! Allocations made by the Day 7 parsing and classification, see `memory::PeakAllocator`.
+//! Unlike the timings these are exact, so they are simply printed:
+//!
+//!     cargo +nightly bench --bench allocations
+
+extern crate internet_protocol_version_7;
+extern crate memory;
+
+use internet_protocol_version_7::*;
+
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+const INPUT: &str = include_str!("../input.txt");
+
+fn main() {
+    let lines = INPUT.lines().count();
+    let (ips, count) = ALLOCATOR.count_allocations(|| {
+        INPUT.lines().map(|line| line.parse().unwrap()).collect::<Vec<Ipv7Addr>>()
+    });
+    println!("day07/parse: {} allocations for {} addresses", count, lines);
+    let (_, count) = ALLOCATOR.count_allocations(|| ips.iter().filter(|ip| ip.has_tls_support()).count());
+    println!("day07/tls: {} allocations for {} addresses", count, lines);
+    let (_, count) = ALLOCATOR.count_allocations(|| ips.iter().filter(|ip| ip.has_ssl_support()).count());
+    println!("day07/ssl: {} allocations for {} addresses", count, lines);
+}
@@ -0,0 +1,56 @@
+//! Day 7 TLS/SSL classification, reported per byte of input so that both backends (and later
+//! rewrites of the segment storage) can be compared in MB/s.
+
+#[macro_use]
+extern crate criterion;
+extern crate internet_protocol_version_7;
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use internet_protocol_version_7::*;
+use std::hint::black_box;
+
+const INPUT: &str = include_str!("../input.txt");
+
+fn addresses() -> Vec<Ipv7Addr> {
+    INPUT.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day07");
+    group.throughput(Throughput::Bytes(INPUT.len() as u64));
+    group.bench_function("parse", |b| b.iter(addresses));
+    group.finish();
+}
+
+fn classify(c: &mut Criterion) {
+    let ips = addresses();
+    let mut group = c.benchmark_group("day07");
+    group.throughput(Throughput::Bytes(INPUT.len() as u64));
+    let backends = [
+        ("windows", Backend::Windows),
+        ("char-queue", Backend::CharQueue),
+        #[cfg(feature = "simd")]
+        ("simd", Backend::Simd),
+    ];
+    for &(name, backend) in &backends {
+        group.bench_with_input(BenchmarkId::new("tls", name), &backend, |b, &backend| {
+            b.iter(|| ips.iter().filter(|ip| ip.has_tls_support_using(backend)).count())
+        });
+        group.bench_with_input(BenchmarkId::new("ssl", name), &backend, |b, &backend| {
+            b.iter(|| ips.iter().filter(|ip| ip.has_ssl_support_using(backend)).count())
+        });
+        group.bench_with_input(BenchmarkId::new("census", name), &backend, |b, &backend| {
+            b.iter(|| {
+                let mut census = Census::new();
+                for ip in &ips {
+                    census.register(ip, backend);
+                }
+                black_box(census)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, classify);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate internet_protocol_version_7;
+
+use internet_protocol_version_7::{Backend, Ipv7Addr, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = ::std::str::from_utf8(data) {
+        for &(ignore_case, strict) in [(false, false), (false, true), (true, false), (true, true)].iter() {
+            if let Ok(ip) = Ipv7Addr::parse_with(s, ParseOptions { ignore_case, strict }) {
+                // both backends must agree on everything.
+                let (windows, queue) = (Backend::Windows, Backend::CharQueue);
+                assert_eq!(ip.has_tls_support_using(windows), ip.has_tls_support_using(queue));
+                assert_eq!(ip.has_ssl_support_using(windows), ip.has_ssl_support_using(queue));
+                assert_eq!(ip.tls_witness(windows), ip.tls_witness(queue));
+                assert_eq!(ip.ssl_witness(windows), ip.ssl_witness(queue));
+            }
+        }
+    }
+});
@@ -0,0 +1,953 @@
+//! Day 7: Internet Protocol Version 7, parsing IPv7 addresses and classifying their TLS and
+//! SSL support.
+//!
+//! The ABBA and ABA/BAB scanning only needs `core` and `alloc`: without the default `std`
+//! feature the crate is `#![no_std]` and only provides the `sequences` module. The `simd` feature
+//! adds a backend comparing many byte windows at once with SSE2 or AVX2.
+
+// XXX: as of December 2016 the `pattern` API is unstable, see #27721
+#![feature(pattern)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate aoc_core;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "std")]
+extern crate rand;
+#[cfg(feature = "std")]
+extern crate report;
+
+pub mod sequences {
+    use ::alloc::collections::VecDeque;
+    use ::alloc::format;
+    use ::alloc::string::String;
+    use ::core::iter::Enumerate;
+    use ::core::slice::Windows;
+    use ::core::str::{CharIndices, FromStr};
+    use ::core::str::pattern::{Pattern, Searcher, SearchStep};
+
+    /// The scanning strategy used to look for ABBA and ABA/BAB sequences.
+    ///
+    /// Both backends find exactly the same matches, they only differ in how the haystack is
+    /// walked. This is mostly useful to compare their performances.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub enum Backend {
+        /// Slide a fixed-size window over the bytes of the haystack.
+        ///
+        /// This is the fastest scalar backend, but it only works on ASCII haystacks. `CharQueue`
+        /// is used instead for the others.
+        #[cfg_attr(not(feature = "simd"), default)]
+        Windows,
+        /// Compare the byte windows of the haystack 16 or 32 at a time, depending on the CPU.
+        ///
+        /// Like `Windows` it only works on ASCII haystacks, `CharQueue` being used instead for
+        /// the others.
+        #[cfg(feature = "simd")]
+        #[default]
+        Simd,
+        /// Push the haystack characters one by one into a fixed-size queue.
+        CharQueue,
+    }
+
+    impl FromStr for Backend {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Backend, String> {
+            match s {
+                "windows"    => Ok(Backend::Windows),
+                "char-queue" => Ok(Backend::CharQueue),
+                #[cfg(feature = "simd")]
+                "simd"       => Ok(Backend::Simd),
+                _ => Err(format!("unknown backend: {}", s)),
+            }
+        }
+    }
+
+    /// The fixed-length character sequences we are looking for.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Sequence {
+        /// An Autonomous Bridge Bypass Annotation, like `xyyx`.
+        Abba,
+        /// An Area-Broadcast Accessor or Byte Allocation Block, like `xyx`.
+        Bab,
+    }
+
+    impl Sequence {
+        /// Returns the number of characters in the sequence.
+        fn len(self) -> usize {
+            match self {
+                Sequence::Abba => 4,
+                Sequence::Bab  => 3,
+            }
+        }
+
+        /// Returns `true` if the given slice is this sequence, `false` otherwise.
+        ///
+        /// `xs` is expected to have exactly `self.len()` elements.
+        fn is_match<T: Eq>(self, xs: &[T]) -> bool {
+            match self {
+                Sequence::Abba => xs[0] == xs[3] && xs[1] == xs[2] && xs[0] != xs[1],
+                Sequence::Bab  => xs[0] == xs[2] && xs[0] != xs[1],
+            }
+        }
+    }
+
+    /// The `SequenceSearcher` state, depending on the `Backend` used.
+    enum Scanner<'a> {
+        Windows(Enumerate<Windows<'a, u8>>),
+        CharQueue {
+            it: CharIndices<'a>,
+            /// The last characters seen along with their byte offset.
+            queue: VecDeque<(usize, char)>,
+        },
+        /// The byte offset of the next window to check.
+        #[cfg(feature = "simd")]
+        Simd(usize),
+    }
+
+    /// A `Searcher` matching a given `Sequence`.
+    pub struct SequenceSearcher<'a> {
+        haystack: &'a str,
+        sequence: Sequence,
+        scanner: Scanner<'a>,
+    }
+
+    impl<'a> SequenceSearcher<'a> {
+        /// Create a new `SequenceSearcher` looking for `sequence` in `haystack`.
+        fn new(haystack: &'a str, sequence: Sequence, backend: Backend) -> SequenceSearcher<'a> {
+            let scanner = match backend {
+                // NOTE: byte windows could match (or split) a multi-byte character.
+                Backend::Windows if haystack.is_ascii() => {
+                    Scanner::Windows(haystack.as_bytes().windows(sequence.len()).enumerate())
+                },
+                #[cfg(feature = "simd")]
+                Backend::Simd if haystack.is_ascii() => Scanner::Simd(0),
+                _ => Scanner::CharQueue {
+                    it: haystack.char_indices(),
+                    queue: VecDeque::with_capacity(sequence.len()),
+                },
+            };
+            SequenceSearcher { haystack, sequence, scanner }
+        }
+    }
+
+    unsafe impl<'a> Searcher<'a> for SequenceSearcher<'a> {
+        fn haystack(&self) -> &'a str {
+            self.haystack
+        }
+
+        fn next(&mut self) -> SearchStep {
+            let sequence = self.sequence;
+            match self.scanner {
+                Scanner::Windows(ref mut it) => {
+                    if let Some((i, slice)) = it.next() {
+                        let end = i + sequence.len();
+                        if sequence.is_match(slice) {
+                            SearchStep::Match(i, end)
+                        } else {
+                            SearchStep::Reject(i, end)
+                        }
+                    } else {
+                        SearchStep::Done
+                    }
+                },
+                Scanner::CharQueue { ref mut it, ref mut queue } => {
+                    // fill the queue until we have enough characters to check for a match.
+                    while queue.len() < sequence.len() {
+                        match it.next() {
+                            Some(x) => queue.push_back(x),
+                            None    => return SearchStep::Done,
+                        }
+                    }
+                    // copy the characters out of the queue, 4 being the longest sequence, to avoid
+                    // allocating on every step.
+                    let mut chars = ['\0'; 4];
+                    for (dst, &(_, c)) in chars.iter_mut().zip(queue.iter()) {
+                        *dst = c;
+                    }
+                    let (start, _) = queue.pop_front().unwrap();
+                    let (last, c) = *queue.back().unwrap();
+                    let end = last + c.len_utf8();
+                    if sequence.is_match(&chars[..sequence.len()]) {
+                        SearchStep::Match(start, end)
+                    } else {
+                        SearchStep::Reject(start, end)
+                    }
+                },
+                #[cfg(feature = "simd")]
+                Scanner::Simd(ref mut position) => {
+                    let bytes = self.haystack.as_bytes();
+                    let start = *position;
+                    if start + sequence.len() > bytes.len() {
+                        return SearchStep::Done;
+                    }
+                    // reject every window up to the next match at once.
+                    match simd::find(bytes, start, sequence) {
+                        Some(i) if i == start => {
+                            *position = i + 1;
+                            SearchStep::Match(i, i + sequence.len())
+                        },
+                        Some(i) => {
+                            *position = i;
+                            SearchStep::Reject(start, i)
+                        },
+                        None => {
+                            *position = bytes.len();
+                            SearchStep::Reject(start, bytes.len())
+                        },
+                    }
+                },
+            }
+        }
+    }
+
+    /// Explicit SIMD search of the byte windows matching a `Sequence`.
+    #[cfg(feature = "simd")]
+    mod simd {
+        use super::Sequence;
+
+        /// Returns the offset of the first window of `bytes` matching `sequence` at or after
+        /// `from`, if any.
+        pub fn find(bytes: &[u8], from: usize, sequence: Sequence) -> Option<usize> {
+            #[cfg(target_arch = "x86_64")]
+            {
+                // SAFETY: SSE2 is part of the x86_64 baseline, AVX2 is checked at runtime.
+                if has_avx2() {
+                    unsafe { x86::find_avx2(bytes, from, sequence) }
+                } else {
+                    unsafe { x86::find_sse2(bytes, from, sequence) }
+                }
+            }
+            #[cfg(not(target_arch = "x86_64"))]
+            {
+                find_scalar(bytes, from, sequence)
+            }
+        }
+
+        /// The fallback of `find()`, checking one window at a time.
+        #[cfg_attr(target_arch = "x86_64", allow(dead_code))]
+        pub fn find_scalar(bytes: &[u8], from: usize, sequence: Sequence) -> Option<usize> {
+            bytes[from..].windows(sequence.len()).position(|w| sequence.is_match(w)).map(|i| from + i)
+        }
+
+        /// Returns `true` if the CPU supports AVX2, `false` otherwise.
+        #[cfg(all(target_arch = "x86_64", feature = "std"))]
+        fn has_avx2() -> bool {
+            is_x86_feature_detected!("avx2")
+        }
+
+        /// Returns `true` if the CPU supports AVX2, `false` otherwise.
+        // NOTE: the runtime detection needs `std`, without it only the target features count.
+        #[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+        fn has_avx2() -> bool {
+            cfg!(target_feature = "avx2")
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        mod x86 {
+            use ::core::arch::x86_64::*;
+            use super::super::Sequence;
+
+            /// Define a `find()` comparing `$lanes` windows at once with the `$feature` vector
+            /// instructions. `$v` is the vector type and the rest its intrinsics.
+            macro_rules! find_with {
+                ($name:ident, $feature:tt, $lanes:expr, $v:ty, $loadu:ident, $cmpeq:ident, $and:ident,
+                 $andnot:ident, $movemask:ident) => {
+                    #[target_feature(enable = $feature)]
+                    pub unsafe fn $name(bytes: &[u8], from: usize, sequence: Sequence) -> Option<usize> {
+                        let len = sequence.len();
+                        let mut block = from;
+                        while block + len <= bytes.len() {
+                            // NOTE: the windows starting in this block end in the next one, and
+                            // the segments are often shorter than a block, so the bytes are
+                            // copied with a zero padding when there are not enough.
+                            let mut padded = [0u8; $lanes + 3];
+                            let ptr = if block + $lanes + 3 <= bytes.len() {
+                                bytes[block..].as_ptr()
+                            } else {
+                                padded[..bytes.len() - block].copy_from_slice(&bytes[block..]);
+                                padded.as_ptr()
+                            };
+                            let x0 = $loadu(ptr as *const $v);
+                            let x1 = $loadu(ptr.add(1) as *const $v);
+                            let x2 = $loadu(ptr.add(2) as *const $v);
+                            let x01 = $cmpeq(x0, x1);
+                            let matches = match sequence {
+                                Sequence::Abba => {
+                                    let x3 = $loadu(ptr.add(3) as *const $v);
+                                    $andnot(x01, $and($cmpeq(x0, x3), $cmpeq(x1, x2)))
+                                },
+                                Sequence::Bab => $andnot(x01, $cmpeq(x0, x2)),
+                            };
+                            let mut bits = $movemask(matches) as u32 as u64;
+                            // only keep the windows ending in `bytes`.
+                            let windows = bytes.len() + 1 - len - block;
+                            if windows < $lanes {
+                                bits &= (1 << windows) - 1;
+                            }
+                            if bits != 0 {
+                                return Some(block + bits.trailing_zeros() as usize);
+                            }
+                            block += $lanes;
+                        }
+                        None
+                    }
+                };
+            }
+
+            find_with!(find_sse2, "sse2", 16, __m128i, _mm_loadu_si128, _mm_cmpeq_epi8, _mm_and_si128,
+                       _mm_andnot_si128, _mm_movemask_epi8);
+            find_with!(find_avx2, "avx2", 32, __m256i, _mm256_loadu_si256, _mm256_cmpeq_epi8,
+                       _mm256_and_si256, _mm256_andnot_si256, _mm256_movemask_epi8);
+        }
+    }
+
+    /// `Pattern` associated with `SequenceSearcher`.
+    pub struct SequencePattern {
+        sequence: Sequence,
+        backend: Backend,
+    }
+
+    impl SequencePattern {
+        /// Create a new `SequencePattern` matching all ABBA sequences.
+        pub fn abba(backend: Backend) -> SequencePattern {
+            SequencePattern { sequence: Sequence::Abba, backend }
+        }
+
+        /// Create a new `SequencePattern` matching all ABA/BAB sequences.
+        pub fn bab(backend: Backend) -> SequencePattern {
+            SequencePattern { sequence: Sequence::Bab, backend }
+        }
+    }
+
+    impl Pattern for SequencePattern {
+        type Searcher<'a> = SequenceSearcher<'a>;
+
+        fn into_searcher(self, haystack: &str) -> SequenceSearcher<'_> {
+            SequenceSearcher::new(haystack, self.sequence, self.backend)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod internet_protocol_version_7 {
+    use ::aoc_core::{Event, Sink, Span, Spanned};
+    use ::std::collections::{BTreeMap, HashMap, HashSet};
+    use ::std::fmt;
+    use ::std::io::{self, BufRead};
+    use ::std::iter::{self, Map};
+    use ::std::ops::Range;
+    use ::std::str::{FromStr, Matches};
+    use ::rand::Rng;
+    use ::report::InputError;
+    use ::sequences::{Backend, SequencePattern};
+
+    /// Represents an ABA/BAB pattern.
+    // We use `Bab` because `Aba` would be too easy to confuse with `Abba`.
+    #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
+    struct Bab {
+        b: char, // NOTE: the first and third character
+        a: char, // NOTE: the second character
+    }
+
+    impl Bab {
+        /// returns the logical inverse of self (eg. 'aba' when self is 'bab').
+        fn inverse(&self) -> Bab {
+            Bab { b: self.a, a: self.b }
+        }
+    }
+
+    impl FromStr for Bab {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Bab, String> {
+            let mut it = s.chars();
+            let (b, a, b2) = match (it.next(), it.next(), it.next(), it.next()) {
+                (Some(b), Some(a), Some(b2), None) => (b, a, b2),
+                _ => return Err("empty ABA/BAB string".to_string()),
+            };
+            if b != b2 {
+                return Err("non-ABA/BAB string".to_string());
+            }
+            Ok(Bab { b, a })
+        }
+    }
+
+    /// Represents an `Ipv7Addr` "segment", either an hypernet or a supernet.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    struct Segment<'a> {
+        /// `true` if this `Segment` is hypernet, false otherwise (supernet).
+        hypernet: bool,
+        /// Borrowed from the `Ipv7Addr` address.
+        number: &'a str,
+    }
+
+    impl<'a> Segment<'a> {
+        /// Returns `true` if self is a hypernet segment, `false` otherwise.
+        fn is_hypernet(&self) -> bool {
+            self.hypernet
+        }
+
+        /// Returns `true` if self is a supernet segment, `false` otherwise.
+        fn is_supernet(&self) -> bool {
+            !self.hypernet
+        }
+
+        /// Returns `true` if self contains an ABBA pattern, `false` otherwise.
+        fn has_abba(&self, backend: Backend) -> bool {
+            // XXX: could be cached because matching is costly, but we only call it once per
+            // `Segment` so that's ok for now.
+            self.abba(backend).is_some()
+        }
+
+        /// Returns the first ABBA pattern contained in self, if any.
+        fn abba(&self, backend: Backend) -> Option<&'a str> {
+            self.number.matches(SequencePattern::abba(backend)).next()
+        }
+
+        /// Returns an iterator over all the `Bab` patterns contained in self.
+        fn bab(&self, backend: Backend) -> Map<Matches<'a, SequencePattern>, fn(&str) -> Bab>
+        {
+            // https://www.reddit.com/r/rust/comments/31x7jj/returning_iterators_from_a_function/
+            // helped me a lot here.
+            fn parse(s: &str) -> Bab {
+                s.parse().unwrap()
+            }
+            self.number.matches(SequencePattern::bab(backend)).map(parse as fn(&str) -> Bab)
+        }
+
+        /// Returns an iterator over all the ABA/BAB sequences contained in self, as they appear.
+        fn bab_matches(&self, backend: Backend) -> Matches<'a, SequencePattern> {
+            self.number.matches(SequencePattern::bab(backend))
+        }
+    }
+
+    /// Where a `Segment` is in its `Ipv7Addr` address.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct SegmentSpan {
+        hypernet: bool,
+        /// The byte range of the segment number, hypernet markers excluded.
+        range: Range<usize>,
+    }
+
+    /// Represents an IPv7 from the local network of Easter Bunny HQ.
+    ///
+    /// The address is kept as a whole and its segments are borrowed from it, so that parsing
+    /// only allocates twice however many segments there are.
+    #[derive(Debug, Eq, PartialEq)]
+    pub struct Ipv7Addr {
+        address: String,
+        spans: Vec<SegmentSpan>,
+    }
+
+    impl Ipv7Addr {
+        /// Returns an iterator over the segments of self, in order.
+        fn segments(&self) -> impl Iterator<Item = Segment<'_>> {
+            self.spans.iter().map(move |span| Segment {
+                hypernet: span.hypernet,
+                number: &self.address[span.range.clone()],
+            })
+        }
+
+
+        /// Returns `true` if self has TLS (transport-layer snooping) support, `false` otherwise.
+        ///
+        /// > An IP supports TLS if it has an Autonomous Bridge Bypass Annotation, or ABBA […]
+        /// > However, the IP also must not have an ABBA within any hypernet sequences […]
+        pub fn has_tls_support(&self) -> bool {
+            self.has_tls_support_using(Backend::default())
+        }
+
+        /// Returns `true` if self has TLS support, `false` otherwise, scanning the segments with
+        /// the given `backend`.
+        pub fn has_tls_support_using(&self, backend: Backend) -> bool {
+            // we have four cases to consider:
+            //
+            // 1. one  of our hypernet segments has ABBA and one  of our supernet segments has ABBA
+            // 2. one  of our hypernet segments has ABBA and none of our supernet segments has ABBA
+            // 3. none of our hypernet segments has ABBA and one  of our supernet segments has ABBA
+            // 4. none of our hypernet segments has ABBA and none of our supernet segments has ABBA
+            //
+            // Of the four cases only one, namely #3, is a success (i.e. has TLS support). #1 and
+            // #2 fail because of one of our hypernet segment has ABBA and #4 fail because of the
+            // lack of any supernet segment with ABBA.
+            //
+            // Here we're considering the analyze order between our hypernet segments first vs our
+            // supernet segments first. Since we don't have any clue and to simplify our reasoning
+            // we consider that having ABBA is equally likely in a hypernet segment and a supernet
+            // segment of the same length.
+            //
+            // Intuitively, we find that analyzing our hypernet segments first should be faster
+            // because we can "shortcut" (i.e. skip analyzing our supernet segments) in cases #1
+            // and #2 as soon as the first hypernet segment with ABBA is found. If we analyze our
+            // supernet segments first we can "shortcut" in cases #2 and #4 but only after having
+            // analyzing all of them.
+            let mut hypernets = self.segments().filter(Segment::is_hypernet);
+            let mut supernets = self.segments().filter(Segment::is_supernet);
+            !hypernets.any(|seg| seg.has_abba(backend)) && supernets.any(|seg| seg.has_abba(backend))
+        }
+
+        /// Returns `true` if self has SSL (super-secret listening) support, `false` otherwise.
+        ///
+        /// > An IP supports SSL if it has an Area-Broadcast Accessor, or ABA, anywhere in the
+        /// > supernet sequences (outside any square bracketed sections), and a corresponding Byte
+        /// > Allocation Block, or BAB, anywhere in the hypernet sequences.
+        pub fn has_ssl_support(&self) -> bool {
+            self.has_ssl_support_using(Backend::default())
+        }
+
+        /// Returns `true` if self has SSL support, `false` otherwise, scanning the segments with
+        /// the given `backend`.
+        pub fn has_ssl_support_using(&self, backend: Backend) -> bool {
+            let mut hypernets = self.segments().filter(Segment::is_hypernet);
+            let     supernets = self.segments().filter(Segment::is_supernet);
+            // collect from all the Area-Broadcast Accessor from the supernet sequences.
+            let mut babset = HashSet::new();
+            for snet in supernets {
+                for aba in snet.bab(backend) {
+                    babset.insert(aba.inverse());
+                }
+            }
+            // If we did not find any ABA we're done.
+            if babset.is_empty() {
+                return false;
+            }
+            // look through our hypernet for the first BAB match.
+            hypernets.any(|seg| {
+                seg.bab(backend).any(|bab| babset.contains(&bab))
+            })
+        }
+
+        /// Returns the supernet ABBA sequence proving that self has TLS support, or `None` if
+        /// self doesn't support TLS.
+        pub fn tls_witness(&self, backend: Backend) -> Option<&str> {
+            if !self.has_tls_support_using(backend) {
+                return None;
+            }
+            self.segments()
+                .filter(Segment::is_supernet)
+                .filter_map(|seg| seg.abba(backend))
+                .next()
+        }
+
+        /// Returns the supernet ABA and its corresponding hypernet BAB proving that self has SSL
+        /// support, or `None` if self doesn't support SSL.
+        pub fn ssl_witness(&self, backend: Backend) -> Option<(&str, &str)> {
+            let supernets = self.segments().filter(Segment::is_supernet);
+            for aba in supernets.flat_map(|seg| seg.bab_matches(backend)) {
+                let expected = aba.parse::<Bab>().unwrap().inverse();
+                let mut hypernets = self.segments().filter(Segment::is_hypernet);
+                let found = hypernets.find_map(|seg| {
+                    seg.bab_matches(backend).find(|bab| bab.parse::<Bab>().unwrap() == expected)
+                });
+                if let Some(bab) = found {
+                    return Some((aba, bab));
+                }
+            }
+            None
+        }
+
+        /// Explain the support of self: the `tls_witness()` and `ssl_witness()` found, if any.
+        pub fn explain(&self, backend: Backend, sink: &mut dyn Sink) {
+            let address = self.to_string();
+            if let Some(abba) = self.tls_witness(backend) {
+                sink.emit(&Event::new("tls_witness", &[("address", address.as_str().into()), ("abba", abba.into())]));
+            }
+            if let Some((aba, bab)) = self.ssl_witness(backend) {
+                sink.emit(&Event::new("ssl_witness", &[
+                    ("address", address.as_str().into()),
+                    ("aba", aba.into()),
+                    ("bab", bab.into()),
+                ]));
+            }
+        }
+    }
+
+    impl Ipv7Addr {
+        /// Returns statistics about the segments of self.
+        pub fn stats(&self) -> Stats {
+            let mut stats = Stats { addresses: 1, ..Stats::default() };
+            for seg in self.segments() {
+                let len = seg.number.chars().count();
+                if seg.is_hypernet() {
+                    stats.hypernets += 1;
+                    stats.hypernet_len += len;
+                } else {
+                    stats.supernets += 1;
+                    stats.supernet_len += len;
+                }
+                stats.longest_segment = stats.longest_segment.max(len);
+            }
+            stats
+        }
+    }
+
+    /// Segments statistics about one or many `Ipv7Addr`.
+    ///
+    /// Lengths are expressed in characters. `Stats` can be summed to aggregate the statistics of
+    /// many addresses.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Stats {
+        /// The number of `Ipv7Addr` accounted for.
+        pub addresses: usize,
+        /// The number of supernet segments.
+        pub supernets: usize,
+        /// The number of hypernet segments.
+        pub hypernets: usize,
+        /// The total length of the supernet segments.
+        pub supernet_len: usize,
+        /// The total length of the hypernet segments.
+        pub hypernet_len: usize,
+        /// The length of the longest segment, either supernet or hypernet.
+        pub longest_segment: usize,
+    }
+
+    impl Stats {
+        /// Aggregate `other` into self.
+        pub fn merge(&mut self, other: &Stats) {
+            self.addresses += other.addresses;
+            self.supernets += other.supernets;
+            self.hypernets += other.hypernets;
+            self.supernet_len += other.supernet_len;
+            self.hypernet_len += other.hypernet_len;
+            self.longest_segment = self.longest_segment.max(other.longest_segment);
+        }
+    }
+
+    impl iter::Sum for Stats {
+        fn sum<I: Iterator<Item=Stats>>(iter: I) -> Stats {
+            iter.fold(Stats::default(), |mut acc, stats| {
+                acc.merge(&stats);
+                acc
+            })
+        }
+    }
+
+    impl fmt::Display for Stats {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            writeln!(f, "addresses: {}", self.addresses)?;
+            writeln!(f, "supernets: {} segments, {} characters", self.supernets, self.supernet_len)?;
+            writeln!(f, "hypernets: {} segments, {} characters", self.hypernets, self.hypernet_len)?;
+            write!(f, "longest segment: {} characters", self.longest_segment)
+        }
+    }
+
+    /// The composition of many `Ipv7Addr`, to characterize an input (e.g. a fuzz corpus) beyond
+    /// the `Stats` totals: how long their segments are and which ABA sequences they share.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Composition {
+        /// The segments statistics of the addresses.
+        pub stats: Stats,
+        /// The number of segments, either supernet or hypernet, by length in characters.
+        pub segment_lengths: BTreeMap<usize, usize>,
+        /// The number of occurrences of every ABA sequence found in the supernet segments.
+        abas: HashMap<String, usize>,
+    }
+
+    impl Composition {
+        /// Create a new `Composition` of no address.
+        pub fn new() -> Composition {
+            Composition::default()
+        }
+
+        /// Account for the given `ip`, looking for its ABA sequences using `backend`.
+        pub fn register(&mut self, ip: &Ipv7Addr, backend: Backend) {
+            self.stats.merge(&ip.stats());
+            for seg in ip.segments() {
+                *self.segment_lengths.entry(seg.number.chars().count()).or_insert(0) += 1;
+                if seg.is_supernet() {
+                    for aba in seg.bab_matches(backend) {
+                        *self.abas.entry(aba.to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        /// Returns the average number of supernet segments per address, zero without address.
+        pub fn average_supernets(&self) -> f64 {
+            average(self.stats.supernets, self.stats.addresses)
+        }
+
+        /// Returns the average number of hypernet segments per address, zero without address.
+        pub fn average_hypernets(&self) -> f64 {
+            average(self.stats.hypernets, self.stats.addresses)
+        }
+
+        /// Returns the (at most) `n` most common ABA sequences along with their occurrences, the
+        /// most common first and ties in lexicographic order.
+        pub fn most_common_abas(&self, n: usize) -> Vec<(&str, usize)> {
+            let mut abas: Vec<_> = self.abas.iter().map(|(aba, &count)| (aba.as_str(), count)).collect();
+            abas.sort_by(|&(a, acount), &(b, bcount)| bcount.cmp(&acount).then(a.cmp(b)));
+            abas.truncate(n);
+            abas
+        }
+    }
+
+    /// Returns `total / count`, zero when `count` is zero.
+    fn average(total: usize, count: usize) -> f64 {
+        if count == 0 {
+            0.0
+        } else {
+            total as f64 / count as f64
+        }
+    }
+
+    impl<'a> iter::FromIterator<&'a Ipv7Addr> for Composition {
+        fn from_iter<I: IntoIterator<Item=&'a Ipv7Addr>>(iter: I) -> Composition {
+            let mut composition = Composition::new();
+            for ip in iter {
+                composition.register(ip, Backend::default());
+            }
+            composition
+        }
+    }
+
+    impl fmt::Display for Composition {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            writeln!(f, "{}", self.stats)?;
+            writeln!(f, "segments per address: {:.2} supernets, {:.2} hypernets",
+                     self.average_supernets(), self.average_hypernets())?;
+            write!(f, "segment lengths:")?;
+            for (len, count) in &self.segment_lengths {
+                write!(f, " {}×{}", len, count)?;
+            }
+            write!(f, "\nmost common ABA:")?;
+            for (aba, count) in self.most_common_abas(MOST_COMMON_ABAS) {
+                write!(f, " {}×{}", aba, count)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// The number of ABA sequences shown when displaying a `Composition`.
+    const MOST_COMMON_ABAS: usize = 5;
+
+    impl fmt::Display for Ipv7Addr {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for seg in self.segments() {
+                if seg.is_hypernet() {
+                    write!(f, "{}{}{}", HYPERNET_START, seg.number, HYPERNET_STOP)?;
+                } else {
+                    write!(f, "{}", seg.number)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// The hypernet start/stop markers in an `Ipv7Addr`.
+    const HYPERNET_START: char = '[';
+    const HYPERNET_STOP:  char = ']';
+
+    /// Character handling options used when parsing an `Ipv7Addr`.
+    ///
+    /// The default options are lenient: characters are matched as-is and anything goes.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub struct ParseOptions {
+        /// When `true`, the address is lowercased so that matching ignore the case.
+        pub ignore_case: bool,
+        /// When `true`, segments characters outside of `[a-z]` and unterminated hypernet
+        /// sequences are rejected.
+        pub strict: bool,
+    }
+
+    impl Ipv7Addr {
+        /// Parse an `Ipv7Addr` from `s` according to the given `options`.
+        ///
+        /// Returns an error describing the first violation found when `options.strict` is set.
+        pub fn parse_with(s: &str, options: ParseOptions) -> Result<Ipv7Addr, Spanned<String>> {
+            let address = if options.ignore_case {
+                s.to_lowercase()
+            } else {
+                s.to_string()
+            };
+            // NOTE: there are at most two segments per hypernet start marker, plus the leading
+            // supernet.
+            let mut spans = Vec::with_capacity(2 * address.matches(HYPERNET_START).count() + 1);
+            let mut start = 0;
+            let mut target = HYPERNET_START;
+            for (column, (i, c)) in address.char_indices().enumerate() {
+                if c == target {
+                    spans.push(SegmentSpan {
+                        hypernet: (target == HYPERNET_STOP),
+                        range: start..i,
+                    });
+                    // update state for the next segment
+                    start = i + 1;
+                    target = if target == HYPERNET_START {
+                        HYPERNET_STOP
+                    } else {
+                        HYPERNET_START
+                    };
+                } else if options.strict && !c.is_ascii_lowercase() {
+                    return Err(Spanned::new(Span::new(1, column + 1, 1), format!("invalid character {:?}", c)));
+                }
+            }
+            if options.strict && target == HYPERNET_STOP {
+                // NOTE: from the hypernet start marker to the end of the address.
+                return Err(Spanned::of(&address, start - 1..address.len(), "unterminated hypernet sequence".to_string()));
+            }
+            // trailing supernet handling
+            if start < address.len() {
+                spans.push(SegmentSpan {
+                    hypernet: false,
+                    range: start..address.len(),
+                });
+            }
+            Ok(Ipv7Addr { address, spans })
+        }
+    }
+
+    impl FromStr for Ipv7Addr {
+        type Err = Spanned<String>;
+
+        fn from_str(s: &str) -> Result<Ipv7Addr, Spanned<String>> {
+            Ipv7Addr::parse_with(s, ParseOptions::default())
+        }
+    }
+
+    /// Error returned when reading a `Census` fails.
+    #[derive(Debug)]
+    pub enum CensusError {
+        /// The reader failed.
+        Io(io::Error),
+        /// A line could not be parsed as an `Ipv7Addr`.
+        Parse(InputError),
+    }
+
+    impl fmt::Display for CensusError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                CensusError::Io(ref e) => write!(f, "{}", e),
+                CensusError::Parse(ref e) => write!(f, "{}", e),
+            }
+        }
+    }
+
+    impl ::std::error::Error for CensusError {}
+
+    impl From<CensusError> for InputError {
+        fn from(error: CensusError) -> InputError {
+            match error {
+                CensusError::Io(e) => InputError::new(e),
+                CensusError::Parse(e) => e,
+            }
+        }
+    }
+
+    /// Running counters of `Ipv7Addr` classified by the protocols they support.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Census {
+        /// The number of `Ipv7Addr` registered.
+        pub total: usize,
+        /// The number of `Ipv7Addr` with TLS (transport-layer snooping) support.
+        pub tls: usize,
+        /// The number of `Ipv7Addr` with SSL (super-secret listening) support.
+        pub ssl: usize,
+    }
+
+    impl Census {
+        /// Create a new `Census` with all counters at zero.
+        pub fn new() -> Census {
+            Census::default()
+        }
+
+        /// Classify the given `ip` using `backend` and update the counters accordingly.
+        pub fn register(&mut self, ip: &Ipv7Addr, backend: Backend) {
+            self.total += 1;
+            if ip.has_tls_support_using(backend) {
+                self.tls += 1;
+            }
+            if ip.has_ssl_support_using(backend) {
+                self.ssl += 1;
+            }
+        }
+
+        /// Parse one `Ipv7Addr` per line from `reader` according to `options` and count them.
+        /// Blank lines are skipped, like in `report::parse_lines()`.
+        ///
+        /// The addresses are classified and dropped as soon as they are read, so the memory usage
+        /// only depends on the longest line and not on the input size.
+        pub fn from_reader<R: BufRead>(reader: R, options: ParseOptions, backend: Backend)
+            -> Result<Census, CensusError>
+        {
+            Census::from_reader_with(reader, options, backend, |_| ())
+        }
+
+        /// Like `from_reader()`, but `inspect` is called with every `Ipv7Addr` before it is
+        /// dropped.
+        pub fn from_reader_with<R, F>(mut reader: R, options: ParseOptions, backend: Backend,
+                                      mut inspect: F) -> Result<Census, CensusError>
+            where R: BufRead, F: FnMut(&Ipv7Addr)
+        {
+            let mut census = Census::new();
+            let mut line = String::new();
+            let mut line_no = 0;
+            while reader.read_line(&mut line).map_err(CensusError::Io)? > 0 {
+                line_no += 1;
+                {
+                    let trimmed = line.trim_end_matches(&['\n', '\r'][..]);
+                    if trimmed.trim().is_empty() {
+                        line.clear();
+                        continue;
+                    }
+                    let ip = Ipv7Addr::parse_with(trimmed, options).map_err(|e| {
+                        CensusError::Parse(InputError::in_line(line_no, trimmed, e))
+                    })?;
+                    census.register(&ip, backend);
+                    inspect(&ip);
+                }
+                line.clear();
+            }
+            Ok(census)
+        }
+    }
+
+    /// Returns `size` random IPv7 addresses, formatted like the puzzle input.
+    ///
+    /// Random letters rarely make an ABBA or ABA, so some are injected in about a third of the
+    /// addresses each, either in a supernet or a hypernet sequence.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            // an odd number of alternating supernet and hypernet sequences, starting and ending
+            // with a supernet.
+            let mut sequences: Vec<Vec<u8>> = (0..rng.gen_range(3, 8) | 1).map(|_| {
+                (0..rng.gen_range(8, 20)).map(|_| rng.gen_range(b'a', b'z' + 1)).collect()
+            }).collect();
+            let (a, b) = (rng.gen_range(b'a', b'z' + 1), rng.gen_range(b'a', b'z'));
+            // NOTE: the inner characters must differ from the outer ones.
+            let b = if b >= a { b + 1 } else { b };
+            match rng.gen_range(0, 3) {
+                0 => {
+                    let i = rng.gen_range(0, sequences.len());
+                    sequences[i].extend_from_slice(&[a, b, b, a]);
+                },
+                1 => {
+                    let supernet = rng.gen_range(0, sequences.len() / 2 + 1) * 2;
+                    let hypernet = rng.gen_range(0, sequences.len() / 2) * 2 + 1;
+                    sequences[supernet].extend_from_slice(&[a, b, a]);
+                    sequences[hypernet].extend_from_slice(&[b, a, b]);
+                },
+                _ => (),
+            }
+            for (i, sequence) in sequences.iter().enumerate() {
+                if i % 2 == 1 {
+                    input.push('[');
+                }
+                input.extend(sequence.iter().map(|&ch| char::from(ch)));
+                if i % 2 == 1 {
+                    input.push(']');
+                }
+            }
+            input.push('\n');
+        }
+        input
+    }
+}
+
+pub use sequences::*;
+#[cfg(feature = "std")]
+pub use internet_protocol_version_7::*;
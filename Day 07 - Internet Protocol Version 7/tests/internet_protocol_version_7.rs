@@ -0,0 +1,282 @@
+extern crate internet_protocol_version_7;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+
+use internet_protocol_version_7::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn part1_first_example() {
+    let ip: Ipv7Addr = "abba[mnop]qrst".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(ip.has_tls_support());
+}
+
+#[test]
+fn part1_second_example() {
+    let ip: Ipv7Addr = "abcd[bddb]xyyx".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(!ip.has_tls_support());
+}
+
+#[test]
+fn part1_third_example() {
+    let ip: Ipv7Addr = "aaaa[qwer]tyui".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(!ip.has_tls_support());
+}
+
+#[test]
+fn part1_fourth_example() {
+    let ip: Ipv7Addr = "ioxxoj[asdfgh]zxcvbn".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(ip.has_tls_support());
+}
+
+#[test]
+fn part2_first_example() {
+    let ip: Ipv7Addr = "aba[bab]xyz".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(ip.has_ssl_support());
+}
+
+#[test]
+fn part2_second_example() {
+    let ip: Ipv7Addr = "xyx[xyx]xyx".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(!ip.has_ssl_support());
+}
+
+#[test]
+fn part2_third_example() {
+    let ip: Ipv7Addr = "aaa[kek]eke".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(ip.has_ssl_support());
+}
+
+#[test]
+fn part2_fourth_example() {
+    let ip: Ipv7Addr = "zazbz[bzb]cdb".parse().unwrap();
+    println!("{:?}", ip);
+    assert!(ip.has_ssl_support());
+}
+
+/// Every backend available with the enabled features.
+const BACKENDS: &[Backend] = &[
+    Backend::Windows,
+    Backend::CharQueue,
+    #[cfg(feature = "simd")]
+    Backend::Simd,
+];
+
+#[test]
+fn sequence_patterns() {
+    for &backend in BACKENDS {
+        let abba: Vec<&str> = "ioxxojasdfgh".matches(SequencePattern::abba(backend)).collect();
+        assert_eq!(abba, vec!["oxxo"]);
+        let bab: Vec<&str> = "zazbz".matches(SequencePattern::bab(backend)).collect();
+        assert_eq!(bab, vec!["zaz", "zbz"]);
+    }
+}
+
+#[test]
+fn census_from_reader() {
+    let input = "abba[mnop]qrst\r\nabcd[bddb]xyyx\naba[bab]xyz\n\naaa[kek]eke";
+    let census = Census::from_reader(input.as_bytes(), ParseOptions::default(), Backend::default())
+        .unwrap();
+    // the blank line is not an address.
+    assert_eq!(census, Census { total: 4, tls: 1, ssl: 2 });
+    let input = "abba[mnop]qrst\n  \nabba[mnop\n";
+    let strict = ParseOptions { ignore_case: false, strict: true };
+    match Census::from_reader(input.as_bytes(), strict, Backend::default()) {
+        Err(CensusError::Parse(e)) => {
+            assert_eq!((e.line_no, e.text), (Some(3), Some("abba[mnop".to_string())));
+            assert_eq!(e.span.map(|span| (span.line, span.col)), Some((3, 5)));
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn parse_ignore_case() {
+    let options = ParseOptions { ignore_case: true, strict: false };
+    let ip = Ipv7Addr::parse_with("abBA[mnop]qrst", options).unwrap();
+    assert!(ip.has_tls_support());
+    let ip: Ipv7Addr = "abBA[mnop]qrst".parse().unwrap();
+    assert!(!ip.has_tls_support());
+}
+
+#[test]
+fn parse_strict() {
+    let options = ParseOptions { ignore_case: false, strict: true };
+    assert!(Ipv7Addr::parse_with("abba[mnop]qrst", options).is_ok());
+    assert_eq!(Ipv7Addr::parse_with("abba[mnOp]qrst", options).unwrap_err().to_string(),
+               "line 1, column 8: invalid character 'O'");
+    assert_eq!(Ipv7Addr::parse_with("ab[ba[mnop]]", options).unwrap_err().to_string(),
+               "line 1, column 6: invalid character '['");
+    let error = Ipv7Addr::parse_with("abba[mnop", options).unwrap_err();
+    assert_eq!((error.span.col, error.span.len, error.error.as_str()), (5, 5, "unterminated hypernet sequence"));
+    // normalization happens before validation.
+    let options = ParseOptions { ignore_case: true, strict: true };
+    assert!(Ipv7Addr::parse_with("ABBA[MNOP]QRST", options).is_ok());
+}
+
+#[test]
+fn display_round_trip() {
+    for s in &["abba[mnop]qrst", "ioxxoj[asdfgh]zxcvbn", "[a]b[c]", "zazbz[bzb]cdb"] {
+        let ip: Ipv7Addr = s.parse().unwrap();
+        assert_eq!(ip.to_string(), s.to_string());
+    }
+}
+
+#[test]
+fn witnesses() {
+    let ip: Ipv7Addr = "ioxxoj[asdfgh]zxcvbn".parse().unwrap();
+    assert_eq!(ip.tls_witness(Backend::default()), Some("oxxo"));
+    let ip: Ipv7Addr = "abcd[bddb]xyyx".parse().unwrap();
+    assert_eq!(ip.tls_witness(Backend::default()), None);
+    let ip: Ipv7Addr = "zazbz[bzb]cdb".parse().unwrap();
+    assert_eq!(ip.ssl_witness(Backend::CharQueue), Some(("zbz", "bzb")));
+    let ip: Ipv7Addr = "xyx[xyx]xyx".parse().unwrap();
+    assert_eq!(ip.ssl_witness(Backend::default()), None);
+}
+
+#[test]
+fn explain() {
+    let mut events: Vec<String> = Vec::new();
+    for line in &["ioxxoj[asdfgh]zxcvbn", "zazbz[bzb]cdb", "abba[aba]bab", "xyx[xyx]xyx"] {
+        line.parse::<Ipv7Addr>().unwrap().explain(Backend::default(), &mut events);
+    }
+    assert_eq!(events, [
+        r#"{"event":"tls_witness","address":"ioxxoj[asdfgh]zxcvbn","abba":"oxxo"}"#,
+        r#"{"event":"ssl_witness","address":"zazbz[bzb]cdb","aba":"zbz","bab":"bzb"}"#,
+        r#"{"event":"tls_witness","address":"abba[aba]bab","abba":"abba"}"#,
+        r#"{"event":"ssl_witness","address":"abba[aba]bab","aba":"bab","bab":"aba"}"#,
+    ]);
+}
+
+#[test]
+fn address_stats() {
+    let ip: Ipv7Addr = "zazbz[bzb]cdb[a]".parse().unwrap();
+    assert_eq!(ip.stats(), Stats {
+        addresses: 1,
+        supernets: 2,
+        hypernets: 2,
+        supernet_len: 8,
+        hypernet_len: 4,
+        longest_segment: 5,
+    });
+}
+
+#[test]
+fn aggregated_stats() {
+    let input = "abba[mnop]qrst\nioxxoj[asdfgh]zxcvbn\naba[bab]xyz";
+    let stats: Stats = input.lines().map(|line| line.parse::<Ipv7Addr>().unwrap().stats()).sum();
+    assert_eq!(stats, Stats {
+        addresses: 3,
+        supernets: 6,
+        hypernets: 3,
+        supernet_len: 26,
+        hypernet_len: 13,
+        longest_segment: 6,
+    });
+}
+
+#[test]
+fn address_composition() {
+    let input = "abba[mnop]qrst\nioxxoj[asdfgh]zxcvbn\naba[bab]xyz\nxyxzaza[z]xyx";
+    let ips: Vec<Ipv7Addr> = input.lines().map(|line| line.parse().unwrap()).collect();
+    let composition: Composition = ips.iter().collect();
+    assert_eq!(composition.stats.addresses, 4);
+    assert_eq!(composition.average_supernets(), 2.0);
+    assert_eq!(composition.average_hypernets(), 1.0);
+    let lengths: Vec<_> = composition.segment_lengths.into_iter().collect();
+    assert_eq!(lengths, vec![(1, 1), (3, 4), (4, 3), (6, 3), (7, 1)]);
+    let composition: Composition = ips.iter().collect();
+    assert_eq!(composition.most_common_abas(2), vec![("xyx", 2), ("aba", 1)]);
+    assert_eq!(composition.most_common_abas(10).len(), 4);
+    assert_eq!(Composition::new().average_hypernets(), 0.0);
+}
+
+#[test]
+fn backends_agree() {
+    let input = "abba[mnop]qrst\nabcd[bddb]xyyx\naaaa[qwer]tyui\nioxxoj[asdfgh]zxcvbn\n\
+                 aba[bab]xyz\nxyx[xyx]xyx\naaa[kek]eke\nzazbz[bzb]cdb";
+    for line in input.lines() {
+        let ip: Ipv7Addr = line.parse().unwrap();
+        assert_eq!(ip.has_tls_support_using(Backend::Windows),
+                   ip.has_tls_support_using(Backend::CharQueue));
+        assert_eq!(ip.has_ssl_support_using(Backend::Windows),
+                   ip.has_ssl_support_using(Backend::CharQueue));
+    }
+}
+
+#[test]
+fn char_queue_backend_non_ascii() {
+    let ip: Ipv7Addr = "éaaé[bcd]xyz".parse().unwrap();
+    assert!(ip.has_tls_support_using(Backend::CharQueue));
+    let ip: Ipv7Addr = "éaé[aéa]xyz".parse().unwrap();
+    assert!(ip.has_ssl_support_using(Backend::CharQueue));
+}
+
+proptest! {
+    #[test]
+    fn ipv7addr_round_trip(s in "[a-z]{0,8}(\\[[a-z]{0,8}\\][a-z]{0,8}){0,4}") {
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        let ip = Ipv7Addr::parse_with(&s, strict).unwrap();
+        prop_assert_eq!(ip.to_string(), s.clone());
+        prop_assert_eq!(s.parse::<Ipv7Addr>(), Ok(ip));
+    }
+
+    #[test]
+    fn ipv7addr_parse_never_panics(s in "[a-zA-Z\\[\\]]{0,20}|\\PC*", ignore_case: bool) {
+        let lenient = ParseOptions { ignore_case, strict: false };
+        let strict = ParseOptions { ignore_case, strict: true };
+        prop_assert!(Ipv7Addr::parse_with(&s, lenient).is_ok());
+        if let Ok(ip) = Ipv7Addr::parse_with(&s, strict) {
+            prop_assert_eq!(ip.to_string().parse::<Ipv7Addr>(), Ok(ip));
+        }
+    }
+
+    #[test]
+    fn backends_agree_on_any_input(s in "[aÃé\\[\\]]{0,20}|\\PC*") {
+        let ip: Ipv7Addr = s.parse().unwrap();
+        prop_assert_eq!(ip.has_tls_support_using(Backend::Windows), ip.has_tls_support_using(Backend::CharQueue));
+        prop_assert_eq!(ip.has_ssl_support_using(Backend::Windows), ip.has_ssl_support_using(Backend::CharQueue));
+        prop_assert_eq!(ip.tls_witness(Backend::Windows), ip.tls_witness(Backend::CharQueue));
+        prop_assert_eq!(ip.ssl_witness(Backend::Windows), ip.ssl_witness(Backend::CharQueue));
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_finds_the_same_sequences(s in "[ab]{0,80}|[a-z]{0,80}") {
+        for &backend in &[Backend::Windows, Backend::Simd] {
+            let abba: Vec<_> = s.match_indices(SequencePattern::abba(backend)).collect();
+            let bab: Vec<_> = s.match_indices(SequencePattern::bab(backend)).collect();
+            prop_assert_eq!(abba, s.match_indices(SequencePattern::abba(Backend::CharQueue)).collect::<Vec<_>>());
+            prop_assert_eq!(bab, s.match_indices(SequencePattern::bab(Backend::CharQueue)).collect::<Vec<_>>());
+        }
+    }
+}
+
+#[test]
+fn non_ascii_addresses() {
+    // "Ã" is two bytes long, and its second byte matches the byte windows of "ÃÃÃ".
+    let ip: Ipv7Addr = "aÃÃa[ÃaÃ]aÃa".parse().unwrap();
+    assert!(ip.has_tls_support_using(Backend::Windows));
+    assert!(ip.has_ssl_support_using(Backend::Windows));
+    assert_eq!(ip.ssl_witness(Backend::Windows), Some(("aÃa", "ÃaÃ")));
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 3000);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 3000));
+    let census = Census::from_reader(input.as_bytes(), ParseOptions { strict: true, ..ParseOptions::default() },
+                                     Backend::default()).unwrap();
+    assert_eq!(census.total, 3000);
+    assert!(census.tls > 500 && census.tls < 1000, "{:?}", census);
+    assert!(census.ssl > 800 && census.ssl < 1200, "{:?}", census);
+}
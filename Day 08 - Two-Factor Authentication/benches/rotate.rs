@@ -0,0 +1,54 @@
+//! Day 8 column rotations: the word-level rotation of the screen against the previous per-pixel
+//! implementation, on a tall screen so that a column spans many words.
+
+#[macro_use]
+extern crate criterion;
+extern crate two_factor_authentication;
+
+use criterion::Criterion;
+use two_factor_authentication::*;
+
+const WIDTH: usize = 50;
+const HEIGHT: usize = 4096;
+/// How many columns are rotated in each iteration.
+const ROUNDS: usize = 2000;
+
+/// Returns the column and offset of the `i`th rotation.
+fn rotation(i: usize) -> (usize, usize) {
+    (i % WIDTH, (i * 7) % HEIGHT)
+}
+
+fn rotate_col(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day08");
+    group.bench_function("rotate_col/per-pixel", |b| {
+        b.iter(|| {
+            let mut pixels = vec![false; WIDTH * HEIGHT];
+            for px in pixels.iter_mut().take(WIDTH * 3) {
+                *px = true;
+            }
+            for i in 0..ROUNDS {
+                let (x, yoffset) = rotation(i);
+                let col: Vec<bool> = (0..HEIGHT).map(|y| pixels[y * WIDTH + x]).collect();
+                for (y, &px) in col.iter().enumerate() {
+                    pixels[((y + yoffset) % HEIGHT) * WIDTH + x] = px;
+                }
+            }
+            pixels
+        })
+    });
+    group.bench_function("rotate_col/word-level", |b| {
+        b.iter(|| {
+            let mut screen = Screen::blank(WIDTH, HEIGHT);
+            screen.execute(Operation::Rect(WIDTH as u32, 3)).unwrap();
+            for i in 0..ROUNDS {
+                let (x, yoffset) = rotation(i);
+                screen.execute(Operation::RotateCol(x as u32, yoffset as i32)).unwrap();
+            }
+            screen
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, rotate_col);
+criterion_main!(benches);
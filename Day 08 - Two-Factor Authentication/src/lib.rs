@@ -0,0 +1,777 @@
+//! Day 8: Two-Factor Authentication, parsing the screen operations and running them on the
+//! little screen, along with its rendering and snapshots.
+
+extern crate aoc_core;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(feature = "proptest")]
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate regex;
+extern crate report;
+
+pub mod two_factor_authentication {
+    use ::aoc_core::{Event, Font, Grid, GridError, Sink, Span, Spanned, Unrecognized};
+    #[cfg(feature = "proptest")]
+    use ::proptest::collection::vec;
+    #[cfg(feature = "proptest")]
+    use ::proptest::prelude::{any, any_with, Arbitrary, BoxedStrategy, Strategy};
+    #[cfg(feature = "proptest")]
+    use ::rand::Rng;
+    use ::regex::{Captures, Regex};
+    use ::report::InputError;
+    #[cfg(feature = "proptest")]
+    use ::report::Sampler;
+    use ::std::borrow::Cow;
+    use ::std::collections::HashMap;
+    use ::std::fmt::Display;
+    use ::std::str::FromStr;
+
+    /// Represent a `Screen` operation.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Operation {
+        Rect(u32, u32),
+        RectOff(u32, u32),
+        Toggle(u32, u32),
+        /// Rotate a row right, or left when the offset is negative.
+        RotateRow(u32, i32),
+        /// Rotate a column down, or up when the offset is negative.
+        RotateCol(u32, i32),
+    }
+
+    impl FromStr for Operation {
+        type Err = Spanned<String>;
+
+        fn from_str(s: &str) -> Result<Operation, Spanned<String>> {
+            lazy_static! {
+                static ref RECT: Regex = Regex::new(r"^rect (?P<A>\d+)x(?P<B>\d+)$").unwrap();
+                static ref ROFF: Regex = Regex::new(r"^rect off (?P<A>\d+)x(?P<B>\d+)$").unwrap();
+                static ref TOGL: Regex = Regex::new(r"^toggle (?P<A>\d+)x(?P<B>\d+)$").unwrap();
+                static ref ROTR: Regex = Regex::new(r"^rotate row y=(?P<A>\d+) by (?P<B>-?\d+)$").unwrap();
+                static ref ROTC: Regex = Regex::new(r"^rotate column x=(?P<A>\d+) by (?P<B>-?\d+)$").unwrap();
+            }
+            // the regexes ensure that we have numbers, but they may still be too large. NOTE: as
+            // the regexes are anchored, the whole match is the string parsed.
+            fn arg<T: FromStr>(caps: &Captures, name: &str) -> Result<T, Spanned<String>> {
+                let number = caps.name(name).unwrap();
+                number.as_str().parse().map_err(|_| {
+                    Spanned::of(caps.get(0).unwrap().as_str(), number.range(), format!("number out of range: {}", number.as_str()))
+                })
+            }
+            if let Some(caps) = RECT.captures(s) {
+                Ok(Operation::Rect(arg(&caps, "A")?, arg(&caps, "B")?))
+            } else if let Some(caps) = ROFF.captures(s) {
+                Ok(Operation::RectOff(arg(&caps, "A")?, arg(&caps, "B")?))
+            } else if let Some(caps) = TOGL.captures(s) {
+                Ok(Operation::Toggle(arg(&caps, "A")?, arg(&caps, "B")?))
+            } else if let Some(caps) = ROTR.captures(s) {
+                Ok(Operation::RotateRow(arg(&caps, "A")?, arg(&caps, "B")?))
+            } else if let Some(caps) = ROTC.captures(s) {
+                Ok(Operation::RotateCol(arg(&caps, "A")?, arg(&caps, "B")?))
+            } else {
+                Err(Spanned::of(s, 0..s.len(), Unrecognized::new("operation", s).into()))
+            }
+        }
+    }
+
+    impl Display for Operation {
+        /// Format the `Operation` the way it is parsed.
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Operation::Rect(a, b) => write!(f, "rect {}x{}", a, b),
+                Operation::RectOff(a, b) => write!(f, "rect off {}x{}", a, b),
+                Operation::Toggle(a, b) => write!(f, "toggle {}x{}", a, b),
+                Operation::RotateRow(y, by) => write!(f, "rotate row y={} by {}", y, by),
+                Operation::RotateCol(x, by) => write!(f, "rotate column x={} by {}", x, by),
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for Operation {
+        /// The screen width and height the operations must fit in, any number goes when `None`.
+        /// A screen without pixels only gets empty rects, there being nothing to rotate.
+        type Parameters = Option<(u32, u32)>;
+        type Strategy = BoxedStrategy<Operation>;
+
+        fn arbitrary_with(screen: Self::Parameters) -> Self::Strategy {
+            match screen {
+                None => prop_oneof![
+                    (any::<u32>(), any::<u32>()).prop_map(|(a, b)| Operation::Rect(a, b)),
+                    (any::<u32>(), any::<u32>()).prop_map(|(a, b)| Operation::RectOff(a, b)),
+                    (any::<u32>(), any::<u32>()).prop_map(|(a, b)| Operation::Toggle(a, b)),
+                    (any::<u32>(), any::<i32>()).prop_map(|(y, by)| Operation::RotateRow(y, by)),
+                    (any::<u32>(), any::<i32>()).prop_map(|(x, by)| Operation::RotateCol(x, by)),
+                ].boxed(),
+                Some((width, height)) if width == 0 || height == 0 => prop_oneof![
+                    (0..=width, 0..=height).prop_map(|(a, b)| Operation::Rect(a, b)),
+                    (0..=width, 0..=height).prop_map(|(a, b)| Operation::RectOff(a, b)),
+                    (0..=width, 0..=height).prop_map(|(a, b)| Operation::Toggle(a, b)),
+                ].boxed(),
+                Some((width, height)) => {
+                    // NOTE: a rotation offset must be shorter than the row or column.
+                    let (xoffset, yoffset) = (width as i32 - 1, height as i32 - 1);
+                    prop_oneof![
+                        (0..=width, 0..=height).prop_map(|(a, b)| Operation::Rect(a, b)),
+                        (0..=width, 0..=height).prop_map(|(a, b)| Operation::RectOff(a, b)),
+                        (0..=width, 0..=height).prop_map(|(a, b)| Operation::Toggle(a, b)),
+                        (0..height, -xoffset..=xoffset).prop_map(|(y, by)| Operation::RotateRow(y, by)),
+                        (0..width, -yoffset..=yoffset).prop_map(|(x, by)| Operation::RotateCol(x, by)),
+                    ].boxed()
+                },
+            }
+        }
+    }
+
+    /// An error encountered while parsing a line of `Operation`.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct OperationParseError {
+        /// The line number, starting at 1.
+        pub line_no: usize,
+        /// The line content.
+        pub text: String,
+        /// Why the line could not be parsed.
+        pub reason: String,
+        /// Where the error is in the line, when known.
+        pub span: Option<Span>,
+    }
+
+    impl Display for OperationParseError {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match self.span {
+                Some(span) => write!(f, "line {}, column {}: {}", self.line_no, span.col, self.reason),
+                None => write!(f, "line {}: {}", self.line_no, self.reason),
+            }
+        }
+    }
+
+    impl From<OperationParseError> for InputError {
+        fn from(error: OperationParseError) -> InputError {
+            InputError { span: error.span, ..InputError::at(error.line_no, &error.text, error.reason) }
+        }
+    }
+
+    /// Parse one `Operation` per line of `input`.
+    ///
+    /// Parsing doesn't stop at the first bad line. Returns the successfully parsed operations
+    /// along with their line number, and the errors for every line that could not be parsed.
+    pub fn parse_operations(input: &str) -> (Vec<(usize, Operation)>, Vec<OperationParseError>) {
+        let mut operations = Vec::new();
+        let mut errors = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            match line.parse() {
+                Ok(op) => operations.push((i + 1, op)),
+                Err(Spanned { span, error }) => errors.push(OperationParseError {
+                    line_no: i + 1,
+                    text: line.to_string(),
+                    reason: error,
+                    span: Some(span.on_line(i + 1)),
+                }),
+            }
+        }
+        (operations, errors)
+    }
+
+    /// The maximum number of operations a script may expand to, see `compile_script()`.
+    pub const MAX_SCRIPT_OPERATIONS: usize = 1_000_000;
+
+    /// Compile a script into a list of `Operation` along with the line number they come from.
+    ///
+    /// A script is made of one `Operation` per line, with a few additions to make hand-writing
+    /// them less tedious:
+    ///
+    /// - empty lines and everything following a `#` are ignored,
+    /// - `const NAME = VALUE` defines a numeric constant, where `NAME` is made of uppercase
+    ///   letters, digits and underscores. `$NAME` is then replaced by `VALUE` in the following
+    ///   lines,
+    /// - `repeat N {` and `}` delimit a block repeated `N` times. Blocks may be nested.
+    ///
+    /// Compilation stops at the first error, including a script expanding to more than
+    /// `MAX_SCRIPT_OPERATIONS` operations.
+    pub fn compile_script(src: &str) -> Result<Vec<(usize, Operation)>, OperationParseError> {
+        lazy_static! {
+            static ref CONST: Regex = Regex::new(r"^const (?P<NAME>[A-Z_][A-Z0-9_]*) = (?P<VALUE>-?\d+)$").unwrap();
+            static ref REPEAT: Regex = Regex::new(r"^repeat (?P<N>\d+) \{$").unwrap();
+            static ref VAR: Regex = Regex::new(r"\$(?P<NAME>[A-Z_][A-Z0-9_]*)").unwrap();
+        }
+        let mut constants: HashMap<String, i64> = HashMap::new();
+        /// A block being compiled.
+        struct Block {
+            /// The line number of the `repeat` statement.
+            line_no: usize,
+            /// Where the count is in the `repeat` statement, when known.
+            span: Option<Span>,
+            /// How many times the block is repeated.
+            count: usize,
+            operations: Vec<(usize, Operation)>,
+        }
+        // The first block is the top-level one, which is never repeated.
+        let mut blocks = vec![Block { line_no: 0, span: None, count: 1, operations: Vec::new() }];
+        for (i, line) in src.lines().enumerate() {
+            let line_no = i + 1;
+            let error = |reason: String| OperationParseError {
+                line_no,
+                text: line.to_string(),
+                reason,
+                span: None,
+            };
+            // strip comments and surrounding whitespaces.
+            let code = line.split('#').next().unwrap().trim();
+            if code.is_empty() {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+            // constants substitution.
+            let mut undefined = None;
+            let code = VAR.replace_all(code, |caps: &Captures| {
+                match constants.get(&caps["NAME"]) {
+                    Some(value) => value.to_string(),
+                    None => {
+                        undefined = Some(caps["NAME"].to_string());
+                        String::new()
+                    },
+                }
+            });
+            if let Some(name) = undefined {
+                return Err(error(format!("undefined constant: {}", name)));
+            }
+            if let Some(caps) = CONST.captures(&code) {
+                let value = caps["VALUE"].parse().map_err(|_| {
+                    error(format!("number out of range: {}", &caps["VALUE"]))
+                })?;
+                constants.insert(caps["NAME"].to_string(), value);
+            } else if let Some(caps) = REPEAT.captures(&code) {
+                let count = caps["N"].parse().map_err(|_| {
+                    error(format!("number out of range: {}", &caps["N"]))
+                })?;
+                let n = caps.name("N").unwrap();
+                let span = match code {
+                    Cow::Borrowed(_) => Some(Span::of(line, indent + n.start()..indent + n.end()).on_line(line_no)),
+                    Cow::Owned(_) => None,
+                };
+                blocks.push(Block { line_no, span, count, operations: Vec::new() });
+            } else if code == "}" {
+                if blocks.len() == 1 {
+                    return Err(error("unexpected end of block".to_string()));
+                }
+                let block = blocks.pop().unwrap();
+                let parent = &mut blocks.last_mut().unwrap().operations;
+                let expanded = block.operations.len().checked_mul(block.count).and_then(|n| n.checked_add(parent.len()));
+                if !expanded.is_some_and(|n| n <= MAX_SCRIPT_OPERATIONS) {
+                    return Err(OperationParseError {
+                        line_no: block.line_no,
+                        text: src.lines().nth(block.line_no - 1).unwrap().to_string(),
+                        reason: format!("block expands to more than {} operations", MAX_SCRIPT_OPERATIONS),
+                        span: block.span,
+                    });
+                }
+                for _ in 0..block.count {
+                    parent.extend_from_slice(&block.operations);
+                }
+            } else {
+                let op = code.parse().map_err(|e: Spanned<String>| {
+                    // NOTE: the span is only known in the line when no constant was substituted,
+                    // the code then starting after the line indentation.
+                    let indent = line[..indent].chars().count();
+                    let span = match code {
+                        Cow::Borrowed(_) => Some(Span { col: e.span.col + indent, ..e.span.on_line(line_no) }),
+                        Cow::Owned(_) => None,
+                    };
+                    OperationParseError { span, ..error(e.error) }
+                })?;
+                blocks.last_mut().unwrap().operations.push((line_no, op));
+            }
+        }
+        if blocks.len() > 1 {
+            let line_no = blocks.pop().unwrap().line_no;
+            let text = src.lines().nth(line_no - 1).unwrap().to_string();
+            return Err(OperationParseError { line_no, text, reason: "unterminated block".to_string(), span: None });
+        }
+        Ok(blocks.pop().unwrap().operations)
+    }
+
+    /// Reasons why an `Operation` could not be executed on a `Screen`.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum ExecuteError {
+        /// The rotated row is not on the screen.
+        BadRow(u32),
+        /// The rotated column is not on the screen.
+        BadColumn(u32),
+        /// The rotation offset is not smaller than the rotated row or column length.
+        BadOffset(i32),
+        /// The rectangle, given as width and height, is larger than the screen.
+        OversizedRect(u32, u32),
+    }
+
+    impl Display for ExecuteError {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                ExecuteError::BadRow(y) => write!(f, "no row y={} on the screen", y),
+                ExecuteError::BadColumn(x) => write!(f, "no column x={} on the screen", x),
+                ExecuteError::BadOffset(offset) => write!(f, "rotation offset {} out of range", offset),
+                ExecuteError::OversizedRect(width, height) => {
+                    write!(f, "rect {}x{} larger than the screen", width, height)
+                },
+            }
+        }
+    }
+
+    /// The number of pixels stored in a `Screen` word.
+    const WORD_BITS: usize = 64;
+
+    /// Returns the number of words needed to store `len` pixels.
+    fn words_for(len: usize) -> usize {
+        len.div_ceil(WORD_BITS)
+    }
+
+    /// Returns the mask of the valid bits in the last word of a `len` bits bitset.
+    fn last_word_mask(len: usize) -> u64 {
+        match len % WORD_BITS {
+            0 => !0,
+            n => (1 << n) - 1,
+        }
+    }
+
+    /// OR into `dst` the `src` bitset shifted by `shift` bits, i.e. the bit `i` of `src` is OR'ed
+    /// into the bit `i + shift` of `dst`. Bits shifted out of `dst` are lost.
+    fn or_shifted(dst: &mut [u64], src: &[u64], shift: isize) {
+        let n = dst.len() as isize;
+        let (wshift, bshift) = (shift.div_euclid(WORD_BITS as isize), shift.rem_euclid(WORD_BITS as isize));
+        for i in 0..n {
+            // the source words landing in dst[i], the low one being partially shifted out.
+            let (hi, lo) = (i - wshift, i - wshift - 1);
+            let mut word = 0;
+            if hi >= 0 && hi < n {
+                word |= src[hi as usize] << bshift;
+            }
+            if bshift > 0 && lo >= 0 && lo < n {
+                word |= src[lo as usize] >> (WORD_BITS as isize - bshift);
+            }
+            dst[i as usize] |= word;
+        }
+    }
+
+    /// Rotate the `len` bits bitset `words` so that its bit `i` moves to the bit `(i + k) % len`.
+    ///
+    /// The bits past `len` in the last word are expected to be unset.
+    fn rotate_bits(words: &mut [u64], len: usize, k: usize) {
+        if k == 0 {
+            return;
+        }
+        let src = words.to_vec();
+        for word in words.iter_mut() {
+            *word = 0;
+        }
+        // the bits before `len - k` move up by `k`, the others wrap around to the start.
+        or_shifted(words, &src, k as isize);
+        or_shifted(words, &src, k as isize - len as isize);
+        if let Some(last) = words.last_mut() {
+            *last &= last_word_mask(len);
+        }
+    }
+
+    lazy_static! {
+        /// The font used by the little screen, see `Font::parse()` for its format.
+        static ref FONT: Font = Font::parse(include_str!("../font.txt")).unwrap();
+    }
+
+    /// Represent a little smashable screen.
+    // NOTE: pixels are stored as one bitset per column where bit `y % 64` of the word `y / 64` is
+    // set when the pixel at `y` is lit. Column rotation, the hot path for tall screens, is then a
+    // word-level operation, as are rect and voltage usage. Row rotation has to gather and scatter
+    // one bit per column, but the typical screen has few of them.
+    #[derive(Clone)]
+    pub struct Screen {
+        width: usize,
+        height: usize,
+        /// The number of words per column.
+        stride: usize,
+        words: Vec<u64>,
+    }
+
+    impl Screen {
+        /// Create a new blank `Screen` of given dimensions, with all pixels off.
+        pub fn blank(width: usize, height: usize) -> Screen {
+            let stride = words_for(height);
+            Screen {
+                width,
+                height,
+                stride,
+                words: vec![0; stride * width],
+            }
+        }
+
+        /// Execute the given `Operation`.
+        ///
+        /// When the operation doesn't fit self an error is returned and self is left untouched.
+        pub fn execute(&mut self, op: Operation) -> Result<(), ExecuteError> {
+            match op {
+                Operation::Rect(width, height)    => self.rect(width, height, |word, mask| word | mask),
+                Operation::RectOff(width, height) => self.rect(width, height, |word, mask| word & !mask),
+                Operation::Toggle(width, height)  => self.rect(width, height, |word, mask| word ^ mask),
+                Operation::RotateRow(y, xoffset)  => {
+                    let xoffset = forward_offset(xoffset, self.width)?;
+                    self.rotate_row(y, xoffset)
+                },
+                Operation::RotateCol(x, yoffset)  => {
+                    let yoffset = forward_offset(yoffset, self.height)?;
+                    self.rotate_col(x, yoffset)
+                },
+            }
+        }
+
+        /// Execute the given `Operation` and returns the (x, y) positions of the pixels that
+        /// changed state.
+        pub fn apply_and_diff(&mut self, op: Operation) -> Result<Vec<(usize, usize)>, ExecuteError> {
+            let before = self.words.clone();
+            self.execute(op)?;
+            let mut diff = Vec::new();
+            for (i, (old, new)) in before.iter().zip(self.words.iter()).enumerate() {
+                let (x, y0) = (i / self.stride, (i % self.stride) * WORD_BITS);
+                let mut changed = old ^ new;
+                while changed != 0 {
+                    diff.push((x, y0 + changed.trailing_zeros() as usize));
+                    changed &= changed - 1;
+                }
+            }
+            // report the pixels in reading order.
+            diff.sort_by_key(|&(x, y)| (y, x));
+            Ok(diff)
+        }
+
+        /// Returns the width of self, in pixels.
+        pub fn width(&self) -> usize {
+            self.width
+        }
+
+        /// Returns the height of self, in pixels.
+        pub fn height(&self) -> usize {
+            self.height
+        }
+
+        /// Returns `true` if the pixel at the given (x, y) position is lit, `false` otherwise.
+        /// Panic if either `x` or `y` is out of range.
+        pub fn is_lit(&self, x: usize, y: usize) -> bool {
+            assert!(x < self.width && y < self.height, "({}, {}) is out of range", x, y);
+            self.words[x * self.stride + y / WORD_BITS] & (1 << (y % WORD_BITS)) != 0
+        }
+
+        /// Switch the pixel at the given (x, y) position on if it was off and off if it was on.
+        fn flip(&mut self, x: usize, y: usize) {
+            self.words[x * self.stride + y / WORD_BITS] ^= 1 << (y % WORD_BITS);
+        }
+
+        /// Returns an iterator over the (x, y) positions of the lit pixels of self, in reading
+        /// order (i.e. row by row, from left to right).
+        pub fn lit_pixels(&self) -> impl Iterator<Item=(usize, usize)> + '_ {
+            let width = self.width;
+            (0..self.height)
+                .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                .filter(move |&(x, y)| self.is_lit(x, y))
+        }
+
+        /// Returns the voltage used by `self`, i.e. the count of pixel lit.
+        pub fn voltage_usage(&self) -> usize {
+            self.words.iter().map(|word| word.count_ones() as usize).sum()
+        }
+
+        /// Decode the text displayed by self in the screen's font, see `Screen::read_text_with()`.
+        pub fn read_text(&self) -> Option<String> {
+            self.read_text_with(&FONT)
+        }
+
+        /// Decode the text displayed by self in the given `font`, e.g. one loaded from a file to
+        /// read the taller letters of other puzzles.
+        ///
+        /// The display is split into glyphs of the font width that are looked up in the font.
+        /// Glyphs that could not be recognized are decoded as `?`. Returns `None` when the height
+        /// of self doesn't match the font height.
+        pub fn read_text_with(&self, font: &Font) -> Option<String> {
+            font.read(self.width, self.height, |x, y| self.is_lit(x, y))
+        }
+
+        /// > turns on all of the pixels in a rectangle at the top-left of the screen which is `A`
+        /// > wide and `B` tall.
+        ///
+        /// Except that instead of turning on the pixels, `action` is called with each word of the
+        /// rectangle and the mask of the rectangle's pixels in that word, and returns the new word.
+        fn rect<F>(&mut self, /* A */ width: u32, /* B */ height: u32, action: F)
+            -> Result<(), ExecuteError>
+            where F: Fn(u64, u64) -> u64
+        {
+            if width as usize > self.width || height as usize > self.height {
+                return Err(ExecuteError::OversizedRect(width, height));
+            }
+            let nwords = words_for(height as usize);
+            for x in 0..(width as usize) {
+                let col = &mut self.words[(x * self.stride)..(x * self.stride + nwords)];
+                for (i, word) in col.iter_mut().enumerate() {
+                    let mask = if i + 1 == nwords { last_word_mask(height as usize) } else { !0 };
+                    *word = action(*word, mask);
+                }
+            }
+            Ok(())
+        }
+
+        /// > shifts all of the pixels in row `A` (`0` is the top row) right by `B` pixels. Pixels
+        /// > that would fall off the right end appear at the left end of the row.
+        // NOTE: the row is gathered into a bitset, rotated at the word level and then scattered
+        // back into the columns.
+        fn rotate_row(&mut self, /* A */ y: u32, /* B */ xoffset: usize) -> Result<(), ExecuteError> {
+            if y as usize >= self.height {
+                return Err(ExecuteError::BadRow(y));
+            }
+            let (offset, bit) = (y as usize / WORD_BITS, y as usize % WORD_BITS);
+            let mut row = vec![0u64; words_for(self.width)];
+            let pixels = self.words.iter().skip(offset).step_by(self.stride);
+            for (x, word) in pixels.enumerate() {
+                row[x / WORD_BITS] |= ((word >> bit) & 1) << (x % WORD_BITS);
+            }
+            rotate_bits(&mut row, self.width, xoffset);
+            let pixels = self.words.iter_mut().skip(offset).step_by(self.stride);
+            for (x, word) in pixels.enumerate() {
+                let px = (row[x / WORD_BITS] >> (x % WORD_BITS)) & 1;
+                *word = (*word & !(1 << bit)) | (px << bit);
+            }
+            Ok(())
+        }
+
+        /// > shifts all of the pixels in column `A` (`0` is the left column) down by `B` pixels.
+        /// > Pixels that would fall off the bottom appear at the top of the column.
+        fn rotate_col(&mut self, /* A */ x: u32, /* B */ yoffset: usize) -> Result<(), ExecuteError> {
+            if x as usize >= self.width {
+                return Err(ExecuteError::BadColumn(x));
+            }
+            let start = x as usize * self.stride;
+            rotate_bits(&mut self.words[start..(start + self.stride)], self.height, yoffset);
+            Ok(())
+        }
+
+        /// Turn on the pixel at the given (x, y) position. Panic if either `x` or `y` is out of
+        /// range.
+        fn turn_on(&mut self, x: usize, y: usize) {
+            assert!(x < self.width && y < self.height, "({}, {}) is out of range", x, y);
+            self.words[x * self.stride + y / WORD_BITS] |= 1 << (y % WORD_BITS);
+        }
+    }
+
+    /// Convert a possibly negative rotation `offset` into the equivalent forward rotation offset
+    /// on `len` pixels. Returns an error if `offset` is out of range, i.e. not in `(-len, len)`.
+    fn forward_offset(offset: i32, len: usize) -> Result<usize, ExecuteError> {
+        let abs = offset.unsigned_abs() as usize;
+        if abs >= len {
+            Err(ExecuteError::BadOffset(offset))
+        } else if offset < 0 {
+            Ok((len - abs) % len)
+        } else {
+            Ok(abs)
+        }
+    }
+
+    /// Parse a `Screen` snapshot, as displayed (i.e. one line per row, `#` for lit pixels and `.`
+    /// otherwise).
+    impl FromStr for Screen {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Screen, String> {
+            let pixels = Grid::parse(s, |ch| match ch {
+                '#' => Ok(true),
+                '.' => Ok(false),
+                _ => Err(ch),
+            }).map_err(|e| match e {
+                GridError::Empty => "empty screen snapshot".to_string(),
+                GridError::Ragged { row, width } => format!("row {}: expected {} pixels", row, width),
+                GridError::Cell { row, error, .. } => format!("row {}: invalid pixel {:?}", row, error),
+            })?;
+            let mut screen = Screen::blank(pixels.width(), pixels.height());
+            for (x, y, _) in pixels.iter().filter(|&(_, _, &lit)| lit) {
+                screen.turn_on(x, y);
+            }
+            Ok(screen)
+        }
+    }
+
+    /// The ways a `Screen` can be rendered as text.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum RenderStyle {
+        /// One character per pixel, `#` when lit and `.` otherwise. This is the `Display` style.
+        Ascii,
+        /// One Unicode half-block character per 1x2 pixels.
+        HalfBlock,
+        /// One Unicode Braille pattern per 2x4 pixels.
+        Braille,
+    }
+
+    impl FromStr for RenderStyle {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<RenderStyle, String> {
+            match s {
+                "ascii"      => Ok(RenderStyle::Ascii),
+                "half-block" => Ok(RenderStyle::HalfBlock),
+                "braille"    => Ok(RenderStyle::Braille),
+                _ => Err(format!("unknown render style: {}", s)),
+            }
+        }
+    }
+
+    impl Screen {
+        /// Render self in the given `style`, one line of text at a time.
+        pub fn render(&self, style: RenderStyle) -> String {
+            // the size of the pixel block represented by each character.
+            let (bwidth, bheight) = match style {
+                RenderStyle::Ascii     => (1, 1),
+                RenderStyle::HalfBlock => (1, 2),
+                RenderStyle::Braille   => (2, 4),
+            };
+            let mut out = String::new();
+            for by in 0..self.height.div_ceil(bheight) {
+                for bx in 0..self.width.div_ceil(bwidth) {
+                    let lit = |dx: usize, dy: usize| {
+                        let (x, y) = (bx * bwidth + dx, by * bheight + dy);
+                        x < self.width && y < self.height && self.is_lit(x, y)
+                    };
+                    let ch = match style {
+                        RenderStyle::Ascii => if lit(0, 0) { '#' } else { '.' },
+                        RenderStyle::HalfBlock => match (lit(0, 0), lit(0, 1)) {
+                            (true,  true)  => '\u{2588}', // █
+                            (true,  false) => '\u{2580}', // ▀
+                            (false, true)  => '\u{2584}', // ▄
+                            (false, false) => ' ',
+                        },
+                        RenderStyle::Braille => {
+                            // the dot numbering of the Braille patterns Unicode block.
+                            const DOTS: [(usize, usize, u32); 8] = [
+                                (0, 0, 0x01), (0, 1, 0x02), (0, 2, 0x04), (1, 0, 0x08),
+                                (1, 1, 0x10), (1, 2, 0x20), (0, 3, 0x40), (1, 3, 0x80),
+                            ];
+                            let bits = DOTS.iter()
+                                .filter(|&&(dx, dy, _)| lit(dx, dy))
+                                .fold(0, |acc, &(_, _, bit)| acc | bit);
+                            ::std::char::from_u32(0x2800 + bits).unwrap()
+                        },
+                    };
+                    out.push(ch);
+                }
+                out.push('\n');
+            }
+            out
+        }
+
+        /// Explain the operation `op` of the input line `line_no` once executed by self: the frame
+        /// it left on the screen. Skipped altogether when the `sink` discards the events.
+        pub fn explain(&self, line_no: usize, op: Operation, sink: &mut dyn Sink) {
+            if sink.enabled() {
+                let (operation, frame) = (op.to_string(), self.to_string());
+                sink.emit(&Event::new("frame", &[
+                    ("line", line_no.into()),
+                    ("operation", operation.as_str().into()),
+                    ("lit", self.voltage_usage().into()),
+                    ("screen", frame.as_str().into()),
+                ]));
+            }
+        }
+    }
+
+    impl Display for Screen {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    write!(f, "{}", if self.is_lit(x, y) { '#' } else { '.' })?;
+                }
+                writeln!(f)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// A `Screen` remembering the operations executed on it so that they can be undone.
+    // NOTE: the pixels changed by each operation are kept rather than a copy of the screen, flipping
+    // them back undoes any operation.
+    pub struct UndoableScreen {
+        screen: Screen,
+        history: Vec<(Operation, Vec<(usize, usize)>)>,
+    }
+
+    impl UndoableScreen {
+        /// Create a new `UndoableScreen` starting from `screen`, with nothing to undo.
+        pub fn new(screen: Screen) -> UndoableScreen {
+            UndoableScreen { screen, history: Vec::new() }
+        }
+
+        /// Returns the current state of the screen.
+        pub fn screen(&self) -> &Screen {
+            &self.screen
+        }
+
+        /// Returns the current state of the screen, forgetting the operations history.
+        pub fn into_screen(self) -> Screen {
+            self.screen
+        }
+
+        /// Execute the given `Operation` like `Screen::apply_and_diff()`, remembering it unless
+        /// it failed.
+        pub fn execute(&mut self, op: Operation) -> Result<&[(usize, usize)], ExecuteError> {
+            let diff = self.screen.apply_and_diff(op)?;
+            self.history.push((op, diff));
+            Ok(&self.history.last().unwrap().1)
+        }
+
+        /// Undo the last operation executed and returns it, or `None` when there is nothing left
+        /// to undo.
+        pub fn undo(&mut self) -> Option<Operation> {
+            let (op, diff) = self.history.pop()?;
+            for (x, y) in diff {
+                self.screen.flip(x, y);
+            }
+            Some(op)
+        }
+
+        /// Returns the number of operations that can be undone.
+        pub fn undo_len(&self) -> usize {
+            self.history.len()
+        }
+    }
+
+    /// A line entered in the interactive mode: either an `Operation` or a command.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Command {
+        /// Execute the operation.
+        Execute(Operation),
+        /// Undo the last operation.
+        Undo,
+        /// Display the screen.
+        Show,
+    }
+
+    impl FromStr for Command {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Command, String> {
+            match s.trim() {
+                "undo" => Ok(Command::Undo),
+                "show" => Ok(Command::Show),
+                op => op.parse().map(Command::Execute).map_err(|e: Spanned<String>| e.error),
+            }
+        }
+    }
+
+    /// Returns `size` random operations for the puzzle 50x6 screen, formatted like the puzzle
+    /// input. They are drawn from the `Operation` strategy, keeping only the puzzle operations,
+    /// i.e. non-empty rect and rotations to the right or down.
+    #[cfg(feature = "proptest")]
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let puzzle = any_with::<Operation>(Some((50, 6))).prop_filter("not a puzzle operation", |op| {
+            match *op {
+                Operation::Rect(a, b) => a > 0 && b > 0,
+                Operation::RotateRow(_, by) | Operation::RotateCol(_, by) => by > 0,
+                Operation::RectOff(..) | Operation::Toggle(..) => false,
+            }
+        });
+        let operations = Sampler::new(rng.gen()).draw(&vec(puzzle, size));
+        operations.iter().map(|op| format!("{}\n", op)).collect()
+    }
+}
+
+pub use two_factor_authentication::*;
@@ -1,249 +1,332 @@
+extern crate aoc_cli;
+extern crate aoc_core;
+#[cfg(feature = "gif")]
+extern crate checked;
+#[cfg(feature = "gif")]
+extern crate gif;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
 #[macro_use]
-extern crate lazy_static;
-extern crate regex;
-
-mod two_factor_authentication {
-    use ::regex::Regex;
-    use ::std::fmt::Display;
-    use ::std::str::FromStr;
-
-    /// Represent a `Screen` operation.
-    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-    pub enum Operation {
-        Rect(u32, u32),
-        RotateRow(u32, u32),
-        RotateCol(u32, u32),
-    }
+extern crate profiling;
+extern crate report;
+extern crate two_factor_authentication;
 
-    impl FromStr for Operation {
-        type Err = String;
 
-        fn from_str(s: &str) -> Result<Operation, String> {
-            lazy_static! {
-                static ref RECT: Regex = Regex::new(r"^rect (?P<A>\d+)x(?P<B>\d+)$").unwrap();
-                static ref ROTR: Regex = Regex::new(r"^rotate row y=(?P<A>\d+) by (?P<B>\d+)$").unwrap();
-                static ref ROTC: Regex = Regex::new(r"^rotate column x=(?P<A>\d+) by (?P<B>\d+)$").unwrap();
-            }
-            if let Some(caps) = RECT.captures(s) {
-                Ok(Operation::Rect(caps["A"].parse().unwrap(), caps["B"].parse().unwrap()))
-            } else if let Some(caps) = ROTR.captures(s) {
-                Ok(Operation::RotateRow(caps["A"].parse().unwrap(), caps["B"].parse().unwrap()))
-            } else if let Some(caps) = ROTC.captures(s) {
-                Ok(Operation::RotateCol(caps["A"].parse().unwrap(), caps["B"].parse().unwrap()))
-            } else {
-                Err(format!("unrecognized operation: {}", s))
-            }
-        }
+/// Animated GIF export of a `Screen` operation replay, available with the `gif` feature.
+#[cfg(feature = "gif")]
+mod replay_gif {
+    use ::gif::{Encoder, Frame, Repeat};
+    use ::std::fs::File;
+    use ::std::io;
+    use two_factor_authentication::Screen;
+
+    /// The colors of the GIF frames, as RGB triplets.
+    const PALETTE: &[u8] = &[
+        0x0f, 0x0f, 0x23, // off pixel
+        0xcc, 0xcc, 0xcc, // lit pixel
+        0x33, 0x33, 0x66, // pixel turned off by the last operation
+        0xff, 0xff, 0x66, // pixel turned on by the last operation
+    ];
+
+    /// Record each state of a `Screen` as a frame of an animated GIF.
+    pub struct GifRecorder {
+        encoder: Encoder<File>,
+        /// The size of a screen pixel in the GIF, in GIF pixels.
+        scale: usize,
+        /// The delay between each frame, in hundredths of a second.
+        delay: u16,
     }
 
-    /// Represent a Pixel state: either lit or not, `On` respectively `Off`.
-    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-    enum PixelState {
-        On,
-        Off,
+    /// Convert a `gif` error into an `io::Error`.
+    fn to_io_error(e: ::gif::EncodingError) -> io::Error {
+        io::Error::other(e)
     }
 
-    /// Represent a pixel on the `Sreen`. `true` if the pixel is lit, `false` otherwise.
-    #[derive(Copy, Clone, Debug)]
-    struct Pixel {
-        state: PixelState,
+    /// Returns the GIF length of `len` screen pixels drawn `scale` GIF pixels wide, or an error
+    /// when it doesn't fit in a GIF.
+    fn gif_len(len: usize, scale: usize, what: &'static str) -> io::Result<u16> {
+        // NOTE: a saturated length is out of range anyway.
+        let len = len.saturating_mul(scale);
+        ::checked::convert(len, what).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
     }
 
-    impl Pixel {
-        /// Create a new pixel in "off" state, i.e. not lit.
-        fn off() -> Pixel {
-            Pixel { state: PixelState::Off }
+    impl GifRecorder {
+        /// Create the GIF file at `path` for a `width` x `height` screen, each screen pixel being
+        /// drawn as a `scale` x `scale` square and each frame lasting `delay_ms` milliseconds.
+        pub fn create(path: &str, width: usize, height: usize, scale: usize, delay_ms: u64)
+            -> io::Result<GifRecorder>
+        {
+            let gwidth = gif_len(width, scale, "GIF width")?;
+            let gheight = gif_len(height, scale, "GIF height")?;
+            let delay = ::checked::convert::<u16, _>((delay_ms / 10).max(1), "GIF frame delay")
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+            let file = File::create(path)?;
+            let mut encoder = Encoder::new(file, gwidth, gheight, PALETTE).map_err(to_io_error)?;
+            encoder.set_repeat(Repeat::Infinite).map_err(to_io_error)?;
+            Ok(GifRecorder { encoder, scale, delay })
         }
 
-        /// Turn a pixel "on".
-        fn turn_on(&mut self) {
-            self.state = PixelState::On;
+        /// Write a frame of `screen`, emphasizing the pixels that `changed`.
+        pub fn record(&mut self, screen: &Screen, changed: &[(usize, usize)]) -> io::Result<()> {
+            let scale = self.scale;
+            let width = gif_len(screen.width(), scale, "GIF width")?;
+            let height = gif_len(screen.height(), scale, "GIF height")?;
+            let (gwidth, gheight) = (usize::from(width), usize::from(height));
+            let mut buffer = vec![0u8; gwidth * gheight];
+            for (i, color) in buffer.iter_mut().enumerate() {
+                let (x, y) = ((i % gwidth) / scale, (i / gwidth) / scale);
+                let lit = screen.is_lit(x, y);
+                *color = match (lit, changed.contains(&(x, y))) {
+                    (false, false) => 0,
+                    (true,  false) => 1,
+                    (false, true)  => 2,
+                    (true,  true)  => 3,
+                };
+            }
+            let frame = Frame {
+                width,
+                height,
+                delay: self.delay,
+                buffer: buffer.into(),
+                ..Frame::default()
+            };
+            self.encoder.write_frame(&frame).map_err(to_io_error)
         }
+    }
+}
+
+use aoc_cli::Cli;
+use aoc_core::Font;
+use report::InputError;
+use std::io::{BufRead, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+use two_factor_authentication::*;
 
-        /// Returns `true` if self is lit, `false` otherwise.
-        fn is_on(&self) -> bool {
-            self.state == PixelState::On
+/// Draw an animation frame of `screen` on the terminal after `op` has been executed.
+///
+/// The row or column affected by `op` is highlighted and the pixels that changed state (i.e.
+/// `changed`) are emphasized. Unless this is the `first` frame, the cursor is moved up to draw
+/// over the previous frame.
+fn draw_frame(screen: &Screen, op: Operation, changed: &[(usize, usize)], first: bool) {
+    let mut frame = String::new();
+    if !first {
+        frame.push_str(&format!("\x1b[{}A", screen.height()));
+    }
+    for y in 0..screen.height() {
+        for x in 0..screen.width() {
+            let affected = match op {
+                Operation::Rect(width, height) |
+                Operation::RectOff(width, height) |
+                Operation::Toggle(width, height) => x < width as usize && y < height as usize,
+                Operation::RotateRow(row, _)   => y == row as usize,
+                Operation::RotateCol(col, _)   => x == col as usize,
+            };
+            if affected {
+                frame.push_str("\x1b[44m");
+            }
+            if changed.contains(&(x, y)) {
+                frame.push_str("\x1b[1;33m");
+            }
+            frame.push(if screen.is_lit(x, y) { '#' } else { '.' });
+            frame.push_str("\x1b[0m");
         }
+        frame.push('\n');
     }
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(frame.as_bytes()).and_then(|_| handle.flush()).ok();
+}
 
-    impl Display for Pixel {
-        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-            write!(f, "{}", if self.is_on() { '#' } else { '.' })
+/// Read one `Command` per line of stdin and run it on `screen` until the end of input, redrawing
+/// the screen after each operation or undo. Returns the final screen.
+///
+/// The prompt is only shown when stdin is a terminal, bad lines and failed operations are
+/// reported and ignored.
+fn interact(screen: Screen, style: RenderStyle) -> Screen {
+    let mut screen = UndoableScreen::new(screen);
+    let stdin = std::io::stdin();
+    let prompt = stdin.is_terminal();
+    let mut line = String::new();
+    loop {
+        if prompt {
+            print!("> ");
+            std::io::stdout().flush().ok();
+        }
+        line.clear();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => (),
+            Err(e) => report::exit(format!("<stdin>: {}", e)),
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse() {
+            Ok(Command::Execute(op)) => match screen.execute(op) {
+                Ok(_) => println!("{}", screen.screen().render(style)),
+                Err(e) => eprintln!("{}: {}", op, e),
+            },
+            Ok(Command::Undo) => match screen.undo() {
+                Some(op) => println!("undone: {}\n{}", op, screen.screen().render(style)),
+                None => eprintln!("nothing to undo"),
+            },
+            Ok(Command::Show) => println!("{}", screen.screen().render(style)),
+            Err(e) => eprintln!("{}", e),
         }
     }
-
-    /// Represent a little smashable screen.
-    pub struct Screen {
-        width: usize,
-        height: usize,
-        pixels: Vec<Pixel>,
+    if prompt {
+        println!();
     }
+    screen.into_screen()
+}
 
-    impl Screen {
-        /// Create a new blank `Screen` of given dimensions, with all pixels off.
-        pub fn blank(width: usize, height: usize) -> Screen {
-            Screen {
-                width: width,
-                height: height,
-                pixels: vec![Pixel::off(); width * height],
-            }
-        }
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
 
-        /// Execute the given `Operation`. Returns `true` on success, `false` otherwise.
-        pub fn execute(&mut self, op: Operation) -> bool {
-            match op {
-                Operation::Rect(width, height)   => self.rect(width as usize, height as usize),
-                Operation::RotateRow(y, xoffset) => self.rotate_row(y as usize, xoffset as usize),
-                Operation::RotateCol(x, yoffset) => self.rotate_col(x as usize, yoffset as usize),
-            }
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    #[cfg(feature = "proptest")]
+    let cli = Cli::start_with_generator(generate_input);
+    #[cfg(not(feature = "proptest"))]
+    let cli = Cli::start();
+    // parse the command line, the default screen dimensions are the ones from the puzzle.
+    let (mut width, mut height) = (50, 6);
+    // when set, each operation is drawn on the terminal as it is applied.
+    let mut animate = false;
+    // the delay between each frame in milliseconds, when animating or exporting a GIF.
+    let mut delay: u64 = 50;
+    // when set, lines that could not be parsed are reported and ignored.
+    let mut skip_invalid = false;
+    // when set, the input is compiled as a script instead of a plain list of operations.
+    let mut script = false;
+    // when set, the operations and undo/show commands are read and run one line at a time.
+    let mut interactive = false;
+    // how the final screen is displayed.
+    let mut style = RenderStyle::Ascii;
+    // the file where the operations replay is exported as an animated GIF, if any.
+    let mut gif: Option<String> = None;
+    // snapshot files to start from and to save the final screen into.
+    let (mut restore, mut save): (Option<String>, Option<String>) = (None, None);
+    // the file of the font decoding the screen, instead of the screen's own font.
+    let mut font: Option<String> = None;
+    let mut args = cli.args().iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| report::exit(format!("{}: missing value", arg)));
+        let number = |value: String| -> usize {
+            value.parse().unwrap_or_else(|_| report::exit(format!("{}: bad value: {}", arg, value)))
+        };
+        match arg.as_str() {
+            "--width" => width = number(value()),
+            "--height" => height = number(value()),
+            "--animate" => animate = true,
+            "--delay" => delay = number(value()) as u64,
+            "--skip-invalid" => skip_invalid = true,
+            "--script" => script = true,
+            "--interactive" => interactive = true,
+            "--restore" => restore = Some(value()),
+            "--save" => save = Some(value()),
+            "--gif" => gif = Some(value()),
+            "--font" => font = Some(value()),
+            "--render" => style = value().parse().unwrap_or_else(|e| report::exit(e)),
+            _ => report::exit(format!("unrecognized argument: {}", arg)),
         }
+    }
 
-        /// Returns the voltage used by `self`, i.e. the count of pixel lit.
-        pub fn voltage_usage(&self) -> usize {
-            self.pixels.iter().filter(|&px| px.is_on()).count()
-        }
+    // load the font first, so that a bad one is reported before running the operations.
+    let font = font.map(|path| {
+        let file = report::Input::file(&path);
+        file.check(Font::parse(file.text()).map_err(|e| InputError::spanned(file.text(), e)))
+    });
 
-        /// > turns on all of the pixels in a rectangle at the top-left of the screen which is `A`
-        /// > wide and `B` tall.
-        fn rect(&mut self, /* A */ width: usize, /* B */ height: usize) -> bool {
-            if width > self.width || height > self.height {
-                return false;
-            }
-            for y in 0..height {
-                for x in 0..width {
-                    self.pixel_at_mut(x, y).turn_on();
-                }
-            }
-            true
-        }
+    // screen initialization.
+    let mut screen = match restore {
+        Some(path) => {
+            let screen: Screen = report::Input::file(&path).parse();
+            (width, height) = (screen.width(), screen.height());
+            screen
+        },
+        None => Screen::blank(width, height),
+    };
+    if interactive {
+        screen = phase!("interactive").in_scope(|| interact(screen, style));
+    } else {
+        // acquire the puzzle input.
+        let input = cli.input();
 
-        /// > shifts all of the pixels in row `A` (`0` is the top row) right by `B` pixels. Pixels
-        /// > that would fall off the right end appear at the left end of the row.
-        // NOTE: the typical smashed screen is significantly wider than tall. Our
-        // representation allow an efficient rotate_row operation with three memcpy().
-        fn rotate_row(&mut self, /* A */ y: usize, /* B */ xoffset: usize) -> bool {
-            let (width, height) = (self.width, self.height);
-            if y >= height || xoffset >= width {
-                return false;
+        // Parse one `Operation` per line of input, reporting all the bad lines at once.
+        let (operations, errors) = phase!("parse").in_scope(|| if script {
+            match compile_script(input.text()) {
+                Ok(operations) => (operations, Vec::new()),
+                Err(error) => (Vec::new(), vec![error]),
             }
-            let (row_start, row_end) = (y * width, (y + 1) * width);
-            let mut buf = vec![Pixel::off(); width];
-            // 1. copy the full row into buf
-            buf.copy_from_slice(&self.pixels[row_start..row_end]);
-            // 2. copy the first pixels until the first "shifted" one (not included) at their new
-            //    positions.
-            self.pixels[(row_start + xoffset)..row_end].copy_from_slice(&buf[0..(width - xoffset)]);
-            // 3. copy into our first pixels all the shifted pixels.
-            self.pixels[row_start..(row_start + xoffset)].copy_from_slice(&buf[(width - xoffset)..width]);
-            true
+        } else {
+            parse_operations(input.text())
+        });
+        let errors: Vec<InputError> = errors.into_iter().map(InputError::from).collect();
+        if cli.validating() {
+            input.validate(errors);
         }
-
-        /// > shifts all of the pixels in column `A` (`0` is the left column) down by `B` pixels.
-        /// > Pixels that would fall off the bottom appear at the top of the column.
-        // NOTE: the typical smashed screen is significantly wider than tall. Our rotate_col
-        // implementation is naive but that's ok since height is small.
-        fn rotate_col(&mut self, /* A */ x: usize, /* B */ yoffset: usize) -> bool {
-            let (width, height) = (self.width, self.height);
-            if x >= width || yoffset >= height {
-                return false;
-            }
-            let mut col = vec![Pixel::off(); height];
-            for y in 0..height {
-                col[y] = *self.pixel_at(x, y);
-            }
-            for y in 0..height {
-                *self.pixel_at_mut(x, (y + yoffset) % height) = col[y];
-            }
-            true
+        if !errors.is_empty() && !skip_invalid {
+            input.fail(errors);
         }
-
-        /// Get a reference to the `Pixel` at the given (x, y) position. Panic if either `x` or `y`
-        /// is out of range.
-        fn pixel_at(&self, x: usize, y: usize) -> &Pixel {
-            let index = self.width * y + x;
-            self.pixels.get(index).unwrap()
+        for error in &errors {
+            input.print(error);
         }
 
-        /// Get a mutable reference to the `Pixel` at the given (x, y) position. Panic if either
-        /// `x` or `y` is out of rance.
-        fn pixel_at_mut(&mut self, x: usize, y: usize) -> &mut Pixel {
-            let index = self.width * y + x;
-            self.pixels.get_mut(index).unwrap()
+        #[cfg(feature = "gif")]
+        let mut recorder = gif.map(|path| {
+            let mut recorder = replay_gif::GifRecorder::create(&path, width, height, 8, delay)
+                .unwrap_or_else(|e| report::exit(format!("{}: {}", path, e)));
+            recorder.record(&screen, &[]).unwrap_or_else(|e| report::exit(format!("{}: {}", path, e)));
+            recorder
+        });
+        #[cfg(not(feature = "gif"))]
+        {
+            if gif.is_some() {
+                report::exit("--gif: built without the gif feature");
+            }
         }
-    }
-
-    impl Display for Screen {
-        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-            for y in 0..self.height {
-                for x in 0..self.width {
-                    write!(f, "{}", self.pixel_at(x, y))?;
+        let mut explain = cli.explain();
+        let apply = phase!("apply").entered();
+        for (i, &(line_no, operation)) in operations.iter().enumerate() {
+            let diff = screen.apply_and_diff(operation).unwrap_or_else(|e| {
+                let text = input.text().lines().nth(line_no - 1).unwrap_or("");
+                input.fail(Some(InputError::at(line_no, text, format!("{} ({}x{})", e, width, height))))
+            });
+            screen.explain(line_no, operation, &mut explain);
+            if animate {
+                draw_frame(&screen, operation, &diff, i == 0);
+                thread::sleep(Duration::from_millis(delay));
+            }
+            #[cfg(feature = "gif")]
+            {
+                if let Some(ref mut recorder) = recorder {
+                    recorder.record(&screen, &diff).unwrap_or_else(|e| report::exit(e));
                 }
-                write!(f, "\n")?;
             }
-            Ok(())
+        }
+        drop(apply);
+        if animate {
+            println!();
         }
     }
-}
 
-
-use std::io::Read;
-use two_factor_authentication::*;
-
-fn main() {
-    // acquire data from stdin.
-    let mut input = String::new();
-    let stdin = std::io::stdin();
-    stdin.lock().read_to_string(&mut input).expect("no input given");
-
-    // Parse one `Operation` per line of input.
-    let operations: Vec<Operation> = input.lines().map(|line| line.parse().unwrap()).collect();
-
-    // screen initialization and operations.
-    let mut screen = Screen::blank(50, 6);
-    for &operation in operations.iter() {
-        screen.execute(operation);
+    if let Some(path) = save {
+        std::fs::write(&path, screen.to_string()).unwrap_or_else(|e| report::exit(format!("{}: {}", path, e)));
     }
 
-    // print the screen display and voltage usage.
-    println!("{}", screen);
-    println!("The screen's voltage usage is: {}", screen.voltage_usage());
-}
-
-
-#[test]
-fn part1_example() {
-    let mut screen = Screen::blank(7, 3);
-    let op: Operation = "rect 3x2".parse().unwrap();
-    assert_eq!(op, Operation::Rect(3, 2));
-    screen.execute(op);
-    assert_eq!(screen.to_string(), "\
-###....
-###....
-.......
-");
-    let op: Operation = "rotate column x=1 by 1".parse().unwrap();
-    assert_eq!(op, Operation::RotateCol(1, 1));
-    screen.execute(op);
-    assert_eq!(screen.to_string(), "\
-#.#....
-###....
-.#.....
-");
-    let op: Operation = "rotate row y=0 by 4".parse().unwrap();
-    assert_eq!(op, Operation::RotateRow(0, 4));
-    screen.execute(op);
-    assert_eq!(screen.to_string(), "\
-....#.#
-###....
-.#.....
-");
-    let op: Operation = "rotate column x=1 by 1".parse().unwrap();
-    assert_eq!(op, Operation::RotateCol(1, 1));
-    screen.execute(op);
-    assert_eq!(screen.to_string(), "\
-.#..#.#
-#.#....
-.#.....
-");
-    assert_eq!(screen.voltage_usage(), 6);
+    // print the voltage usage, the screen display and the decoded code.
+    let voltage = screen.voltage_usage();
+    cli.answer(1, voltage, format_args!("The screen's voltage usage is: {}", voltage));
+    cli.note(format_args!("{}", screen.render(style)));
+    let text = match font {
+        Some(ref font) => screen.read_text_with(font),
+        None => screen.read_text(),
+    };
+    if let Some(text) = text {
+        cli.answer(2, &text, format_args!("The screen displays: {}", text));
+    }
 }
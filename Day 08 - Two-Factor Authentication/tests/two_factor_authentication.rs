@@ -0,0 +1,352 @@
+extern crate aoc_core;
+extern crate two_factor_authentication;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+#[macro_use]
+extern crate snapshot;
+
+use aoc_core::{Font, Span};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use proptest::prelude::{any_with, Just, Strategy};
+use two_factor_authentication::*;
+
+#[test]
+fn part1_example() {
+    let mut screen = Screen::blank(7, 3);
+    let op: Operation = "rect 3x2".parse().unwrap();
+    assert_eq!(op, Operation::Rect(3, 2));
+    screen.execute(op).unwrap();
+    assert_eq!(screen.to_string(), "\
+###....
+###....
+.......
+");
+    let op: Operation = "rotate column x=1 by 1".parse().unwrap();
+    assert_eq!(op, Operation::RotateCol(1, 1));
+    screen.execute(op).unwrap();
+    assert_eq!(screen.to_string(), "\
+#.#....
+###....
+.#.....
+");
+    let op: Operation = "rotate row y=0 by 4".parse().unwrap();
+    assert_eq!(op, Operation::RotateRow(0, 4));
+    screen.execute(op).unwrap();
+    assert_eq!(screen.to_string(), "\
+....#.#
+###....
+.#.....
+");
+    let op: Operation = "rotate column x=1 by 1".parse().unwrap();
+    assert_eq!(op, Operation::RotateCol(1, 1));
+    screen.execute(op).unwrap();
+    assert_eq!(screen.to_string(), "\
+.#..#.#
+#.#....
+.#.....
+");
+    assert_eq!(screen.voltage_usage(), 6);
+}
+
+#[test]
+fn read_text() {
+    let mut screen = Screen::blank(50, 6);
+    for line in include_str!("../input.txt").lines() {
+        screen.execute(line.parse().unwrap()).unwrap();
+    }
+    assert_eq!(screen.read_text(), Some("EFEYKFRFIJ".to_string()));
+    // the example screen is too short to display any text.
+    assert_eq!(Screen::blank(7, 3).read_text(), None);
+    // blank glyphs are not recognized.
+    assert_eq!(Screen::blank(10, 6).read_text(), Some("??".to_string()));
+}
+
+#[test]
+fn read_text_with_font() {
+    // a 6x10 font, the glyphs being spaced by two blank columns.
+    let font = Font::parse("8x10\n\
+        H\n#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#\n\
+        I\n.####.\n..#\n..#\n..#\n..#\n..#\n..#\n..#\n..#\n.####.\n").unwrap();
+    let screen: Screen = "\
+        #....#...####.\n#....#....#...\n#....#....#...\n#....#....#...\n######....#...\n\
+        #....#....#...\n#....#....#...\n#....#....#...\n#....#....#...\n#....#...####.\n".parse().unwrap();
+    assert_eq!(screen.read_text_with(&font), Some("HI".to_string()));
+    assert_eq!(screen.read_text(), None);
+    // the screen's own font is a font like any other.
+    let font = Font::parse(include_str!("../font.txt")).unwrap();
+    assert_eq!((font.width(), font.height(), font.len()), (5, 6, 18));
+}
+
+#[test]
+fn apply_and_diff() {
+    let mut screen = Screen::blank(7, 3);
+    let diff = screen.apply_and_diff("rect 3x2".parse().unwrap()).unwrap();
+    assert_eq!(diff, vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    let diff = screen.apply_and_diff("rotate column x=1 by 1".parse().unwrap()).unwrap();
+    assert_eq!(diff, vec![(1, 0), (1, 2)]);
+    // nothing changes when turning on pixels already lit.
+    let diff = screen.apply_and_diff("rect 1x1".parse().unwrap()).unwrap();
+    assert!(diff.is_empty());
+    assert_eq!(screen.apply_and_diff("rect 8x1".parse().unwrap()), Err(ExecuteError::OversizedRect(8, 1)));
+}
+
+#[test]
+fn explain_frames() {
+    let mut screen = Screen::blank(3, 2);
+    let mut events: Vec<String> = Vec::new();
+    for (line_no, op) in [(1, "rect 2x1"), (2, "rotate row y=0 by 2")] {
+        let op: Operation = op.parse().unwrap();
+        screen.execute(op).unwrap();
+        screen.explain(line_no, op, &mut events);
+        screen.explain(line_no, op, &mut ());
+    }
+    assert_eq!(events, [
+        r###"{"event":"frame","line":1,"operation":"rect 2x1","lit":2,"screen":"##.\n...\n"}"###,
+        r###"{"event":"frame","line":2,"operation":"rotate row y=0 by 2","lit":2,"screen":"#.#\n...\n"}"###,
+    ]);
+}
+
+#[test]
+fn undoable_screen() {
+    let mut screen = UndoableScreen::new(Screen::blank(7, 3));
+    assert_eq!(screen.undo(), None);
+    let mut states = vec![screen.screen().to_string()];
+    for op in &["rect 3x2", "rotate column x=1 by 1", "toggle 4x3", "rect off 2x1", "rotate row y=2 by -3"] {
+        screen.execute(op.parse().unwrap()).unwrap();
+        states.push(screen.screen().to_string());
+    }
+    // failed operations are not remembered.
+    assert_eq!(screen.execute("rect 8x1".parse().unwrap()), Err(ExecuteError::OversizedRect(8, 1)));
+    assert_eq!(screen.undo_len(), 5);
+    states.pop();
+    assert_eq!(screen.undo(), Some("rotate row y=2 by -3".parse().unwrap()));
+    while let Some(state) = states.pop() {
+        assert_eq!(screen.screen().to_string(), state);
+        screen.undo();
+    }
+    assert_eq!(screen.undo_len(), 0);
+}
+
+#[test]
+fn interactive_commands() {
+    assert_eq!("undo".parse(), Ok(Command::Undo));
+    assert_eq!(" show ".parse(), Ok(Command::Show));
+    assert_eq!("rect 3x2".parse(), Ok(Command::Execute(Operation::Rect(3, 2))));
+    assert!("redo".parse::<Command>().is_err());
+}
+
+#[test]
+fn extended_operations() {
+    let mut screen = Screen::blank(7, 3);
+    let ops = ["rect 3x2", "rect off 2x1", "toggle 4x3", "rotate row y=1 by -2", "rotate column x=2 by -1"];
+    let expected = [Operation::Rect(3, 2), Operation::RectOff(2, 1), Operation::Toggle(4, 3),
+                    Operation::RotateRow(1, -2), Operation::RotateCol(2, -1)];
+    for (s, &op) in ops.iter().zip(expected.iter()) {
+        assert_eq!(s.parse::<Operation>(), Ok(op));
+        assert_eq!(screen.execute(op), Ok(()));
+    }
+    assert_eq!(screen.to_string(), "\
+##.#...
+.##....
+##.#...
+");
+    assert_eq!(screen.execute(Operation::RotateRow(0, -7)), Err(ExecuteError::BadOffset(-7)));
+    assert_eq!(screen.execute(Operation::RotateCol(0, 3)), Err(ExecuteError::BadOffset(3)));
+    assert_eq!(screen.execute(Operation::RotateRow(3, 1)), Err(ExecuteError::BadRow(3)));
+    assert_eq!(screen.execute(Operation::RotateCol(7, 1)), Err(ExecuteError::BadColumn(7)));
+}
+
+#[test]
+fn parse_operations_errors() {
+    let input = "rect 3x2\nrect 3 by 2\nrotate row y=0 by 4\nrect 99999999999x1";
+    let (operations, errors) = parse_operations(input);
+    assert_eq!(operations, vec![(1, Operation::Rect(3, 2)), (3, Operation::RotateRow(0, 4))]);
+    assert_eq!(errors, vec![
+        OperationParseError {
+            line_no: 2,
+            text: "rect 3 by 2".to_string(),
+            reason: "unrecognized operation: rect 3 by 2".to_string(),
+            span: Some(Span::new(2, 1, 11)),
+        },
+        OperationParseError {
+            line_no: 4,
+            text: "rect 99999999999x1".to_string(),
+            reason: "number out of range: 99999999999".to_string(),
+            span: Some(Span::new(4, 6, 11)),
+        },
+    ]);
+}
+
+#[test]
+fn compile_script_example() {
+    let src = "\
+# a rectangle, spread around.
+const W = 3
+const H = 2
+
+rect $Wx$H  # top-left rectangle
+repeat 2 {
+    repeat 1 {
+        rotate column x=1 by 1
+    }
+    rotate row y=0 by $H
+}
+";
+    let ops: Vec<Operation> = compile_script(src).unwrap().into_iter().map(|(_, op)| op).collect();
+    assert_eq!(ops, vec![
+        Operation::Rect(3, 2),
+        Operation::RotateCol(1, 1),
+        Operation::RotateRow(0, 2),
+        Operation::RotateCol(1, 1),
+        Operation::RotateRow(0, 2),
+    ]);
+}
+
+#[test]
+fn compile_script_errors() {
+    let error = compile_script("rect $Wx2").unwrap_err();
+    assert_eq!((error.line_no, error.reason), (1, "undefined constant: W".to_string()));
+    let error = compile_script("rect 1x1\n}").unwrap_err();
+    assert_eq!((error.line_no, error.reason), (2, "unexpected end of block".to_string()));
+    let error = compile_script("repeat 2 {\nrect 1x1").unwrap_err();
+    assert_eq!((error.line_no, error.reason), (1, "unterminated block".to_string()));
+    let error = compile_script("repeat 2 {\nrect 1 x 1\n}").unwrap_err();
+    assert_eq!(error.line_no, 2);
+    let error = compile_script("repeat 2 {\n  rect 99999999999x1\n}").unwrap_err();
+    assert_eq!(error.span, Some(Span::new(2, 8, 11)));
+    let error = compile_script("const W = 99999999999\nrect $Wx1").unwrap_err();
+    assert_eq!((error.line_no, error.span), (2, None));
+}
+
+#[test]
+fn compile_script_expansion_limit() {
+    let error = compile_script("repeat 1000 {\n  repeat 1000001 {\n    rect 1x1\n  }\n}").unwrap_err();
+    assert_eq!((error.line_no, error.span), (2, Some(Span::new(2, 10, 7))));
+    let error = compile_script("repeat 1001 {\n  repeat 1000 {\n    rect 1x1\n  }\n}").unwrap_err();
+    assert_eq!((error.line_no, error.span), (1, Some(Span::new(1, 8, 4))));
+    let error = compile_script("const N = 9223372036854775807\nrepeat $N {\n  rect 1x1\n  rect 1x1\n}").unwrap_err();
+    assert_eq!((error.line_no, error.span), (2, None));
+    assert_eq!(error.reason, format!("block expands to more than {} operations", MAX_SCRIPT_OPERATIONS));
+    let ops = compile_script("repeat 1000 {\n  repeat 1000 {\n    rect 1x1\n  }\n}").unwrap();
+    assert_eq!(ops.len(), MAX_SCRIPT_OPERATIONS);
+}
+
+#[test]
+fn render_styles() {
+    let mut screen = Screen::blank(7, 3);
+    for line in "rect 3x2\nrotate column x=1 by 1\nrotate row y=0 by 4\nrotate column x=1 by 1".lines() {
+        screen.execute(line.parse().unwrap()).unwrap();
+    }
+    assert_eq!(screen.render(RenderStyle::Ascii), screen.to_string());
+    assert_eq!(screen.render(RenderStyle::HalfBlock), "\
+\u{2584}\u{2580}\u{2584} \u{2580} \u{2580}
+ \u{2580}     
+");
+    assert_eq!(screen.render(RenderStyle::Braille), "\u{282a}\u{2802}\u{2801}\u{2801}\n");
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let snapshot = "\
+.#..#.#
+#.#....
+.#.....
+";
+    let screen: Screen = snapshot.parse().unwrap();
+    assert_eq!((screen.width(), screen.height()), (7, 3));
+    assert_eq!(screen.voltage_usage(), 6);
+    assert_eq!(screen.to_string(), snapshot);
+    assert!("".parse::<Screen>().is_err());
+    assert!("##\n#".parse::<Screen>().is_err());
+    assert!("#x".parse::<Screen>().is_err());
+}
+
+#[test]
+fn wide_and_tall_screens() {
+    // a screen spanning many words per row and per column.
+    let mut screen = Screen::blank(150, 130);
+    for line in "rect 140x3\nrotate row y=1 by 75\nrotate column x=139 by 129\ntoggle 70x130\nrotate column x=3 by -100".lines() {
+        screen.execute(line.parse().unwrap()).unwrap();
+    }
+    let rendered = screen.to_string();
+    let rows: Vec<&str> = rendered.lines().collect();
+    // build a row from its runs of pixels.
+    let runs = |runs: &[(char, usize)]| -> String {
+        runs.iter().map(|&(ch, n)| ch.to_string().repeat(n)).collect()
+    };
+    assert_eq!(rows[0], runs(&[('.', 3), ('#', 1), ('.', 66), ('#', 70), ('.', 10)]));
+    assert_eq!(rows[1], runs(&[('.', 3), ('#', 1), ('.', 61), ('#', 5), ('.', 5), ('#', 75)]));
+    assert_eq!(rows[2], runs(&[('.', 3), ('#', 1), ('.', 66), ('#', 69), ('.', 11)]));
+    assert_eq!(rows[129], runs(&[('#', 70), ('.', 69), ('#', 1), ('.', 10)]));
+    assert_eq!(screen.voltage_usage(), 9110);
+    assert_eq!(screen.voltage_usage(), rendered.matches('#').count());
+}
+
+#[test]
+fn lit_pixels() {
+    let screen: Screen = ".#..#.#\n#.#....\n.#.....\n".parse().unwrap();
+    let pixels: Vec<(usize, usize)> = screen.lit_pixels().collect();
+    assert_eq!(pixels, vec![(1, 0), (4, 0), (6, 0), (0, 1), (2, 1), (1, 2)]);
+    assert_eq!(pixels.len(), screen.voltage_usage());
+    assert_eq!(Screen::blank(7, 3).lit_pixels().next(), None);
+}
+
+proptest! {
+    #[test]
+    fn operation_round_trip(op: Operation) {
+        prop_assert_eq!(op.to_string().parse::<Operation>(), Ok(op));
+    }
+
+    #[test]
+    fn fitting_operations_execute(ops in proptest::collection::vec(any_with::<Operation>(Some((50, 6))), 0..20)) {
+        let mut screen = Screen::blank(50, 6);
+        for op in ops {
+            prop_assert_eq!(screen.execute(op), Ok(()));
+        }
+    }
+
+    #[test]
+    fn small_screens_operations_execute(
+        (width, height, ops) in (0..4u32, 0..4u32).prop_flat_map(|(width, height)| {
+            (Just(width), Just(height), proptest::collection::vec(any_with::<Operation>(Some((width, height))), 0..10))
+        })
+    ) {
+        let mut screen = Screen::blank(width as usize, height as usize);
+        for op in ops {
+            prop_assert_eq!(screen.execute(op), Ok(()));
+        }
+    }
+
+    #[test]
+    fn operation_parse_never_panics(s in "(rect|rect off|toggle) [0-9]{1,12}x[0-9]{1,12}|rotate (row y|column x)=[0-9]{1,12} by -?[0-9]{1,12}|\\PC*") {
+        if let Ok(op) = s.parse::<Operation>() {
+            prop_assert_eq!(op.to_string().parse::<Operation>(), Ok(op));
+        }
+    }
+}
+
+#[test]
+fn screen_snapshots() {
+    let mut screen = Screen::blank(50, 6);
+    for line in include_str!("../input.txt").lines() {
+        screen.execute(line.parse().unwrap()).unwrap();
+    }
+    assert_snapshot!("screen_ascii", screen.to_string());
+    assert_snapshot!("screen_half_block", screen.render(RenderStyle::HalfBlock));
+    assert_snapshot!("screen_braille", screen.render(RenderStyle::Braille));
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 200);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 200));
+    let (operations, errors) = parse_operations(&input);
+    assert!(errors.is_empty());
+    assert_eq!(operations.len(), 200);
+    let mut screen = Screen::blank(50, 6);
+    for (_, op) in operations {
+        screen.execute(op).unwrap();
+    }
+}
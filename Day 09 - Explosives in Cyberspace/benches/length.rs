@@ -0,0 +1,38 @@
+//! Day 9 version 2 decompressed length: the single-pass scan against building the node tree,
+//! one-shot and fed through the incremental parser.
+
+#[macro_use]
+extern crate criterion;
+extern crate explosives_in_cyberspace;
+
+use criterion::{Criterion, Throughput};
+use explosives_in_cyberspace::*;
+
+const INPUT: &str = include_str!("../input.txt");
+
+/// Size of the chunks fed to `EzipParser`, roughly a pipe buffer.
+const CHUNK: usize = 4096;
+
+fn length_v2(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day09");
+    group.throughput(Throughput::Bytes(INPUT.len() as u64));
+    group.bench_function("v2/fast", |b| {
+        b.iter(|| decompressed_len_v2_fast(INPUT).unwrap())
+    });
+    group.bench_function("v2/tree", |b| {
+        b.iter(|| Ezip::parse_v2(INPUT).unwrap().checked_uncompressed_len().unwrap())
+    });
+    group.bench_function("v2/streaming", |b| {
+        b.iter(|| {
+            let mut parser = EzipParser::v2(Whitespace::default());
+            for chunk in INPUT.as_bytes().chunks(CHUNK) {
+                parser.feed(chunk).unwrap();
+            }
+            parser.finish().unwrap().checked_uncompressed_len().unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, length_v2);
+criterion_main!(benches);
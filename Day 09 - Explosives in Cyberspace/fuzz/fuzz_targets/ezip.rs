@@ -0,0 +1,50 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate explosives_in_cyberspace;
+
+use explosives_in_cyberspace::{decompressed_len_v2_fast, Ezip, EzipParser, EzipReader, Whitespace};
+use std::io::Read;
+
+// keep the decompressed data small, the fuzzer would spend its time writing bytes otherwise.
+const LIMIT: usize = 1 << 16;
+
+fuzz_target!(|data: &[u8]| {
+    let policies = [Whitespace::Trim, Whitespace::StripTrailingNewline, Whitespace::Preserve, Whitespace::Reject];
+    for &policy in policies.iter() {
+        let v1 = Ezip::parse_v1_with(data, policy);
+        let v2 = Ezip::parse_v2_with(data, policy);
+        for ezip in v1.iter().chain(v2.iter()) {
+            let mut out = Vec::new();
+            if ezip.decompress_with_limit(&mut out, LIMIT).is_ok() {
+                assert_eq!(Some(out.len() as u128), ezip.checked_uncompressed_len());
+            }
+            ezip.dump_tree(&mut ::std::io::sink()).unwrap();
+        }
+        // the streaming reader agrees with the version 1 parser.
+        let mut streamed = Vec::new();
+        let reader = EzipReader::with_policy(data, policy).take(LIMIT as u64).read_to_end(&mut streamed);
+        if let Ok(ref ezip) = v1 {
+            if ezip.checked_uncompressed_len().is_some_and(|len| len < LIMIT as u128) {
+                let mut out = Vec::new();
+                ezip.decompress(&mut out).unwrap();
+                assert_eq!(reader.ok().map(|_| streamed), Some(out));
+            }
+        } else {
+            assert!(reader.is_err() || streamed.len() == LIMIT);
+        }
+        // the incremental parser agrees with the one-shot parser, whatever the chunk size.
+        let chunk_size = 1 + data.first().map_or(0, |&b| b as usize % 16);
+        let mut parser = EzipParser::v2(policy);
+        let incremental = data.chunks(chunk_size)
+            .try_for_each(|chunk| parser.feed(chunk))
+            .and_then(|_| parser.finish());
+        assert_eq!(incremental.is_ok(), v2.is_ok());
+        if policy == Whitespace::Trim {
+            if let Ok(s) = ::std::str::from_utf8(data) {
+                let fast = decompressed_len_v2_fast(s);
+                assert_eq!(fast, v2.ok().and_then(|ezip| ezip.checked_uncompressed_len()));
+            }
+        }
+    }
+});
@@ -0,0 +1,1093 @@
+//! Day 9: Explosives in Cyberspace, parsing the experimental compression format and
+//! computing or streaming its decompressed data.
+//!
+//! Computing the decompressed length only needs `core`: without the default `std` feature the
+//! crate is `#![no_std]` and only provides the `ezip_length` module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate aoc_core;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(feature = "std")]
+extern crate rand;
+#[cfg(feature = "std")]
+extern crate report;
+
+pub mod ezip_length {
+    /// How deep version 2 markers can be nested, deeper markers are rejected when parsing.
+    pub const MAX_NESTING: usize = 256;
+
+    /// Returns the decompressed length of a string formated in the Experimental data compression
+    /// format version 2, or `None` if it is malformed or the length overflows.
+    ///
+    /// This is a single-pass scan computing the length directly from the input, it yields the
+    /// same result as `Ezip::parse_v2(s)` followed by `checked_uncompressed_len()` without
+    /// building the node tree. Like the parser it rejects an empty input.
+    pub fn decompressed_len_v2_fast(s: &str) -> Option<u128> {
+        if s.is_empty() {
+            return None;
+        }
+        scan_len_v2(s.as_bytes(), 0)
+    }
+
+    // compute the decompressed length of `data` as version 2, `depth` being the number of
+    // markers it is nested in.
+    fn scan_len_v2(data: &[u8], depth: usize) -> Option<u128> {
+        if depth > MAX_NESTING {
+            return None;
+        }
+        let mut len: u128 = 0;
+        let mut i = 0;
+        while i < data.len() {
+            // the uncompressed chunk up to the next marker, trailing whitespaces excluded.
+            let start = data[i..].iter().position(|&b| b == b'(').map_or(data.len(), |n| i + n);
+            let chunk = &data[i..start];
+            let trimmed = chunk.iter().rposition(|b| !b.is_ascii_whitespace()).map_or(0, |n| n + 1);
+            len = len.checked_add(trimmed as u128)?;
+            if start == data.len() {
+                break;
+            }
+            let (count, repeat, end) = scan_marker(data, start)?;
+            let section = data.get(end..end.checked_add(count)?)?;
+            len = len.checked_add(scan_len_v2(section, depth + 1)?.checked_mul(repeat as u128)?)?;
+            i = end + count;
+        }
+        Some(len)
+    }
+
+    // scan the marker starting at `data[start]` (eg. "(3x6)"), returns its two numbers and the
+    // position right after it.
+    pub(crate) fn scan_marker(data: &[u8], start: usize) -> Option<(usize, usize, usize)> {
+        let mut i = start + 1;
+        let number = |i: &mut usize| -> Option<usize> {
+            while data.get(*i)?.is_ascii_whitespace() {
+                *i += 1;
+            }
+            let digits = data[*i..].iter().take_while(|b| b.is_ascii_digit()).count();
+            let n = ::core::str::from_utf8(&data[*i..(*i + digits)]).ok()?.parse().ok()?;
+            *i += digits;
+            while data.get(*i)?.is_ascii_whitespace() {
+                *i += 1;
+            }
+            Some(n)
+        };
+        let count = number(&mut i)?;
+        if data.get(i) != Some(&b'x') {
+            return None;
+        }
+        i += 1;
+        let repeat = number(&mut i)?;
+        if data.get(i) != Some(&b')') {
+            return None;
+        }
+        Some((count, repeat, i + 1))
+    }
+}
+
+#[cfg(feature = "std")]
+pub mod explosives_in_cyberspace {
+    use ::aoc_core::Spanned;
+    use ::std::collections::VecDeque;
+    use ::std::io::{self, Read, Write};
+    use ::std::ops::Range;
+    use ::rand::Rng;
+    use ::report::{AocError, FromInput, Input, InputError};
+    use ::ezip_length::scan_marker;
+
+    /// The largest decompressed data `Ezip::decompress()` accept to write, in bytes (1 GiB).
+    pub const MAX_DECOMPRESSED_LEN: usize = 1 << 30;
+    /// Represents a node from the `Ezip` "tree". Either an uncompressed chunk of data or a
+    /// sub-`Ezip` to be repeated.
+    ///
+    /// Both carry the byte range of the input they were parsed from, marker included.
+    #[derive(Debug)]
+    enum EzipNode {
+        Uncompressed(Range<usize>, Vec<u8>),
+        Compressed(Range<usize>, usize, Ezip),
+    }
+
+    impl EzipNode {
+        /// Returns the byte range of the input this node was parsed from.
+        fn span(&self) -> &Range<usize> {
+            match *self {
+                EzipNode::Uncompressed(ref span, _) | EzipNode::Compressed(ref span, _, _) => span,
+            }
+        }
+
+        /// Returns the uncompressed data length for this node, or `None` on overflow.
+        fn checked_uncompressed_len(&self) -> Option<u128> {
+            match *self {
+                EzipNode::Uncompressed(_, ref s) => Some(s.len() as u128),
+                EzipNode::Compressed(_, repeat, ref children) => {
+                    children.checked_uncompressed_len()?.checked_mul(repeat as u128)
+                },
+            }
+        }
+
+        /// Write the uncompressed data for this node into `out`.
+        fn decompress<W: Write>(&self, out: &mut W) -> io::Result<()> {
+            match *self {
+                EzipNode::Uncompressed(_, ref s) => out.write_all(s),
+                // NOTE: a marker may repeat nothing a huge number of times.
+                EzipNode::Compressed(_, _, ref children) if children.checked_uncompressed_len() == Some(0) => Ok(()),
+                EzipNode::Compressed(_, repeat, ref children) => {
+                    for _ in 0..repeat {
+                        children.write_nodes(out)?;
+                    }
+                    Ok(())
+                },
+            }
+        }
+    }
+
+    /// Error returned when parsing an `Ezip` fails.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct EzipParseError {
+        /// Byte offset in the input where the parsing failed.
+        pub offset: usize,
+        /// What the parser expected to find at `offset`.
+        pub expected: &'static str,
+    }
+
+    impl EzipParseError {
+        /// Returns self along with the span of the offending byte in `input`, the text parsed.
+        pub fn spanned(self, input: &str) -> Spanned<EzipParseError> {
+            Spanned::of(input, self.offset..self.offset + 1, self)
+        }
+    }
+
+    impl ::std::fmt::Display for EzipParseError {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "expected {} at byte {}", self.expected, self.offset)
+        }
+    }
+
+    impl ::std::error::Error for EzipParseError {}
+
+    /// How whitespaces are handled while parsing an `Ezip`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Whitespace {
+        /// Allow whitespaces around marker numbers and drop the trailing whitespaces of every
+        /// uncompressed chunk (the puzzle behaviour).
+        #[default]
+        Trim,
+        /// Strip the trailing newline of the input only, everything else is data.
+        StripTrailingNewline,
+        /// Every byte is data, including the trailing newline.
+        Preserve,
+        /// Like `StripTrailingNewline`, but any other whitespace is an error.
+        Reject,
+    }
+
+    impl Whitespace {
+        /// Returns the part of `data` to be parsed according to this policy.
+        fn prepare(self, data: &[u8]) -> Result<&[u8], EzipParseError> {
+            let data = match self {
+                Whitespace::Trim | Whitespace::Preserve => return Ok(data),
+                Whitespace::StripTrailingNewline | Whitespace::Reject => {
+                    let data = data.strip_suffix(b"\n").unwrap_or(data);
+                    data.strip_suffix(b"\r").unwrap_or(data)
+                },
+            };
+            if self == Whitespace::Reject {
+                if let Some(offset) = data.iter().position(|b| b.is_ascii_whitespace()) {
+                    return Err(EzipParseError { offset, expected: "a non-whitespace byte" });
+                }
+            }
+            Ok(data)
+        }
+    }
+
+    impl ::std::str::FromStr for Whitespace {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Whitespace, String> {
+            match s {
+                "trim" => Ok(Whitespace::Trim),
+                "strip-newline" => Ok(Whitespace::StripTrailingNewline),
+                "preserve" => Ok(Whitespace::Preserve),
+                "reject" => Ok(Whitespace::Reject),
+                _ => Err(format!("unknown whitespace policy: {}", s)),
+            }
+        }
+    }
+
+    /// Experimental data compression format found in the Easter Bunny HQ.
+    #[derive(Debug)]
+    pub struct Ezip {
+        nodes: Vec<EzipNode>,
+    }
+
+    impl Ezip {
+        /// Parse a string formated in the Experimental data compression format version 1.
+        pub fn parse_v1(s: &str) -> Result<Ezip, EzipParseError> {
+            Ezip::parse_v1_bytes(s.as_bytes())
+        }
+
+        /// Parse a string formated in the Experimental data compression format version 2.
+        pub fn parse_v2(s: &str) -> Result<Ezip, EzipParseError> {
+            Ezip::parse_v2_bytes(s.as_bytes())
+        }
+
+        /// Parse data formated in the Experimental data compression format version 1.
+        ///
+        /// Unlike `parse_v1()` the uncompressed sections are raw bytes and don't have to be
+        /// valid UTF-8.
+        pub fn parse_v1_bytes(data: &[u8]) -> Result<Ezip, EzipParseError> {
+            Ezip::parse_v1_with(data, Whitespace::default())
+        }
+
+        /// Parse data formated in the Experimental data compression format version 2.
+        ///
+        /// Unlike `parse_v2()` the uncompressed sections are raw bytes and don't have to be
+        /// valid UTF-8.
+        pub fn parse_v2_bytes(data: &[u8]) -> Result<Ezip, EzipParseError> {
+            Ezip::parse_v2_with(data, Whitespace::default())
+        }
+
+        /// Parse data formated in the Experimental data compression format version 1, handling
+        /// whitespaces according to `policy`.
+        pub fn parse_v1_with(data: &[u8], policy: Whitespace) -> Result<Ezip, EzipParseError> {
+            Ezip::parse_at(data, 0, false, policy)
+        }
+
+        /// Parse data formated in the Experimental data compression format version 2, handling
+        /// whitespaces according to `policy`.
+        pub fn parse_v2_with(data: &[u8], policy: Whitespace) -> Result<Ezip, EzipParseError> {
+            Ezip::parse_at(data, 0, true, policy)
+        }
+
+        /// Parse `data` found at offset `base` of the input, the spans and error offsets being
+        /// relative to the input start.
+        fn parse_at(data: &[u8], base: usize, v2: bool, policy: Whitespace) -> Result<Ezip, EzipParseError> {
+            let data = policy.prepare(data).map_err(|e| EzipParseError { offset: base + e.offset, ..e })?;
+            parsing::Parser::new(data, base, policy == Whitespace::Trim, v2).parse()
+        }
+
+        /// Returns the byte range of the input this `Ezip` was parsed from.
+        pub fn span(&self) -> Range<usize> {
+            match (self.nodes.first(), self.nodes.last()) {
+                (Some(first), Some(last)) => first.span().start..last.span().end,
+                _ => 0..0,
+            }
+        }
+
+        /// Returns the uncompressed data length of the file, or `None` if it doesn't fit in a
+        /// `u128`.
+        ///
+        /// The length is computed as `u128` because deeply nested version 2 markers can easily
+        /// exceed `usize::MAX`, and may still overflow it.
+        pub fn checked_uncompressed_len(&self) -> Option<u128> {
+            self.nodes.iter().try_fold(0u128, |sum, node| {
+                sum.checked_add(node.checked_uncompressed_len()?)
+            })
+        }
+
+        /// Write the uncompressed data of the file into `out`.
+        ///
+        /// The decompression is the same for both format versions, the difference being made at
+        /// parsing time. Nothing is written and an error is returned when the uncompressed data
+        /// would be larger than `MAX_DECOMPRESSED_LEN`, see `decompress_with_limit()`.
+        pub fn decompress<W: Write>(&self, out: &mut W) -> io::Result<()> {
+            self.decompress_with_limit(out, MAX_DECOMPRESSED_LEN)
+        }
+
+        /// Write the uncompressed data of the file into `out` if its length is at most `limit`
+        /// bytes, returns an error without writing anything otherwise.
+        pub fn decompress_with_limit<W: Write>(&self, out: &mut W, limit: usize) -> io::Result<()> {
+            let too_large = match self.checked_uncompressed_len() {
+                Some(len) if len <= limit as u128 => None,
+                Some(len) => Some(format!("{} bytes", len)),
+                None => Some("overflow".to_string()),
+            };
+            if let Some(len) = too_large {
+                let msg = format!("decompressed data too large ({}, limit is {})", len, limit);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+            self.write_nodes(out)
+        }
+
+        /// Returns the digest of the uncompressed data of the file, computed by `D` without
+        /// writing the data anywhere.
+        ///
+        /// Like `decompress()` it is the same for both format versions, but it is meant for the
+        /// version 2 data which is usually far too large to be written: the repeated sections are
+        /// digested once and combined, so the data is never generated.
+        pub fn decompressed_digest_v2<D: CombinableDigest>(&self) -> D::Output {
+            let mut digest = D::default();
+            self.combine_nodes(&mut digest);
+            digest.finalize()
+        }
+
+        /// Returns the digest of the uncompressed data of the file, computed by `D` without
+        /// writing the data anywhere.
+        ///
+        /// Unlike `decompressed_digest_v2()` every byte is generated and fed to `D`, which takes
+        /// as long as writing them.
+        pub fn decompressed_digest<D: Digest>(&self) -> D::Output {
+            let mut digest = D::default();
+            self.digest_nodes(&mut digest);
+            digest.finalize()
+        }
+
+        /// Feed the uncompressed data of all our nodes into `digest`, digesting the repeated
+        /// sections once.
+        fn combine_nodes<D: CombinableDigest>(&self, digest: &mut D) {
+            for node in &self.nodes {
+                match *node {
+                    EzipNode::Uncompressed(_, ref s) => digest.update(s),
+                    EzipNode::Compressed(_, repeat, ref children) => {
+                        let mut part = D::default();
+                        children.combine_nodes(&mut part);
+                        digest.update_repeated(&part, repeat as u128);
+                    },
+                }
+            }
+        }
+
+        /// Feed the uncompressed data of all our nodes into `digest`, byte by byte.
+        fn digest_nodes<D: Digest>(&self, digest: &mut D) {
+            for node in &self.nodes {
+                match *node {
+                    EzipNode::Uncompressed(_, ref s) => digest.update(s),
+                    // NOTE: a marker may repeat nothing a huge number of times.
+                    EzipNode::Compressed(_, _, ref children) if children.checked_uncompressed_len() == Some(0) => (),
+                    EzipNode::Compressed(_, repeat, ref children) => {
+                        for _ in 0..repeat {
+                            children.digest_nodes(digest);
+                        }
+                    },
+                }
+            }
+        }
+
+        /// Write an indented view of the node tree into `out`, one node per line with its span
+        /// in the input and its decompressed length.
+        pub fn dump_tree<W: Write>(&self, out: &mut W) -> io::Result<()> {
+            self.dump_nodes(out, 0)
+        }
+
+        /// Write the tree view of all our nodes into `out`, indented by `depth` levels.
+        fn dump_nodes<W: Write>(&self, out: &mut W, depth: usize) -> io::Result<()> {
+            for node in &self.nodes {
+                let span = node.span();
+                let len = node.checked_uncompressed_len().map_or("overflow".to_string(), |n| n.to_string());
+                write!(out, "{:indent$}{}..{}: ", "", span.start, span.end, indent = depth * 2)?;
+                match *node {
+                    EzipNode::Uncompressed(..) => writeln!(out, "data ({} bytes)", len)?,
+                    EzipNode::Compressed(_, repeat, ref children) => {
+                        writeln!(out, "marker x{} ({} bytes)", repeat, len)?;
+                        children.dump_nodes(out, depth + 1)?;
+                    },
+                }
+            }
+            Ok(())
+        }
+
+        /// Write the uncompressed data of all our nodes into `out`.
+        fn write_nodes<W: Write>(&self, out: &mut W) -> io::Result<()> {
+            for node in &self.nodes {
+                node.decompress(out)?;
+            }
+            Ok(())
+        }
+
+        /// Build a new `Ezip` containing the given nodes.
+        fn build(nodes: Vec<EzipNode>) -> Ezip {
+            Ezip { nodes }
+        }
+    }
+
+    impl FromInput for Ezip {
+        /// Parse the whole `input` as version 1 like the puzzle part 1, see `parse_v1()`. The
+        /// error is reported with the offending byte underlined in its line.
+        fn from_input(input: &Input) -> Result<Ezip, AocError> {
+            let text = input.text();
+            Ezip::parse_v1(text).map_err(|e| InputError::spanned(text, e.spanned(text)).into())
+        }
+    }
+
+    /// A hash function computing the digest of uncompressed data, see
+    /// `Ezip::decompressed_digest_v2()`.
+    ///
+    /// The data is fed in chunks and the digest must not depend on how it was split. A digest
+    /// created with `default()` has been fed nothing.
+    pub trait Digest: Default {
+        /// The digest computed.
+        type Output;
+
+        /// Feed `data` into self.
+        fn update(&mut self, data: &[u8]);
+
+        /// Returns the digest of all the data fed into self.
+        fn finalize(self) -> Self::Output;
+    }
+
+    /// A `Digest` of repeated data computed from the digest of the data repeated, see
+    /// `Ezip::decompressed_digest_v2()`.
+    pub trait CombinableDigest: Digest {
+        /// Feed into self `times` copies of the data fed into `part`.
+        fn update_repeated(&mut self, part: &Self, times: u128);
+    }
+
+    /// The SipHash 1-3 of the standard library, with its zero keys so that the digest is the
+    /// same from one run to the next. It is not a `CombinableDigest`.
+    impl Digest for ::std::collections::hash_map::DefaultHasher {
+        type Output = u64;
+
+        fn update(&mut self, data: &[u8]) {
+            ::std::hash::Hasher::write(self, data);
+        }
+
+        fn finalize(self) -> u64 {
+            ::std::hash::Hasher::finish(&self)
+        }
+    }
+
+    /// The Mersenne prime modulus of `PolynomialHash`, 2^61 - 1.
+    const POLY_MODULUS: u64 = (1 << 61) - 1;
+    /// The base of `PolynomialHash`, an arbitrary number below the modulus.
+    const POLY_BASE: u64 = 0x1d8e_4e27_c47d_124f % POLY_MODULUS;
+
+    /// Returns `a * b` modulo `POLY_MODULUS`.
+    fn mul_mod(a: u64, b: u64) -> u64 {
+        ((a as u128 * b as u128) % POLY_MODULUS as u128) as u64
+    }
+
+    /// A polynomial rolling hash of the data modulo 2^61 - 1, i.e. the sum of every byte `b_i`
+    /// times `BASE^(n - 1 - i)`, `n` being the data length.
+    ///
+    /// It is no cryptographic hash, but it is a `CombinableDigest`: the hash of `a` followed by `b` is
+    /// `hash(a) * BASE^len(b) + hash(b)`, so the digest of astronomically large data can be
+    /// computed from its `Ezip` tree.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PolynomialHash {
+        hash: u64,
+        /// `BASE^n` where `n` is the length of the data fed so far.
+        shift: u64,
+    }
+
+    impl Default for PolynomialHash {
+        fn default() -> PolynomialHash {
+            PolynomialHash { hash: 0, shift: 1 }
+        }
+    }
+
+    impl PolynomialHash {
+        /// Append the data hashed by `other` to the data hashed by self.
+        fn append(&mut self, other: &PolynomialHash) {
+            self.hash = (mul_mod(self.hash, other.shift) + other.hash) % POLY_MODULUS;
+            self.shift = mul_mod(self.shift, other.shift);
+        }
+    }
+
+    impl Digest for PolynomialHash {
+        type Output = u64;
+
+        fn update(&mut self, data: &[u8]) {
+            for &byte in data {
+                self.hash = (mul_mod(self.hash, POLY_BASE) + u64::from(byte)) % POLY_MODULUS;
+                self.shift = mul_mod(self.shift, POLY_BASE);
+            }
+        }
+
+        fn finalize(self) -> u64 {
+            self.hash
+        }
+    }
+
+    impl CombinableDigest for PolynomialHash {
+        fn update_repeated(&mut self, part: &PolynomialHash, mut times: u128) {
+            // square-and-multiply, `power` being `part` repeated 1, 2, 4… times.
+            let mut power = *part;
+            let mut repeated = PolynomialHash::default();
+            while times > 0 {
+                if times & 1 == 1 {
+                    repeated.append(&power);
+                }
+                let square = power;
+                power.append(&square);
+                times >>= 1;
+            }
+            self.append(&repeated);
+        }
+    }
+
+    /// An incremental `Ezip` parser, fed with chunks of data as they arrive.
+    ///
+    /// Every complete top-level marker (along with the uncompressed chunk preceding it) is
+    /// parsed as soon as it is available and its data dropped, so only the incomplete tail of
+    /// the input is kept around.
+    pub struct EzipParser {
+        v2: bool,
+        policy: Whitespace,
+        /// The data fed but not yet parsed.
+        pending: Vec<u8>,
+        /// How many bytes have been parsed so far, used to report error offsets.
+        consumed: usize,
+        nodes: Vec<EzipNode>,
+    }
+
+    impl EzipParser {
+        /// Create a new parser for the Experimental data compression format version 1.
+        pub fn v1(policy: Whitespace) -> EzipParser {
+            EzipParser::new(false, policy)
+        }
+
+        /// Create a new parser for the Experimental data compression format version 2.
+        pub fn v2(policy: Whitespace) -> EzipParser {
+            EzipParser::new(true, policy)
+        }
+
+        fn new(v2: bool, policy: Whitespace) -> EzipParser {
+            EzipParser { v2, policy, pending: Vec::new(), consumed: 0, nodes: Vec::new() }
+        }
+
+        /// Feed the next chunk of data, parsing what can be.
+        pub fn feed(&mut self, chunk: &[u8]) -> Result<(), EzipParseError> {
+            self.pending.extend_from_slice(chunk);
+            // the policies stripping the trailing newline may strip the last two bytes of the
+            // input ("\r\n"), which are kept for finish() as they could be the last ones.
+            let available = match self.policy {
+                Whitespace::StripTrailingNewline | Whitespace::Reject => self.pending.len().saturating_sub(2),
+                Whitespace::Trim | Whitespace::Preserve => self.pending.len(),
+            };
+            // find the end of the last complete top-level marker data section.
+            let mut complete = 0;
+            while let Some(n) = self.pending[complete..].iter().position(|&b| b == b'(') {
+                let start = complete + n;
+                let end = match scan_marker(&self.pending, start).and_then(|(count, _, end)| end.checked_add(count)) {
+                    Some(end) if end <= available => end,
+                    // either incomplete or invalid, leave it to the next feed() or finish().
+                    _ => break,
+                };
+                complete = end;
+            }
+            if complete == 0 {
+                return Ok(());
+            }
+            // the trailing newline can only be at the very end of the input, so all the
+            // policies but Trim keep every byte of a complete prefix as data.
+            let policy = match self.policy {
+                Whitespace::Trim => Whitespace::Trim,
+                Whitespace::Reject => {
+                    let data = &self.pending[..complete];
+                    if let Some(offset) = data.iter().position(|b| b.is_ascii_whitespace()) {
+                        let offset = self.consumed + offset;
+                        return Err(EzipParseError { offset, expected: "a non-whitespace byte" });
+                    }
+                    Whitespace::Preserve
+                },
+                _ => Whitespace::Preserve,
+            };
+            self.parse(complete, policy)
+        }
+
+        /// Parse the remaining data and returns the complete `Ezip`.
+        pub fn finish(mut self) -> Result<Ezip, EzipParseError> {
+            let len = self.pending.len();
+            // an empty tail (once its trailing newline is stripped) is fine as long as we have
+            // parsed something before.
+            let empty = self.policy.prepare(&self.pending).is_ok_and(|tail| tail.is_empty());
+            if !empty || self.nodes.is_empty() {
+                self.parse(len, self.policy)?;
+            }
+            Ok(Ezip::build(self.nodes))
+        }
+
+        // parse the `len` first pending bytes using `policy`, and drop them.
+        fn parse(&mut self, len: usize, policy: Whitespace) -> Result<(), EzipParseError> {
+            let ezip = Ezip::parse_at(&self.pending[..len], self.consumed, self.v2, policy)?;
+            self.nodes.extend(ezip.nodes);
+            self.pending.drain(..len);
+            self.consumed += len;
+            Ok(())
+        }
+    }
+
+    /// A random Ezip data generator, mostly useful for fuzzing and testing.
+    ///
+    /// The generated data is valid for both format versions: every marker data section is
+    /// itself made of complete markers and uncompressed chunks.
+    #[derive(Debug, Clone)]
+    pub struct EzipGenerator {
+        /// How deep markers can be nested.
+        pub max_depth: usize,
+        /// The maximum count of nodes (markers or uncompressed chunks) at each level.
+        pub max_nodes: usize,
+        /// The maximum length of an uncompressed chunk.
+        pub max_chunk_len: usize,
+        /// The maximum repeat count of a marker.
+        pub max_repeat: usize,
+        /// When true, the uncompressed chunks may contain whitespaces, trailing ones included.
+        pub whitespace: bool,
+        /// When true, one random byte of the generated data is replaced by a random marker or
+        /// data byte, likely (but not always) making it invalid.
+        pub corrupt: bool,
+    }
+
+    impl Default for EzipGenerator {
+        fn default() -> EzipGenerator {
+            EzipGenerator {
+                max_depth: 3,
+                max_nodes: 4,
+                max_chunk_len: 8,
+                max_repeat: 9,
+                whitespace: false,
+                corrupt: false,
+            }
+        }
+    }
+
+    impl EzipGenerator {
+        /// Generate random Ezip data.
+        pub fn generate<R: Rng>(&self, rng: &mut R) -> String {
+            let mut data = self.nodes(rng, 0);
+            if self.corrupt {
+                const BYTES: &[u8] = b"()x0123456789AZ";
+                let mut bytes = data.into_bytes();
+                let i = rng.gen_range(0, bytes.len());
+                bytes[i] = BYTES[rng.gen_range(0, BYTES.len())];
+                // only ASCII bytes are generated.
+                data = String::from_utf8(bytes).unwrap();
+            }
+            data
+        }
+
+        // generate a non-empty chain of compressed and uncompressed chunk at the given depth.
+        fn nodes<R: Rng>(&self, rng: &mut R, depth: usize) -> String {
+            let mut data = String::new();
+            for _ in 0..rng.gen_range(1, self.max_nodes.max(1) + 1) {
+                if depth < self.max_depth && rng.gen() {
+                    let section = self.nodes(rng, depth + 1);
+                    let repeat = rng.gen_range(0, self.max_repeat + 1);
+                    data.push_str(&format!("({}x{}){}", section.len(), repeat, section));
+                } else {
+                    for _ in 0..rng.gen_range(1, self.max_chunk_len.max(1) + 1) {
+                        if self.whitespace && rng.gen_ratio(1, 4) {
+                            const WHITESPACES: &[u8] = b" \t\r\n";
+                            data.push(WHITESPACES[rng.gen_range(0, WHITESPACES.len())] as char);
+                        } else {
+                            data.push(rng.gen_range(b'A', b'Z' + 1) as char);
+                        }
+                    }
+                }
+            }
+            data
+        }
+    }
+
+    /// Returns random Ezip data of about `size` bytes, formatted like the puzzle input.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let generator = EzipGenerator::default();
+        let mut input = String::new();
+        while input.len() < size {
+            input.push_str(&generator.generate(rng));
+        }
+        input.push('\n');
+        input
+    }
+
+    /// An Experimental data compression format version 1 encoder.
+    ///
+    /// Repeated sequences are greedily replaced by a marker when it makes the data shorter.
+    /// Parenthesis and whitespaces are wrapped in a `(1x1)` marker so that the encoded data is
+    /// decompressed back to the original bytes by both `Ezip::parse_v1()` and `EzipReader`.
+    #[derive(Debug, Clone)]
+    pub struct EzipEncoder {
+        /// The longest sequence looked for repetitions, in bytes.
+        pub max_period: usize,
+    }
+
+    impl Default for EzipEncoder {
+        fn default() -> EzipEncoder {
+            EzipEncoder { max_period: 32 }
+        }
+    }
+
+    impl EzipEncoder {
+        /// Encode `s`, markers being only inserted between characters so that the result is
+        /// valid UTF-8.
+        ///
+        /// Note that an empty `s` is encoded as an empty string, which is not valid Ezip data.
+        pub fn encode(&self, s: &str) -> String {
+            let data = self.encode_with(s.as_bytes(), |i| s.is_char_boundary(i));
+            // markers are ASCII and only inserted at character boundaries.
+            String::from_utf8(data).unwrap()
+        }
+
+        /// Encode raw bytes.
+        ///
+        /// Note that an empty `data` is encoded as nothing, which is not valid Ezip data.
+        pub fn encode_bytes(&self, data: &[u8]) -> Vec<u8> {
+            self.encode_with(data, |_| true)
+        }
+
+        // encode `data`, only cutting it at the offsets accepted by `boundary`.
+        fn encode_with<F: Fn(usize) -> bool>(&self, data: &[u8], boundary: F) -> Vec<u8> {
+            let mut out = Vec::with_capacity(data.len());
+            let mut i = 0;
+            while i < data.len() {
+                if let Some((period, repeat)) = self.best_run(&data[i..], |n| boundary(i + n)) {
+                    out.extend_from_slice(format!("({}x{})", period, repeat).as_bytes());
+                    out.extend_from_slice(&data[i..(i + period)]);
+                    i += period * repeat;
+                    continue;
+                }
+                let end = (i + 1..=data.len()).find(|&n| boundary(n)).unwrap_or(data.len());
+                if data[i] == b'(' || data[i].is_ascii_whitespace() {
+                    out.extend_from_slice(b"(1x1)");
+                }
+                out.extend_from_slice(&data[i..end]);
+                i = end;
+            }
+            out
+        }
+
+        // find the period and repeat count of the run at the start of `data` saving the most
+        // bytes once encoded, if any.
+        fn best_run<F: Fn(usize) -> bool>(&self, data: &[u8], boundary: F) -> Option<(usize, usize)> {
+            let mut best = None;
+            let mut best_saving = 0;
+            for period in (1..=self.max_period.min(data.len() / 2)).filter(|&n| boundary(n)) {
+                let chunk = &data[..period];
+                let repeat = 1 + data[period..].chunks(period).take_while(|&c| c == chunk).count();
+                // "(", the two numbers, "x" and ")".
+                let marker_len = digits(period) + digits(repeat) + 3;
+                let saving = (period * repeat).saturating_sub(marker_len + period);
+                if saving > best_saving {
+                    best = Some((period, repeat));
+                    best_saving = saving;
+                }
+            }
+            best
+        }
+    }
+
+    // helper counting the decimal digits of `n`.
+    fn digits(mut n: usize) -> usize {
+        let mut count = 1;
+        while n >= 10 {
+            n /= 10;
+            count += 1;
+        }
+        count
+    }
+
+    /// The longest marker `EzipReader` accept, parenthesis included.
+    const MAX_MARKER_LEN: usize = 64;
+
+    /// A reader decompressing Experimental data compression format version 1 data on the fly.
+    ///
+    /// Unlike `Ezip::parse_v1()`, the data is never fully loaded in memory: only the data
+    /// section of the current marker is buffered. Whitespaces are handled like
+    /// `Ezip::parse_v1_with()` does for the same `Whitespace` policy.
+    pub struct EzipReader<R> {
+        inner: io::Bytes<io::BufReader<R>>,
+        policy: Whitespace,
+        /// The bytes read ahead from `inner`, to find out whether they are a trailing newline.
+        lookahead: VecDeque<u8>,
+        /// True once `inner` is exhausted.
+        eof: bool,
+        /// True until a byte is decompressed, empty data being invalid.
+        empty: bool,
+        /// The whitespaces read after some uncompressed data, dropped when they turn out to be
+        /// trailing.
+        spaces: Vec<u8>,
+        /// The data section of the current marker.
+        chunk: Vec<u8>,
+        /// Our position in `chunk`.
+        pos: usize,
+        /// How many times `chunk` still has to be repeated (the current one included).
+        repeat: usize,
+    }
+
+    impl<R: io::Read> EzipReader<R> {
+        /// Create a new `EzipReader` decompressing the data read from `inner`.
+        pub fn new(inner: R) -> EzipReader<R> {
+            EzipReader::with_policy(inner, Whitespace::default())
+        }
+
+        /// Create a new `EzipReader` decompressing the data read from `inner`, handling
+        /// whitespaces according to `policy`.
+        pub fn with_policy(inner: R, policy: Whitespace) -> EzipReader<R> {
+            EzipReader {
+                inner: io::BufReader::new(inner).bytes(),
+                policy,
+                lookahead: VecDeque::new(),
+                eof: false,
+                empty: true,
+                spaces: Vec::new(),
+                chunk: Vec::new(),
+                pos: 0,
+                repeat: 0,
+            }
+        }
+
+        /// Returns the next byte to decompress, the trailing newline being stripped and
+        /// whitespaces rejected as requested by the policy. Fails on empty data.
+        fn next_byte(&mut self) -> io::Result<Option<u8>> {
+            let b = match self.policy {
+                Whitespace::Trim | Whitespace::Preserve => self.inner.next().transpose()?,
+                Whitespace::StripTrailingNewline | Whitespace::Reject => self.next_prepared_byte()?,
+            };
+            match b {
+                None if self.empty => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "empty data")),
+                Some(b) if self.policy == Whitespace::Reject && b.is_ascii_whitespace() => {
+                    Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected whitespace"))
+                },
+                b => {
+                    self.empty = false;
+                    Ok(b)
+                },
+            }
+        }
+
+        /// Returns the next byte from `inner`, the trailing newline being stripped.
+        fn next_prepared_byte(&mut self) -> io::Result<Option<u8>> {
+            // a trailing "\r\n" is two bytes long, so keep that many bytes ahead.
+            while !self.eof && self.lookahead.len() < 3 {
+                match self.inner.next().transpose()? {
+                    Some(b) => self.lookahead.push_back(b),
+                    None => {
+                        self.eof = true;
+                        if self.lookahead.back() == Some(&b'\n') {
+                            self.lookahead.pop_back();
+                        }
+                        if self.lookahead.back() == Some(&b'\r') {
+                            self.lookahead.pop_back();
+                        }
+                    },
+                }
+            }
+            Ok(self.lookahead.pop_front())
+        }
+
+        /// Read a marker (eg. "3x6)", the opening parenthesis being already consumed) and its
+        /// data section, setting up `chunk` and `repeat` accordingly.
+        fn read_marker(&mut self) -> io::Result<()> {
+            let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+            let mut marker = Vec::new();
+            loop {
+                match self.next_byte()? {
+                    Some(b')') => break,
+                    Some(b) => marker.push(b),
+                    None => return Err(io::ErrorKind::UnexpectedEof.into()),
+                }
+                if marker.len() > MAX_MARKER_LEN {
+                    return Err(invalid("marker too long".to_string()));
+                }
+            }
+            let marker = String::from_utf8_lossy(&marker).into_owned();
+            let trim = self.policy == Whitespace::Trim;
+            let numbers: Vec<Option<usize>> = marker.split('x')
+                .map(|n| if trim { n.trim() } else { n }.parse().ok())
+                .collect();
+            let (len, repeat) = match numbers.as_slice() {
+                [Some(len), Some(repeat)] => (*len, *repeat),
+                _ => return Err(invalid(format!("invalid marker: ({})", marker))),
+            };
+            self.chunk.clear();
+            for _ in 0..len {
+                match self.next_byte()? {
+                    Some(b) => self.chunk.push(b),
+                    None => return Err(io::ErrorKind::UnexpectedEof.into()),
+                }
+            }
+            self.pos = 0;
+            self.repeat = if len == 0 { 0 } else { repeat };
+            Ok(())
+        }
+    }
+
+    impl<R: io::Read> io::Read for EzipReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut written = 0;
+            while written < buf.len() {
+                if self.repeat > 0 {
+                    // copy as much as we can of the current chunk repetition.
+                    let n = (self.chunk.len() - self.pos).min(buf.len() - written);
+                    buf[written..(written + n)].copy_from_slice(&self.chunk[self.pos..(self.pos + n)]);
+                    written += n;
+                    self.pos += n;
+                    if self.pos == self.chunk.len() {
+                        self.pos = 0;
+                        self.repeat -= 1;
+                    }
+                    continue;
+                }
+                match self.next_byte()? {
+                    None => break,
+                    Some(b'(') => {
+                        self.spaces.clear();
+                        self.read_marker()?;
+                    },
+                    Some(b) if self.policy == Whitespace::Trim && b.is_ascii_whitespace() => self.spaces.push(b),
+                    Some(b) if !self.spaces.is_empty() => {
+                        // the whitespaces were not trailing after all, write them out first.
+                        self.spaces.push(b);
+                        ::std::mem::swap(&mut self.chunk, &mut self.spaces);
+                        self.spaces.clear();
+                        self.pos = 0;
+                        self.repeat = 1;
+                    },
+                    Some(b) => {
+                        buf[written] = b;
+                        written += 1;
+                    },
+                }
+            }
+            Ok(written)
+        }
+    }
+
+    // the Ezip recursive-descent parser, tracking the byte span of every node.
+    mod parsing {
+        use explosives_in_cyberspace::{EzipNode, Ezip, EzipParseError};
+        use ezip_length::MAX_NESTING;
+        use std::ops::Range;
+        use std::str;
+
+        pub struct Parser<'a> {
+            data: &'a [u8],
+            // offset of `data` in the whole input, added to every span and error offset.
+            base: usize,
+            pos: usize,
+            // allow whitespaces around marker numbers and drop the trailing whitespaces of
+            // uncompressed chunks.
+            trim: bool,
+            // parse markers data sections recursively (version 2) or as raw data (version 1).
+            v2: bool,
+            // how many markers we're currently nested in.
+            depth: usize,
+        }
+
+        impl<'a> Parser<'a> {
+            pub fn new(data: &'a [u8], base: usize, trim: bool, v2: bool) -> Parser<'a> {
+                Parser { data, base, pos: 0, trim, v2, depth: 0 }
+            }
+
+            // parse a full Ezip file.
+            pub fn parse(mut self) -> Result<Ezip, EzipParseError> {
+                if self.data.is_empty() {
+                    return Err(self.error("data"));
+                }
+                let end = self.data.len();
+                Ok(Ezip::build(self.nodes(end)?))
+            }
+
+            // build an error at the current position.
+            fn error(&self, expected: &'static str) -> EzipParseError {
+                EzipParseError { offset: self.base + self.pos, expected }
+            }
+
+            // the span from `start` to the current position.
+            fn span(&self, start: usize) -> Range<usize> {
+                (self.base + start)..(self.base + self.pos)
+            }
+
+            // parse a chain of compressed and uncompressed chunk up to `end`.
+            fn nodes(&mut self, end: usize) -> Result<Vec<EzipNode>, EzipParseError> {
+                let mut nodes = Vec::new();
+                while self.pos < end {
+                    let node = if self.data[self.pos] == b'(' {
+                        self.compressed(end)?
+                    } else {
+                        self.uncompressed(end)
+                    };
+                    nodes.push(node);
+                }
+                Ok(nodes)
+            }
+
+            // parse an uncompressed chunk of data (i.e. "decompressed section").
+            fn uncompressed(&mut self, end: usize) -> EzipNode {
+                let start = self.pos;
+                self.pos = self.data[start..end].iter().position(|&b| b == b'(').map_or(end, |n| start + n);
+                let data = &self.data[start..self.pos];
+                let data = if self.trim { trim_end(data) } else { data };
+                EzipNode::Uncompressed(self.span(start), data.to_vec())
+            }
+
+            // parse a marker (eg. "(3x6)") and its associated data.
+            fn compressed(&mut self, end: usize) -> Result<EzipNode, EzipParseError> {
+                let start = self.pos;
+                self.expect(b'(', "'('", end)?;
+                let len = self.number(end)?;
+                self.expect(b'x', "'x'", end)?;
+                let repeat = self.number(end)?;
+                self.expect(b')', "')'", end)?;
+                let data_end = match self.pos.checked_add(len) {
+                    Some(data_end) if data_end <= end => data_end,
+                    _ => {
+                        self.pos = end;
+                        return Err(self.error("more marker data"));
+                    },
+                };
+                let children = if self.v2 {
+                    if self.depth == MAX_NESTING {
+                        return Err(self.error("fewer nested markers"));
+                    }
+                    self.depth += 1;
+                    let children = self.nodes(data_end)?;
+                    self.depth -= 1;
+                    children
+                } else {
+                    let data_start = self.pos;
+                    self.pos = data_end;
+                    let data = self.data[data_start..data_end].to_vec();
+                    vec![EzipNode::Uncompressed(self.span(data_start), data)]
+                };
+                Ok(EzipNode::Compressed(self.span(start), repeat, Ezip::build(children)))
+            }
+
+            // parse a string of digit as usize, used for the compression data length and repeat
+            // count.
+            fn number(&mut self, end: usize) -> Result<usize, EzipParseError> {
+                self.skip_whitespaces(end);
+                let start = self.pos;
+                let digits = self.data[start..end].iter().take_while(|b| b.is_ascii_digit()).count();
+                if digits == 0 {
+                    return Err(self.error("a digit"));
+                }
+                // the digits are ASCII, so from_utf8() can't fail.
+                let number = str::from_utf8(&self.data[start..(start + digits)]).unwrap().parse();
+                let number = number.map_err(|_| self.error("a smaller number"))?;
+                self.pos += digits;
+                self.skip_whitespaces(end);
+                Ok(number)
+            }
+
+            // consume `byte` or fail.
+            fn expect(&mut self, byte: u8, expected: &'static str, end: usize) -> Result<(), EzipParseError> {
+                if self.pos < end && self.data[self.pos] == byte {
+                    self.pos += 1;
+                    Ok(())
+                } else {
+                    Err(self.error(expected))
+                }
+            }
+
+            // skip whitespaces when trimming.
+            fn skip_whitespaces(&mut self, end: usize) {
+                if self.trim {
+                    while self.pos < end && self.data[self.pos].is_ascii_whitespace() {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        // helper stripping the trailing whitespaces of `data`.
+        fn trim_end(data: &[u8]) -> &[u8] {
+            let len = data.iter().rposition(|b| !b.is_ascii_whitespace()).map_or(0, |n| n + 1);
+            &data[..len]
+        }
+    }
+}
+
+pub use ezip_length::*;
+#[cfg(feature = "std")]
+pub use explosives_in_cyberspace::*;
@@ -1,241 +1,84 @@
+extern crate aoc_cli;
+extern crate explosives_in_cyberspace;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
 #[macro_use]
-extern crate nom;
+extern crate profiling;
+extern crate report;
 
-mod explosives_in_cyberspace {
-    /// Represents a node from the `Ezip` "tree". Either an uncompressed chunk of data or a
-    /// sub-`Ezip` to be repeated.
-    #[derive(Debug)]
-    enum EzipNode {
-        Uncompressed(String),
-        Compressed(usize, Ezip),
-    }
-
-    impl EzipNode {
-        /// Returns the uncompressed data length for this node.
-        fn uncompressed_len(&self) -> usize {
-            match *self {
-                EzipNode::Uncompressed(ref s) => s.len(),
-                EzipNode::Compressed(repeat, ref children) => {
-                    repeat * children.uncompressed_len()
-                },
-            }
-        }
-    }
-
-    /// Experimental data compression format found in the Easter Bunny HQ.
-    #[derive(Debug)]
-    pub struct Ezip {
-        nodes: Vec<EzipNode>,
-    }
-
-    impl Ezip {
-        /// Parse a string formated in the Experimental data compression format version 1.
-        // XXX: leaking nom stuff through the error, oh well.
-        pub fn parse_v1(s: &str) -> Result<Ezip, ::nom::IError> {
-                parsing::ezipv1(s).to_full_result()
-        }
 
-        /// Parse a string formated in the Experimental data compression format version 2.
-        // XXX: leaking nom stuff through the error, oh well.
-        pub fn parse_v2(s: &str) -> Result<Ezip, ::nom::IError> {
-                parsing::ezipv2(s).to_full_result()
-        }
-
-        /// Returns the uncompressed data length of the file.
-        pub fn uncompressed_len(&self) -> usize {
-            self.nodes.iter().map(|node| node.uncompressed_len()).sum()
-        }
+use aoc_cli::Cli;
+use explosives_in_cyberspace::*;
+use report::InputError;
+use std::io::Write;
 
-        /// Build a new `Ezip` containing the given nodes.
-        fn build(nodes: Vec<EzipNode>) -> Ezip {
-            Ezip { nodes: nodes }
-        }
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
 
-        /// Build a new `Ezip` containing only one uncompressed node.
-        fn build_uncompressed(data: &str) -> Ezip {
-            Ezip {
-                nodes: vec![EzipNode::Uncompressed(data.to_string())],
-            }
-        }
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    let cli = Cli::start_with_generator(generate_input);
+    let stdout = std::io::stdout();
+
+    // when asked, write the decompressed data to stdout instead of reporting lengths. Version 1
+    // is decompressed on the fly.
+    let arg = cli.args().first();
+    if arg.map(String::as_str) == Some("--decompress-v1") {
+        let mut reader = EzipReader::new(cli.reader());
+        std::io::copy(&mut reader, &mut stdout.lock())
+            .unwrap_or_else(|e| report::exit(format!("{}: {}", cli.input_name(), e)));
+        return;
     }
 
-    // the Ezip parsing stuff using nom.
-    mod parsing {
-        use explosives_in_cyberspace::{EzipNode, Ezip};
-        use nom::{self, digit};
-        use std::str::{self, FromStr};
-
-        // parse a string of digit as usize, used for the compression data length and repeat count.
-        named!(number<usize>,
-            map_res!(
-                map_res!(ws!(digit), str::from_utf8),
-                FromStr::from_str
-            )
-        );
-
-        // helper returning true as long as `x` is not the start of a compression marker.
-        fn not_marker_start(x: u8) -> bool {
-            x != '(' as u8
-        }
-
-        // parse an uncompressed chunk of data (i.e. "decompressed section").
-        named!(uncompressed<EzipNode>,
-            do_parse!(
-                data: map_res!(take_while!(not_marker_start), str::from_utf8) >>
-                (EzipNode::Uncompressed(data.trim_end().to_string()))
-            )
-        );
-
-        // parse a marker (eg. "(3x6)") and return a tuple with its two numbers (eg. `(3, 6)`).
-        named!(marker<(usize, usize)>,
-            do_parse!(
-                char!('(') >> len: number >> char!('x') >> count: number >> char!(')') >>
-                (len, count)
-            )
-        );
-
-        // parse a full marker (eg. "(3x6)") and return only the data length (eg. `3`).
-        named!(marker_len<usize>,
-            do_parse!(
-                char!('(') >> len: number >> char!('x') >> number >> char!(')') >>
-                (len)
-            )
-        );
-
-        // parse a compressed version 1 marker and its associated data, eg. "(3x6)XYZ".
-        named!(compressed_v1<EzipNode>,
-            do_parse!(
-                mark: marker >>
-                children: map!(take_str!(mark.0), Ezip::build_uncompressed) >>
-                (EzipNode::Compressed(mark.1, children))
-            )
-        );
-
-        // parse a compressed version 2 marker and its associated data, eg. "(3x6)XYZ".
-        named!(compressed_v2<EzipNode>,
-            do_parse!(
-                mark: peek!(marker) >> // peek! the marker so that length_value! can consume it.
-                children: map!(length_value!(marker_len, nodes_v2), Ezip::build) >>
-                (EzipNode::Compressed(mark.1, children))
-            )
-        );
-
-        // parse a chain of compressed and uncompressed chunk.
-        named!(nodes_v1<Vec<EzipNode>>, many1!(alt!(compressed_v1 | uncompressed)));
-        named!(nodes_v2<Vec<EzipNode>>, many1!(alt!(compressed_v2 | uncompressed)));
-
-        // parse a full Ezip file.
-        named!(parse_ezipv1<Ezip>, map!(nodes_v1, Ezip::build));
-        named!(parse_ezipv2<Ezip>, map!(nodes_v2, Ezip::build));
-
-        // expose the ezipv1 parser outside this mod.
-        pub fn ezipv1(s: &str) -> nom::IResult<&[u8], Ezip> {
-            parse_ezipv1(s.as_bytes())
-        }
-
-        // expose the ezipv2 parser outside this mod.
-        pub fn ezipv2(s: &str) -> nom::IResult<&[u8], Ezip> {
-            parse_ezipv2(s.as_bytes())
-        }
+    // acquire the puzzle input.
+    let input = cli.input();
+    // the version 2 parser errors are reported with the offending byte underlined in its line, like
+    // the version 1 ones through `FromInput`.
+    let check = |result: Result<Ezip, EzipParseError>| -> Ezip {
+        input.check(result.map_err(|e| InputError::spanned(input.text(), e.spanned(input.text()))))
+    };
+
+    if cli.validating() {
+        // no whitespace is allowed but the trailing newline, as both versions.
+        let data = input.text().as_bytes();
+        let v1 = Ezip::parse_v1_with(data, Whitespace::Reject).err();
+        let v2 = Ezip::parse_v2_with(data, Whitespace::Reject).err();
+        let mut errors: Vec<InputError> = v1.into_iter().chain(v2)
+            .map(|e| InputError::spanned(input.text(), e.spanned(input.text())))
+            .collect();
+        errors.dedup();
+        input.validate(errors);
     }
-}
 
+    if let Some(arg) = arg {
+        let result = match arg.as_str() {
+            "--decompress-v2" => check(Ezip::parse_v2(input.text())).decompress(&mut stdout.lock()),
+            "--digest-v2" => {
+                let ezip = check(Ezip::parse_v2(input.text()));
+                writeln!(stdout.lock(), "{:016x}", ezip.decompressed_digest_v2::<PolynomialHash>())
+            },
+            "--tree-v1" => input.build::<Ezip>().dump_tree(&mut stdout.lock()),
+            "--tree-v2" => check(Ezip::parse_v2(input.text())).dump_tree(&mut stdout.lock()),
+            _ => report::exit(format!("unrecognized argument: {}", arg)),
+        };
+        result.unwrap_or_else(|e| report::exit(e));
+        return;
+    }
 
-use std::io::Read;
-use explosives_in_cyberspace::*;
-
-fn main() {
-    // acquire data from stdin.
-    let mut input = String::new();
-    let stdin = std::io::stdin();
-    stdin.lock().read_to_string(&mut input).expect("no input given");
+    // the lengths may overflow even a u128, with deeply nested version 2 markers.
+    let checked_len = |ezip: Ezip, version: &str| -> u128 {
+        ezip.checked_uncompressed_len().unwrap_or_else(|| {
+            input.fail(Some(format!("the decompressed length of the file ({}) overflows a u128", version).into()))
+        })
+    };
 
     // parse input as Ezip version 1
-    let compressed = Ezip::parse_v1(input.as_str()).unwrap();
-    println!("the decompressed length of the file (v1) is {}.", compressed.uncompressed_len());
+    let length = phase!("part1").in_scope(|| checked_len(input.build::<Ezip>(), "v1"));
+    cli.answer(1, length, format_args!("the decompressed length of the file (v1) is {}.", length));
 
     // parse input as Ezip version 2
-    let compressed = Ezip::parse_v2(input.as_str()).unwrap();
-    println!("the decompressed length of the file (v2) is {}.", compressed.uncompressed_len());
-}
-
-
-#[test]
-fn part1_first_example() {
-    let s = "ADVENT";
-    let compressed = Ezip::parse_v1(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 6);
-}
-
-#[test]
-fn part1_second_example() {
-    let s = "A(1x5)BC";
-    let compressed = Ezip::parse_v1(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 7);
-}
-
-#[test]
-fn part1_third_example() {
-    let s = "(3x3)XYZ";
-    let compressed = Ezip::parse_v1(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 9);
-}
-
-#[test]
-fn part1_fourth_example() {
-    let s = "A(2x2)BCD(2x2)EFG";
-    let compressed = Ezip::parse_v1(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 11);
-}
-
-#[test]
-fn part1_fifth_example() {
-    let s = "(6x1)(1x3)A";
-    let compressed = Ezip::parse_v1(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 6);
-}
-
-#[test]
-fn part1_sixth_example() {
-    let s = "X(8x2)(3x3)ABCY";
-    let compressed = Ezip::parse_v1(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 18);
-}
-
-#[test]
-fn part2_first_example() {
-    let s = "(3x3)XYZ";
-    let compressed = Ezip::parse_v2(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 9);
-}
-
-#[test]
-fn part2_second_example() {
-    let s = "X(8x2)(3x3)ABCY";
-    let compressed = Ezip::parse_v2(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 20);
-}
-
-#[test]
-fn part2_third_example() {
-    let s = "(27x12)(20x12)(13x14)(7x10)(1x12)A";
-    let compressed = Ezip::parse_v2(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 241920);
-}
-
-#[test]
-fn part2_fourth_example() {
-    let s = "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN";
-    let compressed = Ezip::parse_v2(s).unwrap();
-    println!("{:?}", compressed);
-    assert_eq!(compressed.uncompressed_len(), 445);
+    let length = phase!("part2").in_scope(|| checked_len(check(Ezip::parse_v2(input.text())), "v2"));
+    cli.answer(2, length, format_args!("the decompressed length of the file (v2) is {}.", length));
 }
@@ -0,0 +1,532 @@
+extern crate explosives_in_cyberspace;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate report;
+#[macro_use]
+extern crate snapshot;
+
+use explosives_in_cyberspace::*;
+use proptest::collection::vec;
+use proptest::prelude::{any, Just};
+use report::Input;
+
+#[test]
+fn part1_first_example() {
+    let s = "ADVENT";
+    let compressed = Ezip::parse_v1(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 6);
+}
+
+#[test]
+fn part1_second_example() {
+    let s = "A(1x5)BC";
+    let compressed = Ezip::parse_v1(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 7);
+}
+
+#[test]
+fn part1_third_example() {
+    let s = "(3x3)XYZ";
+    let compressed = Ezip::parse_v1(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 9);
+}
+
+#[test]
+fn part1_fourth_example() {
+    let s = "A(2x2)BCD(2x2)EFG";
+    let compressed = Ezip::parse_v1(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 11);
+}
+
+#[test]
+fn part1_fifth_example() {
+    let s = "(6x1)(1x3)A";
+    let compressed = Ezip::parse_v1(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 6);
+}
+
+#[test]
+fn part1_sixth_example() {
+    let s = "X(8x2)(3x3)ABCY";
+    let compressed = Ezip::parse_v1(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 18);
+}
+
+#[test]
+fn part2_first_example() {
+    let s = "(3x3)XYZ";
+    let compressed = Ezip::parse_v2(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 9);
+}
+
+#[test]
+fn part2_second_example() {
+    let s = "X(8x2)(3x3)ABCY";
+    let compressed = Ezip::parse_v2(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 20);
+}
+
+#[test]
+fn part2_third_example() {
+    let s = "(27x12)(20x12)(13x14)(7x10)(1x12)A";
+    let compressed = Ezip::parse_v2(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 241920);
+}
+
+#[test]
+fn part2_fourth_example() {
+    let s = "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN";
+    let compressed = Ezip::parse_v2(s).unwrap();
+    println!("{:?}", compressed);
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 445);
+}
+
+#[test]
+fn decompress_v1() {
+    let examples = [
+        ("ADVENT", "ADVENT"),
+        ("A(1x5)BC", "ABBBBBC"),
+        ("(3x3)XYZ", "XYZXYZXYZ"),
+        ("A(2x2)BCD(2x2)EFG", "ABCBCDEFEFG"),
+        ("(6x1)(1x3)A", "(1x3)A"),
+        ("X(8x2)(3x3)ABCY", "X(3x3)ABC(3x3)ABCY"),
+    ];
+    for &(s, expected) in examples.iter() {
+        let mut out = Vec::new();
+        Ezip::parse_v1(s).unwrap().decompress(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
+
+#[test]
+fn decompress_v2() {
+    let mut out = Vec::new();
+    Ezip::parse_v2("X(8x2)(3x3)ABCY").unwrap().decompress(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "XABCABCABCABCABCABCY");
+}
+
+#[test]
+fn decompress_limit() {
+    let compressed = Ezip::parse_v2("(27x12)(20x12)(13x14)(7x10)(1x12)A").unwrap();
+    let mut out = Vec::new();
+    assert!(compressed.decompress_with_limit(&mut out, 241919).is_err());
+    assert!(out.is_empty());
+    compressed.decompress_with_limit(&mut out, 241920).unwrap();
+    assert_eq!(out, vec![b'A'; 241920]);
+}
+
+#[test]
+fn decompressed_digest() {
+    use std::collections::hash_map::DefaultHasher;
+
+    // the digest of the decompressed data, fed in one go.
+    fn digest<D: Digest>(data: &[u8]) -> D::Output {
+        let mut digest = D::default();
+        digest.update(data);
+        digest.finalize()
+    }
+    for s in &["ADVENT", "X(8x2)(3x3)ABCY", "(27x12)(20x12)(13x14)(7x10)(1x12)A", "(3x0)ABC(2x2)DE"] {
+        let compressed = Ezip::parse_v2(s).unwrap();
+        let mut out = Vec::new();
+        compressed.decompress(&mut out).unwrap();
+        assert_eq!(compressed.decompressed_digest_v2::<PolynomialHash>(), digest::<PolynomialHash>(&out), "{}", s);
+        assert_eq!(compressed.decompressed_digest::<PolynomialHash>(), digest::<PolynomialHash>(&out), "{}", s);
+        assert_eq!(compressed.decompressed_digest::<DefaultHasher>(), digest::<DefaultHasher>(&out), "{}", s);
+    }
+    // the same astronomically large data, compressed two ways.
+    let a = Ezip::parse_v2("(45x4294967295)(30x4294967295)(15x4294967295)(1x4294967295)A").unwrap();
+    let b = Ezip::parse_v2("(60x4294967295)(45x4294967295)(30x4294967295)(1x2147483648)A(1x2147483647)A").unwrap();
+    assert_eq!(a.decompressed_digest_v2::<PolynomialHash>(), b.decompressed_digest_v2::<PolynomialHash>());
+    let c = Ezip::parse_v2("(45x4294967295)(30x4294967295)(15x4294967295)(1x4294967295)B").unwrap();
+    assert_ne!(a.decompressed_digest_v2::<PolynomialHash>(), c.decompressed_digest_v2::<PolynomialHash>());
+}
+
+#[test]
+fn ezip_reader() {
+    use std::io::Read;
+
+    let examples = [
+        "ADVENT", "A(1x5)BC", "(3x3)XYZ", "A(2x2)BCD(2x2)EFG", "(6x1)(1x3)A", "X(8x2)(3x3)ABCY",
+        "(0x3)A(4x0)BCDE\n", "AB( 2 x 3 )C D\n", "A B \t(1x1)C \n",
+    ];
+    for s in examples.iter() {
+        let mut expected = Vec::new();
+        Ezip::parse_v1(s).unwrap().decompress(&mut expected).unwrap();
+        let mut out = Vec::new();
+        EzipReader::new(s.as_bytes()).read_to_end(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), String::from_utf8(expected).unwrap());
+    }
+    // small reads.
+    let mut reader = EzipReader::new("A(2x3)BC".as_bytes());
+    let mut buf = [0; 4];
+    assert_eq!(reader.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"ABCB");
+    assert_eq!(reader.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"CBC");
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn ezip_reader_errors() {
+    use std::io::{ErrorKind, Read};
+
+    let read = |s: &str| EzipReader::new(s.as_bytes()).read_to_end(&mut Vec::new());
+    assert_eq!(read("A(2x3").unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(read("A(2x3)B").unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(read("A(2y3)BC").unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(read("").unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    let read_with = |s: &str, policy| {
+        let mut out = Vec::new();
+        EzipReader::with_policy(s.as_bytes(), policy).read_to_end(&mut out).map(|_| out)
+    };
+    assert_eq!(read_with("A(1x2)B \r\n", Whitespace::Preserve).unwrap(), b"ABB \r\n");
+    assert_eq!(read_with("A(1x2)B \r\n", Whitespace::StripTrailingNewline).unwrap(), b"ABB ");
+    assert_eq!(read_with("A(1x2)B\n", Whitespace::Reject).unwrap(), b"ABB");
+    assert_eq!(read_with("A(1x2)B \n", Whitespace::Reject).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(read_with("A( 1x2)B", Whitespace::Preserve).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(read_with("\n", Whitespace::StripTrailingNewline).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn parse_errors() {
+    let err = |offset, expected| Err(EzipParseError { offset, expected });
+    assert_eq!(Ezip::parse_v1("").map(|_| ()), err(0, "data"));
+    assert_eq!(Ezip::parse_v1("AB(2y3)CD").map(|_| ()), err(4, "'x'"));
+    assert_eq!(Ezip::parse_v2("AB(2y3)CD").map(|_| ()), err(4, "'x'"));
+    assert_eq!(Ezip::parse_v1("A(5x2)AB").map(|_| ()), err(8, "more marker data"));
+    assert_eq!(Ezip::parse_v2("A(2x").map(|_| ()), err(4, "a digit"));
+    assert_eq!(Ezip::parse_v2("A(2x3").map(|_| ()), err(5, "')'"));
+    assert_eq!(Ezip::parse_v1("(99999999999999999999x1)").map(|_| ()), err(1, "a smaller number"));
+    // nested markers can't go past the data section of their parent.
+    assert_eq!(Ezip::parse_v2("(4x2)(1x2)AB").map(|_| ()), err(9, "')'"));
+    assert_eq!(Ezip::parse_v2("(6x2)(2x2)AB").map(|_| ()), err(11, "more marker data"));
+    assert_eq!(Ezip::parse_v1("A(2y3)").unwrap_err().to_string(), "expected 'x' at byte 3");
+}
+
+#[test]
+fn from_input() {
+    // built as version 1.
+    let ezip: Ezip = Input::new("<test>", "X(8x2)(3x3)ABCY".to_string()).build();
+    assert_eq!(ezip.checked_uncompressed_len().unwrap(), 18);
+    let errors = Input::new("<test>", "AB(2y3)CD".to_string()).build_errors::<Ezip>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].line_no, errors[0].reason.as_str()), (Some(1), "expected 'x' at byte 4"));
+    assert!(errors[0].span.is_some());
+}
+
+#[test]
+fn huge_uncompressed_len() {
+    // 2^32 - 1 repeated four times is way beyond 2^64.
+    let s = "(45x4294967295)(30x4294967295)(15x4294967295)(1x4294967295)A";
+    let compressed = Ezip::parse_v2(s).unwrap();
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), (u32::MAX as u128).pow(4));
+    assert!(compressed.checked_uncompressed_len().unwrap() > usize::MAX as u128);
+    assert!(compressed.decompress(&mut Vec::new()).is_err());
+    // and (2^32 - 1)^5 doesn't even fit in a u128.
+    let s = format!("(60x4294967295){}", s);
+    let compressed = Ezip::parse_v2(&s).unwrap();
+    assert_eq!(compressed.checked_uncompressed_len(), None);
+    assert!(compressed.decompress(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn fast_v2_len() {
+    let examples = [
+        "(3x3)XYZ", "X(8x2)(3x3)ABCY", "(27x12)(20x12)(13x14)(7x10)(1x12)A",
+        "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN", "A B\n(1x3)C \n", "( 2 x 3 )AB",
+    ];
+    for s in examples.iter() {
+        assert_eq!(decompressed_len_v2_fast(s), Some(Ezip::parse_v2(s).unwrap().checked_uncompressed_len().unwrap()));
+    }
+    // an empty input is rejected by the parser, but whitespaces only are trimmed to no data.
+    for s in ["", " ", " \n "].iter() {
+        assert_eq!(decompressed_len_v2_fast(s), Ezip::parse_v2(s).ok().and_then(|ezip| ezip.checked_uncompressed_len()));
+    }
+    assert_eq!(decompressed_len_v2_fast(""), None);
+    assert_eq!(decompressed_len_v2_fast("A(5x2)AB"), None);
+    assert_eq!(decompressed_len_v2_fast("A(2y2)AB"), None);
+    assert_eq!(decompressed_len_v2_fast("(2x3"), None);
+}
+
+#[test]
+fn parse_non_utf8() {
+    let data = b"\xff(2x3)\xfe\x00Z\x80\n";
+    let mut out = Vec::new();
+    let compressed = Ezip::parse_v1_bytes(data).unwrap();
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 9);
+    compressed.decompress(&mut out).unwrap();
+    assert_eq!(out, b"\xff\xfe\x00\xfe\x00\xfe\x00Z\x80");
+    let compressed = Ezip::parse_v2_bytes(b"(7x2)(2x2)\xc3\xc3").unwrap();
+    assert_eq!(compressed.checked_uncompressed_len().unwrap(), 8);
+}
+
+#[test]
+fn whitespace_policies() {
+    let len = |s: &str, policy| Ezip::parse_v2_with(s.as_bytes(), policy).map(|ezip| ezip.checked_uncompressed_len().unwrap());
+    let s = "A B (3x2)C \n\n";
+    assert_eq!(len(s, Whitespace::Trim), Ok(3 + 2));
+    assert_eq!(len(s, Whitespace::StripTrailingNewline), Ok(4 + 2 * 3));
+    assert_eq!(len(s, Whitespace::Preserve), Ok(4 + 2 * 3 + 1));
+    assert_eq!(len(s, Whitespace::Reject), Err(EzipParseError { offset: 1, expected: "a non-whitespace byte" }));
+    assert_eq!(len("AB(2x2)CD\r\n", Whitespace::Reject), Ok(6));
+    // whitespaces inside markers are only allowed when trimming.
+    let s = "( 2 x 2 )AB";
+    assert_eq!(len(s, Whitespace::Trim), Ok(4));
+    assert_eq!(len(s, Whitespace::Preserve), Err(EzipParseError { offset: 1, expected: "a digit" }));
+    assert_eq!("strip-newline".parse(), Ok(Whitespace::StripTrailingNewline));
+}
+
+#[test]
+fn incremental_parser() {
+    let input = include_str!("../input.txt").as_bytes();
+    for &chunk_size in [1, 7, 64, 4096].iter() {
+        let mut v1 = EzipParser::v1(Whitespace::Trim);
+        let mut v2 = EzipParser::v2(Whitespace::Trim);
+        for chunk in input.chunks(chunk_size) {
+            v1.feed(chunk).unwrap();
+            v2.feed(chunk).unwrap();
+        }
+        assert_eq!(v1.finish().unwrap().checked_uncompressed_len().unwrap(), 70186);
+        assert_eq!(v2.finish().unwrap().checked_uncompressed_len().unwrap(), 10915059201);
+    }
+    // errors are reported with their offset in the whole input.
+    let mut parser = EzipParser::v1(Whitespace::Reject);
+    parser.feed(b"AB(2x2)CD").unwrap();
+    assert_eq!(parser.feed(b"(1x1) (3x2)ABC"), Err(EzipParseError { offset: 14, expected: "a non-whitespace byte" }));
+    let mut parser = EzipParser::v2(Whitespace::Preserve);
+    parser.feed(b"AB(2x2)CD(5x").unwrap();
+    assert_eq!(parser.finish().unwrap_err(), EzipParseError { offset: 12, expected: "a digit" });
+    assert!(EzipParser::v1(Whitespace::Trim).finish().is_err());
+}
+
+#[test]
+fn spans() {
+    let s = b"AB(8x2)(1x3)CDE\n";
+    assert_eq!(Ezip::parse_v2_bytes(s).unwrap().span(), 0..16);
+    assert_eq!(Ezip::parse_v2_with(s, Whitespace::Reject).unwrap().span(), 0..15);
+    assert_eq!(Ezip::parse_v1_bytes(&s[2..15]).unwrap().span(), 0..13);
+    // spans are relative to the whole input with the incremental parser too.
+    let mut parser = EzipParser::v1(Whitespace::Trim);
+    parser.feed(b"AB(2x2)CD").unwrap();
+    parser.feed(b"(1x1)E").unwrap();
+    assert_eq!(parser.finish().unwrap().span(), 0..15);
+}
+
+#[test]
+fn dump_tree() {
+    let mut out = Vec::new();
+    Ezip::parse_v2("AB(12x2)(1x3)C(1x2)DE\n").unwrap().dump_tree(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "\
+0..2: data (2 bytes)
+2..20: marker x2 (10 bytes)
+  8..14: marker x3 (3 bytes)
+    13..14: data (1 bytes)
+  14..20: marker x2 (2 bytes)
+    19..20: data (1 bytes)
+20..22: data (1 bytes)
+");
+}
+
+// check that the tree-based, streaming and single-pass implementations agree on random data.
+fn check_implementations_agree(data: &str, chunk_size: usize) {
+    use std::io::Read;
+
+    let v1 = Ezip::parse_v1(data);
+    let mut out = Vec::new();
+    let streamed = EzipReader::new(data.as_bytes()).read_to_end(&mut out);
+    assert_eq!(v1.is_ok(), streamed.is_ok(), "v1 validity mismatch on {:?}", data);
+    if let Ok(ref v1) = v1 {
+        assert_eq!(v1.checked_uncompressed_len().unwrap(), out.len() as u128, "v1 length mismatch on {:?}", data);
+        let mut decompressed = Vec::new();
+        v1.decompress(&mut decompressed).unwrap();
+        assert_eq!(decompressed, out, "v1 data mismatch on {:?}", data);
+    }
+    for &policy in [Whitespace::StripTrailingNewline, Whitespace::Preserve, Whitespace::Reject].iter() {
+        let v1 = Ezip::parse_v1_with(data.as_bytes(), policy);
+        let mut out = Vec::new();
+        let streamed = EzipReader::with_policy(data.as_bytes(), policy).read_to_end(&mut out);
+        assert_eq!(v1.is_ok(), streamed.is_ok(), "v1 {:?} validity mismatch on {:?}", policy, data);
+        if let Ok(ref v1) = v1 {
+            let mut decompressed = Vec::new();
+            v1.decompress(&mut decompressed).unwrap();
+            assert_eq!(decompressed, out, "v1 {:?} data mismatch on {:?}", policy, data);
+        }
+    }
+
+    let v2 = Ezip::parse_v2(data);
+    let fast = decompressed_len_v2_fast(data);
+    assert_eq!(v2.as_ref().ok().and_then(Ezip::checked_uncompressed_len), fast, "v2 length mismatch on {:?}", data);
+
+    let mut parser = EzipParser::v2(Whitespace::Trim);
+    let fed = data.as_bytes().chunks(chunk_size).try_for_each(|chunk| parser.feed(chunk));
+    let incremental = fed.and_then(|_| parser.finish());
+    assert_eq!(
+        incremental.map(|ezip| ezip.checked_uncompressed_len().unwrap()),
+        v2.map(|ezip| ezip.checked_uncompressed_len().unwrap()),
+        "incremental v2 mismatch on {:?}", data,
+    );
+}
+
+#[test]
+fn random_valid_data() {
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+    let generator = EzipGenerator { whitespace: true, ..EzipGenerator::default() };
+    for _ in 0..500 {
+        let data = generator.generate(&mut rng);
+        assert!(Ezip::parse_v1(&data).is_ok() && Ezip::parse_v2(&data).is_ok(), "invalid data: {:?}", data);
+        check_implementations_agree(&data, rng.gen_range(1, 16));
+    }
+}
+
+#[test]
+fn random_corrupted_data() {
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+    let generator = EzipGenerator { whitespace: true, corrupt: true, ..EzipGenerator::default() };
+    for _ in 0..2000 {
+        let data = generator.generate(&mut rng);
+        check_implementations_agree(&data, rng.gen_range(1, 16));
+    }
+}
+
+#[test]
+fn generated_input() {
+    use rand::SeedableRng;
+
+    let input = generate_input(&mut rand::rngs::StdRng::seed_from_u64(7), 10_000);
+    assert_eq!(input, generate_input(&mut rand::rngs::StdRng::seed_from_u64(7), 10_000));
+    assert!(input.len() > 10_000);
+    assert!(Ezip::parse_v1(&input).is_ok() && Ezip::parse_v2(&input).is_ok());
+}
+
+#[test]
+fn hostile_markers() {
+    let huge = format!("(1x{})A({}x1)B", usize::MAX, usize::MAX);
+    assert!(Ezip::parse_v2(&huge).is_err());
+    assert_eq!(decompressed_len_v2_fast(&huge), None);
+    let mut parser = EzipParser::v2(Whitespace::Trim);
+    assert!(parser.feed(huge.as_bytes()).is_ok());
+    assert!(parser.finish().is_err());
+    // the trailing newline may be all that's left once the markers are parsed.
+    let mut parser = EzipParser::v2(Whitespace::StripTrailingNewline);
+    assert!(parser.feed(b"(1x1)A").is_ok() && parser.feed(b"\n").is_ok());
+    assert_eq!(parser.finish().map(|ezip| ezip.checked_uncompressed_len().unwrap()), Ok(1));
+    // or the end of a marker data section.
+    let mut parser = EzipParser::v2(Whitespace::StripTrailingNewline);
+    assert!(parser.feed(b"(2x1)A\n").is_ok());
+    assert!(parser.finish().is_err());
+    // repeating nothing is quick, however large the repeat count.
+    let ezip = Ezip::parse_v2(&format!("(0x{})", usize::MAX)).unwrap();
+    let mut out = Vec::new();
+    ezip.decompress(&mut out).unwrap();
+    assert!(out.is_empty());
+    // nested markers, each one wrapping the next one.
+    let nested = |depth: usize| {
+        let mut data = "A".to_string();
+        for _ in 0..depth {
+            data = format!("({}x1){}", data.len(), data);
+        }
+        data
+    };
+    assert_eq!(Ezip::parse_v2(&nested(MAX_NESTING)).unwrap().checked_uncompressed_len().unwrap(), 1);
+    assert_eq!(decompressed_len_v2_fast(&nested(MAX_NESTING)), Some(1));
+    assert!(Ezip::parse_v2(&nested(MAX_NESTING + 1)).is_err());
+    assert_eq!(decompressed_len_v2_fast(&nested(MAX_NESTING + 1)), None);
+}
+
+#[test]
+fn tree_snapshots() {
+    let dump = |ezip: Ezip| {
+        let mut out = Vec::new();
+        ezip.dump_tree(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    let input = "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN";
+    assert_snapshot!("tree_v1", dump(Ezip::parse_v1(input).unwrap()));
+    assert_snapshot!("tree_v2", dump(Ezip::parse_v2(input).unwrap()));
+}
+
+#[test]
+fn encoder() {
+    let encoder = EzipEncoder::default();
+    assert_eq!(encoder.encode("ADVENT"), "ADVENT");
+    assert_eq!(encoder.encode("ABCBCBCBCBCD"), "A(2x5)BCD");
+    assert_eq!(encoder.encode("A(B C)\n"), "A(1x1)(B(1x1) C)(1x1)\n");
+    assert_eq!(encoder.encode(&"é".repeat(10)), "(2x10)é");
+    assert_eq!(encoder.encode(""), "");
+    assert_eq!(EzipEncoder { max_period: 1 }.encode("ABABABABAB"), "ABABABABAB");
+}
+
+// encode `data`, then check that both the parsed data and the streaming decompressor yield it
+// back.
+fn check_round_trip(data: &[u8]) -> Result<(), proptest::test_runner::TestCaseError> {
+    use std::io::Read;
+
+    let encoded = EzipEncoder::default().encode_bytes(data);
+    let mut decompressed = Vec::new();
+    Ezip::parse_v1_bytes(&encoded).unwrap().decompress(&mut decompressed).unwrap();
+    prop_assert_eq!(&decompressed, &data);
+    let mut streamed = Vec::new();
+    EzipReader::new(encoded.as_slice()).read_to_end(&mut streamed).unwrap();
+    prop_assert_eq!(&streamed, &data);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn encoded_bytes_round_trip(data in vec(any::<u8>(), 1..256)) {
+        check_round_trip(&data)?;
+    }
+
+    #[test]
+    fn encoded_text_round_trip(s in "[A-C( \n\t)é]{1,256}") {
+        use std::io::Read;
+
+        let encoded = EzipEncoder::default().encode(&s);
+        let mut decompressed = Vec::new();
+        Ezip::parse_v1(&encoded).unwrap().decompress(&mut decompressed).unwrap();
+        prop_assert_eq!(String::from_utf8(decompressed).unwrap(), s.clone());
+        let mut streamed = String::new();
+        EzipReader::new(encoded.as_bytes()).read_to_string(&mut streamed).unwrap();
+        prop_assert_eq!(streamed, s);
+    }
+
+    #[test]
+    fn encoded_repetitions_round_trip(
+        prefix in vec(any::<u8>(), 0..8),
+        pattern in vec(prop_oneof![Just(b'('), Just(b')'), Just(b' '), Just(b'x'), Just(b'1'), any::<u8>()], 1..40),
+        repeat in 1usize..500,
+        suffix in vec(any::<u8>(), 0..8),
+    ) {
+        let mut data = prefix;
+        for _ in 0..repeat {
+            data.extend_from_slice(&pattern);
+        }
+        data.extend_from_slice(&suffix);
+        check_round_trip(&data)?;
+    }
+
+    #[test]
+    fn encoded_nested_repetitions_round_trip(
+        pattern in "[AB(]{1,3}",
+        inner in 1usize..20,
+        outer in 1usize..50,
+    ) {
+        // runs of runs (e.g. "AAAB" repeated), version 1 markers can only encode one of them.
+        let data = format!("{}B", pattern.repeat(inner)).repeat(outer);
+        check_round_trip(data.as_bytes())?;
+    }
+}
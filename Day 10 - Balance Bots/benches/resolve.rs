@@ -0,0 +1,86 @@
+//! Day 10 factory resolution. The compiled factory is cached on the first query, so every
+//! iteration starts from a freshly built factory to measure the compilation too.
+//!
+//! The queries are also measured on a large synthetic factory: `LAYERS` layers of `WIDTH`
+//! robots, each robot giving its lower-value microchip to the robot "below" and its higher-value
+//! one to the next robot of the following layer.
+
+#[macro_use]
+extern crate criterion;
+extern crate balance_bots;
+
+use balance_bots::*;
+use criterion::{BatchSize, Criterion};
+
+const INPUT: &str = include_str!("../input.txt");
+
+const LAYERS: Id = 100;
+const WIDTH: Id = 1000;
+
+fn instructions() -> Vec<Instruction> {
+    INPUT.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+fn resolve(c: &mut Criterion) {
+    let parsed = instructions();
+    let factory = || Factory::try_build_from(&parsed).unwrap();
+    let mut group = c.benchmark_group("day10");
+    group.bench_function("parse", |b| b.iter(instructions));
+    group.bench_function("build", |b| b.iter(factory));
+    group.bench_function("robot_comparing", |b| {
+        b.iter_batched(factory, |factory| {
+            factory.robot_comparing(Microchip(17), Microchip(61))
+        }, BatchSize::SmallInput)
+    });
+    group.bench_function("chips_in_bins", |b| {
+        b.iter_batched(factory, |factory| factory.chips_in_bins(&[0, 1, 2]), BatchSize::SmallInput)
+    });
+    group.bench_function("resolve", |b| {
+        b.iter_batched(factory, |factory| factory.resolve().unwrap(), BatchSize::SmallInput)
+    });
+    group.bench_function("simulate", |b| {
+        b.iter_batched(factory, |factory| factory.simulate().unwrap(), BatchSize::SmallInput)
+    });
+    group.finish();
+}
+
+fn synthetic() -> Factory {
+    let mut builder = FactoryBuilder::new();
+    for k in 0..WIDTH {
+        builder.give(Microchip(k * 7919 % 10007), k).give(Microchip(k * 104729 % 10009), k);
+    }
+    for layer in 0..LAYERS {
+        for k in 0..WIDTH {
+            let id = layer * WIDTH + k;
+            if layer == LAYERS - 1 {
+                builder.wire(id, Output::Bin(2 * k), Output::Bin(2 * k + 1));
+            } else {
+                let below = (layer + 1) * WIDTH;
+                builder.wire(id, Output::Robot(below + k), Output::Robot(below + (k + 1) % WIDTH));
+            }
+        }
+    }
+    builder.build().unwrap()
+}
+
+fn queries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day10/synthetic");
+    group.sample_size(10);
+    group.bench_function("build", |b| b.iter(synthetic));
+    group.bench_function("comparisons", |b| {
+        b.iter_batched(synthetic, |factory| factory.comparisons().count(), BatchSize::LargeInput)
+    });
+    // the following queries run on the compiled factory.
+    let factory = synthetic();
+    let pairs: Vec<(Id, Microchip2)> = factory.comparisons().step_by(1000).collect();
+    group.bench_function("bin_value", |b| {
+        b.iter(|| (0..100).filter_map(|id| factory.bin_value(id * 17)).count())
+    });
+    group.bench_function("robots_comparing", |b| {
+        b.iter(|| pairs.iter().map(|&(_, pair)| factory.robots_comparing(pair.low, pair.high).len()).sum::<usize>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, resolve, queries);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate balance_bots;
+
+use balance_bots::{Factory, Instruction};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(s) = ::std::str::from_utf8(data) {
+        let instructions: Vec<Instruction> = s.lines().filter_map(|line| line.parse().ok()).collect();
+        for instruction in instructions.iter() {
+            let again: Instruction = instruction.to_string().parse().unwrap();
+            assert_eq!(&again, instruction);
+        }
+        let _ = Factory::try_build_from(&instructions);
+    }
+});
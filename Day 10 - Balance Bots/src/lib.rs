@@ -0,0 +1,1705 @@
+//! Day 10: Balance Bots, building the factory from its instructions and resolving which
+//! robot compares which microchips and where they end up.
+
+extern crate aoc_core;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(feature = "proptest")]
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate regex;
+extern crate report;
+extern crate search;
+extern crate serde;
+extern crate serde_json;
+
+pub mod balance_bots {
+    use ::aoc_core::{Sink, Spanned, Unrecognized};
+    #[cfg(feature = "proptest")]
+    use ::proptest::collection::vec;
+    #[cfg(feature = "proptest")]
+    use ::proptest::prelude::{any, any_with, Arbitrary, BoxedStrategy, Strategy};
+    #[cfg(feature = "proptest")]
+    use ::rand::Rng;
+    #[cfg(feature = "proptest")]
+    use ::rand::seq::SliceRandom;
+    use ::regex::{Captures, Regex};
+    use ::report::{AocError, FromInput, Input};
+    #[cfg(feature = "proptest")]
+    use ::report::Sampler;
+    use ::serde::Serialize;
+    use ::std::cell::OnceCell;
+    use ::std::collections::{HashMap, VecDeque};
+    #[cfg(feature = "proptest")]
+    use ::std::collections::HashSet;
+    use ::std::str::FromStr;
+
+    /// Used to identify robots and bins.
+    pub type Id = u32;
+
+    /// `Microchip` numbers.
+    pub type Value = u32;
+
+    /// Represents a microchip of a given value.
+    #[derive(Hash, Eq, PartialEq, PartialOrd, Copy, Clone, Debug, Serialize)]
+    pub struct Microchip(pub Value);
+
+    impl Microchip {
+        /// Returns this microchip's value, syntaxic sugar for `self.0`.
+        pub fn value(&self) -> Value {
+            self.0
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for Microchip {
+        /// The highest microchip value, any value goes when `None`. The values are then positive.
+        type Parameters = Option<Value>;
+        type Strategy = BoxedStrategy<Microchip>;
+
+        fn arbitrary_with(max: Self::Parameters) -> Self::Strategy {
+            match max {
+                None => any::<Value>().prop_map(Microchip).boxed(),
+                Some(max) => (1..=max.max(1)).prop_map(Microchip).boxed(),
+            }
+        }
+    }
+
+    /// A couple of microchips. This is useful because robots handle microchips by pair caring
+    /// about which one is the lower-value and high-value.
+    #[derive(Hash, Eq, PartialEq, PartialOrd, Copy, Clone, Debug, Serialize)]
+    pub struct Microchip2 {
+        pub low: Microchip,
+        pub high: Microchip,
+    }
+
+    impl Microchip2 {
+        /// Create a new pair of microchip. `a` and `b` can be given in any order, that is:
+        /// Microchip2::new(a, b) == Microchip2::new(b, a)
+        pub fn new(a: Microchip, b: Microchip) -> Microchip2 {
+            let (low, high) = if a > b { (b, a) } else { (a, b) };
+            Microchip2 { low, high }
+        }
+    }
+
+    /// Used to make a link from an output to their input. An input can be:
+    /// 1. a robot making a `Donation` of one of its microchips, `rank` being its position once
+    ///    the robot's microchips are sorted (i.e. 0 for the lower-value microchip),
+    /// 2. an `Input` bin giving its sole microchip.
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    enum Gift {
+        Donation {
+            from_robot_id: Id,
+            rank: usize,
+        },
+        Input {
+            chip: Microchip,
+        }
+    }
+
+    /// Identify an microchip donation output, either a robot or an output bin.
+    #[derive(Eq, PartialEq, PartialOrd, Copy, Clone, Debug, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Output {
+        Robot(Id),
+        Bin(Id),
+    }
+
+    impl ::std::fmt::Display for Output {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Output::Robot(id) => write!(f, "bot {}", id),
+                Output::Bin(id) => write!(f, "output {}", id),
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for Output {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Output>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            prop_oneof![any::<Id>().prop_map(Output::Robot), any::<Id>().prop_map(Output::Bin)].boxed()
+        }
+    }
+
+    /// Represents a robot from the factory.
+    ///
+    /// The puzzle robots handle two microchips, but robots may hold and sort any number of
+    /// microchips (their capacity) as long as they have as many inputs as outputs.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Robot {
+        id: Id,
+        // Its inputs, each are either another robot's `Donation` or an `Input` bin.
+        from: Vec<Gift>,
+        // the outputs to which this robot donate its microchips, from the lower-value to the
+        // higher-value.
+        to: Vec<Output>,
+    }
+
+    /// Represent an output bin.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Bin {
+        id: Id,
+        // its inputs, only bins with a declared capacity may have more than one.
+        // NOTE: technically this bin could get its microchip from an input bin.
+        from: Vec<Gift>,
+    }
+
+    /// An instruction from the local control computer.
+    #[derive(Eq, PartialEq, Clone, Debug)]
+    pub enum Instruction {
+        // value `chip` goes to bot `robot_id`
+        Take { chip: Microchip, robot_id: Id },
+        // bot `robot_id` gives low to `low` and high to `high`
+        Donate { robot_id: Id, low: Output, high: Output },
+        // bot `robot_id` gives low to `outputs[0]`, middle to `outputs[1]`, ... and high to the
+        // last of `outputs`. There must be at least two outputs.
+        Sort { robot_id: Id, outputs: Vec<Output> },
+        // output `bin_id` holds `capacity` microchips, and may then be given microchips by
+        // several robots. An extension of the puzzle instructions, see `Factory::try_simulate()`.
+        Capacity { bin_id: Id, capacity: usize },
+    }
+
+    impl FromStr for Instruction {
+        type Err = Spanned<String>;
+
+        /// Parse an `Instruction`.
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            lazy_static! {
+                static ref TAKE: Regex = Regex::new(
+                    r"value (?P<value>\d+) goes to bot (?P<rid>\d+)"
+                ).unwrap();
+                static ref DONATE: Regex = Regex::new(
+                    r"bot (?P<rid>\d+) gives low to (?P<l>bot|output) (?P<lid>\d+)(?P<middles>(, middle to (bot|output) \d+)*) and high to (?P<h>bot|output) (?P<hid>\d+)"
+                ).unwrap();
+                static ref MIDDLE: Regex = Regex::new(
+                    r"middle to (?P<m>bot|output) (?P<mid>\d+)"
+                ).unwrap();
+                static ref CAPACITY: Regex = Regex::new(
+                    r"output (?P<bid>\d+) holds (?P<capacity>\d+)"
+                ).unwrap();
+            }
+            // the regexes ensure that we have numbers, but they may still be too large. `caps`
+            // were found `base` bytes into `s`.
+            fn arg<T: FromStr>(s: &str, base: usize, caps: &Captures, name: &str) -> Result<T, Spanned<String>> {
+                let number = caps.name(name).unwrap();
+                number.as_str().parse().map_err(|_| {
+                    let range = base + number.start()..base + number.end();
+                    Spanned::of(s, range, format!("number out of range: {}", number.as_str()))
+                })
+            }
+            // helper building an `Output` from its kind ("bot" or "output") and id.
+            fn output(s: &str, base: usize, caps: &Captures, kind: &str, id: &str) -> Result<Output, Spanned<String>> {
+                let id: Id = arg(s, base, caps, id)?;
+                Ok(if &caps[kind] == "bot" { Output::Robot(id) } else { Output::Bin(id) })
+            }
+            if let Some(caps) = TAKE.captures(s) {
+                let value: Value = arg(s, 0, &caps, "value")?;
+                let id: Id = arg(s, 0, &caps, "rid")?;
+                Ok(Instruction::Take { chip: Microchip(value), robot_id: id })
+            } else if let Some(caps) = DONATE.captures(s) {
+                let robot_id: Id = arg(s, 0, &caps, "rid")?;
+                let low_receiver = output(s, 0, &caps, "l", "lid")?;
+                let high_receiver = output(s, 0, &caps, "h", "hid")?;
+                if caps["middles"].is_empty() {
+                    return Ok(Instruction::Donate {
+                        robot_id,
+                        low: low_receiver,
+                        high: high_receiver
+                    });
+                }
+                // the generalized form, eg. "bot 1 gives low to bot 2, middle to output 3 and
+                // high to bot 4" for a robot sorting three microchips.
+                let mut outputs = vec![low_receiver];
+                let middles = caps.name("middles").unwrap();
+                for m in MIDDLE.captures_iter(middles.as_str()) {
+                    outputs.push(output(s, middles.start(), &m, "m", "mid")?);
+                }
+                outputs.push(high_receiver);
+                Ok(Instruction::Sort { robot_id, outputs })
+            } else if let Some(caps) = CAPACITY.captures(s) {
+                let bin_id: Id = arg(s, 0, &caps, "bid")?;
+                let capacity: usize = arg(s, 0, &caps, "capacity")?;
+                Ok(Instruction::Capacity { bin_id, capacity })
+            } else {
+                Err(Spanned::of(s, 0..s.len(), Unrecognized::new("instructions", s).into()))
+            }
+        }
+    }
+
+    impl ::std::fmt::Display for Instruction {
+        /// Format the `Instruction` the way it is parsed.
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Instruction::Take { chip, robot_id } => {
+                    write!(f, "value {} goes to bot {}", chip.value(), robot_id)
+                },
+                Instruction::Donate { robot_id, low, high } => {
+                    write!(f, "bot {} gives low to {} and high to {}", robot_id, low, high)
+                },
+                Instruction::Sort { robot_id, ref outputs } => {
+                    let (low, high) = (outputs[0], outputs[outputs.len() - 1]);
+                    write!(f, "bot {} gives low to {}", robot_id, low)?;
+                    for middle in outputs[1..outputs.len() - 1].iter() {
+                        write!(f, ", middle to {}", middle)?;
+                    }
+                    write!(f, " and high to {}", high)
+                },
+                Instruction::Capacity { bin_id, capacity } => {
+                    write!(f, "output {} holds {}", bin_id, capacity)
+                },
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    impl Arbitrary for Instruction {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Instruction>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            prop_oneof![
+                (any::<Microchip>(), any::<Id>()).prop_map(|(chip, robot_id)| {
+                    Instruction::Take { chip, robot_id }
+                }),
+                (any::<Id>(), any::<Output>(), any::<Output>()).prop_map(|(robot_id, low, high)| {
+                    Instruction::Donate { robot_id, low, high }
+                }),
+                // NOTE: a robot sorting two microchips is parsed as a `Donate` instruction.
+                (any::<Id>(), vec(any::<Output>(), 3..6)).prop_map(|(robot_id, outputs)| {
+                    Instruction::Sort { robot_id, outputs }
+                }),
+                (any::<Id>(), any::<usize>()).prop_map(|(bin_id, capacity)| {
+                    Instruction::Capacity { bin_id, capacity }
+                }),
+            ].boxed()
+        }
+    }
+
+    /// A comparison made by a robot.
+    #[derive(Eq, PartialEq, Clone, Debug)]
+    pub struct Comparison {
+        pub robot_id: Id,
+        pub low: Microchip,
+        pub high: Microchip,
+        /// The microchips between `low` and `high` in order, for robots sorting more than two
+        /// microchips.
+        pub middle: Vec<Microchip>,
+    }
+
+    /// Something that happened while running a `Factory` forward.
+    #[derive(Eq, PartialEq, Clone, Debug)]
+    pub enum Event {
+        /// A robot took a microchip from an input bin.
+        Take { robot_id: Id, chip: Microchip },
+        /// A robot compared the two microchips it was holding.
+        Compare(Comparison),
+        /// A robot gave one of its microchips to a robot or an output bin.
+        Give { robot_id: Id, chip: Microchip, to: Output },
+    }
+
+    impl ::std::fmt::Display for Event {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                Event::Take { robot_id, chip } => {
+                    write!(f, "bot {} takes value {} from an input bin", robot_id, chip.value())
+                },
+                Event::Compare(Comparison { robot_id, low, high, ref middle }) if middle.is_empty() => {
+                    write!(f, "bot {} compares value {} with value {}", robot_id, low.value(), high.value())
+                },
+                Event::Compare(Comparison { robot_id, low, high, ref middle }) => {
+                    write!(f, "bot {} sorts values {}", robot_id, low.value())?;
+                    for chip in middle {
+                        write!(f, ", {}", chip.value())?;
+                    }
+                    write!(f, " and {}", high.value())
+                },
+                Event::Give { robot_id, chip, to: Output::Robot(id) } => {
+                    write!(f, "bot {} gives value {} to bot {}", robot_id, chip.value(), id)
+                },
+                Event::Give { robot_id, chip, to: Output::Bin(id) } => {
+                    write!(f, "bot {} gives value {} to output {}", robot_id, chip.value(), id)
+                },
+            }
+        }
+    }
+
+    /// The outcome of running a `Factory` forward, see `Factory::simulate()`.
+    #[derive(Debug)]
+    pub struct Simulation {
+        // every comparison made, in order.
+        comparisons: Vec<Comparison>,
+        // everything that happened, in order.
+        events: Vec<Event>,
+        // the microchips given to each output bin, in order.
+        bins: HashMap<Id, Vec<Microchip>>,
+    }
+
+    impl Simulation {
+        /// Returns every comparison made during the simulation, in order.
+        pub fn comparisons(&self) -> &[Comparison] {
+            &self.comparisons
+        }
+
+        /// Returns everything that happened during the simulation, in order.
+        pub fn events(&self) -> &[Event] {
+            &self.events
+        }
+
+        /// Returns the microchips given to the output bin `id`, in order.
+        pub fn bin_contents(&self, id: Id) -> &[Microchip] {
+            self.bins.get(&id).map_or(&[], |chips| chips.as_slice())
+        }
+
+        /// Returns the robot which compared the microchip pair `(m0, m1)`.
+        pub fn robot_comparing(&self, m0: Microchip, m1: Microchip) -> Option<Id> {
+            let target = Microchip2::new(m0, m1);
+            self.comparisons.iter()
+                .find(|c| c.middle.is_empty() && Microchip2::new(c.low, c.high) == target)
+                .map(|c| c.robot_id)
+        }
+    }
+
+    /// A condition stopping `Stepper::run_until()`.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub enum Breakpoint {
+        /// Stop when the robot compares its microchips.
+        Compare(Id),
+        /// Stop when a microchip of the given value is taken, compared or given.
+        Chip(Value),
+    }
+
+    impl Breakpoint {
+        /// Returns `true` if the `event` triggers this breakpoint, `false` otherwise.
+        pub fn matches(&self, event: &Event) -> bool {
+            match (*self, event) {
+                (Breakpoint::Compare(id), Event::Compare(comparison)) => comparison.robot_id == id,
+                (Breakpoint::Chip(value), Event::Compare(comparison)) => {
+                    comparison.low.value() == value || comparison.high.value() == value ||
+                        comparison.middle.iter().any(|chip| chip.value() == value)
+                },
+                (Breakpoint::Chip(value), &Event::Take { chip, .. }) |
+                (Breakpoint::Chip(value), &Event::Give { chip, .. }) => chip.value() == value,
+                _ => false,
+            }
+        }
+    }
+
+    impl FromStr for Breakpoint {
+        type Err = String;
+
+        /// Parse a breakpoint, either "compare ROBOT" or "chip VALUE".
+        fn from_str(s: &str) -> Result<Breakpoint, String> {
+            let words: Vec<&str> = s.split_whitespace().collect();
+            match words.as_slice() {
+                ["compare", id] => id.parse().map(Breakpoint::Compare).map_err(|e| format!("{}: {}", id, e)),
+                ["chip", value] => value.parse().map(Breakpoint::Chip).map_err(|e| format!("{}: {}", value, e)),
+                _ => Err(Unrecognized::new("breakpoint", s).into()),
+            }
+        }
+    }
+
+    /// A robot giving a microchip to an output bin already holding as many microchips as its
+    /// declared capacity, see `Factory::try_simulate()`.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub struct BinOverflow {
+        pub bin_id: Id,
+        pub capacity: usize,
+        pub robot_id: Id,
+        pub chip: Microchip,
+    }
+
+    impl ::std::fmt::Display for BinOverflow {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            write!(f, "bot {} gives value {} to output {} already holding its {} microchip{}",
+                   self.robot_id, self.chip.value(), self.bin_id, self.capacity,
+                   if self.capacity == 1 { "" } else { "s" })
+        }
+    }
+
+    impl ::std::error::Error for BinOverflow {}
+
+    /// Why a `Factory` could not be simulated, see `Factory::try_simulate()`.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub enum SimulationError {
+        /// The factory is not fully built yet.
+        Invalid(FactoryError),
+        Overflow(BinOverflow),
+    }
+
+    impl From<FactoryError> for SimulationError {
+        fn from(error: FactoryError) -> SimulationError {
+            SimulationError::Invalid(error)
+        }
+    }
+
+    impl From<BinOverflow> for SimulationError {
+        fn from(overflow: BinOverflow) -> SimulationError {
+            SimulationError::Overflow(overflow)
+        }
+    }
+
+    impl ::std::fmt::Display for SimulationError {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                SimulationError::Invalid(ref e) => write!(f, "invalid factory: {}", e),
+                SimulationError::Overflow(ref overflow) => write!(f, "overflow: {}", overflow),
+            }
+        }
+    }
+
+    impl ::std::error::Error for SimulationError {}
+
+    /// Run a `Factory` forward one event at a time, see `Factory::stepper()`.
+    ///
+    /// The microchips are moved around as the events are returned, so the state in between
+    /// steps can be inspected with `held_by()` and `bin_contents()`.
+    #[derive(Debug)]
+    pub struct Stepper<'a> {
+        factory: &'a Factory,
+        // the microchips currently held by each robot.
+        holding: HashMap<Id, Vec<Microchip>>,
+        // the robots ready to compare, processed in order for a reproducible run.
+        ready: VecDeque<Id>,
+        // the events to happen before the next ready robot compares.
+        queued: VecDeque<Event>,
+        // the microchips given to each output bin, in order.
+        bins: HashMap<Id, Vec<Microchip>>,
+    }
+
+    impl<'a> Stepper<'a> {
+        /// Perform the next event and return it, or `None` when no robot is left with
+        /// something to do.
+        ///
+        /// The capacity of the output bins is not checked, see `try_step()`.
+        pub fn step(&mut self) -> Option<Event> {
+            self.queue_next()?;
+            let event = self.queued.pop_front()?;
+            match event {
+                Event::Take { robot_id, chip } => self.receive(robot_id, chip),
+                Event::Compare(_) => (),
+                Event::Give { robot_id, chip, to } => {
+                    let held = self.holding.get_mut(&robot_id).unwrap();
+                    let position = held.iter().position(|&c| c == chip).unwrap();
+                    held.remove(position);
+                    match to {
+                        Output::Robot(id) => self.receive(id, chip),
+                        Output::Bin(id) => self.bins.entry(id).or_default().push(chip),
+                    }
+                },
+            }
+            Some(event)
+        }
+
+        /// Like `step()`, but returns an error instead when the next event would give a
+        /// microchip to an output bin already full. The event is then not performed.
+        pub fn try_step(&mut self) -> Result<Option<Event>, BinOverflow> {
+            if self.queue_next().is_some() {
+                if let Some(&Event::Give { robot_id, chip, to: Output::Bin(bin_id) }) = self.queued.front() {
+                    if let Some(&capacity) = self.factory.capacities.get(&bin_id) {
+                        if self.bin_contents(bin_id).len() >= capacity {
+                            return Err(BinOverflow { bin_id, capacity, robot_id, chip });
+                        }
+                    }
+                }
+            }
+            Ok(self.step())
+        }
+
+        /// Make sure that the next event is queued, the next ready robot comparing its
+        /// microchips when needed. Returns `None` when there is no event left.
+        fn queue_next(&mut self) -> Option<()> {
+            if self.queued.is_empty() {
+                let id = self.ready.pop_front()?;
+                let mut chips = self.holding[&id].clone();
+                chips.sort_by_key(|chip| chip.value());
+                let robot = self.factory.robots.get(&id)?;
+                let last = chips.len() - 1;
+                self.queued.push_back(Event::Compare(Comparison {
+                    robot_id: id,
+                    low: chips[0],
+                    high: chips[last],
+                    middle: chips[1..last].to_vec(),
+                }));
+                for (&chip, &output) in chips.iter().zip(robot.to.iter()) {
+                    self.queued.push_back(Event::Give { robot_id: id, chip, to: output });
+                }
+            }
+            Some(())
+        }
+
+        /// Step until an event triggers one of the `breakpoints` and return it, or `None` when
+        /// the run is over.
+        pub fn run_until(&mut self, breakpoints: &[Breakpoint]) -> Option<Event> {
+            while let Some(event) = self.step() {
+                if breakpoints.iter().any(|breakpoint| breakpoint.matches(&event)) {
+                    return Some(event);
+                }
+            }
+            None
+        }
+
+        /// Returns the microchips currently held by the robot `id`, in the order they were
+        /// received.
+        pub fn held_by(&self, id: Id) -> &[Microchip] {
+            self.holding.get(&id).map_or(&[], |chips| chips.as_slice())
+        }
+
+        /// Returns the microchips given so far to the output bin `id`, in order.
+        pub fn bin_contents(&self, id: Id) -> &[Microchip] {
+            self.bins.get(&id).map_or(&[], |chips| chips.as_slice())
+        }
+
+        /// Give the `chip` to the robot `id`, which is ready to compare once full.
+        fn receive(&mut self, id: Id, chip: Microchip) {
+            let held = self.holding.entry(id).or_default();
+            held.push(chip);
+            if self.factory.robots.get(&id).is_some_and(|robot| held.len() == robot.to.len()) {
+                self.ready.push_back(id);
+            }
+        }
+    }
+
+    /// Errors found while building a `Factory`.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub enum FactoryError {
+        /// A robot doesn't receive exactly as many microchips as it gives (two for the puzzle
+        /// robots).
+        InputCount { robot_id: Id, count: usize },
+        /// A robot receives microchips but is never told where to give them.
+        MissingOutputs(Id),
+        /// A robot is told where to give its microchips more than once.
+        DuplicateOutputs(Id),
+        /// An output bin without a declared capacity receives more than one microchip.
+        DuplicateBinInput(Id),
+        /// An output bin capacity is declared more than once.
+        DuplicateCapacity(Id),
+        /// An output bin with a declared capacity, which may receive microchips from several
+        /// robots, is given a microchip by a robot rewired in `Factory::what_if()`.
+        SharedBin(Id),
+        /// An output bin capacity is given to `Factory::what_if()`, which only rewires robots.
+        /// The capacities only matter to the simulation, see `Factory::try_simulate()`.
+        CapacityChange(Id),
+        /// A robot (indirectly) receives a microchip from itself.
+        Cycle(Id),
+    }
+
+    impl ::std::fmt::Display for FactoryError {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+                FactoryError::InputCount { robot_id, count } => {
+                    write!(f, "robot {} has {} inputs", robot_id, count)
+                },
+                FactoryError::MissingOutputs(id) => {
+                    write!(f, "robot {} referenced but never given outputs", id)
+                },
+                FactoryError::DuplicateOutputs(id) => write!(f, "robot {} is given outputs twice", id),
+                FactoryError::DuplicateBinInput(id) => write!(f, "output {} has more than one input", id),
+                FactoryError::DuplicateCapacity(id) => write!(f, "output {} is given a capacity twice", id),
+                FactoryError::SharedBin(id) => write!(f, "output {} may have more than one input", id),
+                FactoryError::CapacityChange(id) => {
+                    write!(f, "output {} capacity only matters to the simulation", id)
+                },
+                FactoryError::Cycle(id) => write!(f, "robot {} depends on its own outputs", id),
+            }
+        }
+    }
+
+    impl ::std::error::Error for FactoryError {}
+
+    /// A robot of a resolved factory, see `ResolvedFactory`.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct ResolvedRobot {
+        pub id: Id,
+        pub compares: Microchip2,
+        pub low_to: Output,
+        pub high_to: Output,
+        /// The microchips between the lower-value and the higher-value ones and their outputs,
+        /// only for robots sorting more than two microchips.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub middle: Vec<Microchip>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub middle_to: Vec<Output>,
+    }
+
+    /// An output bin of a resolved factory, see `ResolvedFactory`. A bin given several microchips
+    /// (see `Instruction::Capacity`) is listed once per microchip.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct ResolvedBin {
+        pub id: Id,
+        pub chip: Microchip,
+    }
+
+    /// The fully resolved state of a `Factory`: what every robot compares and what every output
+    /// bin ends up with, both sorted by id so that two runs can easily be compared.
+    #[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+    pub struct ResolvedFactory {
+        pub robots: Vec<ResolvedRobot>,
+        pub bins: Vec<ResolvedBin>,
+    }
+
+    impl ResolvedFactory {
+        /// Returns this resolved factory as (pretty-printed) JSON.
+        pub fn to_json(&self) -> String {
+            // NOTE: there are only structs, sequences and integers in there, so this can't fail.
+            ::serde_json::to_string_pretty(self).unwrap()
+        }
+    }
+
+    /// A robot comparing another microchip pair once an instruction is modified, see `WhatIf`.
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub struct RobotChange {
+        pub id: Id,
+        pub before: Microchip2,
+        pub after: Microchip2,
+    }
+
+    /// An output bin getting another microchip once an instruction is modified, see `WhatIf`.
+    /// A bin no longer (or newly) given a microchip has `None` after (or before).
+    #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+    pub struct BinChange {
+        pub id: Id,
+        pub before: Option<Microchip>,
+        pub after: Option<Microchip>,
+    }
+
+    /// The difference between a resolved factory and the same factory with one of its
+    /// instructions modified, see `Factory::what_if()`.
+    #[derive(Eq, PartialEq, Clone, Debug, Default)]
+    pub struct WhatIf {
+        /// The robots comparing another pair, sorted by id.
+        pub robots: Vec<RobotChange>,
+        /// The output bins getting another microchip, sorted by id.
+        pub bins: Vec<BinChange>,
+    }
+
+    impl WhatIf {
+        /// Returns `true` when modifying the instruction changes nothing.
+        pub fn is_empty(&self) -> bool {
+            self.robots.is_empty() && self.bins.is_empty()
+        }
+    }
+
+    impl ::std::fmt::Display for WhatIf {
+        fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            let chip = |chip: Option<Microchip>| chip.map_or("nothing".to_string(), |c| c.value().to_string());
+            for change in &self.robots {
+                writeln!(f, "bot {} compares value {} with value {} instead of value {} with value {}",
+                         change.id, change.after.low.value(), change.after.high.value(),
+                         change.before.low.value(), change.before.high.value())?;
+            }
+            for change in &self.bins {
+                writeln!(f, "output {} gets {} instead of {}", change.id, chip(change.after), chip(change.before))?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Everything that can contribute to an output, see `Factory::provenance()`.
+    #[derive(Eq, PartialEq, Clone, Debug)]
+    pub struct Provenance {
+        /// The microchips taken from input bins, sorted by value.
+        pub chips: Vec<Microchip>,
+        /// The robots, sorted by id.
+        pub robots: Vec<Id>,
+    }
+
+    /// The robots of a factory linked to the robots giving them microchips, used to walk the
+    /// graph backwards.
+    struct Donors<'a>(&'a Factory);
+
+    impl<'a> ::search::Space for Donors<'a> {
+        type State = Id;
+        type Key = Id;
+
+        fn successors(&self, id: &Id) -> Vec<(Id, ::search::Cost)> {
+            let from = self.0.robots.get(id).map_or(&[][..], |robot| robot.from.as_slice());
+            from.iter().filter_map(|&gift| match gift {
+                Gift::Input { .. } => None,
+                Gift::Donation { from_robot_id, .. } => Some((from_robot_id, 1)),
+            }).collect()
+        }
+
+        fn is_goal(&self, _: &Id) -> bool {
+            false
+        }
+
+        fn key(&self, id: &Id) -> Id {
+            *id
+        }
+    }
+
+    /// Build a `Factory` without going through text parsing, eg.:
+    ///
+    /// ```ignore
+    /// let factory = FactoryBuilder::new()
+    ///     .give(Microchip(5), 0)
+    ///     .give(Microchip(2), 0)
+    ///     .wire(0, Output::Bin(0), Output::Bin(1))
+    ///     .build()?;
+    /// ```
+    ///
+    /// Like with the instructions, the order doesn't matter and the factory is only validated
+    /// by `build()`.
+    #[derive(Default, Debug)]
+    pub struct FactoryBuilder {
+        instructions: Vec<Instruction>,
+    }
+
+    impl FactoryBuilder {
+        /// Creates a new builder of an "empty" factory.
+        pub fn new() -> FactoryBuilder {
+            FactoryBuilder::default()
+        }
+
+        /// The `chip` goes from an input bin to the robot `robot_id`.
+        pub fn give(&mut self, chip: Microchip, robot_id: Id) -> &mut FactoryBuilder {
+            self.instructions.push(Instruction::Take { chip, robot_id });
+            self
+        }
+
+        /// The robot `robot_id` gives its lower-value microchip to `low_to` and its higher-value
+        /// one to `high_to`.
+        pub fn wire(&mut self, robot_id: Id, low_to: Output, high_to: Output) -> &mut FactoryBuilder {
+            self.instructions.push(Instruction::Donate { robot_id, low: low_to, high: high_to });
+            self
+        }
+
+        /// The robot `robot_id` sorts its microchips and gives them to `outputs`, from the
+        /// lower-value to the higher-value.
+        pub fn sort(&mut self, robot_id: Id, outputs: Vec<Output>) -> &mut FactoryBuilder {
+            self.instructions.push(Instruction::Sort { robot_id, outputs });
+            self
+        }
+
+        /// The output bin `bin_id` holds at most `capacity` microchips, see
+        /// `Instruction::Capacity`.
+        pub fn capacity(&mut self, bin_id: Id, capacity: usize) -> &mut FactoryBuilder {
+            self.instructions.push(Instruction::Capacity { bin_id, capacity });
+            self
+        }
+
+        /// Returns the factory built so far, or the first error found, see
+        /// `Factory::try_build_from()`.
+        pub fn build(&self) -> Result<Factory, FactoryError> {
+            Factory::try_build_from(&self.instructions)
+        }
+    }
+
+    /// The robots of a resolvable factory stored in dense arenas and resolved once, see
+    /// `Factory::arena()`. Robots are refered to by index (their position when sorted by id)
+    /// and their input slots by offset: robot `i` slots are `offsets[i]..offsets[i + 1]`.
+    #[derive(Debug)]
+    struct Arena {
+        // the robot ids, sorted.
+        ids: Vec<Id>,
+        // robot `i` has `offsets[i + 1] - offsets[i]` input slots (and as many outputs).
+        offsets: Vec<usize>,
+        // the microchips of every robot sorted by value, i.e. by rank.
+        chips: Vec<Microchip>,
+        // the rank of the microchip received through every input slot.
+        ranks: Vec<usize>,
+        // where every ranked microchip goes: a robot index and its input slot, `None` for an
+        // output bin.
+        next: Vec<Option<(usize, usize)>>,
+        // the microchips taken from input bins with their robot index and input slot, sorted.
+        inputs: Vec<(Microchip, usize, usize)>,
+        // the microchips of every output bin, in the order of their inputs.
+        bins: HashMap<Id, Vec<Microchip>>,
+    }
+
+    impl Arena {
+        /// Resolve the given `factory` robots in topological order, returns an error if the
+        /// factory is not resolvable.
+        fn compile(factory: &Factory) -> Result<Arena, FactoryError> {
+            if let Some(e) = factory.pending_error() {
+                return Err(e);
+            }
+            factory.validate()?;
+            let mut ids: Vec<Id> = factory.robots.keys().cloned().collect();
+            ids.sort();
+            let index: HashMap<Id, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+            let mut offsets = vec![0];
+            for id in ids.iter() {
+                offsets.push(offsets[offsets.len() - 1] + factory.robots[id].from.len());
+            }
+            let size = offsets[ids.len()];
+
+            // link every donation to its receiving slot, counting the microchips each robot
+            // waits for from other robots.
+            let mut received: Vec<Option<Microchip>> = vec![None; size];
+            let mut next = vec![None; size];
+            let mut inputs = Vec::new();
+            let mut waiting = vec![0; ids.len()];
+            for (i, id) in ids.iter().enumerate() {
+                for (slot, &gift) in factory.robots[id].from.iter().enumerate() {
+                    match gift {
+                        Gift::Input { chip } => {
+                            received[offsets[i] + slot] = Some(chip);
+                            inputs.push((chip, i, slot));
+                        },
+                        Gift::Donation { from_robot_id, rank } => {
+                            next[offsets[index[&from_robot_id]] + rank] = Some((i, slot));
+                            waiting[i] += 1;
+                        },
+                    }
+                }
+            }
+
+            // Kahn's algorithm: resolve the robots once they've received all their microchips.
+            let mut chips = vec![Microchip(0); size];
+            let mut ranks = vec![0; size];
+            let mut ready: VecDeque<usize> = (0..ids.len()).filter(|&i| waiting[i] == 0).collect();
+            let mut resolved = 0;
+            while let Some(i) = ready.pop_front() {
+                resolved += 1;
+                let (start, end) = (offsets[i], offsets[i + 1]);
+                // NOTE: the sort is stable so microchips of the same value keep their input
+                // order, like in the simulation.
+                let mut slots: Vec<usize> = (0..end - start).collect();
+                slots.sort_by_key(|&slot| received[start + slot].unwrap().value());
+                for (rank, &slot) in slots.iter().enumerate() {
+                    chips[start + rank] = received[start + slot].unwrap();
+                    ranks[start + slot] = rank;
+                }
+                for rank in 0..end - start {
+                    if let Some((j, slot)) = next[start + rank] {
+                        received[offsets[j] + slot] = Some(chips[start + rank]);
+                        waiting[j] -= 1;
+                        if waiting[j] == 0 {
+                            ready.push_back(j);
+                        }
+                    }
+                }
+            }
+            // NOTE: every robot is resolved as the factory has been validated.
+            debug_assert_eq!(resolved, ids.len());
+
+            let bins = factory.bins.values().map(|bin| {
+                let bin_chips = bin.from.iter().map(|&gift| match gift {
+                    Gift::Input { chip } => chip,
+                    Gift::Donation { from_robot_id, rank } => chips[offsets[index[&from_robot_id]] + rank],
+                }).collect();
+                (bin.id, bin_chips)
+            }).collect();
+            Ok(Arena { ids, offsets, chips, ranks, next, inputs, bins })
+        }
+
+        /// Returns the microchips of the robot `i`, sorted by value.
+        fn chips(&self, i: usize) -> &[Microchip] {
+            &self.chips[self.offsets[i]..self.offsets[i + 1]]
+        }
+
+        /// Returns the microchip pair compared by the robot `i`, i.e. its lower-value and
+        /// higher-value microchips.
+        fn pair(&self, i: usize) -> Microchip2 {
+            let chips = self.chips(i);
+            Microchip2::new(chips[0], chips[chips.len() - 1])
+        }
+
+        /// Resolve again the robots downstream of the robot `r` when it gives its microchips to
+        /// `outputs` instead, the robots given microchips being the same. Returns the microchips
+        /// of every robot resolved again, sorted by value.
+        fn what_if(&self, r: usize, outputs: &[Output]) -> HashMap<usize, Vec<Microchip>> {
+            // the input slots fed by the robot are reused, in order, by the robots it feeds.
+            let mut slots: HashMap<usize, VecDeque<usize>> = HashMap::new();
+            for rank in 0..outputs.len() {
+                if let Some((j, slot)) = self.next[self.offsets[r] + rank] {
+                    slots.entry(j).or_default().push_back(slot);
+                }
+            }
+            let next: Vec<Option<(usize, usize)>> = outputs.iter().map(|&output| match output {
+                Output::Robot(id) => {
+                    let j = self.ids.binary_search(&id).unwrap();
+                    Some((j, slots.get_mut(&j).unwrap().pop_front().unwrap()))
+                },
+                Output::Bin(_) => None,
+            }).collect();
+
+            // count the inputs of the downstream robots coming from the rewired robot or from
+            // other downstream robots, the ones to wait for.
+            let mut waiting: HashMap<usize, usize> = HashMap::new();
+            let mut stack = vec![r];
+            while let Some(i) = stack.pop() {
+                for &(j, _) in self.next[self.offsets[i]..self.offsets[i + 1]].iter().flatten() {
+                    let count = waiting.entry(j).or_insert(0);
+                    if *count == 0 {
+                        stack.push(j);
+                    }
+                    *count += 1;
+                }
+            }
+
+            // Kahn's algorithm again, but only over the downstream robots. The rewired robot
+            // microchips don't change, and the inputs coming from upstream keep their microchip.
+            let mut received: HashMap<(usize, usize), Microchip> = HashMap::new();
+            let mut resolved: HashMap<usize, Vec<Microchip>> = HashMap::new();
+            let mut ready = VecDeque::new();
+            let mut current = Some((r, self.chips(r).to_vec(), next));
+            while let Some((i, chips, next)) = current.take() {
+                for (&chip, &to) in chips.iter().zip(next.iter()) {
+                    if let Some((j, slot)) = to {
+                        received.insert((j, slot), chip);
+                        let count = waiting.get_mut(&j).unwrap();
+                        *count -= 1;
+                        if *count == 0 {
+                            ready.push_back(j);
+                        }
+                    }
+                }
+                if i != r {
+                    resolved.insert(i, chips);
+                }
+                if let Some(j) = ready.pop_front() {
+                    let (start, end) = (self.offsets[j], self.offsets[j + 1]);
+                    // NOTE: the sort is stable, like in `compile()`.
+                    let mut chips: Vec<Microchip> = (0..end - start).map(|slot| {
+                        received.get(&(j, slot)).cloned().unwrap_or(self.chips[start + self.ranks[start + slot]])
+                    }).collect();
+                    chips.sort_by_key(|chip| chip.value());
+                    current = Some((j, chips, self.next[start..end].to_vec()));
+                }
+            }
+            resolved
+        }
+
+        /// Follow the microchip entering the robot `i` through its input `slot` and returns
+        /// the ids of the robots comparing `target_pair` along its path, in order.
+        fn follow(&self, mut i: usize, mut slot: usize, target_pair: Microchip2) -> Vec<Id> {
+            // Each microchip follow a similar path. It start with an input bin, then goes through
+            // a number of robots comparing it, and finally is given to an output bin. We can
+            // represent the "path" that a microchip goes through like this:
+            //
+            //     input bin → first robot → another robot → another robot → ... → output bin
+            //
+            // Microchips are tracked by the input slot they use rather than by their value, so
+            // that microchips sharing the same value are not mixed up.
+            let mut found = Vec::new();
+            loop {
+                if self.chips(i).len() == 2 && self.pair(i) == target_pair {
+                    found.push(self.ids[i]);
+                }
+                let rank = self.ranks[self.offsets[i] + slot];
+                match self.next[self.offsets[i] + rank] {
+                    Some((j, next_slot)) => {
+                        i = j;
+                        slot = next_slot;
+                    },
+                    None => return found, // we've reached the end of the path.
+                }
+            }
+        }
+    }
+
+    /// The strange place we end up in: full of robots, bins and microchips.
+    #[derive(Debug)]
+    pub struct Factory {
+        robots: HashMap<Id, Robot>,
+        bins:   HashMap<Id, Bin>,
+        // the declared capacity of the output bins, see `Instruction::Capacity`.
+        capacities: HashMap<Id, usize>,
+        // While our `Robot` struct must be fully defined (inputs and outputs), its parameters
+        // may be provided across many non-consecutive instructions (one per input, one for its
+        // outputs). The parameters of the robots not yet fully defined are kept here
+        // until they are complete.
+        //
+        // On the other hand, output `Bin` may be created from a single instruction (defining its
+        // only input) so they're created directly.
+        pending_inputs:  HashMap<Id, Vec<Gift>>,
+        pending_outputs: HashMap<Id, Vec<Output>>,
+        // The resolved factory (or why it can't be resolved), compiled on the first query and
+        // dropped on change.
+        arena: OnceCell<Result<Arena, FactoryError>>,
+    }
+
+    impl Default for Factory {
+        fn default() -> Factory {
+            Factory::new()
+        }
+    }
+
+    impl PartialEq for Factory {
+        /// Two factories are equal when they are wired the same, whether they have been
+        /// resolved or not.
+        fn eq(&self, other: &Factory) -> bool {
+            self.robots == other.robots && self.bins == other.bins && self.capacities == other.capacities &&
+                self.pending_inputs == other.pending_inputs && self.pending_outputs == other.pending_outputs
+        }
+    }
+
+    /// The entries of a `Factory` an instruction may change, saved by `Factory::apply()` to roll
+    /// it back when the factory ends up invalid.
+    struct Snapshot {
+        robots: Vec<(Id, Option<Robot>)>,
+        bins: Vec<(Id, Option<Bin>)>,
+        capacities: Vec<(Id, Option<usize>)>,
+        pending_inputs: Vec<(Id, Option<Vec<Gift>>)>,
+        pending_outputs: Vec<(Id, Option<Vec<Output>>)>,
+    }
+
+    /// Returns the entries of `map` for the given `ids`, see `restore()`.
+    fn save<V: Clone>(map: &HashMap<Id, V>, ids: &[Id]) -> Vec<(Id, Option<V>)> {
+        ids.iter().map(|id| (*id, map.get(id).cloned())).collect()
+    }
+
+    /// Put back in `map` the entries returned by `save()`.
+    fn restore<V>(map: &mut HashMap<Id, V>, saved: Vec<(Id, Option<V>)>) {
+        for (id, value) in saved {
+            match value {
+                Some(value) => map.insert(id, value),
+                None => map.remove(&id),
+            };
+        }
+    }
+
+    impl Factory {
+        /// Creates a new "empty" factory.
+        pub fn new() -> Factory {
+            Factory {
+                robots: HashMap::new(),
+                bins:   HashMap::new(),
+                capacities: HashMap::new(),
+                pending_inputs:  HashMap::new(),
+                pending_outputs: HashMap::new(),
+                arena: OnceCell::new(),
+            }
+        }
+
+        /// Build a new factory based on a given list of instructions. Panics if the instructions
+        /// don't describe a valid factory, see `try_build_from()`.
+        pub fn build_from(instructions: &[Instruction]) -> Factory {
+            Factory::try_build_from(instructions).unwrap_or_else(|e| panic!("{}", e))
+        }
+
+        /// Build a new factory based on a given list of instructions, returns an error if they
+        /// don't describe a valid factory.
+        pub fn try_build_from(instructions: &[Instruction]) -> Result<Factory, FactoryError> {
+            let mut factory = Factory::new();
+            // the capacities first, so that the bins sharing is allowed wherever they are.
+            let (capacities, others): (Vec<&Instruction>, Vec<&Instruction>) = instructions.iter()
+                .partition(|instruction| matches!(instruction, Instruction::Capacity { .. }));
+            for instruction in capacities.into_iter().chain(others) {
+                factory.apply(instruction.clone())?;
+            }
+            match factory.pending_error() {
+                Some(e) => Err(e),
+                None => Ok(factory),
+            }
+        }
+
+        /// Update the factory wiring with the given instruction.
+        ///
+        /// Returns `true` when the factory is fully resolvable, i.e. every robot it knows about
+        /// is fully defined, `false` while some robots are still waiting for their inputs or
+        /// outputs. On error, the factory is left unchanged.
+        pub fn apply(&mut self, instruction: Instruction) -> Result<bool, FactoryError> {
+            // the errors found before any change are returned right away, the factory is only
+            // validated (and then rolled back on error) once changed.
+            let snapshot = self.snapshot(&instruction);
+            match instruction {
+                Instruction::Take { robot_id, chip } => {
+                    self.check_new_inputs(&[robot_id])?;
+                    self.pending_inputs.entry(robot_id).or_default().push(Gift::Input { chip });
+                    self.try_complete(robot_id);
+                },
+                Instruction::Donate { robot_id, low, high } => self.wire(robot_id, vec![low, high])?,
+                Instruction::Sort { robot_id, outputs } => self.wire(robot_id, outputs)?,
+                Instruction::Capacity { bin_id, capacity } => {
+                    if self.capacities.contains_key(&bin_id) {
+                        return Err(FactoryError::DuplicateCapacity(bin_id));
+                    }
+                    self.capacities.insert(bin_id, capacity);
+                },
+            }
+            self.arena = OnceCell::new();
+            if self.pending_inputs.is_empty() && self.pending_outputs.is_empty() {
+                if let Err(e) = self.validate() {
+                    self.rollback(snapshot);
+                    return Err(e);
+                }
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        }
+
+        /// Returns the entries of self `instruction` may change, see `rollback()`.
+        fn snapshot(&self, instruction: &Instruction) -> Snapshot {
+            let (robot_id, outputs, bin_id) = match *instruction {
+                Instruction::Take { robot_id, .. } => (Some(robot_id), Vec::new(), None),
+                Instruction::Donate { robot_id, low, high } => (Some(robot_id), vec![low, high], None),
+                Instruction::Sort { robot_id, ref outputs } => (Some(robot_id), outputs.clone(), None),
+                Instruction::Capacity { bin_id, .. } => (None, Vec::new(), Some(bin_id)),
+            };
+            let mut robot_ids: Vec<Id> = robot_id.into_iter().collect();
+            let mut bin_ids: Vec<Id> = bin_id.into_iter().collect();
+            for output in outputs {
+                match output {
+                    Output::Robot(id) => robot_ids.push(id),
+                    Output::Bin(id) => bin_ids.push(id),
+                }
+            }
+            Snapshot {
+                robots: save(&self.robots, &robot_ids),
+                bins: save(&self.bins, &bin_ids),
+                capacities: save(&self.capacities, &bin_ids),
+                pending_inputs: save(&self.pending_inputs, &robot_ids),
+                pending_outputs: save(&self.pending_outputs, &robot_ids),
+            }
+        }
+
+        /// Put back the entries saved by `snapshot()`.
+        fn rollback(&mut self, snapshot: Snapshot) {
+            restore(&mut self.robots, snapshot.robots);
+            restore(&mut self.bins, snapshot.bins);
+            restore(&mut self.capacities, snapshot.capacities);
+            restore(&mut self.pending_inputs, snapshot.pending_inputs);
+            restore(&mut self.pending_outputs, snapshot.pending_outputs);
+        }
+
+        /// Set the outputs of the robot `from_robot_id`, from the lower-value microchip to the
+        /// higher-value one.
+        fn wire(&mut self, from_robot_id: Id, outputs: Vec<Output>) -> Result<(), FactoryError> {
+            if outputs.len() < 2 {
+                return Err(FactoryError::MissingOutputs(from_robot_id));
+            }
+            if self.robots.contains_key(&from_robot_id) || self.pending_outputs.contains_key(&from_robot_id) {
+                return Err(FactoryError::DuplicateOutputs(from_robot_id));
+            }
+            // check everything first so that the factory is left unchanged on error.
+            let count = self.pending_inputs.get(&from_robot_id).map_or(0, Vec::len);
+            if count > outputs.len() {
+                return Err(FactoryError::InputCount { robot_id: from_robot_id, count });
+            }
+            let mut robot_ids = Vec::new();
+            let mut bin_ids = Vec::new();
+            for &output in outputs.iter() {
+                match output {
+                    Output::Robot(id) => robot_ids.push(id),
+                    Output::Bin(id) if self.capacities.contains_key(&id) => (),
+                    Output::Bin(id) if self.bins.contains_key(&id) || bin_ids.contains(&id) => {
+                        return Err(FactoryError::DuplicateBinInput(id));
+                    },
+                    Output::Bin(id) => bin_ids.push(id),
+                }
+            }
+            self.check_new_inputs(&robot_ids)?;
+
+            for (rank, &output) in outputs.iter().enumerate() {
+                let from = Gift::Donation { from_robot_id, rank };
+                match output {
+                    Output::Robot(id) => self.pending_inputs.entry(id).or_default().push(from),
+                    Output::Bin(id) => {
+                        self.bins.entry(id).or_insert_with(|| Bin { id, from: Vec::new() }).from.push(from);
+                    },
+                }
+            }
+            self.pending_outputs.insert(from_robot_id, outputs);
+            for id in robot_ids {
+                self.try_complete(id);
+            }
+            self.try_complete(from_robot_id);
+            Ok(())
+        }
+
+        /// Returns `true` when every robot of the factory is fully defined.
+        pub fn is_resolvable(&self) -> bool {
+            self.pending_error().is_none() && self.validate().is_ok()
+        }
+
+        /// Returns an error if the robots in `ids` (one id per new input) can't receive another
+        /// microchip. The capacity of robots whose outputs are not known yet is not checked.
+        fn check_new_inputs(&self, ids: &[Id]) -> Result<(), FactoryError> {
+            for &id in ids {
+                let (count, capacity) = match self.robots.get(&id) {
+                    Some(robot) => (robot.from.len(), robot.to.len()),
+                    None => match self.pending_outputs.get(&id) {
+                        Some(outputs) => (self.pending_inputs.get(&id).map_or(0, Vec::len), outputs.len()),
+                        None => continue,
+                    },
+                };
+                let count = count + ids.iter().filter(|&&other| other == id).count();
+                if count > capacity {
+                    return Err(FactoryError::InputCount { robot_id: id, count });
+                }
+            }
+            Ok(())
+        }
+
+        /// Create the `Robot` `id` if all its parameters are known.
+        fn try_complete(&mut self, id: Id) {
+            let complete = match (self.pending_inputs.get(&id), self.pending_outputs.get(&id)) {
+                (Some(from), Some(to)) => from.len() == to.len(),
+                _ => false,
+            };
+            if complete {
+                let from = self.pending_inputs.remove(&id).unwrap();
+                let to = self.pending_outputs.remove(&id).unwrap();
+                self.robots.insert(id, Robot { id, from, to });
+            }
+        }
+
+        /// Returns the error describing why a robot is not fully defined yet, if any.
+        fn pending_error(&self) -> Option<FactoryError> {
+            // robots giving microchips without ever receiving any.
+            let mut givers: Vec<Id> = self.pending_outputs.keys()
+                .filter(|rid| !self.pending_inputs.contains_key(rid))
+                .cloned()
+                .collect();
+            givers.sort();
+            if let Some(&robot_id) = givers.first() {
+                return Some(FactoryError::InputCount { robot_id, count: 0 });
+            }
+            // NOTE: the robots are processed in id order so that the reported error (if any)
+            // doesn't depend on the HashMap ordering.
+            let mut rids: Vec<Id> = self.pending_inputs.keys().cloned().collect();
+            rids.sort();
+            rids.first().map(|&rid| match self.pending_outputs.get(&rid) {
+                Some(_) => FactoryError::InputCount { robot_id: rid, count: self.pending_inputs[&rid].len() },
+                None => FactoryError::MissingOutputs(rid),
+            })
+        }
+
+        /// Check that every robot input comes from an existing robot and that no robot depends
+        /// on its own output (which would make the factory impossible to run).
+        pub fn validate(&self) -> Result<(), FactoryError> {
+            // iterative depth-first search over the robots inputs, `done` robots are known to
+            // be fine while `visiting` ones are on the current path.
+            let mut done: HashMap<Id, bool> = HashMap::new();
+            let mut rids: Vec<Id> = self.robots.keys().cloned().collect();
+            rids.sort();
+            for rid in rids {
+                if done.contains_key(&rid) {
+                    continue;
+                }
+                // stack of (robot id, whether its inputs have been pushed already).
+                let mut stack = vec![(rid, false)];
+                while let Some((id, expanded)) = stack.pop() {
+                    if expanded {
+                        done.insert(id, true);
+                        continue;
+                    }
+                    match done.get(&id) {
+                        Some(true) => continue,
+                        Some(false) => return Err(FactoryError::Cycle(id)),
+                        None => (),
+                    }
+                    done.insert(id, false);
+                    stack.push((id, true));
+                    let robot = &self.robots[&id];
+                    for &gift in robot.from.iter() {
+                        if let Gift::Donation { from_robot_id, .. } = gift {
+                            match done.get(&from_robot_id) {
+                                Some(true) => (),
+                                Some(false) => return Err(FactoryError::Cycle(from_robot_id)),
+                                None if self.robots.contains_key(&from_robot_id) => {
+                                    stack.push((from_robot_id, false));
+                                },
+                                None => return Err(FactoryError::InputCount { robot_id: from_robot_id, count: 0 }),
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns the resolved factory, compiling it on first use, or the error making it not
+        /// resolvable (e.g. while it is still being built by `apply()`).
+        fn arena(&self) -> Result<&Arena, FactoryError> {
+            self.arena.get_or_init(|| Arena::compile(self)).as_ref().map_err(|&e| e)
+        }
+
+        /// Returns the robot responsible for comparing the microchip pair `(m0, m1)`, `None`
+        /// when there is none or the factory is not resolvable.
+        ///
+        /// When several robots compare the pair, the first one along the path of the first
+        /// `m0` microchip is returned, see `robots_comparing()` to get all of them.
+        pub fn robot_comparing(&self, m0: Microchip, m1: Microchip) -> Option<Id> {
+            let arena = self.arena().ok()?;
+            let target_pair = Microchip2::new(m0, m1);
+            arena.inputs.iter()
+                .filter(|&&(chip, _, _)| chip == m0)
+                .filter_map(|&(_, i, slot)| arena.follow(i, slot, target_pair).first().cloned())
+                .next()
+        }
+
+        /// Returns all the robots comparing the microchip pair `(m0, m1)` sorted by id, none when
+        /// the factory is not resolvable.
+        ///
+        /// Several microchips may share the same value, so every microchip of value `m0` is
+        /// followed.
+        pub fn robots_comparing(&self, m0: Microchip, m1: Microchip) -> Vec<Id> {
+            let arena = match self.arena() {
+                Ok(arena) => arena,
+                Err(_) => return Vec::new(),
+            };
+            let target_pair = Microchip2::new(m0, m1);
+            let mut ids: Vec<Id> = arena.inputs.iter()
+                .filter(|&&(chip, _, _)| chip == m0)
+                .flat_map(|&(_, i, slot)| arena.follow(i, slot, target_pair))
+                .collect();
+            ids.sort();
+            ids.dedup();
+            ids
+        }
+
+        /// Returns every robot id along with the microchip pair it compares, sorted by id. For
+        /// robots sorting more than two microchips, the pair is their lower-value and
+        /// higher-value microchips. There is none when the factory is not resolvable.
+        pub fn comparisons(&self) -> impl Iterator<Item = (Id, Microchip2)> + '_ {
+            self.arena().ok().into_iter()
+                .flat_map(|arena| arena.ids.iter().enumerate().map(move |(i, &id)| (id, arena.pair(i))))
+        }
+
+        /// Explain the factory: every robot comparison, in the order of `comparisons()`.
+        pub fn explain(&self, sink: &mut dyn Sink) {
+            for (id, pair) in self.comparisons() {
+                sink.emit(&::aoc_core::Event::new("comparison", &[
+                    ("robot", id.into()),
+                    ("low", pair.low.value().into()),
+                    ("high", pair.high.value().into()),
+                ]));
+            }
+        }
+
+        /// Resolve the whole factory, see `ResolvedFactory`. Returns an error if the factory is not
+        /// resolvable.
+        pub fn resolve(&self) -> Result<ResolvedFactory, FactoryError> {
+            let arena = self.arena()?;
+            let robots = arena.ids.iter().enumerate().map(|(i, id)| {
+                let robot = &self.robots[id];
+                let chips = arena.chips(i);
+                let last = chips.len() - 1;
+                ResolvedRobot {
+                    id: robot.id,
+                    compares: Microchip2::new(chips[0], chips[last]),
+                    low_to: robot.to[0],
+                    high_to: robot.to[last],
+                    middle: chips[1..last].to_vec(),
+                    middle_to: robot.to[1..last].to_vec(),
+                }
+            }).collect();
+            let mut bins: Vec<ResolvedBin> = arena.bins.iter()
+                .flat_map(|(&id, chips)| chips.iter().map(move |&chip| ResolvedBin { id, chip }))
+                .collect();
+            bins.sort_by_key(|bin| bin.id);
+            Ok(ResolvedFactory { robots, bins })
+        }
+
+        /// Run the factory forward: every robot holding two microchips compares them and hand
+        /// them off to its outputs, until no robot is left holding two microchips.
+        ///
+        /// Unlike `robot_comparing()` and `chips_in_bins()` which walk the dependency graph
+        /// backward, this actually moves the microchips around. Returns an error if some robot
+        /// is not fully defined yet, see `stepper()`.
+        pub fn simulate(&self) -> Result<Simulation, FactoryError> {
+            let mut stepper = self.stepper()?;
+            let mut comparisons = Vec::new();
+            let mut events = Vec::new();
+            while let Some(event) = stepper.step() {
+                if let Event::Compare(ref comparison) = event {
+                    comparisons.push(comparison.clone());
+                }
+                events.push(event);
+            }
+            Ok(Simulation { comparisons, events, bins: stepper.bins })
+        }
+
+        /// Like `simulate()`, but returns an error when a robot gives a microchip to an output
+        /// bin already holding as many microchips as its declared capacity, see
+        /// `Instruction::Capacity`.
+        pub fn try_simulate(&self) -> Result<Simulation, SimulationError> {
+            let mut stepper = self.stepper()?;
+            let mut comparisons = Vec::new();
+            let mut events = Vec::new();
+            while let Some(event) = stepper.try_step()? {
+                if let Event::Compare(ref comparison) = event {
+                    comparisons.push(comparison.clone());
+                }
+                events.push(event);
+            }
+            Ok(Simulation { comparisons, events, bins: stepper.bins })
+        }
+
+        /// Returns a `Stepper` running the factory forward one event at a time, see
+        /// `simulate()` to run it at once. Returns an error if some robot is not fully defined
+        /// yet, e.g. while the factory is built through `apply()`.
+        pub fn stepper(&self) -> Result<Stepper<'_>, FactoryError> {
+            if let Some(e) = self.pending_error() {
+                return Err(e);
+            }
+            let mut queued = VecDeque::new();
+            let mut rids: Vec<Id> = self.robots.keys().cloned().collect();
+            rids.sort();
+            for rid in rids {
+                for &gift in self.robots[&rid].from.iter() {
+                    if let Gift::Input { chip } = gift {
+                        queued.push_back(Event::Take { robot_id: rid, chip });
+                    }
+                }
+            }
+            Ok(Stepper {
+                factory: self,
+                holding: HashMap::new(),
+                ready: VecDeque::new(),
+                queued,
+                bins: self.bins.values().map(|bin| (bin.id, Vec::new())).collect(),
+            })
+        }
+
+        /// "map" a vector of output bin ids to their given microchips, in order (the bins given
+        /// several microchips contributing all of them). Panic if any of the bin id is invalid,
+        /// empty when the factory is not resolvable.
+        pub fn chips_in_bins(&self, bin_ids: &[Id]) -> Vec<Microchip> {
+            match self.arena() {
+                Ok(arena) => bin_ids.iter().flat_map(|id| arena.bins[id].iter().cloned()).collect(),
+                Err(_) => Vec::new(),
+            }
+        }
+
+        /// Returns the microchip given to the output bin `id`, or `None` if there is no such
+        /// output bin or if it is given several microchips, see `bin_contents()`.
+        pub fn bin_value(&self, id: Id) -> Option<Microchip> {
+            match *self.bin_contents(id) {
+                [chip] => Some(chip),
+                _ => None,
+            }
+        }
+
+        /// Returns the microchips given to the output bin `id`, in the order of its inputs. Empty
+        /// when there is no such output bin or the factory is not resolvable.
+        pub fn bin_contents(&self, id: Id) -> &[Microchip] {
+            match self.arena() {
+                Ok(arena) => arena.bins.get(&id).map_or(&[], |chips| chips.as_slice()),
+                Err(_) => &[],
+            }
+        }
+
+        /// Returns what would change if the outputs of a robot were the ones given by
+        /// `instruction` (a `Donate` or `Sort` instruction) instead, e.g. a robot giving its
+        /// higher-value microchip elsewhere. The factory itself is left unchanged.
+        ///
+        /// Only the robots downstream of the rewired robot are resolved again, which is much
+        /// faster than building and resolving the modified factory on large factories. Returns
+        /// an error if the factory is not resolvable, or if the modified factory would be
+        /// invalid: the robot must exist and the robots it gives microchips to can only be
+        /// swapped, giving another robot one more (or one less) microchip would unbalance it.
+        /// `Take` instructions always add an input to a robot and are rejected. The output bins
+        /// with a declared capacity may be given microchips by several robots and the changes
+        /// involving them are rejected too, as are the `Capacity` instructions.
+        pub fn what_if(&self, instruction: Instruction) -> Result<WhatIf, FactoryError> {
+            let inputs = |robot_id: Id| self.robots.get(&robot_id).map_or(0, |robot| robot.from.len());
+            let (robot_id, outputs) = match instruction {
+                Instruction::Take { robot_id, .. } => {
+                    return Err(FactoryError::InputCount { robot_id, count: inputs(robot_id) + 1 });
+                },
+                Instruction::Donate { robot_id, low, high } => (robot_id, vec![low, high]),
+                Instruction::Sort { robot_id, outputs } => (robot_id, outputs),
+                Instruction::Capacity { bin_id, .. } => return Err(FactoryError::CapacityChange(bin_id)),
+            };
+            let robot = self.robots.get(&robot_id).ok_or(FactoryError::InputCount { robot_id, count: 0 })?;
+            let shared = |output: &Output| match *output {
+                Output::Bin(id) if self.capacities.contains_key(&id) => Some(FactoryError::SharedBin(id)),
+                _ => None,
+            };
+            if let Some(e) = robot.to.iter().chain(outputs.iter()).find_map(shared) {
+                return Err(e);
+            }
+            if outputs.len() < 2 {
+                return Err(FactoryError::MissingOutputs(robot_id));
+            }
+            if outputs.len() != robot.from.len() {
+                return Err(FactoryError::InputCount { robot_id, count: robot.from.len() });
+            }
+            // the robots given microchips must be the same, with as many microchips each.
+            let robots_of = |outputs: &[Output]| -> Vec<Id> {
+                let mut ids: Vec<Id> = outputs.iter().filter_map(|&output| match output {
+                    Output::Robot(id) => Some(id),
+                    Output::Bin(_) => None,
+                }).collect();
+                ids.sort();
+                ids
+            };
+            let (before, after) = (robots_of(&robot.to), robots_of(&outputs));
+            if before != after {
+                let mut ids: Vec<Id> = before.iter().chain(after.iter()).cloned().collect();
+                ids.sort();
+                for id in ids {
+                    let delta = after.iter().filter(|&&x| x == id).count() as isize
+                        - before.iter().filter(|&&x| x == id).count() as isize;
+                    if delta != 0 {
+                        let count = (inputs(id) as isize + delta) as usize;
+                        return Err(FactoryError::InputCount { robot_id: id, count });
+                    }
+                }
+            }
+            let mut bin_ids = Vec::new();
+            for &output in outputs.iter() {
+                if let Output::Bin(id) = output {
+                    let taken = match self.bins.get(&id) {
+                        Some(bin) => !matches!(bin.from[..], [Gift::Donation { from_robot_id, .. }] if from_robot_id == robot_id),
+                        None => false,
+                    };
+                    if taken || bin_ids.contains(&id) {
+                        return Err(FactoryError::DuplicateBinInput(id));
+                    }
+                    bin_ids.push(id);
+                }
+            }
+            let arena = self.arena()?;
+            let r = arena.ids.binary_search(&robot_id).unwrap();
+            let resolved = arena.what_if(r, &outputs);
+            let mut robots: Vec<RobotChange> = resolved.iter().filter_map(|(&i, chips)| {
+                let (before, after) = (arena.pair(i), Microchip2::new(chips[0], chips[chips.len() - 1]));
+                if before == after {
+                    None
+                } else {
+                    Some(RobotChange { id: arena.ids[i], before, after })
+                }
+            }).collect();
+            robots.sort_by_key(|change| change.id);
+            // the bins given microchips by the rewired robot (before and after) and the robots
+            // resolved again.
+            let mut given: HashMap<Id, Option<Microchip>> = HashMap::new();
+            for &output in robot.to.iter() {
+                if let Output::Bin(id) = output {
+                    given.insert(id, None);
+                }
+            }
+            let gifts = outputs.iter().zip(arena.chips(r).iter())
+                .chain(resolved.iter().flat_map(|(&i, chips)| self.robots[&arena.ids[i]].to.iter().zip(chips.iter())));
+            for (&output, &chip) in gifts {
+                if let Some(e) = shared(&output) {
+                    return Err(e);
+                }
+                if let Output::Bin(id) = output {
+                    given.insert(id, Some(chip));
+                }
+            }
+            let mut bins: Vec<BinChange> = given.into_iter().filter_map(|(id, after)| {
+                let before = arena.bins.get(&id).map(|chips| chips[0]);
+                if before == after {
+                    None
+                } else {
+                    Some(BinChange { id, before, after })
+                }
+            }).collect();
+            bins.sort_by_key(|change| change.id);
+            Ok(WhatIf { robots, bins })
+        }
+
+        /// Returns the input bin microchips and the robots that can contribute to the given
+        /// `output`, or `None` if there is no such output or some robot is not fully defined yet.
+        /// When `output` is a robot, it is part of its own provenance.
+        ///
+        /// Unlike the microchip queries this doesn't resolve anything and only walk the graph
+        /// backwards, so every input of the contributing robots is accounted for: a robot
+        /// comparing two microchips needs both to decide which one to give.
+        pub fn provenance(&self, output: Output) -> Option<Provenance> {
+            if self.pending_error().is_some() {
+                return None;
+            }
+            // the robots to walk back from, a bin given several microchips having many of them.
+            let (gifts, starts): (&[Gift], Vec<Id>) = match output {
+                Output::Bin(id) => {
+                    let from = &self.bins.get(&id)?.from;
+                    let starts = from.iter().filter_map(|&gift| match gift {
+                        Gift::Input { .. } => None,
+                        Gift::Donation { from_robot_id, .. } => Some(from_robot_id),
+                    }).collect();
+                    (from, starts)
+                },
+                Output::Robot(id) => {
+                    self.robots.get(&id)?;
+                    (&[], vec![id])
+                },
+            };
+            // NOTE: different paths may lead to the same robot, the exploration visits it once.
+            // The explorations from different starts may overlap though.
+            let mut robots: Vec<Id> = starts.into_iter()
+                .flat_map(|start| ::search::explore(&Donors(self), start, None).states.into_iter().map(|(id, _)| id))
+                .collect();
+            robots.sort();
+            robots.dedup();
+            let mut chips: Vec<Microchip> = gifts.iter()
+                .chain(robots.iter().filter_map(|id| self.robots.get(id)).flat_map(|robot| robot.from.iter()))
+                .filter_map(|&gift| match gift {
+                    Gift::Input { chip } => Some(chip),
+                    Gift::Donation { .. } => None,
+                })
+                .collect();
+            chips.sort_by_key(|chip| chip.value());
+            Some(Provenance { chips, robots })
+        }
+    }
+
+    impl FromInput for Factory {
+        /// Parse one `Instruction` per line of `input` and build the factory, see
+        /// `try_build_from()`. The factory is only built once every instruction could be parsed.
+        fn from_input(input: &Input) -> Result<Factory, AocError> {
+            let instructions = ::report::parse_spanned_lines::<Instruction, _>(input.text())?;
+            Factory::try_build_from(&instructions).map_err(|e| format!("invalid factory: {}", e).into())
+        }
+    }
+
+    /// Returns the instructions of a random factory of `size` robots, formatted like the puzzle
+    /// input.
+    ///
+    /// The factory is balanced: each robot gets two microchips, either from an input bin or from
+    /// a robot built before it, and the outputs left are given to the output bins. Like in the
+    /// puzzle, a robot compares the microchips 17 and 61 and there are at least three output
+    /// bins (unless there is a single robot). The microchips are drawn from the `Microchip`
+    /// strategy.
+    #[cfg(feature = "proptest")]
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let mut sampler = Sampler::new(rng.gen());
+        let mut robot_ids: Vec<Id> = (0..size as Id).collect();
+        robot_ids.shuffle(rng);
+        // the values are distinct, 17 and 61 being given to the first robot.
+        let mut values: HashSet<Value> = [17, 61].iter().cloned().collect();
+        let chips = any_with::<Microchip>(Some((size as Value * 4).max(75)));
+        let mut instructions = Vec::new();
+        // the outputs not wired yet, as (robot index, output index) pairs.
+        let mut pending: Vec<(usize, usize)> = Vec::new();
+        let mut outputs: Vec<[Option<Output>; 2]> = vec![[None, None]; size];
+        for (i, &robot_id) in robot_ids.iter().enumerate() {
+            for input in 0..2 {
+                // NOTE: keep a few outputs pending so that there are enough output bins.
+                if i > 0 && pending.len() > 3 && rng.gen_range(0, 10) > 0 {
+                    let (from, output) = pending.swap_remove(rng.gen_range(0, pending.len()));
+                    outputs[from][output] = Some(Output::Robot(robot_id));
+                    continue;
+                }
+                let value = if i == 0 {
+                    [17, 61][input]
+                } else {
+                    loop {
+                        let Microchip(value) = sampler.draw(&chips);
+                        if values.insert(value) {
+                            break value;
+                        }
+                    }
+                };
+                instructions.push(Instruction::Take { chip: Microchip(value), robot_id });
+            }
+            pending.push((i, 0));
+            pending.push((i, 1));
+        }
+        pending.shuffle(rng);
+        for (bin_id, &(from, output)) in pending.iter().enumerate() {
+            outputs[from][output] = Some(Output::Bin(bin_id as Id));
+        }
+        for (&robot_id, &[low, high]) in robot_ids.iter().zip(&outputs) {
+            // every output has been wired above.
+            instructions.push(Instruction::Donate { robot_id, low: low.unwrap(), high: high.unwrap() });
+        }
+        instructions.shuffle(rng);
+        instructions.iter().map(|instruction| format!("{}\n", instruction)).collect()
+    }
+}
+
+pub use balance_bots::*;
@@ -0,0 +1,655 @@
+extern crate aoc_core;
+extern crate balance_bots;
+#[macro_use]
+extern crate proptest;
+extern crate rand;
+extern crate report;
+#[macro_use]
+extern crate snapshot;
+
+use aoc_core::Span;
+use balance_bots::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use report::Input;
+
+// simple input parsing helper
+fn parse_instructions(input: String) -> Vec<Instruction> {
+    input.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+#[test]
+fn part1_example() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    let factory = Factory::build_from(&instructions);
+    assert_eq!(factory.robot_comparing(Microchip(2), Microchip(5)), Some(2));
+}
+
+#[test]
+fn part2_example() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    let factory = Factory::build_from(&instructions);
+    let bins: Vec<Id> = vec![0, 1, 2];
+    let microchips = factory.chips_in_bins(&bins);
+    let product: Value = microchips.iter().map(|chip| chip.value()).product();
+    assert_eq!(product, 5 * 2 * 3);
+}
+
+#[test]
+fn simulation_example() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    let simulation = Factory::build_from(&instructions).simulate().unwrap();
+    let comparisons: Vec<(Id, Value, Value)> = simulation.comparisons().iter()
+        .map(|c| (c.robot_id, c.low.value(), c.high.value()))
+        .collect();
+    assert_eq!(comparisons, vec![(2, 2, 5), (1, 2, 3), (0, 3, 5)]);
+    assert_eq!(simulation.bin_contents(0), &[Microchip(5)]);
+    assert_eq!(simulation.bin_contents(1), &[Microchip(2)]);
+    assert_eq!(simulation.bin_contents(2), &[Microchip(3)]);
+    assert_eq!(simulation.bin_contents(3), &[]);
+    assert_eq!(simulation.robot_comparing(Microchip(5), Microchip(2)), Some(2));
+}
+
+#[test]
+fn simulation_agrees_with_graph() {
+    let instructions = parse_instructions(include_str!("../input.txt").to_string());
+    let factory = Factory::build_from(&instructions);
+    let simulation = factory.simulate().unwrap();
+    // a pair can be compared by several robots (when a robot gives both its microchips to the
+    // same next robot), the graph yields the first of them.
+    for c in simulation.comparisons() {
+        let id = factory.robot_comparing(c.low, c.high).unwrap();
+        assert!(simulation.comparisons().iter().any(|other| other.robot_id == id && (other.low, other.high) == (c.low, c.high)));
+    }
+    let bins: Vec<Id> = vec![0, 1, 2];
+    let chips: Vec<Microchip> = bins.iter().map(|&id| simulation.bin_contents(id)[0]).collect();
+    assert_eq!(chips, factory.chips_in_bins(&bins));
+}
+
+#[test]
+fn bin_value() {
+    let instructions = parse_instructions(include_str!("../input.txt").to_string());
+    let factory = Factory::build_from(&instructions);
+    let product: Value = (0..3).map(|id| factory.bin_value(id).unwrap().value()).product();
+    assert_eq!(product, 133163);
+    assert_eq!(factory.bin_value(1000), None);
+}
+
+#[test]
+fn validation_errors() {
+    let build = |input: &str| Factory::try_build_from(&parse_instructions(input.to_string())).map(|_| ());
+    assert_eq!(build("value 1 goes to bot 0\nvalue 2 goes to bot 0\nvalue 3 goes to bot 0\n\
+                      bot 0 gives low to output 0 and high to output 1"),
+               Err(FactoryError::InputCount { robot_id: 0, count: 3 }));
+    assert_eq!(build("value 1 goes to bot 7\nvalue 2 goes to bot 7"), Err(FactoryError::MissingOutputs(7)));
+    assert_eq!(build("bot 3 gives low to output 0 and high to output 1"),
+               Err(FactoryError::InputCount { robot_id: 3, count: 0 }));
+    assert_eq!(build("value 1 goes to bot 0\nvalue 2 goes to bot 0\n\
+                      bot 0 gives low to output 0 and high to output 1\n\
+                      bot 0 gives low to output 2 and high to output 3"),
+               Err(FactoryError::DuplicateOutputs(0)));
+    assert_eq!(build("value 1 goes to bot 0\nvalue 2 goes to bot 0\n\
+                      bot 0 gives low to output 0 and high to output 0"),
+               Err(FactoryError::DuplicateBinInput(0)));
+    // bot 0 and bot 1 give their low microchip to each other.
+    assert_eq!(build("value 1 goes to bot 0\nvalue 2 goes to bot 1\n\
+                      bot 0 gives low to bot 1 and high to output 0\n\
+                      bot 1 gives low to bot 0 and high to output 1").unwrap_err().to_string(),
+               "robot 0 depends on its own outputs");
+    assert_eq!(FactoryError::InputCount { robot_id: 12, count: 3 }.to_string(), "robot 12 has 3 inputs");
+}
+
+#[test]
+fn factory_from_input() {
+    let factory: Factory = Input::new("<test>", include_str!("../input.txt").to_string()).build();
+    assert_eq!(factory.robot_comparing(Microchip(17), Microchip(61)), Some(161));
+    let input = Input::new("<test>", "value 1 goes to bot 7\nvalue 2 goes to bot 7\n".to_string());
+    let errors = input.build_errors::<Factory>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "invalid factory: robot 7 referenced but never given outputs");
+    let input = Input::new("<test>", "value 1 goes to bot 7\nvalue x goes to bot 7\nbot 7 gives\n".to_string());
+    let errors = input.build_errors::<Factory>();
+    assert_eq!(errors.iter().map(|e| e.line_no).collect::<Vec<_>>(), [Some(2), Some(3)]);
+}
+
+#[test]
+fn simulation_trace() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    let simulation = Factory::build_from(&instructions).simulate().unwrap();
+    let trace: Vec<String> = simulation.events().iter().map(|event| event.to_string()).collect();
+    assert_eq!(trace, vec![
+        "bot 1 takes value 3 from an input bin",
+        "bot 2 takes value 5 from an input bin",
+        "bot 2 takes value 2 from an input bin",
+        "bot 2 compares value 2 with value 5",
+        "bot 2 gives value 2 to bot 1",
+        "bot 2 gives value 5 to bot 0",
+        "bot 1 compares value 2 with value 3",
+        "bot 1 gives value 2 to output 1",
+        "bot 1 gives value 3 to bot 0",
+        "bot 0 compares value 3 with value 5",
+        "bot 0 gives value 3 to output 2",
+        "bot 0 gives value 5 to output 0",
+    ]);
+}
+
+#[test]
+fn duplicate_values() {
+    // two microchips have the value 3, only the one taken by bot 1 is compared with 9.
+    let input =
+        "value 3 goes to bot 0
+        value 7 goes to bot 0
+        value 3 goes to bot 1
+        value 9 goes to bot 1
+        bot 0 gives low to bot 2 and high to output 0
+        bot 1 gives low to bot 2 and high to output 1
+        bot 2 gives low to output 2 and high to output 3".to_string();
+    let instructions = parse_instructions(input);
+    let factory = Factory::build_from(&instructions);
+    assert_eq!(factory.robot_comparing(Microchip(3), Microchip(9)), Some(1));
+    assert_eq!(factory.robots_comparing(Microchip(9), Microchip(3)), vec![1]);
+    assert_eq!(factory.robots_comparing(Microchip(3), Microchip(3)), vec![2]);
+    assert_eq!(factory.robots_comparing(Microchip(3), Microchip(5)), Vec::<Id>::new());
+    assert_eq!(factory.bin_value(2), Some(Microchip(3)));
+    assert_eq!(factory.bin_value(3), Some(Microchip(3)));
+    let simulation = factory.simulate().unwrap();
+    assert_eq!(simulation.bin_contents(2), &[Microchip(3)]);
+}
+
+#[test]
+fn robots_comparing_agrees_with_simulation() {
+    let instructions = parse_instructions(include_str!("../input.txt").to_string());
+    let factory = Factory::build_from(&instructions);
+    let simulation = factory.simulate().unwrap();
+    for c in simulation.comparisons() {
+        let mut expected: Vec<Id> = simulation.comparisons().iter()
+            .filter(|other| (other.low, other.high) == (c.low, c.high))
+            .map(|other| other.robot_id)
+            .collect();
+        expected.sort();
+        assert_eq!(factory.robots_comparing(c.low, c.high), expected);
+    }
+}
+
+#[test]
+fn comparisons() {
+    let instructions = parse_instructions(include_str!("../input.txt").to_string());
+    let factory = Factory::build_from(&instructions);
+    let comparisons: Vec<(Id, Microchip2)> = factory.comparisons().collect();
+    let mut simulated: Vec<(Id, Microchip2)> = factory.simulate().unwrap().comparisons().iter()
+        .map(|c| (c.robot_id, Microchip2::new(c.low, c.high)))
+        .collect();
+    simulated.sort_by_key(|&(id, _)| id);
+    assert_eq!(comparisons, simulated);
+    let (m0, m1) = (Microchip(17), Microchip(61));
+    let found = factory.comparisons().find(|&(_, pair)| pair == Microchip2::new(m0, m1));
+    assert_eq!(found.map(|(id, _)| id), Some(161));
+}
+
+#[test]
+fn explain() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    let mut events: Vec<String> = Vec::new();
+    Factory::build_from(&instructions).explain(&mut events);
+    assert_eq!(events, [
+        r#"{"event":"comparison","robot":0,"low":3,"high":5}"#,
+        r#"{"event":"comparison","robot":1,"low":2,"high":3}"#,
+        r#"{"event":"comparison","robot":2,"low":2,"high":5}"#,
+    ]);
+}
+
+#[test]
+fn incremental_wiring() {
+    let instructions = parse_instructions(include_str!("../input.txt").to_string());
+    let mut factory = Factory::new();
+    let (last, rest) = instructions.split_last().unwrap();
+    for instruction in rest {
+        assert_eq!(factory.apply(instruction.clone()), Ok(false));
+    }
+    assert!(!factory.is_resolvable());
+    assert_eq!(factory.apply(last.clone()), Ok(true));
+    assert!(factory.is_resolvable());
+    assert_eq!(factory.robot_comparing(Microchip(17), Microchip(61)), Some(161));
+
+    // errors leave the factory unchanged.
+    let mut factory = Factory::new();
+    let take = |value, robot_id| Instruction::Take { chip: Microchip(value), robot_id };
+    assert_eq!(factory.apply(take(1, 0)), Ok(false));
+    assert_eq!(factory.apply(take(2, 0)), Ok(false));
+    assert_eq!(factory.apply(take(3, 0)), Ok(false)); // the robot capacity is not known yet.
+    let donate = Instruction::Donate { robot_id: 0, low: Output::Bin(0), high: Output::Bin(1) };
+    assert_eq!(factory.apply(donate.clone()), Err(FactoryError::InputCount { robot_id: 0, count: 3 }));
+
+    let mut factory = Factory::new();
+    assert_eq!(factory.apply(take(1, 0)), Ok(false));
+    assert_eq!(factory.apply(donate.clone()), Ok(false));
+    assert_eq!(factory.apply(take(2, 0)), Ok(true));
+    assert_eq!(factory.apply(take(3, 0)), Err(FactoryError::InputCount { robot_id: 0, count: 3 }));
+    assert_eq!(factory.bin_value(0), Some(Microchip(1)));
+    assert_eq!(factory.apply(donate), Err(FactoryError::DuplicateOutputs(0)));
+
+    // closing a cycle is only found once every robot is complete, and then rolled back.
+    let wired = || {
+        let mut factory = Factory::new();
+        for instruction in parse_instructions("value 1 goes to bot 0\nvalue 2 goes to bot 1\n\
+                                               bot 0 gives low to bot 1 and high to output 0".to_string()) {
+            assert_eq!(factory.apply(instruction), Ok(false));
+        }
+        factory
+    };
+    let mut factory = wired();
+    let cycle = Instruction::Donate { robot_id: 1, low: Output::Robot(0), high: Output::Bin(1) };
+    assert_eq!(factory.apply(cycle), Err(FactoryError::Cycle(0)));
+    assert!(factory == wired());
+    // the robot 1 outputs can then be given again.
+    let donate = Instruction::Donate { robot_id: 1, low: Output::Bin(2), high: Output::Bin(1) };
+    assert_eq!(factory.apply(donate), Ok(false));
+}
+
+#[test]
+fn partly_built_queries() {
+    // the robot 0 still waits for its second microchip.
+    let mut factory = Factory::new();
+    for instruction in parse_instructions("value 5 goes to bot 0\nbot 0 gives low to output 0 and high to bot 1\n\
+                                           value 3 goes to bot 1\nbot 1 gives low to output 1 and high to output 2"
+                                          .to_string()) {
+        assert_eq!(factory.apply(instruction), Ok(false));
+    }
+    let missing = FactoryError::InputCount { robot_id: 0, count: 1 };
+    assert_eq!(factory.robot_comparing(Microchip(3), Microchip(5)), None);
+    assert!(factory.robots_comparing(Microchip(3), Microchip(5)).is_empty());
+    assert_eq!(factory.comparisons().count(), 0);
+    assert_eq!(factory.resolve().err(), Some(missing));
+    assert!(factory.chips_in_bins(&[0, 1]).is_empty());
+    assert_eq!(factory.bin_value(0), None);
+    assert!(factory.bin_contents(1).is_empty());
+    let rewire = Instruction::Donate { robot_id: 1, low: Output::Bin(2), high: Output::Bin(1) };
+    assert_eq!(factory.what_if(rewire).err(), Some(missing));
+    assert_eq!(factory.simulate().err(), Some(missing));
+    assert_eq!(factory.try_simulate().err(), Some(SimulationError::Invalid(missing)));
+    assert_eq!(factory.stepper().err(), Some(missing));
+    // once complete, the queries are answered.
+    assert_eq!(factory.apply(Instruction::Take { chip: Microchip(2), robot_id: 0 }), Ok(true));
+    assert_eq!(factory.robot_comparing(Microchip(3), Microchip(5)), Some(1));
+    assert_eq!(factory.bin_value(0), Some(Microchip(2)));
+    assert_eq!(factory.simulate().unwrap().bin_contents(0), &[Microchip(2)]);
+
+    // the robot 1 is given a microchip but nothing else yet.
+    let mut factory = Factory::new();
+    for instruction in parse_instructions("value 5 goes to bot 0\nvalue 3 goes to bot 0\n\
+                                           bot 0 gives low to bot 1 and high to output 0".to_string()) {
+        assert_eq!(factory.apply(instruction), Ok(false));
+    }
+    let missing = FactoryError::MissingOutputs(1);
+    assert_eq!(factory.simulate().err(), Some(missing));
+    assert_eq!(factory.try_simulate().err(), Some(SimulationError::Invalid(missing)));
+    assert_eq!(factory.stepper().err(), Some(missing));
+    assert_eq!(SimulationError::Invalid(missing).to_string(), "invalid factory: robot 1 referenced but never given outputs");
+}
+
+#[test]
+fn resolved_json() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    let resolved = Factory::build_from(&instructions).resolve().unwrap();
+    assert_eq!(resolved.robots[2], ResolvedRobot {
+        id: 2,
+        compares: Microchip2::new(Microchip(5), Microchip(2)),
+        low_to: Output::Robot(1),
+        high_to: Output::Robot(0),
+        middle: Vec::new(),
+        middle_to: Vec::new(),
+    });
+    let json: serde_json::Value = serde_json::from_str(&resolved.to_json()).unwrap();
+    assert_eq!(json["robots"][0], serde_json::json!({
+        "id": 0,
+        "compares": { "low": 3, "high": 5 },
+        "low_to": { "bin": 2 },
+        "high_to": { "bin": 0 },
+    }));
+    assert_eq!(json["bins"], serde_json::json!([
+        { "id": 0, "chip": 5 }, { "id": 1, "chip": 2 }, { "id": 2, "chip": 3 },
+    ]));
+}
+
+#[test]
+fn sorting_robots() {
+    let instruction: Instruction = "bot 0 gives low to output 0, middle to bot 1, middle to output 4 and high to output 2".parse().unwrap();
+    match instruction {
+        Instruction::Sort { robot_id, outputs } => {
+            assert_eq!(robot_id, 0);
+            assert_eq!(outputs, vec![Output::Bin(0), Output::Robot(1), Output::Bin(4), Output::Bin(2)]);
+        },
+        _ => panic!("expected a Sort instruction, got {:?}", instruction),
+    }
+
+    let input =
+        "value 5 goes to bot 0
+        value 1 goes to bot 0
+        bot 0 gives low to output 0, middle to bot 1 and high to output 2
+        value 3 goes to bot 0
+        value 7 goes to bot 1
+        bot 1 gives low to output 1 and high to output 3".to_string();
+    let factory = Factory::build_from(&parse_instructions(input));
+    let values: Vec<Value> = factory.chips_in_bins(&[0, 1, 2, 3]).iter().map(|chip| chip.value()).collect();
+    assert_eq!(values, vec![1, 3, 5, 7]);
+    assert_eq!(factory.robot_comparing(Microchip(3), Microchip(7)), Some(1));
+    assert_eq!(factory.robot_comparing(Microchip(1), Microchip(5)), None);
+    let resolved = factory.resolve().unwrap();
+    assert_eq!(resolved.robots[0].middle, vec![Microchip(3)]);
+    assert_eq!(resolved.robots[0].middle_to, vec![Output::Robot(1)]);
+
+    let simulation = factory.simulate().unwrap();
+    assert_eq!(simulation.bin_contents(2), &[Microchip(5)]);
+    let sorted = simulation.events().iter().find(|event| matches!(*event, Event::Compare(Comparison { robot_id: 0, .. })));
+    assert_eq!(sorted.unwrap().to_string(), "bot 0 sorts values 1, 3 and 5");
+}
+
+#[test]
+fn provenance() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2
+        value 7 goes to bot 3
+        value 11 goes to bot 3
+        bot 3 gives low to output 3 and high to output 4".to_string();
+    let factory = Factory::build_from(&parse_instructions(input));
+    let values = |provenance: Provenance| -> (Vec<Value>, Vec<Id>) {
+        (provenance.chips.iter().map(|chip| chip.value()).collect(), provenance.robots)
+    };
+    assert_eq!(factory.provenance(Output::Bin(1)).map(values), Some((vec![2, 3, 5], vec![1, 2])));
+    assert_eq!(factory.provenance(Output::Bin(0)).map(values), Some((vec![2, 3, 5], vec![0, 1, 2])));
+    assert_eq!(factory.provenance(Output::Bin(4)).map(values), Some((vec![7, 11], vec![3])));
+    assert_eq!(factory.provenance(Output::Robot(2)).map(values), Some((vec![2, 5], vec![2])));
+    assert_eq!(factory.provenance(Output::Bin(5)), None);
+    assert_eq!(factory.provenance(Output::Robot(4)), None);
+
+    // nothing is known of the robot 1 but its first microchip.
+    let mut factory = Factory::new();
+    for instruction in parse_instructions("value 5 goes to bot 0\nbot 0 gives low to bot 1 and high to output 0"
+                                          .to_string()) {
+        assert_eq!(factory.apply(instruction), Ok(false));
+    }
+    assert_eq!(factory.provenance(Output::Bin(0)), None);
+    assert_eq!(factory.provenance(Output::Robot(0)), None);
+}
+
+#[test]
+fn stepper() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let factory = Factory::build_from(&parse_instructions(input));
+    let mut stepper = factory.stepper().unwrap();
+    // the three microchips are taken from the input bins first.
+    for _ in 0..3 {
+        assert!(matches!(stepper.step(), Some(Event::Take { .. })));
+    }
+    assert_eq!(stepper.held_by(2), &[Microchip(5), Microchip(2)]);
+    assert_eq!(stepper.held_by(1), &[Microchip(3)]);
+
+    let breakpoints = ["compare 0".parse().unwrap()];
+    let event = stepper.run_until(&breakpoints).unwrap();
+    assert_eq!(event.to_string(), "bot 0 compares value 3 with value 5");
+    assert_eq!(stepper.held_by(0), &[Microchip(5), Microchip(3)]);
+    assert_eq!(stepper.held_by(2), &[]);
+    assert_eq!(stepper.bin_contents(1), &[Microchip(2)]);
+    assert_eq!(stepper.bin_contents(0), &[]);
+    assert_eq!(stepper.run_until(&breakpoints), None);
+    assert_eq!(stepper.bin_contents(0), &[Microchip(5)]);
+
+    // chip breakpoints stop on every event involving the microchip.
+    let mut stepper = factory.stepper().unwrap();
+    let breakpoints = [Breakpoint::Chip(2)];
+    let mut stops = Vec::new();
+    while let Some(event) = stepper.run_until(&breakpoints) {
+        stops.push(event.to_string());
+    }
+    assert_eq!(stops, vec![
+        "bot 2 takes value 2 from an input bin",
+        "bot 2 compares value 2 with value 5",
+        "bot 2 gives value 2 to bot 1",
+        "bot 1 compares value 2 with value 3",
+        "bot 1 gives value 2 to output 1",
+    ]);
+    assert!("break 12".parse::<Breakpoint>().is_err());
+    assert!("compare twelve".parse::<Breakpoint>().is_err());
+}
+
+#[test]
+fn bin_capacities() {
+    let input = |capacity: &str| format!(
+        "value 5 goes to bot 0
+        value 3 goes to bot 0
+        bot 0 gives low to output 0 and high to bot 1
+        value 7 goes to bot 1
+        bot 1 gives low to output 0 and high to output 1
+        {}", capacity).trim_end().to_string();
+    // the capacity may come after the robots sharing the bin.
+    let factory = Factory::build_from(&parse_instructions(input("output 0 holds 2")));
+    let simulation = factory.try_simulate().unwrap();
+    assert_eq!(simulation.bin_contents(0), &[Microchip(3), Microchip(5)]);
+    assert_eq!(factory.bin_contents(0), &[Microchip(3), Microchip(5)]);
+    assert_eq!((factory.bin_value(0), factory.bin_value(1)), (None, Some(Microchip(7))));
+    assert_eq!(factory.chips_in_bins(&[0, 1]), vec![Microchip(3), Microchip(5), Microchip(7)]);
+    let provenance = factory.provenance(Output::Bin(0)).unwrap();
+    assert_eq!((provenance.chips.len(), provenance.robots), (3, vec![0, 1]));
+    assert_eq!(factory.what_if("bot 1 gives low to output 1 and high to output 0".parse().unwrap()),
+               Err(FactoryError::SharedBin(0)));
+    assert_eq!(factory.what_if("output 1 holds 0".parse().unwrap()), Err(FactoryError::CapacityChange(1)));
+    assert_eq!(FactoryError::CapacityChange(1).to_string(), "output 1 capacity only matters to the simulation");
+
+    // overflowing the capacity stops the simulation before the microchip is given.
+    let factory = Factory::build_from(&parse_instructions(input("output 0 holds 1")));
+    let overflow = BinOverflow { bin_id: 0, capacity: 1, robot_id: 1, chip: Microchip(5) };
+    assert_eq!(factory.try_simulate().unwrap_err(), SimulationError::Overflow(overflow));
+    assert_eq!(overflow.to_string(), "bot 1 gives value 5 to output 0 already holding its 1 microchip");
+    let mut stepper = factory.stepper().unwrap();
+    while let Ok(Some(_)) = stepper.try_step() {}
+    assert_eq!(stepper.bin_contents(0), &[Microchip(3)]);
+    assert_eq!(stepper.held_by(1), &[Microchip(7), Microchip(5)]);
+    // the unchecked simulation ignores the capacities.
+    assert_eq!(factory.simulate().unwrap().bin_contents(0), &[Microchip(3), Microchip(5)]);
+
+    let build = |capacity| Factory::try_build_from(&parse_instructions(input(capacity))).err();
+    assert_eq!(build(""), Some(FactoryError::DuplicateBinInput(0)));
+    assert_eq!(build("output 0 holds 2\noutput 0 holds 3"), Some(FactoryError::DuplicateCapacity(0)));
+    assert!("output 1 holds 99999999999999999999999".parse::<Instruction>().is_err());
+}
+
+#[test]
+fn factory_builder() {
+    let factory = FactoryBuilder::new()
+        .give(Microchip(5), 2)
+        .wire(2, Output::Robot(1), Output::Robot(0))
+        .give(Microchip(3), 1)
+        .wire(1, Output::Bin(1), Output::Robot(0))
+        .wire(0, Output::Bin(2), Output::Bin(0))
+        .give(Microchip(2), 2)
+        .build()
+        .unwrap();
+    assert_eq!(factory.robot_comparing(Microchip(2), Microchip(5)), Some(2));
+    assert_eq!(factory.chips_in_bins(&[0, 1, 2]), vec![Microchip(5), Microchip(2), Microchip(3)]);
+
+    // generated factories: a chain of robots each passing its higher-value microchip along.
+    let mut builder = FactoryBuilder::new();
+    builder.give(Microchip(0), 0);
+    for id in 0..100 {
+        builder.give(Microchip(id), id).wire(id, Output::Bin(id), Output::Robot(id + 1));
+    }
+    builder.give(Microchip(100), 100).sort(100, vec![Output::Bin(100), Output::Bin(101)]);
+    let factory = builder.build().unwrap();
+    assert_eq!(factory.bin_value(101), Some(Microchip(100)));
+    assert_eq!(factory.robot_comparing(Microchip(41), Microchip(42)), Some(42));
+
+    let mut builder = FactoryBuilder::new();
+    builder.give(Microchip(1), 0).wire(0, Output::Bin(0), Output::Bin(1));
+    assert_eq!(builder.build().err(), Some(FactoryError::InputCount { robot_id: 0, count: 1 }));
+    builder.wire(1, Output::Bin(1), Output::Robot(0));
+    assert_eq!(builder.build().err(), Some(FactoryError::DuplicateBinInput(1)));
+}
+
+proptest! {
+    #[test]
+    fn instruction_round_trip(instruction: Instruction) {
+        prop_assert_eq!(instruction.to_string().parse::<Instruction>(), Ok(instruction));
+    }
+
+    #[test]
+    fn instruction_parse_never_panics(s in "value [0-9]{1,12} goes to bot [0-9]{1,12}|bot [0-9]{1,12} gives low to (bot|output) [0-9]{1,12}(, middle to (bot|output) [0-9]{1,12})* and high to (bot|output) [0-9]{1,12}|\\PC*") {
+        let _ = s.parse::<Instruction>();
+    }
+}
+
+#[test]
+fn instruction_numbers_out_of_range() {
+    assert!("value 99999999999 goes to bot 1".parse::<Instruction>().is_err());
+    assert!("bot 1 gives low to output 99999999999 and high to bot 2".parse::<Instruction>().is_err());
+    assert!("bot 1 gives low to output 0, middle to bot ٣ and high to bot 2".parse::<Instruction>().is_err());
+}
+
+#[test]
+fn instruction_parse_error_spans() {
+    let span = |s: &str| s.parse::<Instruction>().unwrap_err().span;
+    assert_eq!(span("value 99999999999 goes to bot 1"), Span::new(1, 7, 11));
+    assert_eq!(span("bot 1 gives low to output 0, middle to bot ٣ and high to bot 2"), Span::new(1, 44, 1));
+    assert_eq!(span("bot 1 gives low to output 0 and high to output ٣"), Span::new(1, 48, 1));
+    assert_eq!(span("bot 1 takes everything"), Span::new(1, 1, 22));
+}
+
+#[test]
+fn json_snapshot() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let instructions = parse_instructions(input);
+    assert_snapshot!("resolved", Factory::build_from(&instructions).resolve().unwrap().to_json() + "\n");
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 210);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 210));
+    let instructions = parse_instructions(input);
+    let factory = Factory::try_build_from(&instructions).unwrap();
+    assert!(factory.is_resolvable());
+    assert!(factory.robot_comparing(Microchip(17), Microchip(61)).is_some());
+    assert!((0..3).all(|id| factory.bin_value(id).is_some()));
+    assert_eq!(factory.comparisons().count(), 210);
+}
+
+#[test]
+fn what_if_example() {
+    let input =
+        "value 5 goes to bot 2
+        bot 2 gives low to bot 1 and high to bot 0
+        value 3 goes to bot 1
+        bot 1 gives low to output 1 and high to bot 0
+        bot 0 gives low to output 2 and high to output 0
+        value 2 goes to bot 2".to_string();
+    let factory = Factory::build_from(&parse_instructions(input));
+    let what_if = |s: &str| factory.what_if(s.parse().unwrap());
+    let pair = |a, b| Microchip2::new(Microchip(a), Microchip(b));
+    let changes = what_if("bot 2 gives low to bot 0 and high to bot 1").unwrap();
+    assert_eq!(changes.robots, vec![
+        RobotChange { id: 0, before: pair(3, 5), after: pair(2, 5) },
+        RobotChange { id: 1, before: pair(2, 3), after: pair(3, 5) },
+    ]);
+    assert_eq!(changes.bins, vec![
+        BinChange { id: 1, before: Some(Microchip(2)), after: Some(Microchip(3)) },
+        BinChange { id: 2, before: Some(Microchip(3)), after: Some(Microchip(2)) },
+    ]);
+    let changes = what_if("bot 0 gives low to output 2 and high to output 7").unwrap();
+    assert!(changes.robots.is_empty());
+    assert_eq!(changes.bins, vec![
+        BinChange { id: 0, before: Some(Microchip(5)), after: None },
+        BinChange { id: 7, before: None, after: Some(Microchip(5)) },
+    ]);
+    assert!(what_if("bot 1 gives low to output 1 and high to bot 0").unwrap().is_empty());
+    // the factory is left unchanged.
+    assert_eq!(factory.bin_value(0), Some(Microchip(5)));
+    assert_eq!(what_if("bot 1 gives low to output 1 and high to output 7"),
+               Err(FactoryError::InputCount { robot_id: 0, count: 1 }));
+    assert_eq!(what_if("bot 1 gives low to output 2 and high to bot 0"), Err(FactoryError::DuplicateBinInput(2)));
+    assert_eq!(what_if("bot 9 gives low to output 1 and high to bot 0"),
+               Err(FactoryError::InputCount { robot_id: 9, count: 0 }));
+    assert_eq!(what_if("value 7 goes to bot 1"), Err(FactoryError::InputCount { robot_id: 1, count: 3 }));
+}
+
+#[test]
+fn what_if_agrees_with_rebuild() {
+    let instructions = parse_instructions(generate_input(&mut StdRng::seed_from_u64(7), 210));
+    let factory = Factory::build_from(&instructions);
+    let before = factory.resolve().unwrap();
+    for (i, instruction) in instructions.iter().enumerate() {
+        let swapped = match *instruction {
+            Instruction::Donate { robot_id, low, high } => Instruction::Donate { robot_id, low: high, high: low },
+            _ => continue,
+        };
+        let mut modified = instructions.clone();
+        modified[i] = swapped.clone();
+        let after = Factory::build_from(&modified).resolve().unwrap();
+        let robots: Vec<RobotChange> = before.robots.iter().zip(after.robots.iter())
+            .filter(|&(a, b)| a.compares != b.compares)
+            .map(|(a, b)| RobotChange { id: a.id, before: a.compares, after: b.compares })
+            .collect();
+        let bins: Vec<BinChange> = before.bins.iter().zip(after.bins.iter())
+            .filter(|&(a, b)| a.chip != b.chip)
+            .map(|(a, b)| BinChange { id: a.id, before: Some(a.chip), after: Some(b.chip) })
+            .collect();
+        assert_eq!(factory.what_if(swapped).unwrap(), WhatIf { robots, bins }, "{}", instruction);
+    }
+}
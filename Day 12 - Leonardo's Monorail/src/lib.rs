@@ -0,0 +1,20 @@
+//! Day 12: Leonardo's Monorail, running the assembunny monorail password program.
+
+extern crate aoc_core;
+extern crate assembunny;
+
+pub mod leonardos_monorail {
+    use ::aoc_core::{Cancel, Cancelled};
+    use ::assembunny::{Instruction, Register, Registers, Value, Vm};
+
+    /// Run the monorail password `program` with the register c initialized to `ignition`
+    /// (zero for the first part, one for the second), returns the final register file or
+    /// `Cancelled` when `cancel` was cancelled before the program is over.
+    pub fn run(program: &[Instruction], ignition: Value, cancel: &Cancel) -> Result<Registers, Cancelled> {
+        let mut vm = Vm::new(program.to_vec());
+        vm.registers_mut()[Register::C] = ignition;
+        vm.run_cancellable(cancel).copied()
+    }
+}
+
+pub use leonardos_monorail::*;
@@ -0,0 +1,39 @@
+extern crate aoc_cli;
+extern crate assembunny;
+extern crate leonardos_monorail;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+
+
+use aoc_cli::Cli;
+use assembunny::Register;
+use leonardos_monorail::*;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    // NOTE: the puzzle inputs are hand-written programs, there is nothing realistic to generate.
+    let cli = Cli::start();
+    // acquire the puzzle input, one instruction per line.
+    let input = cli.input();
+    if cli.validating() {
+        input.validate(input.line_errors::<assembunny::Instruction>());
+    }
+    let program: Vec<assembunny::Instruction> = phase!("parse").in_scope(|| input.parse_lines());
+
+    // part 1
+    let registers = cli.finish(phase!("part1").in_scope(|| run(&program, 0, cli.cancel())));
+    cli.answer(1, registers[Register::A], format_args!("After execution the register a holds {} ({}).",
+                                                       registers[Register::A], registers));
+
+    // part 2
+    let registers = cli.finish(phase!("part2").in_scope(|| run(&program, 1, cli.cancel())));
+    cli.answer(2, registers[Register::A], format_args!("With the ignition key, the register a holds {} ({}).",
+                                                       registers[Register::A], registers));
+}
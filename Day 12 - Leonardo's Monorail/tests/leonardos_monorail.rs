@@ -0,0 +1,36 @@
+extern crate aoc_core;
+extern crate assembunny;
+extern crate leonardos_monorail;
+
+use aoc_core::Cancel;
+use assembunny::Register;
+use leonardos_monorail::*;
+
+#[test]
+fn part1_example() {
+    let program = assembunny::parse_program(
+        "cpy 41 a
+        inc a
+        inc a
+        dec a
+        jnz a 2
+        dec a").unwrap();
+    assert_eq!(run(&program, 0, &Cancel::new()).unwrap()[Register::A], 42);
+}
+
+#[test]
+fn part2_ignition() {
+    // copies c into a, so the second part only differs by the ignition key.
+    let program = assembunny::parse_program("cpy c a").unwrap();
+    assert_eq!(run(&program, 0, &Cancel::new()).unwrap()[Register::A], 0);
+    assert_eq!(run(&program, 1, &Cancel::new()).unwrap()[Register::A], 1);
+}
+
+#[test]
+fn cancelled() {
+    // loops forever, only a cancellation stops it.
+    let program = assembunny::parse_program("inc a\njnz 1 -1").unwrap();
+    let cancel = Cancel::new();
+    cancel.cancel();
+    assert!(run(&program, 0, &cancel).is_err());
+}
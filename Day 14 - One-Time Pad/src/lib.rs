@@ -0,0 +1,175 @@
+//! Day 14: One-Time Pad, finding the keys indices of the one-time pad from the salted, and
+//! optionally stretched, MD5 hashes.
+
+extern crate hashing;
+extern crate rand;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[macro_use]
+extern crate tracing;
+
+pub mod one_time_pad {
+    use ::hashing::{to_decimal, to_hex, ErrorStack, Md5};
+    use ::rand::Rng;
+    use ::std::collections::VecDeque;
+
+    /// The number of additional hashings of the key stretching (for the second part).
+    pub const STRETCH: usize = 2016;
+
+    /// How far after a triplet the matching quintuple may be found.
+    const LOOKAHEAD: usize = 1000;
+
+    /// How many hashes are computed at once when the window needs more.
+    const BATCH: u64 = 1024;
+
+    /// What matters about a hash to find keys: the character of its first triplet (if any) and
+    /// the characters of its quintuples as a bitmask.
+    #[derive(Copy, Clone, Debug, Default)]
+    struct Features {
+        triplet: Option<u8>,
+        quintuples: u16,
+    }
+
+    impl Features {
+        /// Extract the features of the given hexadecimal representation of a hash.
+        fn of(hex: &[u8; 32]) -> Features {
+            let mut features = Features::default();
+            for (i, window) in hex.windows(3).enumerate() {
+                if window[0] != window[1] || window[1] != window[2] {
+                    continue;
+                }
+                let digit = hex_value(window[0]);
+                if features.triplet.is_none() {
+                    features.triplet = Some(digit);
+                }
+                if hex[i..].len() >= 5 && hex[i + 3] == window[0] && hex[i + 4] == window[0] {
+                    features.quintuples |= 1 << digit;
+                }
+            }
+            features
+        }
+
+        /// Returns `true` if this hash has a quintuple of the given digit, `false` otherwise.
+        fn has_quintuple(&self, digit: u8) -> bool {
+            self.quintuples & (1 << digit) != 0
+        }
+    }
+
+    /// Returns the value of the given (lowercase) hexadecimal digit.
+    fn hex_value(digit: u8) -> u8 {
+        match digit {
+            b'0'..=b'9' => digit - b'0',
+            _ => digit - b'a' + 10,
+        }
+    }
+
+    /// Returns the hexadecimal representation of the hash of `salt` followed by `index`, hashed
+    /// again `stretch` times.
+    pub fn stretched_hash(md5: &mut Md5, salt: &[u8], index: u64, stretch: usize) -> Result<[u8; 32], ErrorStack> {
+        let mut digits = [0; 20];
+        let mut hex = to_hex(&md5.digest(&[salt, to_decimal(index, &mut digits)])?);
+        for _ in 0..stretch {
+            hex = to_hex(&md5.digest(&[&hex])?);
+        }
+        Ok(hex)
+    }
+
+    /// Iterator over the indices producing keys, or the OpenSSL error that prevented hashing.
+    ///
+    /// Every hash is computed once and only its `Features` are kept in a sliding window
+    /// spanning the indices where a quintuple confirming the current triplet may be found.
+    pub struct KeyFinder {
+        salt: Vec<u8>,
+        stretch: usize,
+        // the index of the first hash in the window.
+        index: u64,
+        window: VecDeque<Features>,
+        #[cfg(not(feature = "parallel"))]
+        md5: Md5,
+    }
+
+    impl KeyFinder {
+        /// Create a new `KeyFinder` for the given `salt`, using `stretch` additional hashings
+        /// (zero for the first part, `STRETCH` for the second).
+        pub fn new(salt: &str, stretch: usize) -> Result<KeyFinder, ErrorStack> {
+            Ok(KeyFinder {
+                salt: salt.as_bytes().to_vec(),
+                stretch,
+                index: 0,
+                window: VecDeque::new(),
+                #[cfg(not(feature = "parallel"))]
+                md5: Md5::new()?,
+            })
+        }
+
+        /// Make sure that the window holds at least `len` hashes.
+        fn fill(&mut self, len: usize) -> Result<(), ErrorStack> {
+            while self.window.len() < len {
+                let start = self.index + self.window.len() as u64;
+                let batch = info_span!("hash_batch", start, stretch = self.stretch).in_scope(|| self.batch(start))?;
+                self.window.extend(batch);
+            }
+            Ok(())
+        }
+
+        /// Returns the features of the `BATCH` hashes starting at index `start`.
+        #[cfg(not(feature = "parallel"))]
+        fn batch(&mut self, start: u64) -> Result<Vec<Features>, ErrorStack> {
+            (start..start + BATCH).map(|index| {
+                stretched_hash(&mut self.md5, &self.salt, index, self.stretch).map(|hex| Features::of(&hex))
+            }).collect()
+        }
+
+        /// Returns the features of the `BATCH` hashes starting at index `start`.
+        #[cfg(feature = "parallel")]
+        fn batch(&mut self, start: u64) -> Result<Vec<Features>, ErrorStack> {
+            use ::rayon::prelude::*;
+
+            let (salt, stretch) = (&self.salt, self.stretch);
+            // NOTE: `Md5` can't be shared, so each worker gets its own.
+            (start..start + BATCH).into_par_iter().map_init(Md5::new, |md5, index| {
+                let md5 = md5.as_mut().map_err(|e| e.clone())?;
+                stretched_hash(md5, salt, index, stretch).map(|hex| Features::of(&hex))
+            }).collect()
+        }
+    }
+
+    impl Iterator for KeyFinder {
+        type Item = Result<u64, ErrorStack>;
+
+        /// Find the index of the next key.
+        ///
+        /// > A hash is a key only if:
+        /// > - It contains three of the same character in a row, like 777. Only consider the
+        /// >   first such triplet in a hash.
+        /// > - One of the next 1000 hashes in the stream contains that same character five times
+        /// >   in a row, like 77777.
+        fn next(&mut self) -> Option<Result<u64, ErrorStack>> {
+            loop {
+                if let Err(e) = self.fill(LOOKAHEAD + 1) {
+                    return Some(Err(e));
+                }
+                let features = self.window.pop_front()?;
+                let index = self.index;
+                self.index += 1;
+                if let Some(digit) = features.triplet {
+                    if self.window.iter().take(LOOKAHEAD).any(|next| next.has_quintuple(digit)) {
+                        return Some(Ok(index));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a random salt, formatted like the puzzle input.
+    ///
+    /// The salt is always eight letters long like the puzzle input ones, `size` is ignored as
+    /// the time spent finding the keys doesn't depend on it.
+    pub fn generate_input<R: Rng>(rng: &mut R, _size: usize) -> String {
+        let mut input: String = (0..8).map(|_| char::from(rng.gen_range(b'a', b'z' + 1))).collect();
+        input.push('\n');
+        input
+    }
+}
+
+pub use one_time_pad::*;
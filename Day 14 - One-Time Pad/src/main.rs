@@ -0,0 +1,51 @@
+extern crate aoc_cli;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+extern crate one_time_pad;
+#[macro_use]
+extern crate profiling;
+extern crate report;
+#[cfg(feature = "parallel")]
+extern crate threads;
+
+
+use aoc_cli::Cli;
+use one_time_pad::*;
+use report::InputError;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    let cli = Cli::start_with_generator(generate_input);
+    #[cfg(feature = "parallel")]
+    threads::configure("day14");
+    // acquire the puzzle input, we only need the first line.
+    let input = cli.input();
+    if cli.validating() {
+        let empty = input.first_line().trim().is_empty();
+        input.validate(if empty { Some(InputError::at(1, input.first_line(), "empty salt")) } else { None });
+    }
+    let salt = input.first_line().trim();
+    let nth_key = |stretch| {
+        let keys = KeyFinder::new(salt, stretch).unwrap_or_else(|e| report::exit(e));
+        // NOTE: the time is checked between two keys, the grace period covers the search of one.
+        let mut keys = keys.take_while(|_| !cli.cancel().is_cancelled());
+        match keys.nth(63) {
+            Some(index) => index.unwrap_or_else(|e| report::exit(e)),
+            None if cli.cancel().is_cancelled() => cli.timed_out(),
+            None => report::exit("key generation failure"),
+        }
+    };
+
+    // part 1
+    let index = phase!("part1").in_scope(|| nth_key(0));
+    cli.answer(1, index, format_args!("The index producing the 64th key is {}.", index));
+
+    // part 2
+    let index = phase!("part2").in_scope(|| nth_key(STRETCH));
+    cli.answer(2, index, format_args!("Using key stretching, the index producing the 64th key is {}.", index));
+}
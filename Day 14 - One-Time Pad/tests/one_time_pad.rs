@@ -0,0 +1,31 @@
+extern crate one_time_pad;
+extern crate rand;
+
+use one_time_pad::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn part1_example() {
+    let mut keys = KeyFinder::new("abc", 0).unwrap();
+    assert_eq!(keys.next().unwrap().unwrap(), 39);
+    assert_eq!(keys.next().unwrap().unwrap(), 92);
+    assert_eq!(keys.nth(61).unwrap().unwrap(), 22728);
+}
+
+#[test]
+fn part2_example() {
+    let mut md5 = hashing::Md5::new().unwrap();
+    let hex = stretched_hash(&mut md5, b"abc", 0, STRETCH).unwrap();
+    assert_eq!(&hex, b"a107ff634856bb300138cac6568c0f24");
+    let mut keys = KeyFinder::new("abc", STRETCH).unwrap();
+    assert_eq!(keys.next().unwrap().unwrap(), 10);
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 0);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 0));
+    assert_eq!(input.len(), 9);
+    assert!(input.trim_end().bytes().all(|b| b.is_ascii_lowercase()));
+}
@@ -0,0 +1,82 @@
+//! Day 19: An Elephant Named Joseph, finding which Elf gets all the presents.
+
+extern crate rand;
+
+pub mod an_elephant_named_joseph {
+    use ::rand::Rng;
+
+    /// Which present an Elf takes on its turn.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Rule {
+        /// > Each Elf takes the presents from the Elf directly to their left.
+        Left,
+        /// > Each Elf takes the presents from the Elf directly across the circle. If two Elves
+        /// > are across the circle, the one on the left (from the perspective of the stealer)
+        /// > is stolen from.
+        Across,
+    }
+
+    /// Returns the position (starting at 1) of the Elf getting all the presents when there are
+    /// `count` Elves in the circle.
+    ///
+    /// Both rules have a closed form, see `simulate()` for the slow but obvious way.
+    pub fn winner(count: usize, rule: Rule) -> usize {
+        assert!(count > 0, "there must be at least one Elf");
+        match rule {
+            // The Josephus problem: every time the circle size is a power of two the Elf
+            // starting the round wins, and each Elf leaving the circle moves the winner two
+            // positions further.
+            Rule::Left => {
+                let power = 1 << (usize::BITS - 1 - count.leading_zeros());
+                2 * (count - power) + 1
+            },
+            // Likewise, every time the circle size is a power of three the last Elf wins. Past
+            // that the winner moves one position for each Elf leaving the circle, then two once
+            // they've reached twice the power of three.
+            Rule::Across => {
+                let mut power = 1;
+                while power * 3 <= count {
+                    power *= 3;
+                }
+                if count == power {
+                    count
+                } else if count <= 2 * power {
+                    count - power
+                } else {
+                    2 * count - 3 * power
+                }
+            },
+        }
+    }
+
+    /// Returns the position (starting at 1) of the Elf getting all the presents when there are
+    /// `count` Elves in the circle, by playing the game Elf by Elf.
+    ///
+    /// This is quadratic and only kept to check `winner()` against.
+    pub fn simulate(count: usize, rule: Rule) -> usize {
+        assert!(count > 0, "there must be at least one Elf");
+        let mut circle: Vec<usize> = (1..=count).collect();
+        let mut current = 0;
+        while circle.len() > 1 {
+            let victim = match rule {
+                Rule::Left => (current + 1) % circle.len(),
+                Rule::Across => (current + circle.len() / 2) % circle.len(),
+            };
+            circle.remove(victim);
+            // the removal may shift the current Elf to the left.
+            if victim < current {
+                current -= 1;
+            }
+            current = (current + 1) % circle.len();
+        }
+        circle[0]
+    }
+
+    /// Returns a random count of Elves between half `size` and `size`, formatted like the
+    /// puzzle input.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        format!("{}\n", rng.gen_range(size / 2 + 1, size.max(1) + 1))
+    }
+}
+
+pub use an_elephant_named_joseph::*;
@@ -0,0 +1,40 @@
+extern crate aoc_cli;
+extern crate an_elephant_named_joseph;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+extern crate report;
+
+
+use aoc_cli::Cli;
+use an_elephant_named_joseph::*;
+use report::InputError;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    let cli = Cli::start_with_generator(generate_input);
+    // acquire the puzzle input, we only need the first line.
+    let input = cli.input();
+    let count = input.first_line().trim().parse::<usize>().map_err(|e| e.to_string()).and_then(|count| {
+        if count > 0 { Ok(count) } else { Err("there must be at least one Elf".to_string()) }
+    });
+    let count = count.map_err(|e| InputError::at(1, input.first_line(), e));
+    if cli.validating() {
+        input.validate(count.err());
+    }
+    let count = input.check(count);
+
+    // part 1
+    let elf = phase!("part1").in_scope(|| winner(count, Rule::Left));
+    cli.answer(1, elf, format_args!("Stealing to the left, the Elf {} gets all the presents.", elf));
+
+    // part 2
+    let elf = phase!("part2").in_scope(|| winner(count, Rule::Across));
+    cli.answer(2, elf, format_args!("Stealing across, the Elf {} gets all the presents.", elf));
+}
@@ -0,0 +1,35 @@
+extern crate an_elephant_named_joseph;
+extern crate rand;
+
+use an_elephant_named_joseph::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn part1_example() {
+    assert_eq!(winner(5, Rule::Left), 3);
+    assert_eq!(simulate(5, Rule::Left), 3);
+}
+
+#[test]
+fn part2_example() {
+    assert_eq!(winner(5, Rule::Across), 2);
+    assert_eq!(simulate(5, Rule::Across), 2);
+}
+
+#[test]
+fn closed_forms_agree_with_simulation() {
+    for count in 1..500 {
+        assert_eq!(winner(count, Rule::Left), simulate(count, Rule::Left), "{} Elves to the left", count);
+        assert_eq!(winner(count, Rule::Across), simulate(count, Rule::Across), "{} Elves across", count);
+    }
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 3_000_000);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 3_000_000));
+    let count: usize = input.trim_end().parse().unwrap();
+    assert!(count > 1_500_000 && count <= 3_000_000);
+    assert_eq!(generate_input(&mut StdRng::seed_from_u64(7), 0), "1\n");
+}
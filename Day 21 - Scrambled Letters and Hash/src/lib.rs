@@ -0,0 +1,211 @@
+//! Day 21: Scrambled Letters and Hash, scrambling and unscrambling passwords.
+
+extern crate aoc_core;
+#[macro_use]
+extern crate lazy_static;
+extern crate rand;
+extern crate regex;
+
+pub mod scrambled_letters_and_hash {
+    use ::aoc_core::Unrecognized;
+    use ::rand::Rng;
+    use ::regex::{Captures, Regex};
+    use ::std::str::FromStr;
+
+    /// A scrambling function step.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Operation {
+        /// swap position X with position Y
+        SwapPosition(usize, usize),
+        /// swap letter X with letter Y
+        SwapLetter(u8, u8),
+        /// rotate left X steps
+        RotateLeft(usize),
+        /// rotate right X steps
+        RotateRight(usize),
+        /// rotate based on position of letter X
+        RotateLetter(u8),
+        /// reverse positions X through Y
+        Reverse(usize, usize),
+        /// move position X to position Y
+        Move(usize, usize),
+    }
+
+    impl FromStr for Operation {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Operation, String> {
+            lazy_static! {
+                static ref SWAPP: Regex = Regex::new(r"^swap position (?P<X>\d+) with position (?P<Y>\d+)$").unwrap();
+                static ref SWAPL: Regex = Regex::new(r"^swap letter (?P<X>[a-z]) with letter (?P<Y>[a-z])$").unwrap();
+                static ref ROTL: Regex = Regex::new(r"^rotate left (?P<X>\d+) steps?$").unwrap();
+                static ref ROTR: Regex = Regex::new(r"^rotate right (?P<X>\d+) steps?$").unwrap();
+                static ref ROTB: Regex = Regex::new(r"^rotate based on position of letter (?P<X>[a-z])$").unwrap();
+                static ref REV: Regex = Regex::new(r"^reverse positions (?P<X>\d+) through (?P<Y>\d+)$").unwrap();
+                static ref MOVE: Regex = Regex::new(r"^move position (?P<X>\d+) to position (?P<Y>\d+)$").unwrap();
+            }
+            // the regexes ensure that we have numbers, but they may still be too large.
+            fn arg(caps: &Captures, name: &str) -> Result<usize, String> {
+                caps[name].parse().map_err(|_| format!("number out of range: {}", &caps[name]))
+            }
+            fn letter(caps: &Captures, name: &str) -> u8 {
+                caps[name].as_bytes()[0]
+            }
+            let s = s.trim();
+            if let Some(caps) = SWAPP.captures(s) {
+                Ok(Operation::SwapPosition(arg(&caps, "X")?, arg(&caps, "Y")?))
+            } else if let Some(caps) = SWAPL.captures(s) {
+                Ok(Operation::SwapLetter(letter(&caps, "X"), letter(&caps, "Y")))
+            } else if let Some(caps) = ROTL.captures(s) {
+                Ok(Operation::RotateLeft(arg(&caps, "X")?))
+            } else if let Some(caps) = ROTR.captures(s) {
+                Ok(Operation::RotateRight(arg(&caps, "X")?))
+            } else if let Some(caps) = ROTB.captures(s) {
+                Ok(Operation::RotateLetter(letter(&caps, "X")))
+            } else if let Some(caps) = REV.captures(s) {
+                Ok(Operation::Reverse(arg(&caps, "X")?, arg(&caps, "Y")?))
+            } else if let Some(caps) = MOVE.captures(s) {
+                Ok(Operation::Move(arg(&caps, "X")?, arg(&caps, "Y")?))
+            } else {
+                Err(Unrecognized::new("operation", s).into())
+            }
+        }
+    }
+
+    impl Operation {
+        /// Apply this operation to the given `password`. Returns an error when a position is
+        /// out of the password bounds or a letter is not found.
+        pub fn apply(&self, password: &mut [u8]) -> Result<(), String> {
+            let len = password.len();
+            let check = |position: usize| if position < len {
+                Ok(position)
+            } else {
+                Err(format!("position {} out of range for a password of length {}", position, len))
+            };
+            let find = |password: &[u8], letter: u8| {
+                password.iter().position(|&ch| ch == letter)
+                    .ok_or_else(|| format!("letter {} not found", letter as char))
+            };
+            match *self {
+                Operation::SwapPosition(x, y) => password.swap(check(x)?, check(y)?),
+                Operation::SwapLetter(x, y) => {
+                    let (x, y) = (find(password, x)?, find(password, y)?);
+                    password.swap(x, y);
+                },
+                Operation::RotateLeft(steps) if len > 0 => password.rotate_left(steps % len),
+                Operation::RotateRight(steps) if len > 0 => password.rotate_right(steps % len),
+                Operation::RotateLeft(_) | Operation::RotateRight(_) => (),
+                Operation::RotateLetter(letter) => {
+                    // > rotate the string to the right one time, plus a number of times equal to
+                    // > that index, plus one additional time if the index was at least 4.
+                    let index = find(password, letter)?;
+                    let steps = 1 + index + if index >= 4 { 1 } else { 0 };
+                    password.rotate_right(steps % len);
+                },
+                Operation::Reverse(x, y) if check(x)? <= check(y)? => password[x..=y].reverse(),
+                Operation::Reverse(x, y) => password[y..=x].reverse(),
+                Operation::Move(x, y) => {
+                    let (x, y) = (check(x)?, check(y)?);
+                    if x < y {
+                        password[x..=y].rotate_left(1);
+                    } else {
+                        password[y..=x].rotate_right(1);
+                    }
+                },
+            }
+            Ok(())
+        }
+
+        /// Undo this operation on the given `scrambled` password, i.e. apply its inverse.
+        ///
+        /// Rotations based on the position of a letter are undone by trying every rotation and
+        /// keeping the one this operation maps back to `scrambled`. Returns an error when there
+        /// isn't exactly one such rotation (which depends on the password length, it is fine for
+        /// the puzzle's 8 letters passwords).
+        pub fn unapply(&self, scrambled: &mut [u8]) -> Result<(), String> {
+            match *self {
+                Operation::SwapPosition(_, _) | Operation::SwapLetter(_, _) | Operation::Reverse(_, _) => {
+                    self.apply(scrambled)
+                },
+                Operation::RotateLeft(steps) => Operation::RotateRight(steps).apply(scrambled),
+                Operation::RotateRight(steps) => Operation::RotateLeft(steps).apply(scrambled),
+                Operation::Move(x, y) => Operation::Move(y, x).apply(scrambled),
+                Operation::RotateLetter(letter) => {
+                    let mut candidates = Vec::new();
+                    for steps in 0..scrambled.len() {
+                        let mut candidate = scrambled.to_vec();
+                        candidate.rotate_left(steps);
+                        let mut check = candidate.clone();
+                        self.apply(&mut check)?;
+                        if check == scrambled {
+                            candidates.push(candidate);
+                        }
+                    }
+                    match candidates.len() {
+                        1 => {
+                            scrambled.copy_from_slice(&candidates[0]);
+                            Ok(())
+                        },
+                        0 => Err(format!("no rotation based on letter {} gives {}",
+                                         letter as char, String::from_utf8_lossy(scrambled))),
+                        n => Err(format!("{} rotations based on letter {} give {}",
+                                         n, letter as char, String::from_utf8_lossy(scrambled))),
+                    }
+                },
+            }
+        }
+    }
+
+    /// Parse one `Operation` per line of `input`, blank lines are ignored.
+    pub fn parse_operations(input: &str) -> Result<Vec<Operation>, String> {
+        input.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.parse())
+            .collect()
+    }
+
+    /// Scramble the given `password` by applying every operation in order.
+    pub fn scramble(password: &str, operations: &[Operation]) -> Result<String, String> {
+        let mut password = password.as_bytes().to_vec();
+        for operation in operations {
+            operation.apply(&mut password)?;
+        }
+        String::from_utf8(password).map_err(|e| e.to_string())
+    }
+
+    /// Unscramble the given `scrambled` password by undoing every operation in reverse order.
+    pub fn unscramble(scrambled: &str, operations: &[Operation]) -> Result<String, String> {
+        let mut password = scrambled.as_bytes().to_vec();
+        for operation in operations.iter().rev() {
+            operation.unapply(&mut password)?;
+        }
+        String::from_utf8(password).map_err(|e| e.to_string())
+    }
+
+    /// Returns `size` random operations scrambling eight letters passwords, formatted like the
+    /// puzzle input.
+    pub fn generate_input<R: Rng>(rng: &mut R, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            // two distinct positions, and the two letters found there in "abcdefgh".
+            let x = rng.gen_range(0, 8);
+            let y = (x + rng.gen_range(1, 8)) % 8;
+            let (a, b) = (char::from(b'a' + x as u8), char::from(b'a' + y as u8));
+            let steps = rng.gen_range(0, 8);
+            let line = match rng.gen_range(0, 7) {
+                0 => format!("swap position {} with position {}", x, y),
+                1 => format!("swap letter {} with letter {}", a, b),
+                2 => format!("rotate left {} step{}", steps, if steps == 1 { "" } else { "s" }),
+                3 => format!("rotate right {} step{}", steps, if steps == 1 { "" } else { "s" }),
+                4 => format!("rotate based on position of letter {}", a),
+                5 => format!("reverse positions {} through {}", x.min(y), x.max(y)),
+                _ => format!("move position {} to position {}", x, y),
+            };
+            input.push_str(&line);
+            input.push('\n');
+        }
+        input
+    }
+}
+
+pub use scrambled_letters_and_hash::*;
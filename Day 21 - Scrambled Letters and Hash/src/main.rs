@@ -0,0 +1,37 @@
+extern crate aoc_cli;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+extern crate report;
+extern crate scrambled_letters_and_hash;
+
+
+use aoc_cli::Cli;
+use scrambled_letters_and_hash::*;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    let cli = Cli::start_with_generator(generate_input);
+    // acquire the puzzle input, one operation per line.
+    let input = cli.input();
+    if cli.validating() {
+        input.validate(input.line_errors::<Operation>());
+    }
+    let operations: Vec<Operation> = phase!("parse").in_scope(|| input.parse_lines());
+
+    // part 1
+    let password = "abcdefgh";
+    let scrambled = phase!("part1").in_scope(|| scramble(password, &operations)).unwrap_or_else(|e| report::exit(e));
+    cli.answer(1, &scrambled, format_args!("Scrambling {} gives {}.", password, scrambled));
+
+    // part 2
+    let scrambled = "fbgdceah";
+    let password = phase!("part2").in_scope(|| unscramble(scrambled, &operations)).unwrap_or_else(|e| report::exit(e));
+    cli.answer(2, &password, format_args!("Unscrambling {} gives {}.", scrambled, password));
+}
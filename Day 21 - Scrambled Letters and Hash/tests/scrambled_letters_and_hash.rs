@@ -0,0 +1,59 @@
+extern crate rand;
+extern crate scrambled_letters_and_hash;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use scrambled_letters_and_hash::*;
+
+const EXAMPLE: &str = "swap position 4 with position 0
+    swap letter d with letter b
+    reverse positions 0 through 4
+    rotate left 1 step
+    move position 1 to position 4
+    move position 3 to position 0
+    rotate based on position of letter b
+    rotate based on position of letter d";
+
+#[test]
+fn part1_example() {
+    let operations = parse_operations(EXAMPLE).unwrap();
+    assert_eq!(scramble("abcde", &operations), Ok("decab".to_string()));
+    let mut password = b"abcde".to_vec();
+    let steps = ["ebcda", "edcba", "abcde", "bcdea", "bdeac", "abdec", "ecabd", "decab"];
+    for (operation, &expected) in operations.iter().zip(steps.iter()) {
+        operation.apply(&mut password).unwrap();
+        assert_eq!(password, expected.as_bytes());
+    }
+}
+
+#[test]
+fn part2_unscramble() {
+    // every rotation based on a letter can be undone on 8 letters passwords.
+    let operations: Vec<Operation> = (b'a'..=b'h').map(Operation::RotateLetter).collect();
+    for operation in operations.iter() {
+        let scrambled = scramble("abcdefgh", &[*operation]).unwrap();
+        assert_eq!(unscramble(&scrambled, &[*operation]), Ok("abcdefgh".to_string()));
+    }
+    let operations = parse_operations(EXAMPLE).unwrap();
+    let scrambled = scramble("fbgdceah", &operations).unwrap();
+    assert_eq!(unscramble(&scrambled, &operations), Ok("fbgdceah".to_string()));
+    // but not on the example 5 letters password.
+    assert!(unscramble("decab", &operations).is_err());
+}
+
+#[test]
+fn invalid_operations() {
+    assert!("rotate up 2 steps".parse::<Operation>().is_err());
+    assert!(scramble("abc", &[Operation::SwapPosition(0, 3)]).is_err());
+    assert!(scramble("abc", &[Operation::SwapLetter(b'a', b'z')]).is_err());
+}
+
+#[test]
+fn generated_input() {
+    let input = generate_input(&mut StdRng::seed_from_u64(7), 100);
+    assert_eq!(input, generate_input(&mut StdRng::seed_from_u64(7), 100));
+    let operations = parse_operations(&input).unwrap();
+    assert_eq!(operations.len(), 100);
+    let scrambled = scramble("abcdefgh", &operations).unwrap();
+    assert_eq!(unscramble(&scrambled, &operations).unwrap(), "abcdefgh");
+}
@@ -0,0 +1,24 @@
+//! Day 23: Safe Cracking, running the self-modifying assembunny safe program.
+
+extern crate aoc_core;
+extern crate assembunny;
+
+pub mod safe_cracking {
+    use ::aoc_core::{Cancel, Cancelled};
+    use ::assembunny::{Instruction, Register, Value, Vm};
+
+    /// Run the safe `program` with the register a initialized to the number of `eggs`, returns
+    /// the value to send to the safe (i.e. the final value of the register a), or `Cancelled`
+    /// when `cancel` was cancelled before the program is over.
+    ///
+    /// The program multiplies through nested increment loops, so the `Vm` loop optimization is
+    /// used unless `optimize` is `false`.
+    pub fn crack(program: &[Instruction], eggs: Value, optimize: bool, cancel: &Cancel) -> Result<Value, Cancelled> {
+        let mut vm = Vm::new(program.to_vec());
+        vm.optimize(optimize);
+        vm.registers_mut()[Register::A] = eggs;
+        vm.run_cancellable(cancel).map(|registers| registers[Register::A])
+    }
+}
+
+pub use safe_cracking::*;
@@ -0,0 +1,36 @@
+extern crate aoc_cli;
+extern crate assembunny;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+extern crate safe_cracking;
+
+
+use aoc_cli::Cli;
+use safe_cracking::*;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    // NOTE: the puzzle inputs are hand-written programs, there is nothing realistic to generate.
+    let cli = Cli::start();
+    // acquire the puzzle input, one instruction per line.
+    let input = cli.input();
+    if cli.validating() {
+        input.validate(input.line_errors::<assembunny::Instruction>());
+    }
+    let program: Vec<assembunny::Instruction> = phase!("parse").in_scope(|| input.parse_lines());
+
+    // part 1
+    let value = cli.finish(phase!("part1").in_scope(|| crack(&program, 7, true, cli.cancel())));
+    cli.answer(1, value, format_args!("With 7 eggs, the value to send to the safe is {}.", value));
+
+    // part 2
+    let value = cli.finish(phase!("part2").in_scope(|| crack(&program, 12, true, cli.cancel())));
+    cli.answer(2, value, format_args!("With 12 eggs, the value to send to the safe is {}.", value));
+}
@@ -0,0 +1,59 @@
+extern crate aoc_core;
+extern crate assembunny;
+extern crate safe_cracking;
+
+use aoc_core::Cancel;
+use safe_cracking::*;
+
+#[test]
+fn part1_example() {
+    let program = assembunny::parse_program(
+        "cpy 2 a
+        tgl a
+        tgl a
+        tgl a
+        cpy 1 a
+        dec a
+        dec a").unwrap();
+    assert_eq!(crack(&program, 0, false, &Cancel::new()).unwrap(), 3);
+    assert_eq!(crack(&program, 0, true, &Cancel::new()).unwrap(), 3);
+}
+
+#[test]
+fn factorial() {
+    // computes a! + 42 (for a >= 6), modifying its own code along the way like the puzzle program.
+    let program = assembunny::parse_program(
+        "cpy a b
+        dec b
+        cpy a d
+        cpy 0 a
+        cpy b c
+        inc a
+        dec c
+        jnz c -2
+        dec d
+        jnz d -5
+        dec b
+        cpy b c
+        cpy c d
+        dec d
+        inc c
+        jnz d -2
+        tgl c
+        cpy -16 c
+        jnz 1 c
+        cpy 6 c
+        jnz 7 d
+        inc a
+        inc d
+        jnz d -2
+        inc c
+        jnz c -5").unwrap();
+    // NOTE: with less than 6 eggs the toggles don't reach the last loop and it never ends.
+    let cancel = Cancel::new();
+    cancel.cancel();
+    assert!(crack(&program, 5, true, &cancel).is_err());
+    assert_eq!(crack(&program, 7, false, &Cancel::new()).unwrap(), 5040 + 42);
+    assert_eq!(crack(&program, 7, true, &Cancel::new()).unwrap(), 5040 + 42);
+    assert_eq!(crack(&program, 12, true, &Cancel::new()).unwrap(), 479_001_600 + 42);
+}
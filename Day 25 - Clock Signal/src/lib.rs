@@ -0,0 +1,51 @@
+//! Day 25: Clock Signal, finding the lowest register value making the antenna transmit a
+//! clock signal.
+
+extern crate aoc_core;
+extern crate assembunny;
+
+pub mod clock_signal {
+    use ::aoc_core::{Cancel, Cancelled};
+    use ::assembunny::{Instruction, Register, Signal, Value, Vm};
+
+    /// The maximum number of steps the antenna program may take to transmit the next value.
+    const MAX_STEPS: u64 = 1_000_000;
+
+    /// The maximum number of values transmitted before the signal should repeat itself.
+    const MAX_OUTPUTS: usize = 10_000;
+
+    /// Returns `true` if `signal` is a clock signal, i.e. 0, 1, 0, 1... forever, `false`
+    /// otherwise.
+    pub fn is_clock(signal: &Signal) -> bool {
+        // checking the first value of the period once more ensures that the period has an even
+        // length and thus alternates forever.
+        let values = signal.prefix.iter().chain(signal.period.iter()).chain(signal.period.iter().take(1));
+        !signal.period.is_empty() && values.enumerate().all(|(i, &value)| value == (i % 2) as Value)
+    }
+
+    /// Returns the signal transmitted by the antenna `program` with the register a initialized
+    /// to `a`, or `None` if it doesn't transmit a periodic signal.
+    pub fn transmitted(program: &[Instruction], a: Value) -> Option<Signal> {
+        let mut vm = Vm::new(program.to_vec());
+        vm.optimize(true);
+        vm.registers_mut()[Register::A] = a;
+        vm.signal(MAX_STEPS, MAX_OUTPUTS).ok()
+    }
+
+    /// Returns the lowest positive integer (up to `max`) to initialize the register a with so
+    /// that the antenna `program` transmits a clock signal. `cancel` is checked before trying
+    /// each value, returns `Cancelled` once it was cancelled.
+    pub fn lowest_clock_input(program: &[Instruction], max: Value, cancel: &Cancel)
+        -> Result<Option<Value>, Cancelled>
+    {
+        for a in 1..=max {
+            cancel.check()?;
+            if transmitted(program, a).is_some_and(|signal| is_clock(&signal)) {
+                return Ok(Some(a));
+            }
+        }
+        Ok(None)
+    }
+}
+
+pub use clock_signal::*;
@@ -0,0 +1,35 @@
+extern crate aoc_cli;
+extern crate assembunny;
+extern crate clock_signal;
+#[cfg(feature = "peak-memory")]
+extern crate memory;
+#[macro_use]
+extern crate profiling;
+
+
+use aoc_cli::Cli;
+use clock_signal::*;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    #[cfg(feature = "peak-memory")]
+    let _peak = ALLOCATOR.report_on_drop();
+    // NOTE: the puzzle inputs are hand-written programs, there is nothing realistic to generate.
+    let cli = Cli::start();
+    // acquire the puzzle input, one instruction per line.
+    let input = cli.input();
+    if cli.validating() {
+        input.validate(input.line_errors::<assembunny::Instruction>());
+    }
+    let program: Vec<assembunny::Instruction> = phase!("parse").in_scope(|| input.parse_lines());
+
+    // part 1 (there is no part 2 puzzle, only the fiftieth star).
+    let lowest = phase!("part1").in_scope(|| lowest_clock_input(&program, 1_000_000, cli.cancel()));
+    match cli.finish(lowest) {
+        Some(a) => cli.answer(1, a, format_args!("The lowest register a value producing a clock signal is {}.", a)),
+        None => cli.note(format_args!("Failed to find a register a value producing a clock signal.")),
+    }
+}
@@ -0,0 +1,61 @@
+extern crate aoc_core;
+extern crate assembunny;
+extern crate clock_signal;
+
+use aoc_core::Cancel;
+use clock_signal::*;
+
+#[test]
+fn clock() {
+    use assembunny::Signal;
+
+    assert!(is_clock(&Signal { prefix: vec![], period: vec![0, 1] }));
+    assert!(is_clock(&Signal { prefix: vec![0, 1, 0], period: vec![1, 0, 1, 0] }));
+    assert!(!is_clock(&Signal { prefix: vec![], period: vec![0, 1, 0] }));
+    assert!(!is_clock(&Signal { prefix: vec![1], period: vec![0, 1] }));
+    assert!(!is_clock(&Signal { prefix: vec![0, 1], period: vec![] }));
+}
+
+#[test]
+fn part1() {
+    // transmits the bits of a + 12 forever, least significant first.
+    let program = assembunny::parse_program(
+        "cpy a d
+        cpy 3 c
+        cpy 4 b
+        inc d
+        dec b
+        jnz b -2
+        dec c
+        jnz c -5
+        cpy d a
+        jnz 0 0
+        cpy a b
+        cpy 0 a
+        cpy 2 c
+        jnz b 2
+        jnz 1 6
+        dec b
+        dec c
+        jnz c -4
+        inc a
+        jnz 1 -7
+        cpy 2 b
+        jnz c 2
+        jnz 1 4
+        dec b
+        dec c
+        jnz 1 -4
+        jnz 0 0
+        out b
+        jnz a -19
+        jnz 1 -21").unwrap();
+    let signal = transmitted(&program, 1).unwrap();
+    let values: Vec<_> = signal.prefix.iter().chain(signal.period.iter().cycle()).take(8).cloned().collect();
+    assert_eq!(values, vec![1, 0, 1, 1, 1, 0, 1, 1]); // 13 = 0b1101
+    // 10 = 0b1010 = -2 + 12 is too low, 42 = 0b101010 = 30 + 12.
+    assert_eq!(lowest_clock_input(&program, 100, &Cancel::new()), Ok(Some(30)));
+    let cancel = Cancel::new();
+    cancel.cancel();
+    assert!(lowest_clock_input(&program, 100, &cancel).is_err());
+}
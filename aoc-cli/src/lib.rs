@@ -0,0 +1,358 @@
+//! Command line shared by the days binaries.
+//!
+//! Every day starts its `main()` with `Cli::start()` (or `Cli::start_with_generator()` when it
+//! has an input generator), which handles the options common to all the days so that they behave
+//! the same everywhere:
+//!
+//! - `--generate SIZE SEED` prints a random input and exits, see `report::GENERATE`,
+//! - `--trace-output FILE` records the phases as a Chrome trace, see `profiling`,
+//! - `--validate` only checks the input, see `report::VALIDATE`,
+//! - `--input FILE` reads the puzzle input from FILE instead of the standard input,
+//! - `--answers` prints only the answers, one "PART ANSWER" per line,
+//! - `--quiet` prints only the answers themselves, one per line (part 1 then part 2), for the
+//!   scripts,
+//! - `--time` reports how long the day took on the standard error,
+//! - `--timeout SECS` gives up after SECS seconds, see `Cli::cancel()`,
+//! - `--explain` prints the facts found by the solvers as JSON lines on the standard error, see
+//!   `Cli::explain()`.
+//!
+//! The other arguments are left to the day, see `Cli::args()`.
+
+extern crate aoc_core;
+extern crate profiling;
+extern crate rand;
+extern crate report;
+
+use aoc_core::input::BOM;
+use aoc_core::{Cancel, Cancelled, Event, Sink};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The command line option reading the puzzle input from a file.
+pub const INPUT: &str = "--input";
+
+/// The command line option printing only the answers.
+pub const ANSWERS: &str = "--answers";
+
+/// The command line option printing only the answers themselves, without the part numbers.
+pub const QUIET: &str = "--quiet";
+
+/// The command line option reporting how long the day took.
+pub const TIME: &str = "--time";
+
+/// The command line option giving up on the puzzle after the given number of seconds.
+pub const TIMEOUT: &str = "--timeout";
+
+/// The command line option printing the events explaining the solvers.
+pub const EXPLAIN: &str = "--explain";
+
+/// How long a cancelled day has to stop by itself before being killed anyway, see
+/// `Cli::cancel()`.
+const GRACE: Duration = Duration::from_secs(1);
+
+/// The parsed command line of a day binary.
+///
+/// It should live until the end of `main()`: the Chrome trace is complete and the time is
+/// reported once it is dropped.
+pub struct Cli {
+    args: Vec<String>,
+    input: Option<String>,
+    validate: bool,
+    output: Output,
+    time: bool,
+    timeout: Option<Duration>,
+    cancel: Cancel,
+    explain: bool,
+    trace: Option<String>,
+    started: Instant,
+    guard: Option<profiling::FlushGuard>,
+}
+
+impl Cli {
+    /// Parse the command line of a day without input generator, `--generate` exits with an
+    /// error. Exit on error.
+    pub fn start() -> Cli {
+        if report::generating().is_some() {
+            report::exit("no input generator for this day");
+        }
+        Cli::from_env()
+    }
+
+    /// Parse the command line of a day, printing the input returned by `generate` for the given
+    /// random generator and size and exiting when given `--generate SIZE SEED`. Exit on error.
+    pub fn start_with_generator<F>(generate: F) -> Cli
+        where F: FnOnce(&mut StdRng, usize) -> String
+    {
+        if let Some((size, seed)) = report::generating() {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            out.write_all(generate(&mut StdRng::seed_from_u64(seed), size).as_bytes())
+                .and_then(|_| out.flush())
+                .unwrap_or_else(|e| report::exit(format!("<stdout>: {}", e)));
+            process::exit(0);
+        }
+        Cli::from_env()
+    }
+
+    /// Parse the process arguments, start recording the trace and the timeout watchdog when
+    /// asked to.
+    fn from_env() -> Cli {
+        let mut cli = Cli::parse(std::env::args().skip(1)).unwrap_or_else(|e| report::exit(e));
+        if let Some(ref path) = cli.trace {
+            cli.guard = Some(profiling::record(path).unwrap_or_else(|e| report::exit(e)));
+        }
+        if let Some(timeout) = cli.timeout {
+            let cancel = cli.cancel.clone();
+            thread::spawn(move || {
+                thread::sleep(timeout);
+                cancel.cancel();
+                // NOTE: most of the days never check the token, they are killed once the grace
+                // period is over.
+                thread::sleep(GRACE);
+                timed_out(timeout)
+            });
+        }
+        cli
+    }
+
+    /// Parse the given command line arguments (without the program name), without acting on
+    /// them.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Cli, String> {
+        let (rest, trace) = profiling::split_args(args)?;
+        let mut cli = Cli {
+            args: Vec::new(),
+            input: None,
+            validate: false,
+            output: Output::Prose,
+            time: false,
+            timeout: None,
+            cancel: Cancel::new(),
+            explain: false,
+            trace,
+            started: Instant::now(),
+            guard: None,
+        };
+        let mut rest = rest.into_iter();
+        while let Some(arg) = rest.next() {
+            match arg.as_str() {
+                INPUT => cli.input = Some(rest.next().ok_or_else(|| format!("usage: {} FILE", INPUT))?),
+                report::VALIDATE => cli.validate = true,
+                ANSWERS => cli.output = Output::Answers,
+                QUIET => cli.output = Output::Quiet,
+                TIME => cli.time = true,
+                TIMEOUT => {
+                    let usage = || format!("usage: {} SECS", TIMEOUT);
+                    let secs = rest.next().ok_or_else(usage)?;
+                    let secs = secs.parse().map_err(|_| usage())?;
+                    cli.timeout = Some(Duration::try_from_secs_f64(secs).map_err(|_| usage())?);
+                },
+                EXPLAIN => cli.explain = true,
+                _ => cli.args.push(arg),
+            }
+        }
+        Ok(cli)
+    }
+
+    /// Returns the arguments left to the day, in order.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Returns `true` when given `--validate`, the day should then only check its input.
+    pub fn validating(&self) -> bool {
+        self.validate
+    }
+
+    /// Returns the name of the puzzle input, the file given with `--input` or `<stdin>`.
+    pub fn input_name(&self) -> &str {
+        self.input.as_deref().unwrap_or("<stdin>")
+    }
+
+    /// Read the whole puzzle input, from the file given with `--input` or the standard input.
+    /// Exit on failure.
+    pub fn input(&self) -> report::Input {
+        match self.input {
+            Some(ref path) => report::Input::file(path),
+            None => report::Input::stdin(),
+        }
+    }
+
+    /// Returns a reader over the puzzle input, for the days processing it as it is read. Exit
+    /// if the file given with `--input` can't be opened.
+    ///
+    /// Only the leading byte order mark is skipped, the days reading their input this way handle
+    /// the other differences `aoc_core::input::normalize()` would remove.
+    pub fn reader(&self) -> Box<dyn BufRead> {
+        let mut reader: Box<dyn BufRead> = match self.input {
+            Some(ref path) => {
+                let file = File::open(path).unwrap_or_else(|e| report::exit(format!("{}: {}", path, e)));
+                Box::new(BufReader::new(file))
+            },
+            None => Box::new(BufReader::new(io::stdin())),
+        };
+        let mut bom = [0; 3];
+        let bom = BOM.encode_utf8(&mut bom).as_bytes();
+        if reader.fill_buf().is_ok_and(|buf| buf.starts_with(bom)) {
+            reader.consume(bom.len());
+        }
+        reader
+    }
+
+    /// Returns the token cancelled once the `--timeout` is over, never cancelled without it.
+    ///
+    /// The slow solvers check it now and then to give up cleanly, the day then reports the
+    /// timeout with `Cli::timed_out()`. A day still running after a short grace period is killed
+    /// with the same report, so that no input can hang it forever.
+    pub fn cancel(&self) -> &Cancel {
+        &self.cancel
+    }
+
+    /// Returns the value of `result`, or report the timeout and exit when the solver was
+    /// cancelled, see `Cli::timed_out()`.
+    pub fn finish<T>(&self, result: Result<T, Cancelled>) -> T {
+        result.unwrap_or_else(|Cancelled| self.timed_out())
+    }
+
+    /// Report on the standard error that the day gave up after its `--timeout` and exit with the
+    /// `report::TIMED_OUT` status.
+    pub fn timed_out(&self) -> ! {
+        timed_out(self.timeout.unwrap_or_default())
+    }
+
+    /// Returns the sink of the events explaining the solvers, printing them as JSON lines on the
+    /// standard error when given `--explain` and discarding them otherwise.
+    pub fn explain(&self) -> Explain {
+        Explain { enabled: self.explain }
+    }
+
+    /// Print the answer to the puzzle `part`: the `sentence` stating it, only the part and
+    /// `answer` when given `--answers`, or only `answer` when given `--quiet`.
+    ///
+    /// The days answer part 1 first, so that the `--quiet` output is one answer per line in
+    /// order.
+    pub fn answer<A: Display>(&self, part: u32, answer: A, sentence: fmt::Arguments) {
+        println!("{}", Answer { part, answer, sentence, output: self.output });
+    }
+
+    /// Print a line of output that is not an answer (e.g. a rendering or a list), unless given
+    /// `--answers` or `--quiet`.
+    pub fn note(&self, line: fmt::Arguments) {
+        if self.output == Output::Prose {
+            println!("{}", line);
+        }
+    }
+}
+
+impl Drop for Cli {
+    fn drop(&mut self) {
+        if self.time {
+            eprintln!("time: {:.1?}", self.started.elapsed());
+        }
+    }
+}
+
+/// The `aoc_core::Sink` of `--explain`, see `Cli::explain()`.
+#[derive(Copy, Clone, Debug)]
+pub struct Explain {
+    enabled: bool,
+}
+
+impl Sink for Explain {
+    fn emit(&mut self, event: &Event) {
+        if self.enabled {
+            eprintln!("{}", event);
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// Report that the day gave up after `timeout` and exit, see `Cli::timed_out()`.
+fn timed_out(timeout: Duration) -> ! {
+    eprintln!("timeout: no answer after {:?}", timeout);
+    process::exit(report::TIMED_OUT)
+}
+
+/// What a day prints on the standard output.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Output {
+    /// The sentences stating the answers, along with the notes.
+    Prose,
+    /// Only the answers, as "PART ANSWER".
+    Answers,
+    /// Only the answers themselves.
+    Quiet,
+}
+
+/// The displayed form of an answer, see `Cli::answer()`.
+struct Answer<'a, A> {
+    part: u32,
+    answer: A,
+    sentence: fmt::Arguments<'a>,
+    output: Output,
+}
+
+impl<'a, A: Display> Display for Answer<'a, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.output {
+            Output::Prose => write!(f, "{}", self.sentence),
+            Output::Answers => write!(f, "{} {}", self.part, self.answer),
+            Output::Quiet => write!(f, "{}", self.answer),
+        }
+    }
+}
+
+
+#[test]
+fn common_options() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    let cli = Cli::parse(args("--time --json --input day04.txt --find north --validate")).unwrap();
+    assert_eq!(cli.args(), &args("--json --find north")[..]);
+    assert_eq!(cli.input_name(), "day04.txt");
+    assert!(cli.validating() && cli.time && cli.output == Output::Prose);
+    let cli = Cli::parse(args("--answers --trace-output run.json")).unwrap();
+    assert!(cli.args().is_empty() && cli.output == Output::Answers && !cli.validating());
+    assert_eq!(cli.input_name(), "<stdin>");
+    assert_eq!(cli.trace.as_deref(), Some("run.json"));
+    assert_eq!(Cli::parse(args("--quiet")).unwrap().output, Output::Quiet);
+    assert!(Cli::parse(args("--input")).is_err());
+}
+
+#[test]
+fn timeout_option() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    let cli = Cli::parse(args("--timeout 2.5 --json")).unwrap();
+    assert_eq!(cli.timeout, Some(Duration::from_millis(2500)));
+    assert_eq!(cli.args(), &args("--json")[..]);
+    assert!(!cli.cancel().is_cancelled());
+    assert_eq!(Cli::parse(args("--time")).unwrap().timeout, None);
+    for bad in &["--timeout", "--timeout soon", "--timeout -1"] {
+        assert!(Cli::parse(args(bad)).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn explain_option() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    let cli = Cli::parse(args("--trace --explain --quiet")).unwrap();
+    assert!(cli.explain().enabled() && cli.output == Output::Quiet);
+    assert_eq!(cli.args(), &args("--trace")[..]);
+    assert!(!Cli::parse(args("--time")).unwrap().explain().enabled());
+}
+
+#[test]
+fn answers() {
+    let answer = |output| Answer { part: 2, answer: 42, sentence: format_args!("the answer is {}.", 42), output }
+        .to_string();
+    assert_eq!(answer(Output::Prose), "the answer is 42.");
+    assert_eq!(answer(Output::Answers), "2 42");
+    assert_eq!(answer(Output::Quiet), "42");
+}
@@ -0,0 +1,815 @@
+//! Core types shared by the days: points, rectangular grids and the fonts of the text displayed
+//! on them, along with the parsing helpers
+//! and the errors going with them (located in the input by a `Span`), the normalization of the
+//! puzzle inputs, the cancellation of the slow solvers and the events they explain themselves
+//! with.
+//!
+//! Like the algorithms using them (e.g. the Day 2 keypad walking), they only need `core` and
+//! `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+extern crate checked;
+
+pub use cancel::{Cancel, Cancelled};
+pub use error::{GridError, Unrecognized};
+pub use explain::{Event, Sink};
+pub use glyph::Font;
+pub use grid::Grid;
+pub use point::Point;
+pub use span::{Span, Spanned};
+
+pub mod point {
+    use ::core::fmt::{self, Display};
+
+    /// A position on a two-dimensional grid: city blocks, keypad buttons…
+    ///
+    /// Which way `y` grows is up to the day, e.g. northward on the Day 1 city grid and downward on
+    /// the Day 2 keypads.
+    #[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug, Default)]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+
+    impl Point {
+        /// Create a new `Point` at the given coordinates.
+        pub fn new(x: i32, y: i32) -> Point {
+            Point { x, y }
+        }
+
+        /// Returns self moved by `dx` and `dy`, `None` when it would overflow.
+        pub fn checked_offset(self, dx: i32, dy: i32) -> Option<Point> {
+            Some(Point { x: self.x.checked_add(dx)?, y: self.y.checked_add(dy)? })
+        }
+
+        /// Compute the "snake distance" from a given other `Point`.
+        /// see [Taxicab geometry](https://en.wikipedia.org/wiki/Taxicab_geometry)
+        pub fn snake_distance(&self, other: &Point) -> u32 {
+            self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+        }
+    }
+
+    impl Display for Point {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "({}, {})", self.x, self.y)
+        }
+    }
+}
+
+pub mod grid {
+    use ::alloc::vec::Vec;
+    use ::error::GridError;
+
+    /// A rectangular grid of cells stored row by row, `(0, 0)` being the top-left cell.
+    #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+    pub struct Grid<T> {
+        width: usize,
+        height: usize,
+        cells: Vec<T>,
+    }
+
+    impl<T: Clone> Grid<T> {
+        /// Create a new `width` x `height` `Grid` with every cell set to `fill`.
+        ///
+        /// Panics if the grid would have more than `usize::MAX` cells.
+        pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+            let len = width.checked_mul(height).expect("grid too large");
+            Grid { width, height, cells: ::alloc::vec![fill; len] }
+        }
+    }
+
+    impl<T> Grid<T> {
+        /// Parse a `Grid` from its text representation, one line per row and one character per
+        /// cell converted by `cell`.
+        ///
+        /// Empty lines are skipped and every row must be as wide as the first one.
+        pub fn parse<F, E>(s: &str, mut cell: F) -> Result<Grid<T>, GridError<E>>
+            where F: FnMut(char) -> Result<T, E>
+        {
+            let mut width = 0;
+            let mut cells = Vec::new();
+            for (row, line) in s.lines().filter(|line| !line.is_empty()).enumerate() {
+                let before = cells.len();
+                for (column, c) in line.chars().enumerate() {
+                    cells.push(cell(c).map_err(|error| GridError::Cell { row, column, error })?);
+                }
+                if row == 0 {
+                    width = cells.len();
+                } else if cells.len() - before != width {
+                    return Err(GridError::Ragged { row, width });
+                }
+            }
+            if cells.is_empty() {
+                return Err(GridError::Empty);
+            }
+            Ok(Grid { width, height: cells.len() / width, cells })
+        }
+
+        /// Returns the number of columns of self.
+        pub fn width(&self) -> usize {
+            self.width
+        }
+
+        /// Returns the number of rows of self.
+        pub fn height(&self) -> usize {
+            self.height
+        }
+
+        /// Returns the cell at column `x` of row `y`, `None` when out of bounds.
+        pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+            if x < self.width && y < self.height { self.cells.get(y * self.width + x) } else { None }
+        }
+
+        /// Returns the mutable cell at column `x` of row `y`, `None` when out of bounds.
+        pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+            if x < self.width && y < self.height { self.cells.get_mut(y * self.width + x) } else { None }
+        }
+
+        /// Returns an iterator over the rows of self, from top to bottom.
+        pub fn rows(&self) -> ::core::slice::Chunks<'_, T> {
+            // NOTE: chunks() panics given zero, an empty grid has no cells to split anyway.
+            self.cells.chunks(self.width.max(1))
+        }
+
+        /// Returns an iterator over the (x, y) positions of the cells of self along with the
+        /// cells, in reading order.
+        pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> + '_ {
+            let width = self.width;
+            self.cells.iter().enumerate().map(move |(i, cell)| (i % width, i / width, cell))
+        }
+    }
+}
+
+pub mod glyph {
+    use ::alloc::format;
+    use ::alloc::string::{String, ToString};
+    use ::alloc::vec::Vec;
+    use ::grid::Grid;
+    use ::span::{Span, Spanned};
+
+    /// A monospace font, decoding the text displayed by lit pixels on a grid, e.g. the Day 8
+    /// screen.
+    ///
+    /// Fonts are parsed from a simple text format (see `Font::parse()`), so that the screens of
+    /// other puzzles using other glyph sizes can be decoded too.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Font {
+        width: usize,
+        height: usize,
+        glyphs: Vec<(char, Grid<bool>)>,
+    }
+
+    impl Font {
+        /// Parse a `Font` from its text representation:
+        ///
+        /// - a `WIDTHxHEIGHT` header, e.g. `5x6`, where WIDTH includes the blank spacing columns
+        ///   between two glyphs,
+        /// - then for each glyph a line holding the sole character it stands for, followed by
+        ///   its HEIGHT rows of at most WIDTH pixels, `#` for lit pixels and `.` otherwise. The
+        ///   missing pixels at the end of a row are off, an empty row being all off.
+        ///
+        /// Empty lines before the header and between the glyphs are skipped.
+        pub fn parse(text: &str) -> Result<Font, Spanned<String>> {
+            let error = |line: usize, s: &str, message: String| {
+                Spanned::new(Span::new(line, 1, s.chars().count()), message)
+            };
+            let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+            let (line_no, header) = lines.find(|&(_, line)| !line.is_empty())
+                .ok_or_else(|| error(1, "", "empty font".to_string()))?;
+            let size = header.split_once('x').and_then(|(width, height)| {
+                Some((width.parse::<usize>().ok()?, height.parse::<usize>().ok()?))
+            });
+            let (width, height) = match size {
+                Some((width, height)) if width > 0 && height > 0 => (width, height),
+                _ => return Err(error(line_no, header, "expected the glyph size, e.g. 5x6".to_string())),
+            };
+            let mut glyphs: Vec<(char, Grid<bool>)> = Vec::new();
+            while let Some((line_no, name)) = lines.find(|&(_, line)| !line.is_empty()) {
+                let mut chars = name.chars();
+                let ch = match (chars.next(), chars.next()) {
+                    (Some(ch), None) => ch,
+                    _ => return Err(error(line_no, name, "expected the character of a glyph".to_string())),
+                };
+                if glyphs.iter().any(|&(other, _)| other == ch) {
+                    return Err(error(line_no, name, format!("duplicate glyph {:?}", ch)));
+                }
+                let mut pixels = Grid::new(width, height, false);
+                for y in 0..height {
+                    let (line_no, row) = lines.next()
+                        .ok_or_else(|| error(line_no, name, format!("glyph {:?}: expected {} rows", ch, height)))?;
+                    if row.chars().count() > width {
+                        return Err(error(line_no, row, format!("glyph {:?}: expected at most {} pixels", ch, width)));
+                    }
+                    for (x, pixel) in row.chars().enumerate() {
+                        match pixel {
+                            '#' => *pixels.get_mut(x, y).unwrap() = true,
+                            '.' => (),
+                            _ => {
+                                let span = Span::new(line_no, x + 1, 1);
+                                return Err(Spanned::new(span, format!("invalid pixel {:?}", pixel)));
+                            },
+                        }
+                    }
+                }
+                glyphs.push((ch, pixels));
+            }
+            Ok(Font { width, height, glyphs })
+        }
+
+        /// Returns the width of the glyphs of self, including their spacing.
+        pub fn width(&self) -> usize {
+            self.width
+        }
+
+        /// Returns the height of the glyphs of self.
+        pub fn height(&self) -> usize {
+            self.height
+        }
+
+        /// Returns the number of glyphs of self.
+        pub fn len(&self) -> usize {
+            self.glyphs.len()
+        }
+
+        /// Returns `true` if self has no glyph, `false` otherwise.
+        pub fn is_empty(&self) -> bool {
+            self.glyphs.is_empty()
+        }
+
+        /// Decode the text displayed on a `width` x `height` grid of pixels, `lit(x, y)` telling
+        /// whether the pixel at column `x` of row `y` is lit.
+        ///
+        /// The grid is split into glyphs of the font width that are looked up in self, the
+        /// columns past the grid width being off. Glyphs that could not be recognized are decoded
+        /// as `?`. Returns `None` when `height` doesn't match the font height.
+        pub fn read<F: Fn(usize, usize) -> bool>(&self, width: usize, height: usize, lit: F) -> Option<String> {
+            if height != self.height {
+                return None;
+            }
+            let count = width.div_ceil(self.width);
+            let text = (0..count).map(|i| {
+                let x0 = i * self.width;
+                let matches = |pixels: &Grid<bool>| pixels.iter().all(|(dx, y, &on)| {
+                    let x = x0 + dx;
+                    (x < width && lit(x, y)) == on
+                });
+                self.glyphs.iter().find(|&(_, pixels)| matches(pixels)).map_or('?', |&(ch, _)| ch)
+            });
+            Some(text.collect())
+        }
+    }
+}
+
+pub mod cancel {
+    use ::alloc::sync::Arc;
+    use ::core::fmt::{self, Display};
+    use ::core::sync::atomic::{AtomicBool, Ordering};
+
+    /// A token shared by a solver and whoever may ask it to stop early, e.g. the `--timeout` of
+    /// the days binaries. The solver checks it now and then and gives up once it is cancelled.
+    #[derive(Clone, Debug, Default)]
+    pub struct Cancel(Arc<AtomicBool>);
+
+    impl Cancel {
+        /// Create a new `Cancel` token, not cancelled yet.
+        pub fn new() -> Cancel {
+            Cancel::default()
+        }
+
+        /// Ask the solvers checking self (or any of its clones) to stop.
+        pub fn cancel(&self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+
+        /// Returns `true` once self has been cancelled, `false` otherwise.
+        pub fn is_cancelled(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+
+        /// Returns `Cancelled` once self has been cancelled, for the solvers to give up with `?`.
+        pub fn check(&self) -> Result<(), Cancelled> {
+            if self.is_cancelled() { Err(Cancelled) } else { Ok(()) }
+        }
+    }
+
+    /// The error of a solver that gave up because it was cancelled, see `Cancel`.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    pub struct Cancelled;
+
+    impl Display for Cancelled {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "cancelled")
+        }
+    }
+}
+
+pub mod explain {
+    use ::alloc::string::{String, ToString};
+    use ::alloc::vec::Vec;
+    use ::core::convert::TryInto;
+    use ::core::fmt::{self, Display, Write};
+
+    /// The value of an `Event` field.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    pub enum Value<'a> {
+        Int(i64),
+        Str(&'a str),
+        Bool(bool),
+    }
+
+    impl<'a> From<i64> for Value<'a> {
+        fn from(n: i64) -> Value<'a> {
+            Value::Int(n)
+        }
+    }
+
+    impl<'a> From<i32> for Value<'a> {
+        fn from(n: i32) -> Value<'a> {
+            Value::Int(n.into())
+        }
+    }
+
+    impl<'a> From<u32> for Value<'a> {
+        fn from(n: u32) -> Value<'a> {
+            Value::Int(n.into())
+        }
+    }
+
+    impl<'a> From<usize> for Value<'a> {
+        fn from(n: usize) -> Value<'a> {
+            // NOTE: no count nor position in an input comes anywhere close to i64::MAX.
+            Value::Int(n.try_into().unwrap_or(i64::MAX))
+        }
+    }
+
+    impl<'a> From<&'a str> for Value<'a> {
+        fn from(s: &'a str) -> Value<'a> {
+            Value::Str(s)
+        }
+    }
+
+    impl<'a> From<bool> for Value<'a> {
+        fn from(b: bool) -> Value<'a> {
+            Value::Bool(b)
+        }
+    }
+
+    /// A fact a solver found on its way to the answer, e.g. a decoy room along with the checksum
+    /// it should have had. Displayed as a single line JSON object, its `kind` being the `"event"`
+    /// field followed by the other `fields` in order.
+    #[derive(Copy, Clone, Debug)]
+    pub struct Event<'a> {
+        pub kind: &'static str,
+        pub fields: &'a [(&'static str, Value<'a>)],
+    }
+
+    impl<'a> Event<'a> {
+        /// Create a new `Event` of the given `kind` with the given `fields`.
+        pub fn new(kind: &'static str, fields: &'a [(&'static str, Value<'a>)]) -> Event<'a> {
+            Event { kind, fields }
+        }
+    }
+
+    impl<'a> Display for Event<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{{\"event\":{}", Json(self.kind))?;
+            for &(name, value) in self.fields {
+                write!(f, ",{}:", Json(name))?;
+                match value {
+                    Value::Int(n) => write!(f, "{}", n)?,
+                    Value::Str(s) => write!(f, "{}", Json(s))?,
+                    Value::Bool(b) => write!(f, "{}", b)?,
+                }
+            }
+            write!(f, "}}")
+        }
+    }
+
+    /// A string displayed as a JSON string literal.
+    struct Json<'a>(&'a str);
+
+    impl<'a> Display for Json<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_char('"')?;
+            for ch in self.0.chars() {
+                match ch {
+                    '"' => f.write_str("\\\"")?,
+                    '\\' => f.write_str("\\\\")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\t' => f.write_str("\\t")?,
+                    '\r' => f.write_str("\\r")?,
+                    ch if ch < ' ' => write!(f, "\\u{:04x}", ch as u32)?,
+                    ch => f.write_char(ch)?,
+                }
+            }
+            f.write_char('"')
+        }
+    }
+
+    /// Where the solvers send the `Event`s explaining them, e.g. the `--explain` of the days
+    /// binaries.
+    pub trait Sink {
+        /// Record the given `event`.
+        fn emit(&mut self, event: &Event);
+
+        /// Returns `false` when the events are discarded anyway, for the solvers to skip the
+        /// costly ones (e.g. a rendering) altogether. `true` by default.
+        fn enabled(&self) -> bool {
+            true
+        }
+    }
+
+    /// Discard every event.
+    impl Sink for () {
+        fn emit(&mut self, _: &Event) {}
+
+        fn enabled(&self) -> bool {
+            false
+        }
+    }
+
+    /// Collect the events as JSON lines, mostly for the tests.
+    impl Sink for Vec<String> {
+        fn emit(&mut self, event: &Event) {
+            self.push(event.to_string());
+        }
+    }
+}
+
+pub mod error {
+    use ::alloc::string::{String, ToString};
+    use ::core::fmt::{self, Display};
+
+    /// An input that doesn't name any of the things expected, e.g. an unknown operation.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Unrecognized {
+        /// What was expected, e.g. "operation".
+        pub what: &'static str,
+        /// The offending input.
+        pub input: String,
+    }
+
+    impl Unrecognized {
+        /// Create a new `Unrecognized` error for the given `input` that isn't a `what`.
+        pub fn new(what: &'static str, input: &str) -> Unrecognized {
+            Unrecognized { what, input: input.to_string() }
+        }
+    }
+
+    impl Display for Unrecognized {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "unrecognized {}: {}", self.what, self.input)
+        }
+    }
+
+    impl From<Unrecognized> for String {
+        fn from(e: Unrecognized) -> String {
+            e.to_string()
+        }
+    }
+
+    /// An error parsing a `Grid`, see `Grid::parse()`. `E` is the error of a bad cell.
+    ///
+    /// The rows and columns are counted from zero, empty lines aside.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum GridError<E> {
+        /// There is no cell at all.
+        Empty,
+        /// The `row` doesn't have the `width` cells of the first one.
+        Ragged { row: usize, width: usize },
+        /// The cell at `column` of `row` could not be converted.
+        Cell { row: usize, column: usize, error: E },
+    }
+
+    impl<E: Display> Display for GridError<E> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                GridError::Empty => write!(f, "empty grid"),
+                GridError::Ragged { row, width } => write!(f, "row {}: expected {} cells", row, width),
+                GridError::Cell { row, column, ref error } => write!(f, "row {}, column {}: {}", row, column, error),
+            }
+        }
+    }
+
+    impl<E: Display> From<GridError<E>> for String {
+        fn from(e: GridError<E>) -> String {
+            e.to_string()
+        }
+    }
+}
+
+pub mod span {
+    use ::core::fmt::{self, Display};
+    use ::core::ops::Range;
+
+    /// Where something is in a text: `len` characters from column `col` of line `line`, both
+    /// counted from 1.
+    ///
+    /// The parsers working line by line report spans on line 1, moved to the actual line by the
+    /// caller knowing it (see `on_line()`).
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    pub struct Span {
+        pub line: usize,
+        pub col: usize,
+        pub len: usize,
+    }
+
+    impl Span {
+        /// Create a new `Span` of `len` characters at column `col` of line `line`.
+        pub fn new(line: usize, col: usize, len: usize) -> Span {
+            Span { line, col, len }
+        }
+
+        /// Returns the `Span` of the bytes in `range` of `text`.
+        ///
+        /// The range is clamped to `text` and widened to whole characters. A range crossing
+        /// lines is cut at the end of its first line, and a range at the end of a line (e.g. an
+        /// "unexpected end of input") is the column following its last character.
+        pub fn of(text: &str, range: Range<usize>) -> Span {
+            let boundary = |i: usize| (0..=i.min(text.len())).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+            let start = boundary(range.start);
+            // NOTE: the end of a text ending with a newline is the end of its last line.
+            let start = if start == text.len() && text.ends_with('\n') { start - 1 } else { start };
+            let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+            let end = if range.end > range.start { boundary(range.end - 1) } else { start };
+            let end = text[end..].chars().next().map_or(end, |c| end + c.len_utf8()).clamp(start, line_end);
+            Span {
+                line: text[..line_start].matches('\n').count() + 1,
+                col: text[line_start..start].chars().count() + 1,
+                len: text[start..end].chars().count(),
+            }
+        }
+
+        /// Returns self moved to line `line`, for a span found in that line alone.
+        pub fn on_line(self, line: usize) -> Span {
+            Span { line, ..self }
+        }
+
+        /// Returns the line of `text` self is on, without its terminator. `None` when `text` has
+        /// fewer lines.
+        pub fn line_of<'a>(&self, text: &'a str) -> Option<&'a str> {
+            // NOTE: split() rather than lines() as an empty text still has an (empty) first line.
+            text.split('\n').nth(self.line.checked_sub(1)?).map(|line| line.strip_suffix('\r').unwrap_or(line))
+        }
+
+        /// Returns the rustc-style excerpt of `line`, the text of the line self is on, with the
+        /// characters of self underlined.
+        pub fn excerpt<'a>(&self, line: &'a str) -> Excerpt<'a> {
+            Excerpt { span: *self, line }
+        }
+    }
+
+    /// A line of input with a `Span` of it underlined by carets, see `Span::excerpt()`:
+    ///
+    /// ```text
+    ///   |
+    /// 3 | abba[mnOp]qrst
+    ///   |        ^
+    /// ```
+    #[derive(Copy, Clone, Debug)]
+    pub struct Excerpt<'a> {
+        span: Span,
+        line: &'a str,
+    }
+
+    impl<'a> Display for Excerpt<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let gutter = self.span.line.checked_ilog10().map_or(1, |digits| digits as usize + 1);
+            writeln!(f, "{:gutter$} |", "", gutter = gutter)?;
+            writeln!(f, "{} | {}", self.span.line, self.line)?;
+            write!(f, "{:gutter$} | ", "", gutter = gutter)?;
+            // NOTE: the tabs are kept so that the carets line up with the text above.
+            for c in self.line.chars().take(self.span.col.saturating_sub(1)) {
+                f.write_str(if c == '\t' { "\t" } else { " " })?;
+            }
+            for _ in 0..self.span.len.max(1) {
+                f.write_str("^")?;
+            }
+            Ok(())
+        }
+    }
+
+    /// An `error` along with the `Span` of the input it is about.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Spanned<E> {
+        pub span: Span,
+        pub error: E,
+    }
+
+    impl<E> Spanned<E> {
+        /// Create a new `Spanned` error at `span`.
+        pub fn new(span: Span, error: E) -> Spanned<E> {
+            Spanned { span, error }
+        }
+
+        /// Returns the `Spanned` error at the bytes in `range` of `text`, see `Span::of()`.
+        pub fn of(text: &str, range: Range<usize>, error: E) -> Spanned<E> {
+            Spanned { span: Span::of(text, range), error }
+        }
+
+        /// Returns self with its error converted by `f`, at the same span.
+        pub fn map<F, G: FnOnce(E) -> F>(self, f: G) -> Spanned<F> {
+            Spanned { span: self.span, error: f(self.error) }
+        }
+    }
+
+    impl<E: Display> Display for Spanned<E> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "line {}, column {}: {}", self.span.line, self.span.col, self.error)
+        }
+    }
+}
+
+pub mod input {
+    use ::alloc::borrow::Cow;
+    use ::alloc::string::String;
+
+    /// The byte order mark some editors write at the start of an UTF-8 file.
+    pub const BOM: char = '\u{feff}';
+
+    /// Normalize a puzzle input however it was saved, so that the parsers behave the same
+    /// everywhere: the leading byte order mark is removed, every line ends with "\n" (and not
+    /// "\r\n") without trailing whitespace and the blank lines at the end are removed. A
+    /// non-empty input always ends with a single "\n".
+    ///
+    /// Returns `text` itself when it is already normalized.
+    pub fn normalize(text: &str) -> Cow<'_, str> {
+        let body = text.strip_prefix(BOM).unwrap_or(text);
+        let mut normalized = String::with_capacity(body.len() + 1);
+        for line in body.lines() {
+            normalized.push_str(line.trim_end());
+            normalized.push('\n');
+        }
+        // NOTE: this drops the blank lines at the end along with the last line terminator.
+        normalized.truncate(normalized.trim_end().len());
+        if !normalized.is_empty() {
+            normalized.push('\n');
+        }
+        if normalized == text { Cow::Borrowed(text) } else { Cow::Owned(normalized) }
+    }
+}
+
+pub mod parse {
+    use ::checked::RangeError;
+    use ::point::Point;
+
+    /// Returns an iterator over the characters of `s` along with their `Point`, `(0, 0)` being the
+    /// first character of the first line with `x` growing rightward and `y` downward.
+    ///
+    /// A character too far for its coordinates to fit a `Point` is an error.
+    pub fn char_points(s: &str) -> impl Iterator<Item = Result<(Point, char), RangeError<usize>>> + '_ {
+        s.lines().enumerate().flat_map(|(y, line)| {
+            line.chars().enumerate().map(move |(x, c)| {
+                let x = ::checked::convert(x, "column")?;
+                let y = ::checked::convert(y, "row")?;
+                Ok((Point { x, y }, c))
+            })
+        })
+    }
+}
+
+
+#[cfg(test)]
+use alloc::string::String;
+#[cfg(test)]
+use alloc::vec::Vec;
+
+#[test]
+fn points() {
+    let p = Point::new(3, -4);
+    assert_eq!(p.snake_distance(&Point::default()), 7);
+    assert_eq!(p.snake_distance(&p), 0);
+    assert_eq!(Point::new(i32::MIN, 0).snake_distance(&Point::new(i32::MAX, 0)), u32::MAX);
+    assert_eq!(p.checked_offset(-3, 4), Some(Point::default()));
+    assert_eq!(Point::new(i32::MAX, 0).checked_offset(1, 0), None);
+    assert_eq!(alloc::format!("{}", p), "(3, -4)");
+}
+
+#[test]
+fn grids() {
+    let grid = Grid::parse("#.#\n\n..#\n", |c| if c == '#' || c == '.' { Ok(c == '#') } else { Err(c) }).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid.get(2, 1), Some(&true));
+    assert_eq!(grid.get(3, 0), None);
+    assert_eq!(grid.rows().map(|row| row.iter().filter(|&&lit| lit).count()).collect::<Vec<_>>(), [2, 1]);
+    let lit: Vec<(usize, usize)> = grid.iter().filter(|&(_, _, &lit)| lit).map(|(x, y, _)| (x, y)).collect();
+    assert_eq!(lit, [(0, 0), (2, 0), (2, 1)]);
+
+    let mut blank = Grid::new(2, 2, 0u8);
+    *blank.get_mut(1, 1).unwrap() = 7;
+    assert_eq!(blank.rows().collect::<Vec<_>>(), [&[0, 0], &[0, 7]]);
+    assert_eq!(Grid::new(0, 0, 0u8).rows().count(), 0);
+
+    let parse = |s| Grid::parse(s, |c| c.to_digit(10).ok_or(c)).map_err(String::from);
+    assert_eq!(parse("12\n3"), Err("row 1: expected 2 cells".into()));
+    assert_eq!(parse("12\n3x"), Err("row 1, column 1: x".into()));
+    assert_eq!(parse("\n\n"), Err("empty grid".into()));
+}
+
+#[test]
+fn fonts() {
+    let font = Font::parse("3x2\n\nI\n#\n#\n\nL\n#..\n##\n").unwrap();
+    assert_eq!((font.width(), font.height(), font.len()), (3, 2, 2));
+    let screen = ["#..####.", "#.....##"];
+    let lit = |x: usize, y: usize| screen[y].as_bytes()[x] == b'#';
+    assert_eq!(font.read(8, 2, lit).as_deref(), Some("I?L"));
+    assert_eq!(font.read(8, 3, lit), None);
+    assert_eq!(font.read(0, 2, lit).as_deref(), Some(""));
+    // an empty line within a glyph is a row of unlit pixels.
+    let font = Font::parse("\n2x3\n\n\nI\n#\n\n#\n").unwrap();
+    let screen = ["#.", "..", "#."];
+    assert_eq!(font.read(2, 3, |x, y| screen[y].as_bytes()[x] == b'#').as_deref(), Some("I"));
+
+    let error = |s| Font::parse(s).map_err(|e| alloc::format!("{}", e)).unwrap_err();
+    assert_eq!(error(""), "line 1, column 1: empty font");
+    assert_eq!(error("5x0\n"), "line 1, column 1: expected the glyph size, e.g. 5x6");
+    assert_eq!(error("2x1\nAB\n"), "line 2, column 1: expected the character of a glyph");
+    assert_eq!(error("2x1\nA\n#.#\n"), "line 3, column 1: glyph 'A': expected at most 2 pixels");
+    assert_eq!(error("2x1\nA\n#o\n"), "line 3, column 2: invalid pixel 'o'");
+    assert_eq!(error("2x2\nA\n#.\n"), "line 2, column 1: glyph 'A': expected 2 rows");
+    assert_eq!(error("2x1\nA\n#\nA\n.#\n"), "line 4, column 1: duplicate glyph 'A'");
+}
+
+#[test]
+fn cancellation() {
+    let cancel = Cancel::new();
+    let clone = cancel.clone();
+    assert_eq!(clone.check(), Ok(()));
+    cancel.cancel();
+    assert!(clone.is_cancelled());
+    assert_eq!(clone.check(), Err(Cancelled));
+    assert!(!Cancel::new().is_cancelled());
+}
+
+#[test]
+fn events() {
+    use explain::Value;
+    let mut sink: Vec<String> = Vec::new();
+    assert!(sink.enabled() && !().enabled());
+    sink.emit(&Event::new("room_rejected", &[
+        ("line", 3usize.into()),
+        ("name", "a-\"b\"\\c\n\u{1}é".into()),
+        ("sector_id", (-7).into()),
+        ("real", false.into()),
+    ]));
+    sink.emit(&Event::new("done", &[]));
+    assert_eq!(sink, [
+        r#"{"event":"room_rejected","line":3,"name":"a-\"b\"\\c\n\u0001é","sector_id":-7,"real":false}"#,
+        r#"{"event":"done"}"#,
+    ]);
+    assert_eq!(Value::from(usize::MAX), Value::Int(i64::MAX));
+}
+
+#[test]
+fn unrecognized() {
+    assert_eq!(String::from(Unrecognized::new("operation", "rect 3 by 2")), "unrecognized operation: rect 3 by 2");
+}
+
+#[test]
+fn spans() {
+    let text = "abc\ndéf\ngh\tij";
+    assert_eq!(Span::of(text, 0..1), Span::new(1, 1, 1));
+    assert_eq!(Span::of(text, 1..3), Span::new(1, 2, 2));
+    // multibyte characters are counted once, and a range inside one widened to all of it.
+    assert_eq!(Span::of(text, 6..7), Span::new(2, 2, 1));
+    assert_eq!(Span::of(text, 4..99), Span::new(2, 1, 3));
+    assert_eq!(Span::of(text, 3..3), Span::new(1, 4, 0));
+    assert_eq!(Span::of(text, 99..100), Span::new(3, 6, 0));
+    assert_eq!(Span::of("", 0..1), Span::new(1, 1, 0));
+    assert_eq!(Span::of("abc\n", 4..5), Span::new(1, 4, 0));
+    assert_eq!(Span::new(3, 4, 2).line_of(text), Some("gh\tij"));
+    assert_eq!(Span::new(4, 1, 1).line_of(text), None);
+    assert_eq!(Span::new(1, 2, 2).on_line(7), Span::new(7, 2, 2));
+
+    let excerpt = |span: Span| alloc::format!("{}", span.excerpt(span.line_of(text).unwrap()));
+    assert_eq!(excerpt(Span::new(1, 2, 2)), "  |\n1 | abc\n  |  ^^");
+    assert_eq!(excerpt(Span::new(3, 4, 2)), "  |\n3 | gh\tij\n  |   \t^^");
+    assert_eq!(excerpt(Span::new(1, 4, 0)), "  |\n1 | abc\n  |    ^");
+    let spanned = Spanned::of(text, 5..7, "unexpected `é`").map(String::from);
+    assert_eq!(alloc::format!("{}", spanned), "line 2, column 2: unexpected `é`");
+}
+
+#[test]
+fn normalize() {
+    use alloc::borrow::Cow;
+    assert!(matches!(input::normalize("R2, L3\n"), Cow::Borrowed("R2, L3\n")));
+    assert!(matches!(input::normalize(""), Cow::Borrowed("")));
+    assert_eq!(input::normalize("R2, L3"), "R2, L3\n");
+    assert_eq!(input::normalize("\u{feff}ULL\r\nRRDDD \r\n\r\n\n"), "ULL\nRRDDD\n");
+    assert_eq!(input::normalize("  1\t\n\n 234\n"), "  1\n\n 234\n");
+    assert_eq!(input::normalize(" \r\n\n"), "");
+}
+
+#[test]
+fn char_points() {
+    let points: Result<Vec<_>, _> = parse::char_points(" 1\n23").collect();
+    assert_eq!(points.unwrap(), [
+        (Point::new(0, 0), ' '),
+        (Point::new(1, 0), '1'),
+        (Point::new(0, 1), '2'),
+        (Point::new(1, 1), '3'),
+    ]);
+}
@@ -0,0 +1,504 @@
+//! The assembunny language and the virtual machine running it, shared by the days whose
+//! puzzles involve assembunny code.
+
+extern crate aoc_core;
+extern crate checked;
+
+use aoc_core::{Cancel, Cancelled};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+/// How many steps `Vm::run_cancellable()` executes between two checks of its `Cancel` token.
+const CANCEL_CHECK_STEPS: u32 = 1 << 16;
+
+/// The values held by the registers. The arithmetic wraps around on overflow, like a machine
+/// word.
+pub type Value = i64;
+
+/// One of the four registers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Register {
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Register {
+    /// Returns the position of this register in a `Registers` file.
+    fn index(self) -> usize {
+        match self {
+            Register::A => 0,
+            Register::B => 1,
+            Register::C => 2,
+            Register::D => 3,
+        }
+    }
+}
+
+impl FromStr for Register {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Register, String> {
+        match s {
+            "a" => Ok(Register::A),
+            "b" => Ok(Register::B),
+            "c" => Ok(Register::C),
+            "d" => Ok(Register::D),
+            _ => Err(format!("unrecognized register: {}", s)),
+        }
+    }
+}
+
+/// An instruction argument, either a register or an integer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Operand {
+    Register(Register),
+    Immediate(Value),
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Operand, String> {
+        match s.parse() {
+            Ok(value) => Ok(Operand::Immediate(value)),
+            Err(_) => s.parse().map(Operand::Register).map_err(|_| format!("bad operand: {}", s)),
+        }
+    }
+}
+
+/// An assembunny instruction.
+///
+/// Every operand may be an integer even when it doesn't make sense (eg. the destination of
+/// `cpy`), such instructions are skipped when executed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Instruction {
+    /// `cpy x y` copies `x` into the register `y`.
+    Cpy(Operand, Operand),
+    /// `inc x` increases the register `x` by one.
+    Inc(Operand),
+    /// `dec x` decreases the register `x` by one.
+    Dec(Operand),
+    /// `jnz x y` jumps `y` instructions away (forward or backward), but only if `x` is not zero.
+    Jnz(Operand, Operand),
+    /// `tgl x` toggles the instruction `x` away (forward or backward), see `toggled()`.
+    Tgl(Operand),
+    /// `out x` transmits `x` (the next value of the clock signal).
+    Out(Operand),
+}
+
+impl Instruction {
+    /// Returns the toggled version of this instruction:
+    ///
+    /// > - For one-argument instructions, `inc` becomes `dec`, and all other one-argument
+    /// >   instructions become `inc`.
+    /// > - For two-argument instructions, `jnz` becomes `cpy`, and all other two-instructions
+    /// >   become `jnz`.
+    pub fn toggled(self) -> Instruction {
+        match self {
+            Instruction::Inc(x) => Instruction::Dec(x),
+            Instruction::Dec(x) | Instruction::Tgl(x) | Instruction::Out(x) => Instruction::Inc(x),
+            Instruction::Jnz(x, y) => Instruction::Cpy(x, y),
+            Instruction::Cpy(x, y) => Instruction::Jnz(x, y),
+        }
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Instruction, String> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words.as_slice() {
+            ["cpy", x, y] => Ok(Instruction::Cpy(x.parse()?, y.parse()?)),
+            ["inc", x] => Ok(Instruction::Inc(x.parse()?)),
+            ["dec", x] => Ok(Instruction::Dec(x.parse()?)),
+            ["jnz", x, y] => Ok(Instruction::Jnz(x.parse()?, y.parse()?)),
+            ["tgl", x] => Ok(Instruction::Tgl(x.parse()?)),
+            ["out", x] => Ok(Instruction::Out(x.parse()?)),
+            _ => Err(format!("unrecognized instruction: {}", s)),
+        }
+    }
+}
+
+/// Parse an assembunny program, one instruction per line. Blank lines are ignored.
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, String> {
+    input.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse())
+        .collect()
+}
+
+/// The register file of a `Vm`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Registers([Value; 4]);
+
+impl Index<Register> for Registers {
+    type Output = Value;
+
+    fn index(&self, register: Register) -> &Value {
+        &self.0[register.index()]
+    }
+}
+
+impl IndexMut<Register> for Registers {
+    fn index_mut(&mut self, register: Register) -> &mut Value {
+        &mut self.0[register.index()]
+    }
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a={} b={} c={} d={}", self.0[0], self.0[1], self.0[2], self.0[3])
+    }
+}
+
+/// What stopped `Vm::run_until_output()`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Stop {
+    /// The program transmitted a value.
+    Output(Value),
+    /// The program is over.
+    Halted,
+    /// The maximum number of steps was reached.
+    StepLimit,
+}
+
+/// The infinite sequence of values transmitted by a program: `prefix` followed by `period`
+/// repeated forever.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signal {
+    pub prefix: Vec<Value>,
+    pub period: Vec<Value>,
+}
+
+/// Why `Vm::signal()` could not find a periodic signal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NotPeriodic {
+    /// The program is over after transmitting the given values.
+    Halted(Vec<Value>),
+    /// The program ran for the maximum number of steps without transmitting anything.
+    StepLimit,
+    /// The program transmitted the maximum number of values without repeating itself.
+    OutputLimit,
+}
+
+/// The virtual machine running an assembunny program.
+///
+/// The program may modify itself through `tgl`, so the `Vm` owns its copy.
+#[derive(Clone, Debug)]
+pub struct Vm {
+    program: Vec<Instruction>,
+    // the index of the next instruction to execute, may be out of the program bounds.
+    pc: isize,
+    registers: Registers,
+    // whether the add and multiply loops are recognized and executed at once.
+    optimize: bool,
+    // the value transmitted by the last step, if any.
+    output: Option<Value>,
+}
+
+impl Vm {
+    /// Create a new `Vm` ready to run `program` with all its registers set to zero.
+    pub fn new(program: Vec<Instruction>) -> Vm {
+        Vm { program, pc: 0, registers: Registers::default(), optimize: false, output: None }
+    }
+
+    /// Enable or disable the loop optimization, see `step()`.
+    pub fn optimize(&mut self, enabled: bool) {
+        self.optimize = enabled;
+    }
+
+    /// Returns the program, as modified by `tgl` so far.
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Returns the register file.
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// Returns the register file, mutably (eg. to initialize the registers before running).
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// Returns `true` when the program is over, i.e. it jumped out of its bounds.
+    pub fn is_halted(&self) -> bool {
+        ::checked::index(self.pc, self.program.len()).is_none()
+    }
+
+    /// Execute the next instruction. Returns `false` if the program was already over, `true`
+    /// otherwise.
+    ///
+    /// When the loop optimization is enabled and the next instructions are an add or multiply
+    /// loop, the whole loop is executed at once instead.
+    pub fn step(&mut self) -> bool {
+        self.output = None;
+        let pc = match ::checked::index(self.pc, self.program.len()) {
+            Some(pc) => pc,
+            None => return false,
+        };
+        if self.optimize && self.peephole(pc) {
+            return true;
+        }
+        let mut offset = 1;
+        match self.program[pc] {
+            Instruction::Cpy(x, Operand::Register(y)) => self.registers[y] = self.value(x),
+            Instruction::Inc(Operand::Register(x)) => self.registers[x] = self.registers[x].wrapping_add(1),
+            Instruction::Dec(Operand::Register(x)) => self.registers[x] = self.registers[x].wrapping_sub(1),
+            Instruction::Jnz(x, y) if self.value(x) != 0 => offset = self.value(y),
+            Instruction::Tgl(x) => {
+                let target = ::checked::offset(self.pc, self.value(x));
+                if let Some(target) = target.and_then(|target| ::checked::index(target, self.program.len())) {
+                    let instruction = &mut self.program[target];
+                    *instruction = instruction.toggled();
+                }
+            },
+            Instruction::Out(x) => self.output = Some(self.value(x)),
+            // invalid instructions (and `jnz` not jumping) do nothing.
+            _ => (),
+        }
+        // NOTE: a jump too far in either direction is out of the program, so it halts.
+        self.pc = ::checked::offset(self.pc, offset).unwrap_or(isize::MIN);
+        true
+    }
+
+    /// Execute at once the add or multiply loop starting at `pc`, the next instruction, if any.
+    /// Returns `true` if a loop was executed, `false` otherwise.
+    ///
+    /// The program is matched every time since `tgl` may have changed it. Loops are only
+    /// executed at once when their counters are positive, otherwise they would run until the
+    /// counter wraps around which is left to the (very slow) regular execution.
+    fn peephole(&mut self, pc: usize) -> bool {
+        use Instruction::*;
+        use Operand::Register as R;
+        use Operand::Immediate as I;
+
+        let code = &self.program[pc..];
+        // x += y, y = 0:
+        //
+        //     inc x        (or dec y)
+        //     dec y        (or inc x)
+        //     jnz y -2
+        let add = |code: &[Instruction]| match code {
+            [Inc(R(x)), Dec(R(y)), Jnz(R(z), I(-2)), ..] |
+            [Dec(R(y)), Inc(R(x)), Jnz(R(z), I(-2)), ..] if x != y && y == z => Some((*x, *y)),
+            _ => None,
+        };
+        // x += y * w, y = 0, w = 0:
+        //
+        //     cpy v y
+        //     inc x        (add loop)
+        //     dec y
+        //     jnz y -2
+        //     dec w
+        //     jnz w -5
+        if let [Cpy(v, R(y)), _, _, _, Dec(R(w)), Jnz(R(z), I(-5)), ..] = *code {
+            if let Some((x, counter)) = add(&code[1..]) {
+                let distinct = counter == y && w == z && x != w && y != w;
+                let source_is_stable = !matches!(v, R(r) if r == x || r == y || r == w);
+                let (v, count) = (self.value(v), self.registers[w]);
+                if distinct && source_is_stable && v > 0 && count > 0 {
+                    self.registers[x] = self.registers[x].wrapping_add(v.wrapping_mul(count));
+                    self.registers[y] = 0;
+                    self.registers[w] = 0;
+                    self.pc += 6;
+                    return true;
+                }
+            }
+        }
+        if let Some((x, y)) = add(code) {
+            if self.registers[y] > 0 {
+                self.registers[x] = self.registers[x].wrapping_add(self.registers[y]);
+                self.registers[y] = 0;
+                self.pc += 3;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Run the program until it is over and returns the final register file.
+    pub fn run(&mut self) -> &Registers {
+        while self.step() {}
+        &self.registers
+    }
+
+    /// Run the program until it is over and returns the final register file, or until `cancel`
+    /// is cancelled (e.g. a program looping forever given a `--timeout`).
+    pub fn run_cancellable(&mut self, cancel: &Cancel) -> Result<&Registers, Cancelled> {
+        loop {
+            cancel.check()?;
+            for _ in 0..CANCEL_CHECK_STEPS {
+                if !self.step() {
+                    return Ok(&self.registers);
+                }
+            }
+        }
+    }
+
+    /// Run the program until it transmits a value, is over, or `max_steps` steps have been
+    /// executed.
+    pub fn run_until_output(&mut self, max_steps: u64) -> Stop {
+        for _ in 0..max_steps {
+            if !self.step() {
+                return Stop::Halted;
+            }
+            if let Some(value) = self.output {
+                return Stop::Output(value);
+            }
+        }
+        Stop::StepLimit
+    }
+
+    /// Run the program until its output is known to repeat forever, trying at most
+    /// `max_steps` steps for each value and `max_outputs` values.
+    ///
+    /// The whole machine state (including the program, because of `tgl`) is recorded at each
+    /// transmission: once a state is seen again, the values transmitted since are the period.
+    pub fn signal(&mut self, max_steps: u64, max_outputs: usize) -> Result<Signal, NotPeriodic> {
+        let mut values = Vec::new();
+        let mut seen: HashMap<(isize, Registers, Vec<Instruction>), usize> = HashMap::new();
+        while values.len() < max_outputs {
+            match self.run_until_output(max_steps) {
+                Stop::Output(value) => values.push(value),
+                Stop::Halted => return Err(NotPeriodic::Halted(values)),
+                Stop::StepLimit => return Err(NotPeriodic::StepLimit),
+            }
+            let state = (self.pc, self.registers, self.program.clone());
+            if let Some(&start) = seen.get(&state) {
+                let period = values.split_off(start);
+                return Ok(Signal { prefix: values, period });
+            }
+            seen.insert(state, values.len());
+        }
+        Err(NotPeriodic::OutputLimit)
+    }
+
+    /// Returns the value of the given operand.
+    fn value(&self, operand: Operand) -> Value {
+        match operand {
+            Operand::Register(register) => self.registers[register],
+            Operand::Immediate(value) => value,
+        }
+    }
+}
+
+
+#[test]
+fn parse() {
+    assert_eq!("cpy 41 a".parse(), Ok(Instruction::Cpy(Operand::Immediate(41), Operand::Register(Register::A))));
+    assert_eq!("jnz c -2".parse(), Ok(Instruction::Jnz(Operand::Register(Register::C), Operand::Immediate(-2))));
+    assert_eq!("cpy 1 2".parse(), Ok(Instruction::Cpy(Operand::Immediate(1), Operand::Immediate(2))));
+    assert!("cpy a".parse::<Instruction>().is_err());
+    assert!("inc e".parse::<Instruction>().is_err());
+    assert!("mul a b".parse::<Instruction>().is_err());
+}
+
+#[test]
+fn run() {
+    let program = parse_program("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a\n").unwrap();
+    let mut vm = Vm::new(program);
+    assert_eq!(vm.run()[Register::A], 42);
+    assert!(vm.is_halted());
+    assert!(!vm.step());
+}
+
+#[test]
+fn invalid_instructions_are_skipped() {
+    let program = parse_program("cpy 1 2\ninc 3\ninc a\njnz 0 -3").unwrap();
+    let mut vm = Vm::new(program);
+    assert_eq!(vm.run().to_string(), "a=1 b=0 c=0 d=0");
+}
+
+#[test]
+fn toggle() {
+    let program = parse_program("cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a").unwrap();
+    let mut vm = Vm::new(program);
+    assert_eq!(vm.run()[Register::A], 3);
+    assert_eq!(vm.program()[3], "inc a".parse().unwrap());
+    assert_eq!(vm.program()[4], "jnz 1 a".parse().unwrap());
+    // toggling out of the program does nothing.
+    let mut vm = Vm::new(parse_program("tgl 10\ninc a").unwrap());
+    assert_eq!(vm.run()[Register::A], 1);
+    assert_eq!("tgl b".parse::<Instruction>().unwrap().toggled(), "inc b".parse().unwrap());
+}
+
+#[test]
+fn overflows() {
+    // jumping or toggling too far away is out of the program.
+    let mut vm = Vm::new(parse_program("inc a\njnz 1 9223372036854775807\ninc a").unwrap());
+    assert_eq!(vm.run()[Register::A], 1);
+    let mut vm = Vm::new(parse_program("inc a\ntgl -9223372036854775808\ninc a").unwrap());
+    assert_eq!(vm.run()[Register::A], 2);
+    // the registers wrap around, with or without the loop optimization.
+    let source = "cpy 9223372036854775807 a\ncpy 3 b\ninc a\ndec b\njnz b -2";
+    for &optimize in [false, true].iter() {
+        let mut vm = Vm::new(parse_program(source).unwrap());
+        vm.optimize(optimize);
+        assert_eq!(vm.run()[Register::A], i64::MIN + 2);
+    }
+}
+
+#[test]
+fn loop_optimization() {
+    let source = "cpy 7 d\ncpy 3 b\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5\ndec b\ninc d\njnz b -2\ncpy 5 c\ninc a\njnz 0 0";
+    let program = parse_program(source).unwrap();
+    let mut slow = Vm::new(program.clone());
+    let mut fast = Vm::new(program);
+    fast.optimize(true);
+    let mut steps = 0;
+    while fast.step() {
+        steps += 1;
+    }
+    assert_eq!(fast.registers(), slow.run());
+    assert_eq!(fast.registers().to_string(), "a=22 b=0 c=5 d=3");
+    // two cpy, the multiply loop, the add loop and the three last instructions.
+    assert_eq!(steps, 7);
+
+    // non-positive counters are left to the regular execution.
+    let mut vm = Vm::new(parse_program("cpy 0 b\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5").unwrap());
+    vm.optimize(true);
+    vm.registers_mut()[Register::D] = 1;
+    assert!(vm.step() && vm.step() && vm.step());
+    assert_eq!(vm.registers()[Register::A], 1);
+}
+
+#[test]
+fn signal() {
+    // transmits 1, then 0, 2, 0, 2, ...
+    let program = parse_program("out 1\ncpy 2 a\nout b\nout a\njnz 1 -2").unwrap();
+    let mut vm = Vm::new(program);
+    assert_eq!(vm.run_until_output(10), Stop::Output(1));
+    assert_eq!(vm.run_until_output(1), Stop::StepLimit);
+    // the state after transmitting a value determines the next ones, so the period starts
+    // after the first value of the repeating state.
+    assert_eq!(vm.signal(10, 100), Ok(Signal { prefix: vec![0], period: vec![2, 0] }));
+    let mut vm = Vm::new(parse_program("out 1\ncpy 2 a\nout b\nout a\njnz 1 -2").unwrap());
+    assert_eq!(vm.signal(10, 100), Ok(Signal { prefix: vec![1, 0], period: vec![2, 0] }));
+
+    let mut vm = Vm::new(parse_program("out 1\nout 2").unwrap());
+    assert_eq!(vm.signal(10, 100), Err(NotPeriodic::Halted(vec![1, 2])));
+    let mut vm = Vm::new(parse_program("inc a\nout a\njnz 1 -2").unwrap());
+    assert_eq!(vm.signal(10, 100), Err(NotPeriodic::OutputLimit));
+    let mut vm = Vm::new(parse_program("jnz 1 0").unwrap());
+    assert_eq!(vm.signal(10, 100), Err(NotPeriodic::StepLimit));
+}
+
+#[test]
+fn cancellation() {
+    let cancel = Cancel::new();
+    let mut vm = Vm::new(parse_program("cpy 41 a\ninc a").unwrap());
+    assert_eq!(vm.run_cancellable(&cancel).map(|registers| registers[Register::A]), Ok(42));
+    // an infinite loop only stops once cancelled.
+    let mut vm = Vm::new(parse_program("inc a\njnz 1 -1").unwrap());
+    cancel.cancel();
+    assert_eq!(vm.run_cancellable(&cancel), Err(Cancelled));
+    assert!(!vm.is_halted());
+}
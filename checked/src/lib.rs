@@ -0,0 +1,78 @@
+//! Checked numeric conversions shared by the parsers and solvers.
+//!
+//! A bare `as` cast silently wraps or truncates a value that doesn't fit, so that an
+//! adversarial input (a huge keypad, a far away jump…) would corrupt the solver state instead of
+//! being reported. These helpers return a `RangeError` instead. Like the algorithms using them,
+//! they only need `core` and `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use core::convert::TryFrom;
+use core::fmt::{self, Display};
+
+/// A value out of the range of the type it was converted to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RangeError<U> {
+    /// What the value is, e.g. "keypad column".
+    pub what: &'static str,
+    /// The offending value.
+    pub value: U,
+}
+
+impl<U: Display> Display for RangeError<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} out of range: {}", self.what, self.value)
+    }
+}
+
+impl<U: Display> From<RangeError<U>> for String {
+    fn from(e: RangeError<U>) -> String {
+        e.to_string()
+    }
+}
+
+/// Convert `value` into a `T`, `what` naming the value in the error when it doesn't fit.
+pub fn convert<T: TryFrom<U>, U: Copy>(value: U, what: &'static str) -> Result<T, RangeError<U>> {
+    T::try_from(value).map_err(|_| RangeError { what, value })
+}
+
+/// Returns `value` as an index into a slice of `len` items, `None` when it is negative, too
+/// large or out of bounds.
+pub fn index<U>(value: U, len: usize) -> Option<usize> where usize: TryFrom<U> {
+    usize::try_from(value).ok().filter(|&i| i < len)
+}
+
+/// Returns `base` moved by `offset`, `None` on overflow, e.g. for a jump relative to a program
+/// counter.
+pub fn offset(base: isize, offset: i64) -> Option<isize> {
+    isize::try_from(offset).ok().and_then(|offset| base.checked_add(offset))
+}
+
+
+#[test]
+fn conversions() {
+    assert_eq!(convert::<i32, usize>(42, "column"), Ok(42));
+    assert_eq!(convert::<u16, usize>(70_000, "width"), Err(RangeError { what: "width", value: 70_000 }));
+    assert_eq!(convert::<u8, i32>(-1, "byte").unwrap_err().to_string(), "byte out of range: -1");
+    assert_eq!(String::from(RangeError { what: "row", value: 3_000_000_000usize }),
+               "row out of range: 3000000000");
+}
+
+#[test]
+fn indices() {
+    assert_eq!(index(2isize, 3), Some(2));
+    assert_eq!(index(3isize, 3), None);
+    assert_eq!(index(-1isize, 3), None);
+    assert_eq!(index(7u32, 8), Some(7));
+}
+
+#[test]
+fn offsets() {
+    assert_eq!(offset(5, -2), Some(3));
+    assert_eq!(offset(5, -7), Some(-2));
+    assert_eq!(offset(isize::MAX, 1), None);
+    assert_eq!(offset(1, i64::MIN), Some(isize::MIN + 1));
+}
@@ -0,0 +1,215 @@
+//! Golden answers harness: run every day solver on its real puzzle input and check that the
+//! expected answers are still found, see `tests/answers.rs`. The days having several
+//! implementations are also run differentially, every variant against the same inputs, see
+//! `tests/differential.rs`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The expected answer to one part of a day puzzle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Answer {
+    pub day: u32,
+    pub part: u32,
+    pub expected: String,
+}
+
+/// Parse an answers file, made of one "DAY PART ANSWER" per line. Empty lines and lines
+/// starting with `#` are ignored.
+pub fn parse_answers(s: &str) -> Result<Vec<Answer>, String> {
+    let mut answers = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (day, part, expected) = match fields[..] {
+            [day, part, expected] => (day, part, expected),
+            _ => return Err(format!("line {}: expected \"DAY PART ANSWER\", got {:?}", i + 1, line)),
+        };
+        let day = day.parse().map_err(|_| format!("line {}: invalid day: {}", i + 1, day))?;
+        let part = match part {
+            "1" => 1,
+            "2" => 2,
+            _ => return Err(format!("line {}: invalid part: {}", i + 1, part)),
+        };
+        answers.push(Answer { day, part, expected: expected.to_string() });
+    }
+    Ok(answers)
+}
+
+/// Group `answers` by day, in day order.
+pub fn by_day(answers: &[Answer]) -> BTreeMap<u32, Vec<&Answer>> {
+    let mut days = BTreeMap::new();
+    for answer in answers {
+        days.entry(answer.day).or_insert_with(Vec::new).push(answer);
+    }
+    days
+}
+
+/// Returns the directory of the given `day` crate in `root`, e.g. "Day 04 - Security Through
+/// Obscurity" for the day 4.
+pub fn day_dir(root: &Path, day: u32) -> Option<PathBuf> {
+    let prefix = format!("Day {:02} - ", day);
+    fs::read_dir(root).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(&prefix)))
+}
+
+/// Returns the puzzle input path for `day`: "dayNN.txt" in `inputs` when given, otherwise the
+/// day directory "input.txt".
+pub fn input_path(day_dir: &Path, inputs: Option<&Path>, day: u32) -> PathBuf {
+    match inputs {
+        Some(dir) => dir.join(format!("day{:02}.txt", day)),
+        None => day_dir.join("input.txt"),
+    }
+}
+
+/// Build and run the solver of the crate in `day_dir` using `cargo`, feeding it `input` on
+/// stdin. Returns its answers, see `quiet_answers()`.
+pub fn run_solver(cargo: &Path, day_dir: &Path, input: &[u8]) -> Result<Vec<String>, String> {
+    run_solver_with(cargo, day_dir, input, &[], &["--quiet"])
+        .map(|(stdout, _)| quiet_answers(&stdout).into_iter().map(String::from).collect())
+}
+
+/// Like `run_solver()`, building the solver with the given cargo `features`. Returns both its
+/// standard output and standard error.
+pub fn run_solver_with_features(cargo: &Path, day_dir: &Path, input: &[u8], features: &[&str])
+    -> Result<(String, String), String>
+{
+    run_solver_with(cargo, day_dir, input, features, &[])
+}
+
+/// Like `run_solver_with_features()`, passing `args` to the solver.
+pub fn run_solver_with(cargo: &Path, day_dir: &Path, input: &[u8], features: &[&str], args: &[&str])
+    -> Result<(String, String), String>
+{
+    let mut command = Command::new(cargo);
+    command.args(["run", "--release", "--quiet"]);
+    if !features.is_empty() {
+        command.args(["--features", &features.join(",")]);
+    }
+    if !args.is_empty() {
+        command.arg("--").args(args);
+    }
+    let mut child = command
+        .current_dir(day_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", cargo.display(), e))?;
+    // NOTE: some solvers only read the first line, so they may exit before reading it all.
+    let _ = child.stdin.take().unwrap().write_all(input);
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("{}\n{}", output.status, String::from_utf8_lossy(&output.stderr)));
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
+    Ok((stdout, String::from_utf8_lossy(&output.stderr).into_owned()))
+}
+
+/// One of the implementations of a day solver, selected by cargo features and command line
+/// arguments.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Variant {
+    pub name: &'static str,
+    pub features: &'static [&'static str],
+    pub args: &'static [&'static str],
+}
+
+/// The days having more than one implementation in this repository along with their variants,
+/// the first one being the reference the others are compared to.
+pub const VARIANTS: &[(u32, &[Variant])] = &[
+    (5, &[
+        Variant { name: "scalar", features: &[], args: &[] },
+        Variant { name: "simd", features: &["simd"], args: &[] },
+    ]),
+    (7, &[
+        Variant { name: "windows", features: &[], args: &["--list-tls", "--list-ssl", "windows"] },
+        Variant { name: "char-queue", features: &[], args: &["--list-tls", "--list-ssl", "char-queue"] },
+        Variant { name: "simd", features: &["simd"], args: &["--list-tls", "--list-ssl", "simd"] },
+    ]),
+    (14, &[
+        Variant { name: "sequential", features: &[], args: &[] },
+        Variant { name: "parallel", features: &["parallel"], args: &[] },
+    ]),
+];
+
+/// Returns the first line where `expected` and `got` differ, as its 1-based number along with
+/// both lines (empty past the end of an output), or `None` when they are the same.
+pub fn first_difference<'a>(expected: &'a str, got: &'a str) -> Option<(usize, &'a str, &'a str)> {
+    let (mut expected, mut got) = (expected.lines(), got.lines());
+    let mut line = 1;
+    loop {
+        match (expected.next(), got.next()) {
+            (None, None) => return None,
+            (a, b) if a == b => line += 1,
+            (a, b) => return Some((line, a.unwrap_or(""), b.unwrap_or(""))),
+        }
+    }
+}
+
+/// Returns the answers printed by a solver given `--quiet`, the answer to the part N being the
+/// Nth one.
+pub fn quiet_answers(output: &str) -> Vec<&str> {
+    output.lines().map(str::trim_end).collect()
+}
+
+
+#[test]
+fn answers_file() {
+    let answers = parse_answers("# comment\n\n01 1 242\n 8 2 EFEYKFRFIJ \n").unwrap();
+    assert_eq!(answers, vec![
+        Answer { day: 1, part: 1, expected: "242".to_string() },
+        Answer { day: 8, part: 2, expected: "EFEYKFRFIJ".to_string() },
+    ]);
+    assert!(parse_answers("01 1").is_err());
+    assert!(parse_answers("01 3 242").is_err());
+    assert!(parse_answers("first 1 242").is_err());
+    assert_eq!(by_day(&answers).keys().cloned().collect::<Vec<_>>(), vec![1, 8]);
+}
+
+#[test]
+fn quiet_output() {
+    assert_eq!(quiet_answers("53255\n7423A\n"), ["53255", "7423A"]);
+    assert_eq!(quiet_answers("161\r\n"), ["161"]);
+    assert!(quiet_answers("").is_empty());
+}
+
+#[test]
+fn the_repository_answers_file_is_valid() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let answers = parse_answers(&fs::read_to_string(root.join("answers.txt")).unwrap()).unwrap();
+    for (day, answers) in by_day(&answers) {
+        assert!(day_dir(root.parent().unwrap(), day).is_some(), "no crate for day {}", day);
+        assert_eq!(answers.len(), 2);
+    }
+}
+
+#[test]
+fn output_differences() {
+    assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+    assert_eq!(first_difference("a\nb\n", "a\nc\n"), Some((2, "b", "c")));
+    assert_eq!(first_difference("a\n", "a\nb\n"), Some((2, "", "b")));
+    assert_eq!(first_difference("a\nb", "a"), Some((2, "b", "")));
+    assert_eq!(first_difference("", ""), None);
+}
+
+#[test]
+fn the_variants_are_runnable() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    for &(day, variants) in VARIANTS {
+        let dir = day_dir(root, day).unwrap_or_else(|| panic!("no crate for day {}", day));
+        let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(variants.len() > 1, "day {} has a single variant", day);
+        for feature in variants.iter().flat_map(|variant| variant.features) {
+            assert!(manifest.contains(&format!("\n{} = ", feature)), "day {}: no {} feature", day, feature);
+        }
+    }
+}
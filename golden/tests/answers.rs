@@ -0,0 +1,59 @@
+// Check every day solver against its real puzzle input and the expected answers, the solvers
+// being run with `--quiet` so that their answers are read line by line.
+//
+// By default the inputs are the "input.txt" file of each day directory and the answers are read
+// from "answers.txt" in this crate, use:
+//
+//     AOC_INPUTS=/path/to/inputs AOC_ANSWERS=/path/to/answers.txt cargo test
+//
+// to check other inputs, where the inputs directory contains one "dayNN.txt" file per day.
+extern crate golden;
+
+use golden::*;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn answers() {
+    let here = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = here.parent().unwrap();
+    let answers_path = env::var_os("AOC_ANSWERS").map_or_else(|| here.join("answers.txt"), PathBuf::from);
+    let inputs = env::var_os("AOC_INPUTS").map(PathBuf::from);
+    // NOTE: run the solvers with the cargo running us, so that they use the same toolchain.
+    let cargo = env::var_os("CARGO").map_or_else(|| PathBuf::from("cargo"), PathBuf::from);
+
+    let text = fs::read_to_string(&answers_path)
+        .unwrap_or_else(|e| panic!("{}: {}", answers_path.display(), e));
+    let answers = parse_answers(&text).unwrap_or_else(|e| panic!("{}: {}", answers_path.display(), e));
+    let mut failures = Vec::new();
+    for (day, answers) in by_day(&answers) {
+        let dir = match day_dir(root, day) {
+            Some(dir) => dir,
+            None => {
+                failures.push(format!("day {}: no solver", day));
+                continue;
+            },
+        };
+        let input_path = input_path(&dir, inputs.as_deref(), day);
+        let input = match fs::read(&input_path) {
+            Ok(input) => input,
+            Err(e) => {
+                failures.push(format!("day {}: {}: {}", day, input_path.display(), e));
+                continue;
+            },
+        };
+        match run_solver(&cargo, &dir, &input) {
+            Ok(found) => {
+                for answer in answers {
+                    let got = found.get(answer.part as usize - 1).map_or("nothing", String::as_str);
+                    if got != answer.expected {
+                        failures.push(format!("day {} part {}: expected {}, got {}", day, answer.part, answer.expected, got));
+                    }
+                }
+            },
+            Err(e) => failures.push(format!("day {}: {}", day, e)),
+        }
+    }
+    assert!(failures.is_empty(), "{} failure(s):\n{}", failures.len(), failures.join("\n"));
+}
@@ -0,0 +1,78 @@
+// Run every implementation of the days having several of them against the same corpus and report
+// any divergence from the reference (first) variant, see `golden::VARIANTS`.
+//
+// The corpus of a day is its real puzzle input when there is one and inputs generated with
+// `--generate SIZE SEED`, one per seed in 1..=AOC_GENERATED (1 by default):
+//
+//     AOC_GENERATED=5 AOC_INPUTS=/path/to/inputs cargo +nightly test --test differential
+//
+// The Day 7 crate needs a nightly toolchain, it is run with the cargo running us like the other
+// solvers.
+extern crate golden;
+
+use golden::*;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The size of the generated inputs.
+const SIZE: usize = 1000;
+
+#[test]
+fn divergence() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let inputs = env::var_os("AOC_INPUTS").map(PathBuf::from);
+    let generated: u64 = env::var("AOC_GENERATED").map_or(1, |n| n.parse().expect("AOC_GENERATED"));
+    // NOTE: run the solvers with the cargo running us, so that they use the same toolchain.
+    let cargo = env::var_os("CARGO").map_or_else(|| PathBuf::from("cargo"), PathBuf::from);
+
+    let mut failures = Vec::new();
+    for &(day, variants) in VARIANTS {
+        let dir = match day_dir(root, day) {
+            Some(dir) => dir,
+            None => {
+                failures.push(format!("day {}: no solver", day));
+                continue;
+            },
+        };
+        let (reference, others) = variants.split_first().unwrap();
+        let mut corpus = Vec::new();
+        let input_path = input_path(&dir, inputs.as_deref(), day);
+        // NOTE: some days have no real puzzle input, their corpus is only made of generated ones.
+        if input_path.exists() {
+            match fs::read(&input_path) {
+                Ok(input) => corpus.push((input_path.display().to_string(), input)),
+                Err(e) => failures.push(format!("day {}: {}: {}", day, input_path.display(), e)),
+            }
+        }
+        for seed in 1..=generated {
+            let (size, seed) = (SIZE.to_string(), seed.to_string());
+            match run_solver_with(&cargo, &dir, b"", &[], &["--generate", &size, &seed]) {
+                Ok((input, _)) => corpus.push((format!("--generate {} {}", size, seed), input.into_bytes())),
+                Err(e) => failures.push(format!("day {}: --generate {} {}: {}", day, size, seed, e)),
+            }
+        }
+        for (name, input) in &corpus {
+            let run = |variant: &Variant| run_solver_with(&cargo, &dir, input, variant.features, variant.args);
+            let expected = match run(reference) {
+                Ok((output, _)) => output,
+                Err(e) => {
+                    failures.push(format!("day {} {} on {}: {}", day, reference.name, name, e));
+                    continue;
+                },
+            };
+            for variant in others {
+                match run(variant) {
+                    Ok((output, _)) => {
+                        if let Some((line, a, b)) = first_difference(&expected, &output) {
+                            failures.push(format!("day {} {} on {}: line {}: {} got {:?}, {} got {:?}",
+                                                  day, variant.name, name, line, reference.name, a, variant.name, b));
+                        }
+                    },
+                    Err(e) => failures.push(format!("day {} {} on {}: {}", day, variant.name, name, e)),
+                }
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{} divergence(s):\n{}", failures.len(), failures.join("\n"));
+}
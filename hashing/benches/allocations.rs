@@ -0,0 +1,19 @@
+//! Allocations made by the Day 5 hash search, see `memory::PeakAllocator`. Unlike the timings
+//! these are exact, so they are simply printed:
+//!
+//!     cargo bench --bench allocations
+
+extern crate hashing;
+extern crate memory;
+
+use hashing::InterestingHashFinder;
+
+#[global_allocator]
+static ALLOCATOR: memory::PeakAllocator = memory::PeakAllocator::new();
+
+fn main() {
+    let mut finder = InterestingHashFinder::new("abc").unwrap();
+    let (hash, count) = ALLOCATOR.count_allocations(|| finder.next());
+    assert!(hash.is_some());
+    println!("day05/next: {} allocations for {} indices hashed", count, finder.index());
+}
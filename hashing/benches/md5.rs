@@ -0,0 +1,70 @@
+//! Day 5 hash throughput: the password cracker spends all of its time hashing
+//! `door_id + index` and checking the leading zeroes.
+
+#[macro_use]
+extern crate criterion;
+extern crate hashing;
+
+use criterion::{Criterion, Throughput};
+use hashing::{to_decimal, to_hex, Md5};
+use std::hint::black_box;
+
+/// Number of indices hashed per iteration.
+const BATCH: u64 = 10_000;
+
+fn digest(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day05");
+    group.throughput(Throughput::Elements(BATCH));
+    group.bench_function("digest", |b| {
+        let mut md5 = Md5::new().unwrap();
+        let mut digits = [0; 20];
+        b.iter(|| {
+            let mut interesting = 0;
+            for index in 0..BATCH {
+                let hash = md5.digest(&[b"abc", to_decimal(index, &mut digits)]).unwrap();
+                if (hash[0] | hash[1] | (hash[2] & 0xf0)) == 0 {
+                    interesting += 1;
+                }
+            }
+            black_box(interesting)
+        })
+    });
+    group.bench_function("digest+hex", |b| {
+        let mut md5 = Md5::new().unwrap();
+        let mut digits = [0; 20];
+        b.iter(|| {
+            let mut checksum = 0u8;
+            for index in 0..BATCH {
+                let hash = md5.digest(&[b"abc", to_decimal(index, &mut digits)]).unwrap();
+                checksum ^= to_hex(&hash)[0];
+            }
+            black_box(checksum)
+        })
+    });
+    #[cfg(feature = "simd")]
+    group.bench_function("digest_batch", |b| {
+        use hashing::simd::{digest_batch, BATCH as LANES};
+
+        let mut messages = [[0u8; 23]; LANES];
+        b.iter(|| {
+            let mut interesting = 0;
+            for index in (0..BATCH).step_by(LANES) {
+                let mut lens = [0; LANES];
+                for (i, (message, len)) in messages.iter_mut().zip(&mut lens).enumerate() {
+                    let mut digits = [0; 20];
+                    let digits = to_decimal(index + i as u64, &mut digits);
+                    message[..3].copy_from_slice(b"abc");
+                    message[3..3 + digits.len()].copy_from_slice(digits);
+                    *len = 3 + digits.len();
+                }
+                let hashes = digest_batch(&std::array::from_fn(|i| &messages[i][..lens[i]]));
+                interesting += hashes.iter().filter(|hash| (hash[0] | hash[1] | (hash[2] & 0xf0)) == 0).count();
+            }
+            black_box(interesting)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, digest);
+criterion_main!(benches);
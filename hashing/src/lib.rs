@@ -0,0 +1,453 @@
+//! MD5 hashing helpers shared by the days whose puzzles involve hashes.
+
+extern crate openssl;
+#[macro_use]
+extern crate tracing;
+
+use openssl::hash::{Hasher, MessageDigest};
+
+pub use openssl::error::ErrorStack;
+
+/// A reusable MD5 hasher.
+pub struct Md5 {
+    hasher: Hasher,
+}
+
+impl Md5 {
+    /// Create a new `Md5` hasher.
+    pub fn new() -> Result<Md5, ErrorStack> {
+        Ok(Md5 { hasher: Hasher::new(MessageDigest::md5())? })
+    }
+
+    /// Returns the digest of the concatenation of `parts`.
+    pub fn digest(&mut self, parts: &[&[u8]]) -> Result<[u8; 16], ErrorStack> {
+        for part in parts {
+            self.hasher.update(part)?;
+        }
+        // NOTE: finish() will reset the hasher state so we can reuse it later on.
+        let hash = self.hasher.finish()?;
+        let mut digest = [0; 16];
+        digest.copy_from_slice(&hash);
+        Ok(digest)
+    }
+}
+
+/// Returns the lowercase hexadecimal representation of `digest`, as ASCII bytes.
+pub fn to_hex(digest: &[u8; 16]) -> [u8; 32] {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = [0; 32];
+    for (i, byte) in digest.iter().enumerate() {
+        hex[2 * i] = DIGITS[(byte >> 4) as usize];
+        hex[2 * i + 1] = DIGITS[(byte & 0xf) as usize];
+    }
+    hex
+}
+
+/// Returns the decimal representation of `n` as ASCII bytes, written at the end of `buf`.
+///
+/// This is `n.to_string()` without the allocation, as the hashed messages end with an index.
+pub fn to_decimal(n: u64, buf: &mut [u8; 20]) -> &[u8] {
+    let (mut n, mut start) = (n, buf.len());
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return &buf[start..];
+        }
+    }
+}
+
+/// Writes the nonce of an index at the end of a message, see `InterestingHashFinder::with_nonce()`.
+type Nonce<'a> = Box<dyn Fn(u64, &mut Vec<u8>) + 'a>;
+
+/// Iterator over the interesting hashes of door_id, i.e. the MD5 of the door ID followed by an
+/// index nonce.
+///
+/// By default every index is searched starting at zero and the nonce is the index in decimal,
+/// like the Day 5 puzzle. `starting_at()`, `with_step()` and `with_nonce()` change that.
+pub struct InterestingHashFinder<'a> {
+    door_id: &'a [u8],
+    index: u64,
+    step: u64,
+    /// `None` for the index in decimal, which is the only one hashed in batches.
+    nonce: Option<Nonce<'a>>,
+    message: Vec<u8>,
+    md5: Md5,
+}
+
+impl<'a> InterestingHashFinder<'a> {
+    /// Create a new `InterestingHashFinder` for a given door.
+    pub fn new(door_id: &'a str) -> Option<InterestingHashFinder<'a>> {
+        Some(InterestingHashFinder {
+            door_id: door_id.as_bytes(),
+            index: 0,
+            step: 1,
+            nonce: None,
+            message: Vec::new(),
+            md5: Md5::new().ok()?,
+        })
+    }
+
+    /// Start the search at `index` instead of zero.
+    pub fn starting_at(mut self, index: u64) -> InterestingHashFinder<'a> {
+        self.index = index;
+        self
+    }
+
+    /// Search every `step` index instead of every index, e.g. to share the search between
+    /// `step` finders starting at consecutive indices. Panics if `step` is zero.
+    pub fn with_step(mut self, step: u64) -> InterestingHashFinder<'a> {
+        assert!(step > 0, "the step must be positive");
+        self.step = step;
+        self
+    }
+
+    /// Use `nonce` to write the nonce following the door ID for each index, instead of the
+    /// index in decimal. It is given the index and the (empty) nonce buffer to write into.
+    pub fn with_nonce<F: Fn(u64, &mut Vec<u8>) + 'a>(mut self, nonce: F) -> InterestingHashFinder<'a> {
+        self.nonce = Some(Box::new(nonce));
+        self
+    }
+
+    /// Returns the index of the next hash to be searched.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// `next()` hashing `simd::BATCH` indices at once. The door ID followed by any index must
+    /// fit in `simd::MAX_LEN` bytes.
+    ///
+    /// The last indices before `u64::MAX` are left to `next_scalar()`, so that the search ends
+    /// the same way.
+    #[cfg(feature = "simd")]
+    fn next_batched(&mut self) -> Option<String> {
+        let mut messages = [[0u8; simd::MAX_LEN]; simd::BATCH];
+        for message in &mut messages {
+            message[..self.door_id.len()].copy_from_slice(self.door_id);
+        }
+        let mut lens = [0; simd::BATCH];
+        loop {
+            if self.index.checked_add(simd::BATCH as u64).is_none() {
+                return self.next_scalar();
+            }
+            for (i, (message, len)) in messages.iter_mut().zip(&mut lens).enumerate() {
+                let mut digits = [0; 20];
+                let digits = to_decimal(self.index + i as u64, &mut digits);
+                *len = self.door_id.len() + digits.len();
+                message[self.door_id.len()..*len].copy_from_slice(digits);
+            }
+            let hashes = simd::digest_batch(&::std::array::from_fn(|i| &messages[i][..lens[i]]));
+            // NOTE: the hashes of the batch following an interesting one are computed again by
+            // the next call, which is cheap enough as there are few interesting hashes.
+            for hash in &hashes {
+                self.index += 1;
+                if (hash[0] | hash[1] | (hash[2] & 0xf0)) == 0 {
+                    return String::from_utf8(to_hex(hash).to_vec()).ok();
+                }
+            }
+        }
+    }
+
+    /// `next()` hashing one index at a time, the search ending once every index has been
+    /// searched.
+    fn next_scalar(&mut self) -> Option<String> {
+        let mut digits = [0; 20];
+        loop {
+            let hash = match self.nonce {
+                Some(ref nonce) => {
+                    self.message.clear();
+                    nonce(self.index, &mut self.message);
+                    self.md5.digest(&[self.door_id, &self.message])
+                },
+                None => self.md5.digest(&[self.door_id, to_decimal(self.index, &mut digits)]),
+            }.ok()?;
+            // NOTE: the search is over once every index has been searched.
+            self.index = self.index.checked_add(self.step)?;
+            // Since one byte is two characters in hex representation, we test the first two
+            // byte and the most significants 4 bits ("high part") of the third.
+            if (hash[0] | hash[1] | (hash[2] & 0xf0)) == 0 {
+                return String::from_utf8(to_hex(&hash).to_vec()).ok();
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for InterestingHashFinder<'a> {
+    type Item = String;
+
+    /// Find the next interesting hash in the index sequence.
+    ///
+    /// > A hash indicates the next character in the password if its hexadecimal representation
+    /// > starts with five zeroes.
+    fn next(&mut self) -> Option<Self::Item> {
+        // every hash searched until the next interesting one is a single span, a per-hash span
+        // would cost more than the hashing itself.
+        let _span = info_span!("hash_batch", start = self.index).entered();
+        #[cfg(feature = "simd")]
+        {
+            // an index is at most 20 digits long in decimal.
+            let decimal = self.step == 1 && self.nonce.is_none();
+            if decimal && self.door_id.len() + 20 <= simd::MAX_LEN {
+                return self.next_batched();
+            }
+        }
+        self.next_scalar()
+    }
+}
+
+
+/// Multi-buffer MD5, available with the `simd` feature.
+///
+/// Each SSE2 or AVX2 vector lane runs the compression of a different message, so that 8 messages
+/// are hashed about as fast as one. Only the messages fitting in a single block are supported,
+/// which is the case of a door ID followed by an index.
+#[cfg(feature = "simd")]
+pub mod simd {
+    /// The number of messages hashed at once by `digest_batch()`.
+    pub const BATCH: usize = 8;
+
+    /// The length of the longest message fitting in a single block along with its padding.
+    pub const MAX_LEN: usize = 55;
+
+    const INIT: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    /// The left rotation of each step.
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+        5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+        6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+
+    /// The additive constant of each step, `floor(abs(sin(i + 1)) * 2^32)`.
+    const K: [u32; 64] = [
+        0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+        0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+        0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+        0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+        0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+        0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+        0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+        0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+    ];
+
+    /// Returns the index of the block word mixed in at the step `i`.
+    fn word_index(i: usize) -> usize {
+        match i / 16 {
+            0 => i,
+            1 => (5 * i + 1) % 16,
+            2 => (3 * i + 5) % 16,
+            _ => (7 * i) % 16,
+        }
+    }
+
+    /// Returns the single block made of `message` and its padding, as little-endian words.
+    fn block(message: &[u8]) -> [u32; 16] {
+        assert!(message.len() <= MAX_LEN, "message too long for a single block: {} bytes", message.len());
+        let mut bytes = [0u8; 64];
+        bytes[..message.len()].copy_from_slice(message);
+        bytes[message.len()] = 0x80;
+        bytes[56..].copy_from_slice(&(message.len() as u64 * 8).to_le_bytes());
+        let mut words = [0; 16];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        words
+    }
+
+    /// Returns the state after compressing `block`, the scalar fallback of the vector versions.
+    #[cfg_attr(target_arch = "x86_64", allow(dead_code))]
+    fn compress(block: &[u32; 16]) -> [u32; 4] {
+        let [mut a, mut b, mut c, mut d] = INIT;
+        for i in 0..64 {
+            let f = match i / 16 {
+                0 => (b & c) | (!b & d),
+                1 => (d & b) | (!d & c),
+                2 => b ^ c ^ d,
+                _ => c ^ (b | !d),
+            };
+            let f = f.wrapping_add(a).wrapping_add(K[i]).wrapping_add(block[word_index(i)]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(SHIFTS[i]));
+        }
+        [INIT[0].wrapping_add(a), INIT[1].wrapping_add(b), INIT[2].wrapping_add(c), INIT[3].wrapping_add(d)]
+    }
+
+    /// Returns the states after compressing every block, as many at once as the CPU allows.
+    fn compress_batch(blocks: &[[u32; 16]; BATCH]) -> [[u32; 4]; BATCH] {
+        #[cfg(target_arch = "x86_64")]
+        {
+            // SAFETY: SSE2 is part of the x86_64 baseline, AVX2 is checked at runtime.
+            if is_x86_feature_detected!("avx2") {
+                unsafe { x86::compress_avx2(blocks) }
+            } else {
+                let (low, high) = blocks.split_at(BATCH / 2);
+                let (low, high) = unsafe { (x86::compress_sse2(low), x86::compress_sse2(high)) };
+                ::std::array::from_fn(|i| if i < BATCH / 2 { low[i] } else { high[i - BATCH / 2] })
+            }
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            ::std::array::from_fn(|i| compress(&blocks[i]))
+        }
+    }
+
+    /// Returns the MD5 digests of `messages`, which must not be longer than `MAX_LEN` bytes.
+    pub fn digest_batch(messages: &[&[u8]; BATCH]) -> [[u8; 16]; BATCH] {
+        let blocks = ::std::array::from_fn(|i| block(messages[i]));
+        let states = compress_batch(&blocks);
+        ::std::array::from_fn(|i| {
+            let mut digest = [0; 16];
+            for (bytes, word) in digest.chunks_exact_mut(4).zip(&states[i]) {
+                bytes.copy_from_slice(&word.to_le_bytes());
+            }
+            digest
+        })
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    mod x86 {
+        use ::std::arch::x86_64::*;
+        use super::{word_index, INIT, K, SHIFTS};
+
+        /// Define a function compressing `$lanes` blocks at once with the `$feature` vector
+        /// instructions, see `compress()`. `$v` is the vector type and the rest its intrinsics.
+        macro_rules! compress_with {
+            ($name:ident, $feature:tt, $lanes:expr, $v:ty, $set1:ident, $loadu:ident, $storeu:ident,
+             $add:ident, $and:ident, $andnot:ident, $or:ident, $xor:ident, $sll:ident, $srl:ident) => {
+                /// `blocks` must hold exactly one block per lane.
+                #[target_feature(enable = $feature)]
+                pub unsafe fn $name(blocks: &[[u32; 16]]) -> [[u32; 4]; $lanes] {
+                    assert_eq!(blocks.len(), $lanes);
+                    // transpose the blocks, so that each vector holds the same word of every one.
+                    let mut words = [$set1(0); 16];
+                    for (i, word) in words.iter_mut().enumerate() {
+                        let mut lanes = [0u32; $lanes];
+                        for (lane, block) in lanes.iter_mut().zip(blocks) {
+                            *lane = block[i];
+                        }
+                        *word = $loadu(lanes.as_ptr() as *const $v);
+                    }
+                    let ones = $set1(-1);
+                    let (mut a, mut b) = ($set1(INIT[0] as i32), $set1(INIT[1] as i32));
+                    let (mut c, mut d) = ($set1(INIT[2] as i32), $set1(INIT[3] as i32));
+                    for i in 0..64 {
+                        let f = match i / 16 {
+                            0 => $or($and(b, c), $andnot(b, d)),
+                            1 => $or($and(d, b), $andnot(d, c)),
+                            2 => $xor($xor(b, c), d),
+                            _ => $xor(c, $or(b, $xor(d, ones))),
+                        };
+                        let f = $add($add(f, a), $add($set1(K[i] as i32), words[word_index(i)]));
+                        a = d;
+                        d = c;
+                        c = b;
+                        let left = _mm_cvtsi32_si128(SHIFTS[i] as i32);
+                        let right = _mm_cvtsi32_si128(32 - SHIFTS[i] as i32);
+                        b = $add(b, $or($sll(f, left), $srl(f, right)));
+                    }
+                    // transpose the states back, one per lane.
+                    let mut states = [[0u32; 4]; $lanes];
+                    for (i, &word) in [a, b, c, d].iter().enumerate() {
+                        let mut lanes = [0u32; $lanes];
+                        $storeu(lanes.as_mut_ptr() as *mut $v, $add(word, $set1(INIT[i] as i32)));
+                        for (state, &lane) in states.iter_mut().zip(&lanes) {
+                            state[i] = lane;
+                        }
+                    }
+                    states
+                }
+            };
+        }
+
+        compress_with!(compress_sse2, "sse2", 4, __m128i, _mm_set1_epi32, _mm_loadu_si128, _mm_storeu_si128,
+                       _mm_add_epi32, _mm_and_si128, _mm_andnot_si128, _mm_or_si128, _mm_xor_si128,
+                       _mm_sll_epi32, _mm_srl_epi32);
+        compress_with!(compress_avx2, "avx2", 8, __m256i, _mm256_set1_epi32, _mm256_loadu_si256,
+                       _mm256_storeu_si256, _mm256_add_epi32, _mm256_and_si256, _mm256_andnot_si256,
+                       _mm256_or_si256, _mm256_xor_si256, _mm256_sll_epi32, _mm256_srl_epi32);
+    }
+
+    #[test]
+    fn batch_digests_match_openssl() {
+        let mut md5 = ::Md5::new().unwrap();
+        for len in (0..=MAX_LEN).step_by(BATCH) {
+            let messages: Vec<Vec<u8>> = (len..len + BATCH).map(|n| vec![b'a' + (n % 26) as u8; n.min(MAX_LEN)]).collect();
+            let messages: [&[u8]; BATCH] = ::std::array::from_fn(|i| &messages[i][..]);
+            let digests = digest_batch(&messages);
+            for (message, digest) in messages.iter().zip(&digests) {
+                assert_eq!(digest, &md5.digest(&[message]).unwrap());
+                assert_eq!(digest, &{
+                    let state = compress(&block(message));
+                    let mut bytes = [0; 16];
+                    for (bytes, word) in bytes.chunks_exact_mut(4).zip(&state) {
+                        bytes.copy_from_slice(&word.to_le_bytes());
+                    }
+                    bytes
+                });
+            }
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn sse2_and_avx2_agree() {
+        let blocks: [[u32; 16]; BATCH] = ::std::array::from_fn(|i| block(format!("abc{}", i * 1000).as_bytes()));
+        let (low, high) = blocks.split_at(BATCH / 2);
+        let sse2: Vec<_> = unsafe { x86::compress_sse2(low).iter().chain(&x86::compress_sse2(high)).cloned().collect() };
+        assert_eq!(sse2, blocks.iter().map(compress).collect::<Vec<_>>());
+        if is_x86_feature_detected!("avx2") {
+            assert_eq!(unsafe { x86::compress_avx2(&blocks) }.to_vec(), sse2);
+        }
+    }
+}
+
+
+#[test]
+fn md5_digest() {
+    let mut md5 = Md5::new().unwrap();
+    let digest = md5.digest(&[b"abc", b"18"]).unwrap();
+    assert_eq!(&to_hex(&digest), b"0034e0923cc38887a57bd7b1d4f953df");
+    // the hasher is reset after each digest.
+    assert_eq!(md5.digest(&[b"abc18"]).unwrap(), digest);
+}
+
+#[test]
+fn decimal_representation() {
+    let mut buf = [0; 20];
+    assert_eq!(to_decimal(0, &mut buf), b"0");
+    assert_eq!(to_decimal(3231929, &mut buf), b"3231929");
+    assert_eq!(to_decimal(u64::MAX, &mut buf), u64::MAX.to_string().as_bytes());
+}
+
+#[test]
+fn interesting_hashes() {
+    let mut finder = InterestingHashFinder::new("abc").unwrap();
+    assert!(finder.next().unwrap().starts_with("000001"));
+}
+
+#[test]
+fn interesting_hashes_search_end() {
+    // none of the last indices give an interesting hash, the search ends at u64::MAX.
+    let mut finder = InterestingHashFinder::new("abc").unwrap().starting_at(u64::MAX - 20);
+    assert_eq!(finder.next(), None);
+    assert_eq!(finder.index(), u64::MAX);
+}
+
+#[test]
+fn configured_interesting_hashes() {
+    // the first two interesting hashes of "abc" are at the indices 3231929 and 5017308.
+    let mut finder = InterestingHashFinder::new("abc").unwrap().starting_at(3231930);
+    assert!(finder.next().unwrap().starts_with("000008f82"));
+    assert_eq!(finder.index(), 5017309);
+    let mut odd = InterestingHashFinder::new("abc").unwrap().starting_at(1).with_step(2);
+    assert!(odd.next().unwrap().starts_with("000001"));
+    assert_eq!(odd.index(), 3231931);
+    let mut shifted = InterestingHashFinder::new("abc").unwrap()
+        .with_nonce(|index, nonce| nonce.extend_from_slice((index + 3231929).to_string().as_bytes()));
+    assert!(shifted.next().unwrap().starts_with("000001"));
+    assert_eq!(shifted.index(), 1);
+}
@@ -0,0 +1,169 @@
+//! Peak heap usage of the days binaries.
+//!
+//! `PeakAllocator` wraps the system allocator and keeps track of the live heap bytes and their
+//! high-water mark. A day built with its `peak-memory` feature installs it as the global
+//! allocator and reports the peak on stderr when it's done, which the runner picks up and shows
+//! next to the timings. The benchmarks also use it to count the allocations of a hot path.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The cargo feature of the days enabling the peak heap report.
+pub const FEATURE: &str = "peak-memory";
+
+/// How the peak heap report line starts, followed by the number of bytes.
+const REPORT_PREFIX: &str = "peak heap: ";
+
+/// The system allocator, counting the heap bytes in use and the allocations made.
+pub struct PeakAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+}
+
+impl PeakAllocator {
+    /// Create a new `PeakAllocator`, to be used as the `#[global_allocator]`.
+    pub const fn new() -> PeakAllocator {
+        PeakAllocator { current: AtomicUsize::new(0), peak: AtomicUsize::new(0), allocations: AtomicUsize::new(0) }
+    }
+
+    /// Returns the number of heap bytes currently in use.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Returns the largest number of heap bytes in use at once so far.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of allocations (and reallocations) made so far.
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    /// Returns the result of `f` along with the number of allocations it made. Only meaningful
+    /// when no other thread allocates meanwhile.
+    pub fn count_allocations<T, F: FnOnce() -> T>(&self, f: F) -> (T, usize) {
+        let before = self.allocations();
+        let result = f();
+        (result, self.allocations() - before)
+    }
+
+    /// Returns a guard printing the peak heap report on stderr when dropped, meant to live
+    /// until the end of `main()`.
+    pub fn report_on_drop(&'static self) -> Report {
+        Report(self)
+    }
+
+    fn allocated(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn deallocated(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+impl Default for PeakAllocator {
+    fn default() -> PeakAllocator {
+        PeakAllocator::new()
+    }
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.deallocated(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            // NOTE: count the growth before the shrink so that the peak never misses a moving
+            // reallocation, where both blocks are briefly alive.
+            self.allocated(new_size);
+            self.deallocated(layout.size());
+        }
+        new
+    }
+}
+
+/// Print the peak heap report of its allocator on stderr when dropped.
+pub struct Report(&'static PeakAllocator);
+
+impl Drop for Report {
+    fn drop(&mut self) {
+        eprintln!("{}{}", REPORT_PREFIX, self.0.peak());
+    }
+}
+
+/// Returns the peak heap bytes from the report in the given `stderr` of a day, if any.
+pub fn parse_report(stderr: &str) -> Option<usize> {
+    stderr.lines().rev().find_map(|line| line.strip_prefix(REPORT_PREFIX)?.parse().ok())
+}
+
+/// A number of bytes, displayed with a binary unit ("1.5 MiB").
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Bytes(pub usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit + 1 < UNITS.len() {
+            value /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{:.1} {}", value, UNITS[unit])
+    }
+}
+
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator::new();
+
+#[test]
+fn peak_allocator_tracks_the_high_water_mark() {
+    let before = ALLOCATOR.peak();
+    let big = vec![1u8; 4 << 20];
+    assert!(ALLOCATOR.current() >= big.len());
+    drop(big);
+    assert!(ALLOCATOR.peak() >= before.max(4 << 20));
+    let (_, count) = ALLOCATOR.count_allocations(|| vec![0u8; 16]);
+    assert!(count >= 1);
+}
+
+#[test]
+fn report_line() {
+    assert_eq!(parse_report("peak heap: 1536\n"), Some(1536));
+    assert_eq!(parse_report("warning: something\npeak heap: 42\n"), Some(42));
+    assert_eq!(parse_report("error: bad input\n"), None);
+    assert_eq!(Bytes(1536).to_string(), "1.5 KiB");
+    assert_eq!(Bytes(512).to_string(), "512 B");
+    assert_eq!(Bytes(3 << 20).to_string(), "3.0 MiB");
+}
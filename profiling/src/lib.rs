@@ -0,0 +1,156 @@
+//! Chrome trace recording shared by the days binaries.
+//!
+//! The solvers mark their phases (parsing, each part…) with `phase!`, e.g.
+//! `phase!("part1").in_scope(|| …)` or `let _phase = phase!("bfs depth {}", depth).entered();`,
+//! and the shared crates add their own `tracing` spans (hash batches, BFS depth levels…). When a
+//! binary is given `--trace-output FILE` these spans are written to FILE in the Chrome trace
+//! format, to be loaded in `chrome://tracing` or https://ui.perfetto.dev to see where a slow run
+//! spends its time. Without it a phase costs a single disabled span check.
+
+#[macro_use]
+extern crate tracing;
+extern crate tracing_chrome;
+extern crate tracing_subscriber;
+
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use tracing::Subscriber;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, EnteredSpan, Id, Span};
+use tracing_chrome::{ChromeLayerBuilder, EventOrSpan};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+
+pub use tracing_chrome::FlushGuard;
+
+/// The command line option enabling the Chrome trace.
+pub const OPTION: &str = "--trace-output";
+
+/// Remove `--trace-output FILE` from `args`, returns the other arguments in order along with
+/// FILE when it was given.
+pub fn split_args<I: IntoIterator<Item = String>>(args: I) -> Result<(Vec<String>, Option<String>), String> {
+    let mut rest = Vec::new();
+    let mut output = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == OPTION {
+            output = Some(args.next().ok_or_else(|| format!("usage: {} FILE", OPTION))?);
+        } else if let Some(path) = arg.strip_prefix("--trace-output=") {
+            output = Some(path.to_string());
+        } else {
+            rest.push(arg);
+        }
+    }
+    Ok((rest, output))
+}
+
+/// Record every span into the Chrome trace file at `path`. The file is complete once the
+/// returned guard is dropped, so it should live until the end of `main()`.
+pub fn record(path: &str) -> Result<FlushGuard, String> {
+    let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+    let (layer, guard) = ChromeLayerBuilder::new()
+        .writer(BufWriter::new(file))
+        .include_args(true)
+        .name_fn(Box::new(|event_or_span| match event_or_span {
+            EventOrSpan::Event(event) => event.metadata().name().to_string(),
+            EventOrSpan::Span(span) => match span.extensions().get::<Label>() {
+                Some(label) => label.0.clone(),
+                None => span.name().to_string(),
+            },
+        }))
+        .build();
+    tracing_subscriber::registry().with(Labels).with(layer).try_init().map_err(|e| e.to_string())?;
+    Ok(guard)
+}
+
+/// The name of the phase spans, their label being in the `LABEL` field.
+const PHASE: &str = "phase";
+const LABEL: &str = "label";
+
+/// A phase of a solver, see `phase!`.
+#[derive(Debug)]
+pub struct Phase(Span);
+
+impl Phase {
+    /// Create a new phase named `label`, prefer the `phase!` macro.
+    pub fn new(label: fmt::Arguments) -> Phase {
+        Phase(info_span!(target: "phase", PHASE, label = %label))
+    }
+
+    /// Run `f` in this phase.
+    pub fn in_scope<T, F: FnOnce() -> T>(self, f: F) -> T {
+        self.0.in_scope(f)
+    }
+
+    /// Enter this phase until the returned guard is dropped.
+    pub fn entered(self) -> EnteredSpan {
+        self.0.entered()
+    }
+}
+
+/// Create a `Phase` labelled with the given `format!` arguments, e.g. `phase!("parse")` or
+/// `phase!("bfs depth {}", depth)`. The label is only formatted when recording a trace.
+#[macro_export]
+macro_rules! phase {
+    ($($arg:tt)+) => {
+        $crate::Phase::new(format_args!($($arg)+))
+    };
+}
+
+/// The label of a phase span, shown as its name in the trace.
+struct Label(String);
+
+impl Visit for Label {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == LABEL {
+            self.0 = format!("{:?}", value);
+        }
+    }
+}
+
+/// The layer storing the `Label` of every phase span, so that the trace names them by label
+/// instead of all being "phase" spans.
+struct Labels;
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Labels {
+    fn on_new_span(&self, attrs: &Attributes, id: &Id, ctx: Context<S>) {
+        if attrs.metadata().name() != PHASE {
+            return;
+        }
+        let mut label = Label(String::new());
+        attrs.record(&mut label);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(label);
+        }
+    }
+}
+
+
+#[test]
+fn split_args_removes_the_trace_output() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    assert_eq!(split_args(args("--debug input.txt")), Ok((args("--debug input.txt"), None)));
+    assert_eq!(split_args(args("--trace-output run.json --json")),
+               Ok((args("--json"), Some("run.json".to_string()))));
+    assert_eq!(split_args(args("--json --trace-output=run.json")),
+               Ok((args("--json"), Some("run.json".to_string()))));
+    assert!(split_args(args("--json --trace-output")).is_err());
+}
+
+#[test]
+fn phases_are_named_by_label_in_the_trace() {
+    let path = std::env::temp_dir().join(format!("profiling-{}.json", std::process::id()));
+    let guard = record(path.to_str().unwrap()).unwrap();
+    let depth = 3;
+    phase!("parse").in_scope(|| ());
+    drop(phase!("bfs depth {}", depth).entered());
+    drop(guard);
+    let trace = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(trace.contains("\"name\":\"parse\""), "{}", trace);
+    assert!(trace.contains("\"name\":\"bfs depth 3\""), "{}", trace);
+    assert!(!trace.contains("\"name\":\"phase\""), "{}", trace);
+}
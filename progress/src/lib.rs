@@ -0,0 +1,108 @@
+//! Progress bars shared by the long-running days binaries.
+//!
+//! A slow search (e.g. the day 5 MD5 hashes) reports into a `Progress` the index it reached and
+//! how many of the things it looks for were found so far. The bar shows the index, the search
+//! rate and an ETA estimated from the things left to find. It is drawn on the standard error and
+//! only when it is a terminal, so that the answers printed on the standard output stay clean.
+
+extern crate indicatif;
+
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// How often the bar is redrawn when nothing is reported, so that the elapsed time and the
+/// spinner keep going while searching.
+const TICK: Duration = Duration::from_millis(100);
+
+/// The progress of a search looking for a known count of things, e.g. password characters.
+pub struct Progress {
+    bar: ProgressBar,
+    found: Arc<AtomicU64>,
+}
+
+impl Progress {
+    /// Create a new `Progress` for a search over once `target` things have been found.
+    pub fn new(target: u64) -> Progress {
+        Progress::with_bar(ProgressBar::new_spinner(), target)
+    }
+
+    /// Create a new `Progress` drawing nothing, e.g. for tests.
+    pub fn hidden(target: u64) -> Progress {
+        Progress::with_bar(ProgressBar::hidden(), target)
+    }
+
+    fn with_bar(bar: ProgressBar, target: u64) -> Progress {
+        let found = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&found);
+        let style = ProgressStyle::with_template(
+            "{spinner} {msg} [{elapsed}] index {human_pos} ({per_sec}), ETA {search_eta}")
+            .expect("the progress template is valid")
+            .with_key("search_eta", move |state: &ProgressState, w: &mut dyn Write| {
+                let _ = match eta(state.elapsed(), counter.load(Ordering::Relaxed), target) {
+                    Some(eta) => write!(w, "{}s", eta.as_secs()),
+                    None => write!(w, "?"),
+                };
+            });
+        bar.set_style(style);
+        bar.enable_steady_tick(TICK);
+        Progress { bar, found }
+    }
+
+    /// Report that the search reached `index` and found `found` things so far, `message`
+    /// describing them (e.g. the password characters known).
+    pub fn update<M: Into<Cow<'static, str>>>(&self, index: u64, found: u64, message: M) {
+        self.found.store(found, Ordering::Relaxed);
+        self.bar.set_position(index);
+        self.bar.set_message(message);
+    }
+
+    /// Returns the index last reported.
+    pub fn index(&self) -> u64 {
+        self.bar.position()
+    }
+
+    /// Run `f` with the bar hidden, so that it can print lines without garbling it. `f` must not
+    /// use this `Progress`, which is locked meanwhile.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.bar.suspend(f)
+    }
+
+    /// Stop and clear the bar, the search being over.
+    pub fn finish(self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Returns the estimated time left to find `target` things when `found` of them were found in
+/// `elapsed`, assuming they are found at a steady rate. `None` until something is found.
+pub fn eta(elapsed: Duration, found: u64, target: u64) -> Option<Duration> {
+    if found == 0 {
+        return None;
+    }
+    let left = target.saturating_sub(found);
+    Some(elapsed.mul_f64(left as f64 / found as f64))
+}
+
+
+#[test]
+fn steady_rate_eta() {
+    let secs = Duration::from_secs;
+    assert_eq!(eta(secs(10), 0, 16), None);
+    assert_eq!(eta(secs(10), 4, 16), Some(secs(30)));
+    assert_eq!(eta(secs(10), 16, 16), Some(secs(0)));
+    assert_eq!(eta(secs(10), 20, 16), Some(secs(0)));
+}
+
+#[test]
+fn hidden_progress() {
+    let progress = Progress::hidden(16);
+    progress.update(42, 3, "1_3_____");
+    assert_eq!(progress.index(), 42);
+    assert_eq!(progress.suspend(|| "printed"), "printed");
+    assert_eq!(progress.found.load(Ordering::Relaxed), 3);
+    progress.finish();
+}
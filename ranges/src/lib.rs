@@ -0,0 +1,270 @@
+//! Integer interval sets, shared by the days (and tools) working on ranges of numbers.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::RangeInclusive;
+
+/// The integer types an `IntervalSet` can hold.
+pub trait Integer: Copy + Ord + fmt::Debug {
+    /// The smallest value of the type.
+    const MIN: Self;
+    /// The largest value of the type.
+    const MAX: Self;
+
+    /// Returns the next value, or `None` if `self` is `MAX`.
+    fn succ(self) -> Option<Self>;
+
+    /// Returns the previous value, or `None` if `self` is `MIN`.
+    fn pred(self) -> Option<Self>;
+
+    /// Returns the number of values in `start..=end`.
+    fn span(start: Self, end: Self) -> u128;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const MIN: $t = <$t>::MIN;
+                const MAX: $t = <$t>::MAX;
+
+                fn succ(self) -> Option<$t> {
+                    self.checked_add(1)
+                }
+
+                fn pred(self) -> Option<$t> {
+                    self.checked_sub(1)
+                }
+
+                fn span(start: $t, end: $t) -> u128 {
+                    // NOTE: going through i128 handles both signed and unsigned types up to 64
+                    // bits without overflow.
+                    (end as i128 - start as i128 + 1) as u128
+                }
+            }
+        )*
+    }
+}
+
+impl_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A set of integers stored as sorted, disjoint and non-adjacent inclusive intervals.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T: Integer> {
+    // every interval is (start, end) with start <= end, and there is at least one value
+    // between two consecutive intervals.
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: Integer> IntervalSet<T> {
+    /// Create a new empty `IntervalSet`.
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { intervals: Vec::new() }
+    }
+
+    /// Create a new `IntervalSet` holding every value of the type.
+    pub fn full() -> IntervalSet<T> {
+        IntervalSet { intervals: vec![(T::MIN, T::MAX)] }
+    }
+
+    /// Returns `true` if this set holds no value, `false` otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns the number of values in this set.
+    pub fn count(&self) -> u128 {
+        self.intervals.iter().map(|&(start, end)| T::span(start, end)).sum()
+    }
+
+    /// Returns `true` if `value` is in this set, `false` otherwise.
+    pub fn contains(&self, value: T) -> bool {
+        // the first interval ending at or after `value` is the only one that may contain it.
+        let i = self.intervals.partition_point(|&(_, end)| end < value);
+        self.intervals.get(i).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// Returns the smallest value of this set, if any.
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|&(start, _)| start)
+    }
+
+    /// Returns the largest value of this set, if any.
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().map(|&(_, end)| end)
+    }
+
+    /// Add every value of `range` to this set, merging it with the intervals it overlaps or
+    /// touches. Empty ranges are ignored.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = (*range.start(), *range.end());
+        if start > end {
+            return;
+        }
+        // the intervals to merge are the ones ending at or after `start - 1` and starting at or
+        // before `end + 1`.
+        let first = self.intervals.partition_point(|&(_, e)| e.succ().is_some_and(|next| next < start));
+        let last = self.intervals.partition_point(|&(s, _)| end.succ().is_none_or(|next| s <= next));
+        if first < last {
+            start = start.min(self.intervals[first].0);
+            end = end.max(self.intervals[last - 1].1);
+        }
+        self.intervals.splice(first..last, Some((start, end)));
+    }
+
+    /// Add every value of `other` to this set.
+    pub fn merge(&mut self, other: &IntervalSet<T>) {
+        for &(start, end) in other.intervals.iter() {
+            self.insert(start..=end);
+        }
+    }
+
+    /// Returns the union of this set and `other`.
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut union = self.clone();
+        union.merge(other);
+        union
+    }
+
+    /// Returns the set of every value of the type not in this set.
+    pub fn complement(&self) -> IntervalSet<T> {
+        let mut complement = IntervalSet::new();
+        let mut next = Some(T::MIN);
+        for &(start, end) in self.intervals.iter() {
+            if let Some(value) = next {
+                if value < start {
+                    complement.intervals.push((value, start.pred().unwrap()));
+                }
+            }
+            next = end.succ();
+        }
+        if let Some(value) = next {
+            complement.intervals.push((value, T::MAX));
+        }
+        complement
+    }
+
+    /// Returns the intervals of this set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.intervals.iter().map(|&(start, end)| start..=end)
+    }
+
+    /// Returns the values missing between the intervals of this set, in increasing order.
+    /// Values smaller than `min()` and larger than `max()` are not gaps, see `complement()`.
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.intervals.windows(2).map(|pair| pair[0].1.succ().unwrap()..=pair[1].0.pred().unwrap())
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> IntervalSet<T> {
+        let mut set = IntervalSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Integer> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Integer + fmt::Display> fmt::Display for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let intervals: Vec<String> = self.intervals.iter().map(|&(start, end)| {
+            if start == end { format!("{}", start) } else { format!("{}-{}", start, end) }
+        }).collect();
+        write!(f, "{{{}}}", intervals.join(", "))
+    }
+}
+
+
+#[cfg(test)]
+fn ranges<T: Integer>(set: &IntervalSet<T>) -> Vec<(T, T)> {
+    set.iter().map(|range| (*range.start(), *range.end())).collect()
+}
+
+#[test]
+fn insert() {
+    let mut set: IntervalSet<u64> = IntervalSet::new();
+    assert!(set.is_empty());
+    set.insert(5..=8);
+    set.insert(0..=2);
+    set.insert(4..=7);
+    assert_eq!(ranges(&set), vec![(0, 2), (4, 8)]);
+    // adjacent intervals are merged.
+    set.insert(3..=3);
+    assert_eq!(ranges(&set), vec![(0, 8)]);
+    set.insert(20..=30);
+    set.insert(10..=12);
+    set.insert(14..=15);
+    assert_eq!(ranges(&set), vec![(0, 8), (10, 12), (14, 15), (20, 30)]);
+    // spanning several intervals.
+    set.insert(11..=21);
+    assert_eq!(ranges(&set), vec![(0, 8), (10, 30)]);
+    // already covered, or empty.
+    set.insert(1..=7);
+    #[allow(clippy::reversed_empty_ranges)]
+    set.insert(42..=41);
+    assert_eq!(ranges(&set), vec![(0, 8), (10, 30)]);
+    assert_eq!(set.to_string(), "{0-8, 10-30}");
+    assert_eq!(set.count(), 9 + 21);
+}
+
+#[test]
+fn type_bounds() {
+    let mut set: IntervalSet<u64> = IntervalSet::new();
+    set.insert(u64::MAX - 1..=u64::MAX);
+    set.insert(0..=0);
+    set.insert(u64::MAX..=u64::MAX);
+    assert_eq!(ranges(&set), vec![(0, 0), (u64::MAX - 1, u64::MAX)]);
+    assert_eq!(ranges(&set.complement()), vec![(1, u64::MAX - 2)]);
+    assert_eq!(IntervalSet::<u64>::full().count(), 1 << 64);
+    assert!(IntervalSet::<u64>::full().complement().is_empty());
+    assert_eq!(ranges(&IntervalSet::<u8>::new().complement()), vec![(0, 255)]);
+
+    let set: IntervalSet<i32> = vec![-5..=-1, 1..=5].into_iter().collect();
+    assert_eq!(ranges(&set.complement()), vec![(i32::MIN, -6), (0, 0), (6, i32::MAX)]);
+    assert_eq!(set.count(), 10);
+}
+
+#[test]
+fn queries() {
+    let set: IntervalSet<u32> = vec![5..=8, 0..=2, 10..=10, 12..=20].into_iter().collect();
+    let contained: Vec<u32> = (0..22).filter(|&value| set.contains(value)).collect();
+    assert_eq!(contained, vec![0, 1, 2, 5, 6, 7, 8, 10, 12, 13, 14, 15, 16, 17, 18, 19, 20]);
+    assert_eq!((set.min(), set.max()), (Some(0), Some(20)));
+    let gaps: Vec<_> = set.gaps().collect();
+    assert_eq!(gaps, vec![3..=4, 9..=9, 11..=11]);
+    assert_eq!(IntervalSet::<u32>::new().gaps().count(), 0);
+}
+
+#[test]
+fn union() {
+    let a: IntervalSet<u64> = vec![0..=3, 10..=12].into_iter().collect();
+    let b: IntervalSet<u64> = vec![4..=5, 8..=9, 20..=25].into_iter().collect();
+    let union = a.union(&b);
+    assert_eq!(ranges(&union), vec![(0, 5), (8, 12), (20, 25)]);
+    assert_eq!(union, b.union(&a));
+    let mut merged = a.clone();
+    merged.merge(&b);
+    assert_eq!(merged, union);
+    // every value is either in a set or its complement.
+    let complement = union.complement();
+    assert_eq!(union.union(&complement), IntervalSet::full());
+    assert!((0..30).all(|value| union.contains(value) != complement.contains(value)));
+}
+
+#[test]
+fn day20_example() {
+    // the blacklist from the Day 20 puzzle example, where addresses only go up to 9.
+    let mut blacklist: IntervalSet<u32> = vec![5..=8, 0..=2, 4..=7].into_iter().collect();
+    blacklist.insert(10..=u32::MAX);
+    let allowed = blacklist.complement();
+    assert_eq!(ranges(&allowed), vec![(3, 3), (9, 9)]);
+    assert_eq!(allowed.min(), Some(3));
+    assert_eq!(allowed.count(), 2);
+}
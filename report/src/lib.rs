@@ -0,0 +1,588 @@
+//! Puzzle input reading and error reporting shared by the days binaries.
+//!
+//! A bad line of input should not abort the solver with a panic and a backtrace. Instead the
+//! error is reported with the input name, the line number, the offending text and the parser
+//! message, then the process exits with a non-zero status. When the parser knows where the error
+//! is in the line (see `aoc_core::Spanned`), the offending characters are underlined.
+//!
+//! Some days top-level types (e.g. the Day 1 `RecruitingDocument`) are built from the whole
+//! `Input` through `FromInput`, so that the days binaries and their tests read them the same way.
+//!
+//! With the `proptest` feature, the `Sampler` draws the `--generate` inputs from the same
+//! strategies as the property tests.
+
+extern crate aoc_core;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+
+use aoc_core::input::normalize;
+use aoc_core::{Span, Spanned};
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+use std::str::FromStr;
+
+/// The exit status of a solver that could not make sense of its input.
+pub const FAILURE: i32 = 1;
+
+/// The exit status of a solver that gave up after its `--timeout`, the same as timeout(1).
+pub const TIMED_OUT: i32 = 124;
+
+/// The command line option asking a day to only check its input, without solving the puzzle.
+pub const VALIDATE: &str = "--validate";
+
+/// The command line option asking a day to print a random input instead of solving the puzzle,
+/// followed by the input size and the random seed.
+pub const GENERATE: &str = "--generate";
+
+/// An error found in the puzzle input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InputError {
+    /// The line number, starting at 1, when known.
+    pub line_no: Option<usize>,
+    /// The offending text, when known.
+    pub text: Option<String>,
+    /// What went wrong, usually the parser message.
+    pub reason: String,
+    /// Where the error is in the offending text, when known. Its line is `line_no`.
+    pub span: Option<Span>,
+}
+
+impl InputError {
+    /// Create an `InputError` not tied to any particular line.
+    pub fn new<R: Display>(reason: R) -> InputError {
+        InputError { line_no: None, text: None, reason: reason.to_string(), span: None }
+    }
+
+    /// Create an `InputError` for the line `line_no` (starting at 1) holding `text`.
+    pub fn at<R: Display>(line_no: usize, text: &str, reason: R) -> InputError {
+        InputError {
+            line_no: Some(line_no),
+            text: Some(text.to_string()),
+            reason: reason.to_string(),
+            span: None,
+        }
+    }
+
+    /// Create an `InputError` for the line `line_no` (starting at 1) holding `text`, from an
+    /// `error` found by a parser given that line alone.
+    pub fn in_line<E: Display>(line_no: usize, text: &str, error: Spanned<E>) -> InputError {
+        InputError { span: Some(error.span.on_line(line_no)), ..InputError::at(line_no, text, error.error) }
+    }
+
+    /// Create an `InputError` from an `error` found by a parser given the whole `input`.
+    pub fn spanned<E: Display>(input: &str, error: Spanned<E>) -> InputError {
+        let text = error.span.line_of(input).unwrap_or("");
+        InputError { span: Some(error.span), ..InputError::at(error.span.line, text, error.error) }
+    }
+
+    /// Create an `InputError` for the line of `input` holding the byte at `offset`, for parsers
+    /// reporting byte offsets rather than lines.
+    pub fn at_offset<R: Display>(input: &str, offset: usize, reason: R) -> InputError {
+        // NOTE: an offset past the end of input (e.g. "unexpected end of input") is on the last
+        // line, and an offset inside a multibyte character is on the line holding it.
+        let offset = (0..=offset.min(input.len())).rev()
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(0);
+        let start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = input[offset..].find('\n').map_or(input.len(), |i| offset + i);
+        let line_no = input[..start].matches('\n').count() + 1;
+        InputError::at(line_no, &input[start..end], reason)
+    }
+}
+
+impl From<String> for InputError {
+    fn from(reason: String) -> InputError {
+        InputError::new(reason)
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line_no, self.span) {
+            (Some(line_no), Some(span)) => write!(f, "line {}, column {}: {}", line_no, span.col, self.reason),
+            (Some(line_no), None) => write!(f, "line {}: {}", line_no, self.reason),
+            (None, _) => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+impl ::std::error::Error for InputError {}
+
+/// The errors found building a day's top-level type from its `Input`, see `FromInput`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AocError(pub Vec<InputError>);
+
+impl From<InputError> for AocError {
+    fn from(error: InputError) -> AocError {
+        AocError(vec![error])
+    }
+}
+
+impl From<Vec<InputError>> for AocError {
+    fn from(errors: Vec<InputError>) -> AocError {
+        AocError(errors)
+    }
+}
+
+impl From<String> for AocError {
+    fn from(reason: String) -> AocError {
+        AocError::from(InputError::new(reason))
+    }
+}
+
+impl Display for AocError {
+    /// One error per line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl ::std::error::Error for AocError {}
+
+/// A day's top-level type built from the whole puzzle `Input`, e.g. the list of its instructions.
+pub trait FromInput: Sized {
+    /// Build a `Self` from `input`, returns every error found otherwise.
+    fn from_input(input: &Input) -> Result<Self, AocError>;
+}
+
+/// Parse one `T` per line of `input`, blank lines are ignored.
+///
+/// Parsing doesn't stop at the first bad line, all of them are returned on error.
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, Vec<InputError>>
+    where T: FromStr, T::Err: Display
+{
+    collect_lines(input, str::parse, InputError::at)
+}
+
+/// Like `parse_lines()`, for the parsers reporting where the error is in the line.
+pub fn parse_spanned_lines<T, E>(input: &str) -> Result<Vec<T>, Vec<InputError>>
+    where T: FromStr<Err = Spanned<E>>, E: Display
+{
+    collect_lines(input, str::parse, InputError::in_line)
+}
+
+/// Returns every error reported by `check` on the lines of `input`, blank lines are ignored.
+pub fn check_lines<F, T, E>(input: &str, check: F) -> Vec<InputError>
+    where F: FnMut(&str) -> Result<T, E>, E: Display
+{
+    collect_lines(input, check, InputError::at).err().unwrap_or_default()
+}
+
+/// Like `check_lines()`, for the parsers reporting where the error is in the line.
+pub fn check_spanned_lines<F, T, E>(input: &str, check: F) -> Vec<InputError>
+    where F: FnMut(&str) -> Result<T, Spanned<E>>, E: Display
+{
+    collect_lines(input, check, InputError::in_line).err().unwrap_or_default()
+}
+
+/// Returns the values `parse` found on the lines of `input` or, if there was any, the errors
+/// made into an `InputError` by `error`. Blank lines are ignored.
+fn collect_lines<T, E, P, M>(input: &str, mut parse: P, mut error: M) -> Result<Vec<T>, Vec<InputError>>
+    where P: FnMut(&str) -> Result<T, E>, M: FnMut(usize, &str, E) -> InputError
+{
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse(line) {
+            Ok(value) => values.push(value),
+            Err(e) => errors.push(error(i + 1, line, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Returns `true` if the command line has the `--validate` option, `false` otherwise.
+pub fn validating() -> bool {
+    std::env::args().skip(1).any(|arg| arg == VALIDATE)
+}
+
+/// Returns the size and seed following `--generate` in `args`, or `None` when it's not there.
+pub fn parse_generate<I: IntoIterator<Item = String>>(args: I) -> Result<Option<(usize, u64)>, String> {
+    let mut args = args.into_iter().skip_while(|arg| arg != GENERATE);
+    if args.next().is_none() {
+        return Ok(None);
+    }
+    let usage = || format!("usage: {} SIZE SEED", GENERATE);
+    let size = args.next().and_then(|size| size.parse().ok()).ok_or_else(usage)?;
+    let seed = args.next().and_then(|seed| seed.parse().ok()).ok_or_else(usage)?;
+    Ok(Some((size, seed)))
+}
+
+/// Returns the size and seed of the input to generate when the command line has the
+/// `--generate SIZE SEED` option, `None` otherwise. Exit on error.
+pub fn generating() -> Option<(usize, u64)> {
+    parse_generate(std::env::args().skip(1)).unwrap_or_else(|e| exit(e))
+}
+
+/// Draws random values from proptest strategies, e.g. from the `Arbitrary` implementations of the
+/// days domain types, so that the input generators share them with the property tests. The same
+/// seed always draws the same values.
+#[cfg(feature = "proptest")]
+pub struct Sampler(proptest::test_runner::TestRunner);
+
+#[cfg(feature = "proptest")]
+impl Sampler {
+    /// Create a `Sampler` drawing from the given `seed`.
+    pub fn new(seed: [u8; 32]) -> Sampler {
+        use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+
+        let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed);
+        Sampler(TestRunner::new_with_rng(Config::default(), rng))
+    }
+
+    /// Returns a value drawn from `strategy`. Panics if the strategy rejects too many values,
+    /// e.g. through `prop_filter()`.
+    pub fn draw<S: proptest::strategy::Strategy>(&mut self, strategy: &S) -> S::Value {
+        use proptest::strategy::ValueTree;
+
+        strategy.new_tree(&mut self.0).unwrap_or_else(|e| panic!("{}", e)).current()
+    }
+}
+
+/// Print `message` on the standard error and exit with the `FAILURE` status.
+pub fn exit<M: Display>(message: M) -> ! {
+    eprintln!("{}", message);
+    process::exit(FAILURE)
+}
+
+/// Print `error` found in the input named `name` on the standard error.
+pub fn print(name: &str, error: &InputError) {
+    eprintln!("{}", Report { name, error });
+}
+
+/// Print every error found in the input named `name` and exit with the `FAILURE` status.
+pub fn fail<I>(name: &str, errors: I) -> !
+    where I: IntoIterator<Item = InputError>
+{
+    for error in errors {
+        print(name, &error);
+    }
+    process::exit(FAILURE)
+}
+
+/// The displayed form of an `InputError`, the offending text being on its own line or, when the
+/// error span is known, underlined rustc-style.
+struct Report<'a> {
+    name: &'a str,
+    error: &'a InputError,
+}
+
+impl<'a> Display for Report<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.error)?;
+        match (&self.error.text, self.error.span) {
+            (Some(text), Some(span)) => write!(f, "\n{}", span.excerpt(text)),
+            (Some(text), None) => write!(f, "\n    {}", text),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+/// The puzzle input along with its name, used to report errors.
+#[derive(Clone, Debug)]
+pub struct Input {
+    name: String,
+    text: String,
+}
+
+impl Input {
+    /// Create an `Input` named `name` from `text`.
+    pub fn new(name: &str, text: String) -> Input {
+        Input { name: name.to_string(), text }
+    }
+
+    /// Read the whole standard input, exits on failure. The text read is normalized, see
+    /// `aoc_core::input::normalize()`.
+    pub fn stdin() -> Input {
+        let mut text = String::new();
+        match io::stdin().lock().read_to_string(&mut text) {
+            Ok(_) => Input::normalized("<stdin>", text),
+            Err(e) => exit(format!("<stdin>: {}", e)),
+        }
+    }
+
+    /// Read the whole file at `path`, exits on failure. The text read is normalized, see
+    /// `aoc_core::input::normalize()`.
+    pub fn file(path: &str) -> Input {
+        match fs::read_to_string(path) {
+            Ok(text) => Input::normalized(path, text),
+            Err(e) => exit(format!("{}: {}", path, e)),
+        }
+    }
+
+    /// Create an `Input` named `name` from `text` once normalized.
+    fn normalized(name: &str, text: String) -> Input {
+        let normalized = match normalize(&text) {
+            Cow::Owned(normalized) => Some(normalized),
+            Cow::Borrowed(_) => None,
+        };
+        Input::new(name, normalized.unwrap_or(text))
+    }
+
+    /// Returns the name of this input, `<stdin>` or a file path.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the whole input.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Build a `T` from this input, see `FromInput`. Exits after reporting every error on
+    /// failure.
+    pub fn build<T: FromInput>(&self) -> T {
+        T::from_input(self).unwrap_or_else(|e| self.fail(e.0))
+    }
+
+    /// Returns every error found building a `T` from this input, see `FromInput`.
+    pub fn build_errors<T: FromInput>(&self) -> Vec<InputError> {
+        T::from_input(self).err().map_or_else(Vec::new, |e| e.0)
+    }
+
+    /// Returns the first line of input without its line terminator, for the puzzles given a
+    /// single value.
+    pub fn first_line(&self) -> &str {
+        self.text.lines().next().unwrap_or("")
+    }
+
+    /// Parse the whole input as a `T`, exits on failure.
+    pub fn parse<T>(&self) -> T
+        where T: FromStr, T::Err: Display
+    {
+        self.check(self.text.parse().map_err(InputError::new))
+    }
+
+    /// Parse the first line of input as a `T`, exits on failure.
+    pub fn parse_first_line<T>(&self) -> T
+        where T: FromStr, T::Err: Display
+    {
+        self.check(self.first_line_result())
+    }
+
+    /// Returns the error found parsing the first line of input as a `T`, if any.
+    pub fn first_line_error<T>(&self) -> Option<InputError>
+        where T: FromStr, T::Err: Display
+    {
+        self.first_line_result::<T>().err()
+    }
+
+    fn first_line_result<T>(&self) -> Result<T, InputError>
+        where T: FromStr, T::Err: Display
+    {
+        let line = self.first_line();
+        line.trim().parse().map_err(|e| InputError::at(1, line, e))
+    }
+
+    /// Parse one `T` per line of input, see `parse_lines()`. Exits after reporting every bad
+    /// line on failure.
+    pub fn parse_lines<T>(&self) -> Vec<T>
+        where T: FromStr, T::Err: Display
+    {
+        parse_lines(&self.text).unwrap_or_else(|errors| self.fail(errors))
+    }
+
+    /// Parse one `T` per line of input, see `parse_spanned_lines()`. Exits after reporting every
+    /// bad line on failure.
+    pub fn parse_spanned_lines<T, E>(&self) -> Vec<T>
+        where T: FromStr<Err = Spanned<E>>, E: Display
+    {
+        parse_spanned_lines(&self.text).unwrap_or_else(|errors| self.fail(errors))
+    }
+
+    /// Returns every error found parsing one `T` per line of input, see `parse_lines()`.
+    pub fn line_errors<T>(&self) -> Vec<InputError>
+        where T: FromStr, T::Err: Display
+    {
+        check_lines(&self.text, str::parse::<T>)
+    }
+
+    /// Returns every error found parsing one `T` per line of input, see
+    /// `parse_spanned_lines()`.
+    pub fn spanned_line_errors<T, E>(&self) -> Vec<InputError>
+        where T: FromStr<Err = Spanned<E>>, E: Display
+    {
+        check_spanned_lines(&self.text, str::parse::<T>)
+    }
+
+    /// Returns every error reported by `check` on the lines of input, see `check_lines()`.
+    pub fn check_lines<F, T, E>(&self, check: F) -> Vec<InputError>
+        where F: FnMut(&str) -> Result<T, E>, E: Display
+    {
+        check_lines(&self.text, check)
+    }
+
+    /// Returns every error reported by `check` on the lines of input, see
+    /// `check_spanned_lines()`.
+    pub fn check_spanned_lines<F, T, E>(&self, check: F) -> Vec<InputError>
+        where F: FnMut(&str) -> Result<T, Spanned<E>>, E: Display
+    {
+        check_spanned_lines(&self.text, check)
+    }
+
+    /// Report every error found in this input, or that it is valid, and exit. This is the
+    /// `--validate` mode: the exit status is `FAILURE` if there was any error.
+    pub fn validate<I>(&self, errors: I) -> !
+        where I: IntoIterator<Item = InputError>
+    {
+        let errors: Vec<InputError> = errors.into_iter().collect();
+        if !errors.is_empty() {
+            self.fail(errors);
+        }
+        let count = self.text.lines().count();
+        println!("{}: valid, {} line{} checked", self.name, count, if count == 1 { "" } else { "s" });
+        process::exit(0)
+    }
+
+    /// Returns the value of `result`, or report its error and exit.
+    pub fn check<T, E: Into<InputError>>(&self, result: Result<T, E>) -> T {
+        result.unwrap_or_else(|e| self.fail(Some(e.into())))
+    }
+
+    /// Print `error` found in this input, see `print()`.
+    pub fn print(&self, error: &InputError) {
+        print(&self.name, error)
+    }
+
+    /// Print every error found in this input and exit, see `fail()`.
+    pub fn fail<I>(&self, errors: I) -> !
+        where I: IntoIterator<Item = InputError>
+    {
+        fail(&self.name, errors)
+    }
+}
+
+#[test]
+fn parse_lines_reports_every_bad_line() {
+    assert_eq!(parse_lines::<u8>("1\n\n2\n  \n3\n"), Ok(vec![1, 2, 3]));
+    let errors = parse_lines::<u8>("1\nx\n2\n256").unwrap_err();
+    assert_eq!(errors, vec![
+        InputError::at(2, "x", "invalid digit found in string"),
+        InputError::at(4, "256", "number too large to fit in target type"),
+    ]);
+}
+
+#[test]
+fn check_lines_reports_every_bad_line() {
+    let even = |line: &str| match line.parse::<u8>() {
+        Ok(n) if n % 2 == 0 => Ok(n),
+        Ok(n) => Err(format!("{} is odd", n)),
+        Err(e) => Err(e.to_string()),
+    };
+    assert_eq!(check_lines("2\n\n4\n", even), vec![]);
+    assert_eq!(check_lines("2\n3\nx", even), vec![
+        InputError::at(2, "3", "3 is odd"),
+        InputError::at(3, "x", "invalid digit found in string"),
+    ]);
+    let input = Input::new("<test>", "1\n\n256\n".to_string());
+    assert_eq!(input.line_errors::<u8>(), vec![
+        InputError::at(3, "256", "number too large to fit in target type"),
+    ]);
+    assert_eq!(input.first_line_error::<u8>(), None);
+    assert!(input.first_line_error::<bool>().is_some());
+}
+
+#[test]
+fn at_offset() {
+    let input = "abc\ndéf\nghi";
+    assert_eq!(InputError::at_offset(input, 0, "x"), InputError::at(1, "abc", "x"));
+    assert_eq!(InputError::at_offset(input, 3, "x"), InputError::at(1, "abc", "x"));
+    assert_eq!(InputError::at_offset(input, 4, "x"), InputError::at(2, "déf", "x"));
+    // inside the 'é' multibyte character.
+    assert_eq!(InputError::at_offset(input, 6, "x"), InputError::at(2, "déf", "x"));
+    assert_eq!(InputError::at_offset(input, 99, "x"), InputError::at(3, "ghi", "x"));
+    assert_eq!(InputError::at_offset("", 0, "x"), InputError::at(1, "", "x"));
+}
+
+#[test]
+fn spanned_errors() {
+    let column = |line: &str| match line.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => Err(Spanned::of(line, i..i + 1, "not a digit")),
+        None => Ok(line.len()),
+    };
+    assert_eq!(check_spanned_lines("12\n\n3x4", column), vec![
+        InputError { span: Some(Span::new(3, 2, 1)), ..InputError::at(3, "3x4", "not a digit") },
+    ]);
+    let error = InputError::spanned("1\n23 4", Spanned::of("1\n23 4", 4..5, "stray space"));
+    assert_eq!(error, InputError { span: Some(Span::new(2, 3, 1)), ..InputError::at(2, "23 4", "stray space") });
+    assert_eq!(error.to_string(), "line 2, column 3: stray space");
+    assert_eq!(Report { name: "<stdin>", error: &error }.to_string(),
+               "<stdin>: line 2, column 3: stray space\n  |\n2 | 23 4\n  |   ^");
+}
+
+#[test]
+fn report_display() {
+    let located = InputError::at(4, "rect 3 by 2", "unrecognized operation");
+    assert_eq!(located.to_string(), "line 4: unrecognized operation");
+    assert_eq!(Report { name: "<stdin>", error: &located }.to_string(),
+               "<stdin>: line 4: unrecognized operation\n    rect 3 by 2");
+    let unlocated = InputError::new("empty input");
+    assert_eq!(Report { name: "input.txt", error: &unlocated }.to_string(), "input.txt: empty input");
+}
+
+#[test]
+fn input_lines() {
+    let input = Input::new("<test>", "R2, L3\nignored\n".to_string());
+    assert_eq!(input.name(), "<test>");
+    assert_eq!(input.first_line(), "R2, L3");
+    let input = Input::new("<test>", " 42 \n".to_string());
+    assert_eq!(input.parse_first_line::<u32>(), 42);
+    assert_eq!(input.parse::<String>(), " 42 \n");
+}
+
+#[test]
+fn from_input() {
+    struct Sum(u32);
+
+    impl FromInput for Sum {
+        fn from_input(input: &Input) -> Result<Sum, AocError> {
+            Ok(Sum(parse_lines::<u32>(input.text())?.iter().sum()))
+        }
+    }
+
+    let input = Input::new("<test>", "1\n2\n3\n".to_string());
+    assert_eq!(input.build::<Sum>().0, 6);
+    assert!(input.build_errors::<Sum>().is_empty());
+    let input = Input::new("<test>", "1\nx\n3\ny\n".to_string());
+    let errors = input.build_errors::<Sum>();
+    assert_eq!(errors.iter().map(|e| e.line_no).collect::<Vec<_>>(), [Some(2), Some(4)]);
+    assert_eq!(AocError(errors).to_string(), "line 2: invalid digit found in string\nline 4: invalid digit found in string");
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn sampler() {
+    use proptest::collection::vec;
+
+    let strategy = vec(0..1000u32, 10);
+    let values = Sampler::new([7; 32]).draw(&strategy);
+    assert_eq!(values.len(), 10);
+    assert!(values.iter().all(|&n| n < 1000));
+    assert_eq!(Sampler::new([7; 32]).draw(&strategy), values);
+    assert_ne!(Sampler::new([8; 32]).draw(&strategy), values);
+}
+
+#[test]
+fn generate_option() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    assert_eq!(parse_generate(args("input.txt")), Ok(None));
+    assert_eq!(parse_generate(args("--generate 100 7")), Ok(Some((100, 7))));
+    assert!(parse_generate(args("--generate 100")).is_err());
+    assert!(parse_generate(args("--generate big 7")).is_err());
+}
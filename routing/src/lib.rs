@@ -0,0 +1,295 @@
+//! Routing helpers over a distance matrix: permutation iteration, exact Held–Karp dynamic
+//! programming and the nearest-neighbor heuristic. Shared by the days visiting a set of
+//! locations in the shortest possible way.
+
+use std::fmt;
+
+/// The distance between two locations.
+pub type Distance = u64;
+
+/// The distances between every pair of `size` locations, identified by their index.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DistanceMatrix {
+    size: usize,
+    distances: Vec<Distance>,
+}
+
+impl DistanceMatrix {
+    /// Create a new `DistanceMatrix` of `size` locations, all distances set to zero.
+    pub fn new(size: usize) -> DistanceMatrix {
+        DistanceMatrix { size, distances: vec![0; size * size] }
+    }
+
+    /// Create a new `DistanceMatrix` from its rows, which must form a square matrix.
+    pub fn from_rows(rows: Vec<Vec<Distance>>) -> Result<DistanceMatrix, String> {
+        let size = rows.len();
+        if let Some(row) = rows.iter().position(|row| row.len() != size) {
+            return Err(format!("row {} has {} distances, expected {}", row, rows[row].len(), size));
+        }
+        Ok(DistanceMatrix { size, distances: rows.into_iter().flatten().collect() })
+    }
+
+    /// Returns the number of locations.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the distance from the location `from` to the location `to`.
+    pub fn get(&self, from: usize, to: usize) -> Distance {
+        self.distances[from * self.size + to]
+    }
+
+    /// Set the distance from the location `from` to the location `to`.
+    pub fn set(&mut self, from: usize, to: usize, distance: Distance) {
+        self.distances[from * self.size + to] = distance;
+    }
+
+    /// Set the distance between the locations `a` and `b`, in both directions.
+    pub fn set_symmetric(&mut self, a: usize, b: usize, distance: Distance) {
+        self.set(a, b, distance);
+        self.set(b, a, distance);
+    }
+
+    /// Returns the length of the given route, visiting its locations in order.
+    pub fn length(&self, route: &[usize]) -> Distance {
+        route.windows(2).map(|pair| self.get(pair[0], pair[1])).sum()
+    }
+}
+
+/// Describe where a route ends.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Route {
+    /// The route ends at the last location visited.
+    Open,
+    /// The route goes back to its start once every location has been visited.
+    Closed,
+}
+
+/// A route through every location along with its length.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Tour {
+    /// The locations in visit order, ending with the start location again for closed routes.
+    pub route: Vec<usize>,
+    /// The total distance of the route.
+    pub length: Distance,
+}
+
+impl fmt::Display for Tour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let route: Vec<String> = self.route.iter().map(|location| location.to_string()).collect();
+        write!(f, "{} ({})", route.join(" -> "), self.length)
+    }
+}
+
+/// An iterator over every permutation of `0..n` in lexicographic order.
+#[derive(Clone, Debug)]
+pub struct Permutations {
+    next: Option<Vec<usize>>,
+}
+
+/// Returns an iterator over every permutation of `0..n` in lexicographic order.
+pub fn permutations(n: usize) -> Permutations {
+    Permutations { next: Some((0..n).collect()) }
+}
+
+impl Iterator for Permutations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.next.take()?;
+        let mut next = current.clone();
+        // find the rightmost ascent, swap it with the rightmost larger element and reverse
+        // the suffix. When there is no ascent this was the last permutation.
+        if let Some(i) = (1..next.len()).rev().find(|&i| next[i - 1] < next[i]) {
+            let j = (i..next.len()).rev().find(|&j| next[j] > next[i - 1]).unwrap();
+            next.swap(i - 1, j);
+            next[i..].reverse();
+            self.next = Some(next);
+        }
+        Some(current)
+    }
+}
+
+/// Returns the route going from `start` through `rest`, and back to `start` if `kind` is closed.
+fn route_from(start: usize, rest: &[usize], kind: Route) -> Vec<usize> {
+    let mut route = Vec::with_capacity(rest.len() + 2);
+    route.push(start);
+    route.extend_from_slice(rest);
+    if kind == Route::Closed {
+        route.push(start);
+    }
+    route
+}
+
+/// Find the shortest route through every location of `matrix` starting from `start` by
+/// trying every permutation. Returns `None` when `start` is not a location of `matrix`.
+///
+/// This is O(n!) and only suited for a handful of locations, see `held_karp()`.
+pub fn brute_force(matrix: &DistanceMatrix, start: usize, kind: Route) -> Option<Tour> {
+    if start >= matrix.size() {
+        return None;
+    }
+    let others: Vec<usize> = (0..matrix.size()).filter(|&location| location != start).collect();
+    permutations(others.len())
+        .map(|permutation| {
+            let rest: Vec<usize> = permutation.into_iter().map(|i| others[i]).collect();
+            let route = route_from(start, &rest, kind);
+            Tour { length: matrix.length(&route), route }
+        })
+        .min_by_key(|tour| tour.length)
+}
+
+/// Find the shortest route through every location of `matrix` starting from `start` using
+/// the Held–Karp dynamic programming over subsets of locations. Returns `None` when `start`
+/// is not a location of `matrix`.
+///
+/// This is O(2^n n^2) in time and O(2^n n) in memory, so up to about twenty locations.
+pub fn held_karp(matrix: &DistanceMatrix, start: usize, kind: Route) -> Option<Tour> {
+    let n = matrix.size();
+    if start >= n {
+        return None;
+    }
+    if n == 1 {
+        return Some(Tour { route: route_from(start, &[], kind), length: 0 });
+    }
+    // best[mask * n + last] is the length of the shortest route starting at `start`, visiting
+    // exactly the locations in `mask` and ending at `last`.
+    let full = (1usize << n) - 1;
+    let mut best = vec![Distance::MAX; (full + 1) * n];
+    let mut parent = vec![usize::MAX; (full + 1) * n];
+    best[(1 << start) * n + start] = 0;
+    for mask in 1..=full {
+        if mask & (1 << start) == 0 {
+            continue;
+        }
+        for last in (0..n).filter(|&last| mask & (1 << last) != 0) {
+            let length = best[mask * n + last];
+            if length == Distance::MAX {
+                continue;
+            }
+            for next in (0..n).filter(|&next| mask & (1 << next) == 0) {
+                let index = (mask | (1 << next)) * n + next;
+                let candidate = length.saturating_add(matrix.get(last, next));
+                if candidate < best[index] {
+                    best[index] = candidate;
+                    parent[index] = last;
+                }
+            }
+        }
+    }
+    let back = |last: usize| match kind {
+        Route::Open => 0,
+        Route::Closed => matrix.get(last, start),
+    };
+    let (mut last, length) = (0..n)
+        .filter(|&last| last != start)
+        .map(|last| (last, best[full * n + last].saturating_add(back(last))))
+        .min_by_key(|&(_, length)| length)?;
+    // walk the parents back to `start`.
+    let mut rest = Vec::with_capacity(n - 1);
+    let mut mask = full;
+    while last != start {
+        rest.push(last);
+        let previous = parent[mask * n + last];
+        mask &= !(1 << last);
+        last = previous;
+    }
+    rest.reverse();
+    Some(Tour { route: route_from(start, &rest, kind), length })
+}
+
+/// Build a route through every location of `matrix` starting from `start` by always going
+/// to the closest location not visited yet. Returns `None` when `start` is not a location of
+/// `matrix`.
+///
+/// This is O(n^2) but the route found is not always the shortest.
+pub fn nearest_neighbor(matrix: &DistanceMatrix, start: usize, kind: Route) -> Option<Tour> {
+    let n = matrix.size();
+    if start >= n {
+        return None;
+    }
+    let mut visited = vec![false; n];
+    visited[start] = true;
+    let mut rest = Vec::with_capacity(n - 1);
+    let mut current = start;
+    while let Some(next) = (0..n).filter(|&next| !visited[next]).min_by_key(|&next| matrix.get(current, next)) {
+        visited[next] = true;
+        rest.push(next);
+        current = next;
+    }
+    let route = route_from(start, &rest, kind);
+    Some(Tour { length: matrix.length(&route), route })
+}
+
+
+#[cfg(test)]
+fn random_matrix(size: usize, seed: u64) -> DistanceMatrix {
+    // NOTE: a simple LCG is enough to get some arbitrary (but reproducible) distances.
+    let mut state = seed;
+    let mut matrix = DistanceMatrix::new(size);
+    for a in 0..size {
+        for b in (a + 1)..size {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            matrix.set_symmetric(a, b, 1 + (state >> 33) % 100);
+        }
+    }
+    matrix
+}
+
+#[test]
+fn permutations_order() {
+    let all: Vec<Vec<usize>> = permutations(3).collect();
+    assert_eq!(all, vec![
+        vec![0, 1, 2], vec![0, 2, 1], vec![1, 0, 2],
+        vec![1, 2, 0], vec![2, 0, 1], vec![2, 1, 0],
+    ]);
+    assert_eq!(permutations(0).collect::<Vec<_>>(), vec![Vec::<usize>::new()]);
+    assert_eq!(permutations(6).count(), 720);
+}
+
+#[test]
+fn from_rows() {
+    let matrix = DistanceMatrix::from_rows(vec![vec![0, 2], vec![3, 0]]).unwrap();
+    assert_eq!((matrix.size(), matrix.get(0, 1), matrix.get(1, 0)), (2, 2, 3));
+    assert!(DistanceMatrix::from_rows(vec![vec![0, 2], vec![3]]).is_err());
+}
+
+#[test]
+fn day24_example() {
+    // the distances between the locations of the Day 24 puzzle example.
+    let matrix = DistanceMatrix::from_rows(vec![
+        vec![0, 2, 8, 10, 2],
+        vec![2, 0, 6, 8, 4],
+        vec![8, 6, 0, 2, 10],
+        vec![10, 8, 2, 0, 8],
+        vec![2, 4, 10, 8, 0],
+    ]).unwrap();
+    let open = held_karp(&matrix, 0, Route::Open).unwrap();
+    assert_eq!(open.length, 14);
+    assert_eq!(open.route, vec![0, 4, 1, 2, 3]);
+    assert_eq!(open.to_string(), "0 -> 4 -> 1 -> 2 -> 3 (14)");
+    let closed = held_karp(&matrix, 0, Route::Closed).unwrap();
+    assert_eq!(closed.length, 20);
+    assert_eq!(closed.route.first(), closed.route.last());
+    assert_eq!(brute_force(&matrix, 0, Route::Open).unwrap().length, 14);
+    assert_eq!(brute_force(&matrix, 0, Route::Closed).unwrap().length, 20);
+    assert!(held_karp(&matrix, 5, Route::Open).is_none());
+}
+
+#[test]
+fn held_karp_is_exact() {
+    for seed in 0..20 {
+        let size = 2 + (seed as usize) % 7;
+        let matrix = random_matrix(size, seed);
+        for &kind in [Route::Open, Route::Closed].iter() {
+            let start = (seed as usize) % size;
+            let exact = brute_force(&matrix, start, kind).unwrap();
+            let dp = held_karp(&matrix, start, kind).unwrap();
+            assert_eq!(dp.length, exact.length);
+            assert_eq!(matrix.length(&dp.route), dp.length);
+            let heuristic = nearest_neighbor(&matrix, start, kind).unwrap();
+            assert!(heuristic.length >= exact.length);
+            assert_eq!(heuristic.route.len(), exact.route.len());
+        }
+    }
+}
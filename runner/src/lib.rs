@@ -0,0 +1,1221 @@
+//! Run many days at once. The missing puzzle inputs are downloaded concurrently and each day is
+//! solved on the blocking thread pool as soon as its input is available, so that the network
+//! and the solvers overlap on a fresh machine. The answers are cached, so that running again
+//! only solves the days whose input or code changed, see `Cache`.
+//!
+//! The `gen` subcommand prints a random input for a day instead, see `Generate`, the `batch`
+//! subcommand runs one day over many input files, see `Batch`, the `serve` subcommand answers
+//! `POST /solve/{day}` HTTP requests, see `Serve`, and the `dash` subcommand is a terminal
+//! dashboard, see `dashboard`.
+
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::{Request, Response, StatusCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// The Advent of Code edition solved by this repository.
+pub const YEAR: u32 = 2016;
+
+/// The environment variable holding the adventofcode.com session cookie, needed to download the
+/// puzzle inputs.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// What to run, from the command line.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Options {
+    /// The days to run, `--all` being every day of the repository.
+    pub days: Vec<u32>,
+    /// `true` when given `--all`.
+    pub all: bool,
+    /// `true` when given `--memory`, to report the peak heap usage of each solver.
+    pub memory: bool,
+    /// `true` when given `--no-cache`, to solve every day again even when its answers are
+    /// cached.
+    pub no_cache: bool,
+    /// Where the inputs are read from and downloaded to, one "dayNN.txt" file per day. The
+    /// default is the "input.txt" file of each day directory.
+    pub inputs: Option<PathBuf>,
+}
+
+/// What to generate, from the `gen` subcommand command line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Generate {
+    pub day: u32,
+    /// The input size, its meaning depends on the day (e.g. rooms, robots or operations).
+    pub size: usize,
+    /// The random seed, the same seed always generating the same input. Zero by default.
+    pub seed: u64,
+}
+
+/// What to run, from the `batch` subcommand command line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Batch {
+    pub day: u32,
+    /// The input files to solve, in order.
+    pub files: Vec<PathBuf>,
+    /// Given to the solver as `--timeout SECS`, so that a pathological input doesn't hang the
+    /// whole batch.
+    pub timeout: Option<String>,
+}
+
+/// What to serve, from the `serve` subcommand command line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Serve {
+    /// The address to listen on, `Serve::LISTEN` by default.
+    pub listen: SocketAddr,
+    /// Given to the solvers as `--timeout SECS`, see `Batch::timeout`.
+    pub timeout: Option<String>,
+}
+
+impl Serve {
+    /// The default address to listen on, only reachable from the local machine.
+    pub const LISTEN: &'static str = "127.0.0.1:2016";
+}
+
+/// What to show, from the `dash` subcommand command line.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Dash {
+    /// The days to run, every day of the repository when empty.
+    pub days: Vec<u32>,
+    /// See `Options::inputs`, the missing inputs are not downloaded.
+    pub inputs: Option<PathBuf>,
+    /// Given to the solvers as `--timeout SECS`, see `Batch::timeout`.
+    pub timeout: Option<String>,
+}
+
+/// A parsed command line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    Run(Options),
+    Generate(Generate),
+    Batch(Batch),
+    Serve(Serve),
+    Dash(Dash),
+}
+
+const USAGE: &str = "usage: aoc2016 [--memory] [--no-cache] [--inputs DIRECTORY] --all | DAY...\n       \
+                     aoc2016 gen --day DAY --size SIZE [--seed SEED]\n       \
+                     aoc2016 batch --day DAY [--timeout SECS] FILE...\n       \
+                     aoc2016 serve [--listen ADDRESS] [--timeout SECS]\n       \
+                     aoc2016 dash [--inputs DIRECTORY] [--timeout SECS] [DAY...]";
+
+/// Parse the command line arguments (without the program name) into a `Command`.
+pub fn parse_command<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("gen") => {
+            args.next();
+            parse_gen_args(args).map(Command::Generate)
+        },
+        Some("batch") => {
+            args.next();
+            parse_batch_args(args).map(Command::Batch)
+        },
+        Some("serve") => {
+            args.next();
+            parse_serve_args(args).map(Command::Serve)
+        },
+        Some("dash") => {
+            args.next();
+            parse_dash_args(args).map(Command::Dash)
+        },
+        _ => parse_args(args).map(Command::Run),
+    }
+}
+
+/// Parse the `gen` subcommand arguments (without `gen`) into `Generate`.
+pub fn parse_gen_args<I: IntoIterator<Item = String>>(args: I) -> Result<Generate, String> {
+    let (mut day, mut size, mut seed) = (None, None, 0);
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{}: missing value", arg))?;
+        let bad = || format!("{}: bad value: {}", arg, value);
+        match arg.as_str() {
+            "--day" => day = Some(value.parse().ok().filter(|day| (1..=25).contains(day)).ok_or_else(bad)?),
+            "--size" => size = Some(value.parse().map_err(|_| bad())?),
+            "--seed" => seed = value.parse().map_err(|_| bad())?,
+            _ => return Err(format!("unrecognized argument: {}", arg)),
+        }
+    }
+    match (day, size) {
+        (Some(day), Some(size)) => Ok(Generate { day, size, seed }),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Parse the `batch` subcommand arguments (without `batch`) into `Batch`.
+pub fn parse_batch_args<I: IntoIterator<Item = String>>(args: I) -> Result<Batch, String> {
+    let (mut day, mut files, mut timeout) = (None, Vec::new(), None);
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "--timeout" => {
+                let value = args.next().ok_or_else(|| format!("{}: missing value", arg))?;
+                let bad = || format!("{}: bad value: {}", arg, value);
+                if arg == "--day" {
+                    day = Some(value.parse().ok().filter(|day| (1..=25).contains(day)).ok_or_else(bad)?);
+                } else {
+                    value.parse().ok().filter(|&secs: &f64| secs > 0.0).ok_or_else(bad)?;
+                    timeout = Some(value);
+                }
+            },
+            _ if arg.starts_with("--") => return Err(format!("unrecognized argument: {}", arg)),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    match day {
+        Some(day) if !files.is_empty() => Ok(Batch { day, files, timeout }),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Parse the `serve` subcommand arguments (without `serve`) into `Serve`.
+pub fn parse_serve_args<I: IntoIterator<Item = String>>(args: I) -> Result<Serve, String> {
+    let mut serve = Serve { listen: Serve::LISTEN.parse().unwrap(), timeout: None };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{}: missing value", arg))?;
+        let bad = || format!("{}: bad value: {}", arg, value);
+        match arg.as_str() {
+            "--listen" => serve.listen = value.parse().map_err(|_| bad())?,
+            "--timeout" => {
+                value.parse().ok().filter(|&secs: &f64| secs > 0.0).ok_or_else(bad)?;
+                serve.timeout = Some(value);
+            },
+            _ => return Err(format!("unrecognized argument: {}", arg)),
+        }
+    }
+    Ok(serve)
+}
+
+/// Parse the `dash` subcommand arguments (without `dash`) into `Dash`.
+pub fn parse_dash_args<I: IntoIterator<Item = String>>(args: I) -> Result<Dash, String> {
+    let mut dash = Dash::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--inputs" => {
+                let dir = args.next().ok_or("usage: --inputs DIRECTORY")?;
+                dash.inputs = Some(PathBuf::from(dir));
+            },
+            "--timeout" => {
+                let secs = args.next().ok_or("usage: --timeout SECS")?;
+                secs.parse().ok().filter(|&secs: &f64| secs > 0.0).ok_or_else(|| format!("--timeout: bad value: {}", secs))?;
+                dash.timeout = Some(secs);
+            },
+            _ => match arg.parse() {
+                Ok(day @ 1..=25) => dash.days.push(day),
+                _ => return Err(format!("unrecognized argument: {}", arg)),
+            },
+        }
+    }
+    dash.days.sort_unstable();
+    dash.days.dedup();
+    Ok(dash)
+}
+
+/// Parse the command line arguments (without the program name) into `Options`.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => options.all = true,
+            "--memory" => options.memory = true,
+            "--no-cache" => options.no_cache = true,
+            "--inputs" => {
+                let dir = args.next().ok_or("usage: --inputs DIRECTORY")?;
+                options.inputs = Some(PathBuf::from(dir));
+            },
+            _ => match arg.parse() {
+                Ok(day @ 1..=25) => options.days.push(day),
+                _ => return Err(format!("unrecognized argument: {}", arg)),
+            },
+        }
+    }
+    if !options.all && options.days.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    options.days.sort_unstable();
+    options.days.dedup();
+    Ok(options)
+}
+
+/// Returns the days having a crate in `root`, in order.
+pub fn available_days(root: &Path) -> Vec<u32> {
+    (1..=25).filter(|&day| golden::day_dir(root, day).is_some()).collect()
+}
+
+/// Returns the URL of the puzzle input for `day`.
+pub fn input_url(day: u32) -> String {
+    format!("https://adventofcode.com/{}/day/{}/input", YEAR, day)
+}
+
+/// Build and run the solver of `generate.day` in `root` with `--generate`, its random input
+/// being written to our standard output.
+pub fn generate(cargo: &Path, root: &Path, generate: &Generate) -> Result<(), String> {
+    let dir = golden::day_dir(root, generate.day).ok_or_else(|| format!("day {}: no solver", generate.day))?;
+    let status = std::process::Command::new(cargo)
+        .args(["run", "--release", "--quiet", "--", report::GENERATE])
+        .args([generate.size.to_string(), generate.seed.to_string()])
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| format!("failed to run {}: {}", cargo.display(), e))?;
+    if !status.success() {
+        return Err(format!("day {}: {}", generate.day, status));
+    }
+    Ok(())
+}
+
+/// The outcome of solving one input file of a batch.
+#[derive(Debug)]
+pub struct BatchRun {
+    pub file: PathBuf,
+    /// The answers, part 1 first, or what went wrong.
+    pub answers: Result<Vec<String>, String>,
+    /// The time taken by the solver as it reported it, see `aoc_cli::TIME`.
+    pub time: Option<String>,
+}
+
+/// Build and run the solver of `batch.day` in `root` with `--quiet --time` on every file of
+/// `batch`, one after the other so that their timings are comparable.
+pub fn batch(cargo: &Path, root: &Path, batch: &Batch) -> Result<Vec<BatchRun>, String> {
+    let dir = golden::day_dir(root, batch.day).ok_or_else(|| format!("day {}: no solver", batch.day))?;
+    let runs = batch.files.iter().map(|file| {
+        let (answers, time) = match std::fs::read(file) {
+            Ok(input) => solve(cargo, &dir, &input, batch.timeout.as_deref()),
+            Err(e) => (Err(format!("{}: {}", file.display(), e)), None),
+        };
+        BatchRun { file: file.clone(), answers, time }
+    });
+    Ok(runs.collect())
+}
+
+/// Run the solver in `day_dir` on `input` with `--quiet --time` (and `--timeout SECS` when
+/// given), returns its answers (or what went wrong) along with the time it reported.
+pub fn solve(cargo: &Path, day_dir: &Path, input: &[u8], timeout: Option<&str>)
+    -> (Result<Vec<String>, String>, Option<String>)
+{
+    let mut args = vec!["--quiet", "--time"];
+    if let Some(secs) = timeout {
+        args.extend(["--timeout", secs]);
+    }
+    match golden::run_solver_with(cargo, day_dir, input, &[], &args) {
+        Ok((stdout, stderr)) => {
+            let answers = golden::quiet_answers(&stdout).into_iter().map(String::from).collect();
+            (Ok(answers), solver_time(&stderr).map(String::from))
+        },
+        Err(e) => (Err(summary(&e).to_string()), None),
+    }
+}
+
+/// Returns the time reported on `stderr` by a solver given `--time`, if any.
+pub fn solver_time(stderr: &str) -> Option<&str> {
+    stderr.lines().rev().find_map(|line| line.strip_prefix("time: "))
+}
+
+// Returns the gist of a solver failure, i.e. the first line it printed on the standard error
+// (e.g. its report or the timeout) following the exit status, or the exit status when it printed
+// nothing.
+fn summary(error: &str) -> &str {
+    let mut lines = error.lines().map(str::trim);
+    let status = lines.next().unwrap_or(error);
+    lines.find(|line| !line.is_empty()).unwrap_or(status)
+}
+
+/// Render `runs` as a table, one row per input file with the time taken and the answers (or
+/// what went wrong), the columns being aligned.
+pub fn batch_table(runs: &[BatchRun]) -> String {
+    let parts = runs.iter().filter_map(|run| run.answers.as_ref().ok()).map(Vec::len).max().unwrap_or(0);
+    let header = ["FILE".to_string(), "TIME".to_string()].into_iter()
+        .chain((1..=parts).map(|part| format!("PART {}", part)));
+    // the cells of each row, and the error ending it if any. The errors are not aligned so that
+    // they don't widen the answer columns.
+    let mut rows = vec![(header.collect::<Vec<_>>(), None)];
+    for run in runs {
+        let mut cells = vec![run.file.display().to_string(), run.time.clone().unwrap_or_else(|| "-".to_string())];
+        match run.answers {
+            Ok(ref answers) => {
+                cells.extend(answers.iter().cloned());
+                rows.push((cells, None));
+            },
+            Err(ref e) => rows.push((cells, Some(e))),
+        }
+    }
+    let mut widths = vec![0; 2 + parts];
+    for (cells, _) in &rows {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = String::new();
+    for (cells, error) in &rows {
+        let mut line = String::new();
+        for (cell, width) in cells.iter().zip(&widths) {
+            line.push_str(&format!("{:<width$}  ", cell, width = width));
+        }
+        if let Some(error) = error {
+            line.push_str(&format!("error: {}", error));
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// The answers of the previous runs, one file per day, input and solver version holding the
+/// solver output.
+///
+/// The solver version is a SHA-256 of the sources of the day crate and of the workspace crates
+/// it depends on, so that changing the code of another day keeps the answers while changing
+/// e.g. the `assembunny` crate invalidates the days using it.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    /// Where the answers are stored.
+    pub dir: PathBuf,
+    /// `false` when given `--no-cache`: the answers are stored but not looked up.
+    pub lookup: bool,
+}
+
+impl Cache {
+    /// The cache directory, relative to the repository root.
+    pub const DIR: &'static str = "target/answers";
+
+    /// Returns the cache key of the `day` solver in `day_dir` for `input`, `root` being the
+    /// repository root.
+    pub fn key(root: &Path, day: u32, day_dir: &Path, input: &[u8]) -> Result<String, String> {
+        Ok(format!("day{:02}-{}-{}", day, hex(&Sha256::digest(input)), solver_version(root, day_dir)?))
+    }
+
+    /// Returns the solver output stored for `key`, if any.
+    pub fn get(&self, key: &str) -> Option<String> {
+        if !self.lookup {
+            return None;
+        }
+        fs::read_to_string(self.dir.join(key)).ok()
+    }
+
+    /// Store the solver `output` for `key`.
+    pub fn put(&self, key: &str, output: &str) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| format!("{}: {}", self.dir.display(), e))?;
+        let path = self.dir.join(key);
+        fs::write(&path, output).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Returns the version of the solver in `day_dir`, i.e. the SHA-256 of its manifest and sources
+/// along with the ones of every workspace crate it depends on, `root` being the repository
+/// root. The workspace manifest and the toolchain are part of it too.
+pub fn solver_version(root: &Path, day_dir: &Path) -> Result<String, String> {
+    let read = |path: &Path| fs::read(path).map_err(|e| format!("{}: {}", path.display(), e));
+    let workspace = String::from_utf8_lossy(&read(&root.join("Cargo.toml"))?).into_owned();
+    let crates = workspace_crates(&workspace);
+    let mut files = vec![root.join("Cargo.toml"), root.join("rust-toolchain.toml")];
+    let mut dirs = vec![day_dir.to_path_buf()];
+    let mut seen = Vec::new();
+    while let Some(dir) = dirs.pop() {
+        if seen.contains(&dir) {
+            continue;
+        }
+        let manifest = String::from_utf8_lossy(&read(&dir.join("Cargo.toml"))?).into_owned();
+        dirs.extend(workspace_dependencies(&manifest).iter()
+                    .filter_map(|name| crates.get(name))
+                    .map(|path| root.join(path)));
+        files.push(dir.join("Cargo.toml"));
+        source_files(&dir.join("src"), &mut files).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        seen.push(dir);
+    }
+    // NOTE: the order the crates are found in doesn't matter.
+    files.sort();
+    let mut hasher = Sha256::new();
+    for path in &files {
+        hasher.update(path.strip_prefix(root).unwrap_or(path).to_string_lossy().as_bytes());
+        hasher.update([0]);
+        let content = read(path)?;
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+// Returns the path of the workspace crates by name, from the `[workspace.dependencies]` of the
+// workspace `manifest`.
+fn workspace_crates(manifest: &str) -> HashMap<&str, &str> {
+    manifest_section(manifest, "[workspace.dependencies]")
+        .filter_map(|(name, spec)| Some((name, spec.split("path = \"").nth(1)?.split('"').next()?)))
+        .collect()
+}
+
+// Returns the names of the `[dependencies]` of the crate `manifest` inherited from the
+// workspace.
+fn workspace_dependencies(manifest: &str) -> Vec<&str> {
+    manifest_section(manifest, "[dependencies]")
+        .filter(|(_, spec)| spec.contains("workspace = true"))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+// Returns the `name = spec` lines of the `section` of a Cargo `manifest`.
+fn manifest_section<'a>(manifest: &'a str, section: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+    manifest.lines()
+        .map(str::trim)
+        .skip_while(move |&line| line != section)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(" = "))
+}
+
+// Push every file in `dir` and its subdirectories into `files`.
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            source_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Returns `bytes` as lowercase hexadecimal.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The largest request body accepted by `serve()`, far above any puzzle input.
+pub const MAX_INPUT: usize = 16 << 20;
+
+/// The JSON body of a `serve()` reply.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Reply {
+    /// The solver answered, `answers` starting with part 1.
+    Solved {
+        day: u32,
+        answers: Vec<String>,
+        /// The time taken by the solver as it reported it, e.g. "12.3ms".
+        time: Option<String>,
+    },
+    /// Something went wrong, e.g. a malformed input.
+    Failed { error: String },
+}
+
+/// Returns the day to solve for a request with the given `method` and `path`, i.e.
+/// `POST /solve/{day}`, or the status to reply with.
+pub fn route(method: &str, path: &str) -> Result<u32, StatusCode> {
+    let day = path.strip_prefix("/solve/")
+        .and_then(|day| day.parse().ok())
+        .filter(|day| (1..=25).contains(day))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if method != "POST" {
+        return Err(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    Ok(day)
+}
+
+/// Listen on `serve.listen` and answer every `POST /solve/{day}` request, the body being the
+/// puzzle input, by running the solver of the day in `root`. The replies are JSON, see `Reply`.
+///
+/// Like the days runs, at most one solver per core runs at once.
+pub async fn serve(cargo: PathBuf, root: PathBuf, serve: Serve) -> Result<(), String> {
+    let listener = tokio::net::TcpListener::bind(serve.listen).await
+        .map_err(|e| format!("{}: {}", serve.listen, e))?;
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let server = Arc::new(Server { cargo, root, timeout: serve.timeout, solvers: Semaphore::new(cores) });
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("{}: {}", serve.listen, e);
+                continue;
+            },
+        };
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |request| handle(Arc::clone(&server), request));
+            let connection = hyper::server::conn::http1::Builder::new()
+                .serve_connection(hyper_util::rt::TokioIo::new(stream), service);
+            if let Err(e) = connection.await {
+                eprintln!("{}", e);
+            }
+        });
+    }
+}
+
+// What the `serve()` requests share.
+struct Server {
+    cargo: PathBuf,
+    root: PathBuf,
+    timeout: Option<String>,
+    solvers: Semaphore,
+}
+
+// Answer a `serve()` request.
+async fn handle(server: Arc<Server>, request: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
+    let failed = |status, error: String| Ok(reply(status, &Reply::Failed { error }));
+    let day = match route(request.method().as_str(), request.uri().path()) {
+        Ok(day) => day,
+        Err(status) => return failed(status, format!("usage: POST /solve/{{day}}, not {} {}",
+                                                     request.method(), request.uri().path())),
+    };
+    let dir = match golden::day_dir(&server.root, day) {
+        Some(dir) => dir,
+        None => return failed(StatusCode::NOT_FOUND, format!("day {}: no solver", day)),
+    };
+    let input = match Limited::new(request.into_body(), MAX_INPUT).collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => return failed(StatusCode::PAYLOAD_TOO_LARGE, e.to_string()),
+    };
+    let _permit = server.solvers.acquire().await;
+    let solver = Arc::clone(&server);
+    // the solver is a child process we wait for, don't block the async workers.
+    let solved = tokio::task::spawn_blocking(move || solve(&solver.cargo, &dir, &input, solver.timeout.as_deref()))
+        .await
+        .unwrap_or_else(|e| (Err(e.to_string()), None));
+    match solved {
+        (Ok(answers), time) => Ok(reply(StatusCode::OK, &Reply::Solved { day, answers, time })),
+        (Err(error), _) => failed(StatusCode::UNPROCESSABLE_ENTITY, error),
+    }
+}
+
+// Returns a JSON response with the given `status` and `body`.
+fn reply(status: StatusCode, body: &Reply) -> Response<Full<Bytes>> {
+    let json = serde_json::to_vec(body).expect("the replies serialize to JSON");
+    let mut response = Response::new(Full::new(Bytes::from(json)));
+    *response.status_mut() = status;
+    response.headers_mut().insert(hyper::header::CONTENT_TYPE, "application/json".parse().unwrap());
+    response
+}
+
+/// A terminal dashboard running the days one after the other, listing their status, answers and
+/// time. A day can be opened to view its whole output, drawn when the day has a visualization
+/// (e.g. the Day 1 path, see `VISUALIZATIONS`) and as printed otherwise (e.g. the Day 8 screen).
+///
+/// The solvers run as child processes like for the other subcommands, in the background so that
+/// the dashboard stays responsive. The days are not linked in, so only the printed output of a
+/// day can be shown and the Day 1 path is the only one drawn.
+pub mod dashboard {
+    use super::{Dash, solve};
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Layout, Rect};
+    use ratatui::style::{Modifier, Style};
+    use ratatui::symbols::Marker;
+    use ratatui::text::Text;
+    use ratatui::widgets::canvas::{Canvas, Line};
+    use ratatui::widgets::{Block, Paragraph, Row, Table, TableState, Wrap};
+    use ratatui::Frame;
+    use std::io;
+    use std::path::PathBuf;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    /// The days having a visualization, along with the arguments making their solver print it.
+    /// The other days show their regular output.
+    pub const VISUALIZATIONS: &[(u32, &[&str])] = &[
+        (1, &["--path"]),
+    ];
+
+    /// How often the dashboard is redrawn while waiting for a key.
+    const TICK: Duration = Duration::from_millis(100);
+
+    /// Where a day is at.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum Status {
+        Pending,
+        Running,
+        /// `answers` starting with part 1, `time` as reported by the solver.
+        Solved { answers: Vec<String>, time: Option<String> },
+        Failed(String),
+    }
+
+    /// A day shown by the dashboard.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct Day {
+        pub day: u32,
+        pub status: Status,
+        /// The output of the solver run with its visualization, once the day was opened.
+        pub output: Option<Result<String, String>>,
+    }
+
+    /// What the dashboard asks to run in the background.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Job {
+        /// Solve the day for its answers and time.
+        Solve(u32),
+        /// Run the day for its whole output, see `VISUALIZATIONS`.
+        Visualize(u32),
+    }
+
+    /// What the background runs report.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum Update {
+        Running(u32),
+        Solved(u32, Result<Vec<String>, String>, Option<String>),
+        Visualized(u32, Result<String, String>),
+    }
+
+    /// The dashboard state.
+    #[derive(Clone, Debug)]
+    pub struct Dashboard {
+        pub days: Vec<Day>,
+        /// The index of the selected day in `days`.
+        pub selected: usize,
+        /// The scroll offset of the opened day output, `None` when the list is shown.
+        pub opened: Option<u16>,
+        /// `true` once the user asked to quit.
+        pub quit: bool,
+    }
+
+    impl Dashboard {
+        /// Create a new `Dashboard` for `days`, all of them pending.
+        pub fn new(days: &[u32]) -> Dashboard {
+            let days = days.iter().map(|&day| Day { day, status: Status::Pending, output: None }).collect();
+            Dashboard { days, selected: 0, opened: None, quit: false }
+        }
+
+        /// Record what a background run reported.
+        pub fn update(&mut self, update: Update) {
+            let day = match update {
+                Update::Running(day) | Update::Solved(day, _, _) | Update::Visualized(day, _) => day,
+            };
+            let day = match self.days.iter_mut().find(|known| known.day == day) {
+                Some(day) => day,
+                None => return,
+            };
+            match update {
+                Update::Running(_) => day.status = Status::Running,
+                Update::Solved(_, Ok(answers), time) => day.status = Status::Solved { answers, time },
+                Update::Solved(_, Err(e), _) => day.status = Status::Failed(e),
+                Update::Visualized(_, output) => day.output = Some(output),
+            }
+        }
+
+        /// Handle a key press, returns the job to run if any.
+        pub fn key(&mut self, key: KeyCode) -> Option<Job> {
+            // NOTE: quitting doesn't need a selected day, the list may be empty.
+            if self.opened.is_none() && matches!(key, KeyCode::Char('q') | KeyCode::Esc) {
+                self.quit = true;
+                return None;
+            }
+            let selected = self.days.get(self.selected)?.day;
+            match (self.opened, key) {
+                (None, KeyCode::Up) | (None, KeyCode::Char('k')) => self.selected = self.selected.saturating_sub(1),
+                (None, KeyCode::Down) | (None, KeyCode::Char('j')) => {
+                    self.selected = (self.selected + 1).min(self.days.len() - 1);
+                },
+                (None, KeyCode::Char('r')) => {
+                    self.days[self.selected].status = Status::Pending;
+                    self.days[self.selected].output = None;
+                    return Some(Job::Solve(selected));
+                },
+                (None, KeyCode::Enter) => {
+                    self.opened = Some(0);
+                    if self.days[self.selected].output.is_none() {
+                        return Some(Job::Visualize(selected));
+                    }
+                },
+                (Some(_), KeyCode::Char('q')) | (Some(_), KeyCode::Esc) | (Some(_), KeyCode::Backspace) => {
+                    self.opened = None;
+                },
+                (Some(scroll), KeyCode::Up) | (Some(scroll), KeyCode::Char('k')) => {
+                    self.opened = Some(scroll.saturating_sub(1));
+                },
+                (Some(scroll), KeyCode::Down) | (Some(scroll), KeyCode::Char('j')) => {
+                    self.opened = Some(scroll.saturating_add(1));
+                },
+                (Some(scroll), KeyCode::PageUp) => self.opened = Some(scroll.saturating_sub(20)),
+                (Some(scroll), KeyCode::PageDown) => self.opened = Some(scroll.saturating_add(20)),
+                _ => (),
+            }
+            None
+        }
+
+        /// Draw the dashboard: the list of days, or the opened day output.
+        pub fn draw(&self, frame: &mut Frame) {
+            let [body, help] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+            match self.opened {
+                None => {
+                    self.draw_list(frame, body);
+                    frame.render_widget(Paragraph::new("↑/↓ select, Enter open, r run again, q quit"), help);
+                },
+                Some(scroll) => {
+                    self.draw_day(frame, body, scroll);
+                    frame.render_widget(Paragraph::new("↑/↓ PgUp/PgDn scroll, Esc back"), help);
+                },
+            }
+        }
+
+        fn draw_list(&self, frame: &mut Frame, area: Rect) {
+            let rows = self.days.iter().map(|day| {
+                let day_name = format!("Day {:02}", day.day);
+                // the answers (part 1 first) share a column with the errors, which are too long
+                // for a column per part.
+                match day.status {
+                    Status::Pending => Row::new([day_name, "pending".to_string()]),
+                    Status::Running => Row::new([day_name, "running…".to_string()]),
+                    Status::Solved { ref answers, ref time } => {
+                        Row::new([day_name, "solved".to_string(), time.clone().unwrap_or_default(), answers.join("  ")])
+                    },
+                    Status::Failed(ref e) => Row::new([day_name, "failed".to_string(), String::new(), e.clone()]),
+                }
+            });
+            let widths = [Constraint::Length(6), Constraint::Length(8), Constraint::Length(10), Constraint::Fill(1)];
+            let table = Table::new(rows, widths)
+                .header(Row::new(["DAY", "STATUS", "TIME", "ANSWERS"]).style(Style::new().add_modifier(Modifier::BOLD)))
+                .block(Block::bordered().title("Advent of Code 2016"))
+                .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            let mut state = TableState::default().with_selected(Some(self.selected));
+            frame.render_stateful_widget(table, area, &mut state);
+        }
+
+        fn draw_day(&self, frame: &mut Frame, area: Rect, scroll: u16) {
+            let day = &self.days[self.selected];
+            let block = Block::bordered().title(format!("Day {:02}", day.day));
+            let output = match day.output {
+                None => {
+                    frame.render_widget(Paragraph::new("running…").block(block), area);
+                    return;
+                },
+                Some(Err(ref e)) => {
+                    frame.render_widget(Paragraph::new(e.as_str()).block(block), area);
+                    return;
+                },
+                Some(Ok(ref output)) => output,
+            };
+            let corners = path_corners(output);
+            let text = Paragraph::new(Text::raw(output.as_str())).scroll((scroll, 0));
+            if corners.len() < 2 {
+                frame.render_widget(text.block(block), area);
+                return;
+            }
+            let [drawing, printed] = Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)]).areas(area);
+            let bounds = |coordinate: fn(&(f64, f64)) -> f64| {
+                let values = corners.iter().map(coordinate);
+                [values.clone().fold(f64::INFINITY, f64::min), values.fold(f64::NEG_INFINITY, f64::max)]
+            };
+            let canvas = Canvas::default()
+                .block(block)
+                .marker(Marker::Braille)
+                .x_bounds(bounds(|corner| corner.0))
+                .y_bounds(bounds(|corner| corner.1))
+                .paint(|context| {
+                    for pair in corners.windows(2) {
+                        let (from, to) = (pair[0], pair[1]);
+                        context.draw(&Line::new(from.0, from.1, to.0, to.1, ratatui::style::Color::Reset));
+                    }
+                });
+            frame.render_widget(canvas, drawing);
+            // NOTE: the output is narrow next to the drawing, its lines are wrapped.
+            frame.render_widget(text.wrap(Wrap { trim: false }).block(Block::bordered().title("output")), printed);
+        }
+    }
+
+    /// Returns the path corners printed by a solver given its visualization arguments, i.e. the
+    /// "(x, y)" lines of `output`, in order.
+    pub fn path_corners(output: &str) -> Vec<(f64, f64)> {
+        output.lines().filter_map(|line| {
+            let (x, y) = line.trim().strip_prefix('(')?.strip_suffix(')')?.split_once(", ")?;
+            Some((x.parse().ok()?, y.parse().ok()?))
+        }).collect()
+    }
+
+    /// Show the dashboard for `dash.days` on the terminal until the user quits, the solvers of
+    /// the days in `root` being run with `cargo`.
+    pub fn run(cargo: PathBuf, root: PathBuf, dash: Dash) -> io::Result<()> {
+        let days = dash.days.clone();
+        let mut dashboard = Dashboard::new(&days);
+        let (updates, received) = mpsc::channel();
+        let (jobs, pending) = mpsc::channel();
+        let worker = Worker { cargo, root, dash, updates };
+        // the days are solved one after the other so that their times are comparable, the
+        // visualizations asked for meanwhile don't wait for them.
+        let solver = worker.clone();
+        thread::spawn(move || pending.iter().for_each(|job| solver.run(job)));
+        for &day in &days {
+            let _ = jobs.send(Job::Solve(day));
+        }
+        let mut terminal = ratatui::init();
+        let result = dashboard_loop(&mut terminal, &mut dashboard, &received, |job| match job {
+            Job::Solve(_) => {
+                let _ = jobs.send(job);
+            },
+            Job::Visualize(_) => {
+                let worker = worker.clone();
+                thread::spawn(move || worker.run(job));
+            },
+        });
+        ratatui::restore();
+        result
+    }
+
+    fn dashboard_loop<F: FnMut(Job)>(terminal: &mut ratatui::DefaultTerminal, dashboard: &mut Dashboard,
+                                     updates: &Receiver<Update>, mut dispatch: F) -> io::Result<()> {
+        while !dashboard.quit {
+            while let Ok(update) = updates.try_recv() {
+                dashboard.update(update);
+            }
+            terminal.draw(|frame| dashboard.draw(frame))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        if let Some(job) = dashboard.key(key.code) {
+                            dispatch(job);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Runs the `Job`s in the background, reporting to the dashboard.
+    #[derive(Clone)]
+    struct Worker {
+        cargo: PathBuf,
+        root: PathBuf,
+        dash: Dash,
+        updates: Sender<Update>,
+    }
+
+    impl Worker {
+        fn run(&self, job: Job) {
+            let update = match job {
+                Job::Solve(day) => {
+                    let _ = self.updates.send(Update::Running(day));
+                    let (answers, time) = match self.input(day) {
+                        Ok((dir, input)) => solve(&self.cargo, &dir, &input, self.dash.timeout.as_deref()),
+                        Err(e) => (Err(e), None),
+                    };
+                    Update::Solved(day, answers, time)
+                },
+                Job::Visualize(day) => {
+                    let args = VISUALIZATIONS.iter().find(|&&(known, _)| known == day).map_or(&[][..], |v| v.1);
+                    let output = self.input(day)
+                        .and_then(|(dir, input)| ::golden::run_solver_with(&self.cargo, &dir, &input, &[], args))
+                        .map(|(stdout, _)| stdout);
+                    Update::Visualized(day, output)
+                },
+            };
+            let _ = self.updates.send(update);
+        }
+
+        // Returns the directory of the `day` solver and its input.
+        fn input(&self, day: u32) -> Result<(PathBuf, Vec<u8>), String> {
+            let dir = ::golden::day_dir(&self.root, day).ok_or_else(|| "no solver".to_string())?;
+            let path = ::golden::input_path(&dir, self.dash.inputs.as_deref(), day);
+            let input = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok((dir, input))
+        }
+    }
+}
+
+/// The outcome of running one day.
+#[derive(Debug)]
+pub struct Run {
+    pub day: u32,
+    /// The time spent waiting for the input, zero when it was already there.
+    pub download: Duration,
+    /// The time spent by the solver, zero when the answers were cached.
+    pub solve: Duration,
+    /// `true` when the answers were found in the `Cache`.
+    pub cached: bool,
+    /// The peak heap usage of the solver in bytes, when it was measured.
+    pub peak: Option<usize>,
+    /// The solver output, or what went wrong.
+    pub output: Result<String, String>,
+}
+
+/// Read the input of `day` at `path`, downloading it first when it's missing. The download
+/// needs the `session` cookie.
+pub async fn fetch(client: &reqwest::Client, session: Option<&str>, day: u32, path: &Path)
+    -> Result<Vec<u8>, String>
+{
+    if let Ok(input) = tokio::fs::read(path).await {
+        return Ok(input);
+    }
+    let session = session.ok_or_else(|| format!("{}: missing, and {} is not set", path.display(), SESSION_VAR))?;
+    let response = client.get(input_url(day))
+        .header(reqwest::header::COOKIE, format!("session={}", session))
+        .send().await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("{}: {}", input_url(day), e))?;
+    let input = response.bytes().await.map_err(|e| format!("{}: {}", input_url(day), e))?;
+    // save it so that the next runs don't download it again.
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await.map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    tokio::fs::write(path, &input).await.map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(input.to_vec())
+}
+
+/// What every day run shares.
+#[derive(Clone, Debug)]
+pub struct Context {
+    pub client: reqwest::Client,
+    /// The adventofcode.com session cookie, if any.
+    pub session: Option<String>,
+    /// The cargo binary used to build and run the solvers.
+    pub cargo: PathBuf,
+    /// The repository root, holding the day directories.
+    pub root: PathBuf,
+    /// See `Options::inputs`.
+    pub inputs: Option<PathBuf>,
+    /// Bounds the number of solvers running at once, as they are CPU-bound.
+    pub solvers: Arc<Semaphore>,
+    /// See `Options::memory`.
+    pub memory: bool,
+    /// Where the answers are cached, `None` when the solvers must run (e.g. to measure them).
+    pub cache: Option<Cache>,
+}
+
+/// Fetch the input of `day`, then solve it on the blocking thread pool.
+pub async fn run(context: Context, day: u32) -> Run {
+    let failed = |download, e| Run { day, download, solve: Duration::ZERO, cached: false, peak: None, output: Err(e) };
+    let start = Instant::now();
+    let dir = match golden::day_dir(&context.root, day) {
+        Some(dir) => dir,
+        None => return failed(Duration::ZERO, "no solver".to_string()),
+    };
+    let path = golden::input_path(&dir, context.inputs.as_deref(), day);
+    let input = fetch(&context.client, context.session.as_deref(), day, &path).await;
+    let download = start.elapsed();
+    let input = match input {
+        Ok(input) => input,
+        Err(e) => return failed(download, e),
+    };
+    // NOTE: a solver version that can't be computed only disables the cache, the solver will
+    // report the actual problem if any.
+    let key = match context.cache {
+        Some(_) => {
+            let (root, dir, input) = (context.root.clone(), dir.clone(), input.clone());
+            tokio::task::spawn_blocking(move || Cache::key(&root, day, &dir, &input).ok()).await.ok().flatten()
+        },
+        None => None,
+    };
+    let cached = key.as_ref().and_then(|key| context.cache.as_ref()?.get(key));
+    if let Some(output) = cached {
+        return Run { day, download, solve: Duration::ZERO, cached: true, peak: None, output: Ok(output) };
+    }
+    // NOTE: the blocking pool also runs the `tokio::fs` operations, so the solvers are bounded
+    // by a semaphore rather than by the pool size.
+    let _permit = context.solvers.acquire().await;
+    let start = Instant::now();
+    let cargo = context.cargo.clone();
+    let features: &[&str] = if context.memory { &[memory::FEATURE] } else { &[] };
+    // the solver is a child process we wait for, don't block the async workers.
+    let output = tokio::task::spawn_blocking(move || golden::run_solver_with_features(&cargo, &dir, &input, features))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    let solve = start.elapsed();
+    let peak = output.as_ref().ok().and_then(|(_, stderr)| memory::parse_report(stderr));
+    if let (Some(cache), Some(key), Ok((stdout, _))) = (&context.cache, &key, &output) {
+        // NOTE: failing to store the answers only costs solving the day again next time.
+        if let Err(e) = cache.put(key, stdout) {
+            eprintln!("day {}: {}", day, e);
+        }
+    }
+    Run { day, download, solve, cached: false, peak, output: output.map(|(stdout, _)| stdout) }
+}
+
+#[test]
+fn command_line() {
+    let args = |s: &str| parse_args(s.split_whitespace().map(String::from));
+    assert_eq!(args("9 4 9"), Ok(Options { days: vec![4, 9], ..Options::default() }));
+    assert_eq!(args("--all --inputs /tmp/aoc"),
+               Ok(Options { days: vec![], all: true, inputs: Some(PathBuf::from("/tmp/aoc")), ..Options::default() }));
+    assert_eq!(args("--memory 4"), Ok(Options { days: vec![4], memory: true, ..Options::default() }));
+    assert_eq!(args("--no-cache 4"), Ok(Options { days: vec![4], no_cache: true, ..Options::default() }));
+    assert!(args("").is_err());
+    assert!(args("26").is_err());
+    assert!(args("--inputs").is_err());
+}
+
+#[test]
+fn gen_command_line() {
+    let args = |s: &str| parse_command(s.split_whitespace().map(String::from));
+    assert_eq!(args("gen --day 4 --size 1000 --seed 7"),
+               Ok(Command::Generate(Generate { day: 4, size: 1000, seed: 7 })));
+    assert_eq!(args("gen --size 10 --day 10"), Ok(Command::Generate(Generate { day: 10, size: 10, seed: 0 })));
+    assert_eq!(args("4"), Ok(Command::Run(Options { days: vec![4], ..Options::default() })));
+    assert!(args("gen --day 4").is_err());
+    assert!(args("gen --day 26 --size 10").is_err());
+    assert!(args("gen --day 4 --size big").is_err());
+    assert!(args("gen --day 4 --size 10 --seed").is_err());
+}
+
+#[test]
+fn batch_command_line() {
+    let args = |s: &str| parse_command(s.split_whitespace().map(String::from));
+    let files = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+    assert_eq!(args("batch --day 4 a.txt b.txt"),
+               Ok(Command::Batch(Batch { day: 4, files: files(&["a.txt", "b.txt"]), timeout: None })));
+    assert_eq!(args("batch a.txt --timeout 2.5 --day 23"),
+               Ok(Command::Batch(Batch { day: 23, files: files(&["a.txt"]), timeout: Some("2.5".to_string()) })));
+    assert!(args("batch --day 4").is_err());
+    assert!(args("batch a.txt").is_err());
+    assert!(args("batch --day 4 --timeout never a.txt").is_err());
+    assert!(args("batch --day 4 --size 10 a.txt").is_err());
+}
+
+#[test]
+fn batch_results() {
+    let run = |file: &str, answers, time: Option<&str>| BatchRun {
+        file: PathBuf::from(file), answers, time: time.map(String::from),
+    };
+    let runs = vec![
+        run("inputs/day04.txt", Ok(vec!["185371".to_string(), "984".to_string()]), Some("12.3ms")),
+        run("bad.txt", Err("<stdin>: line 3: empty room".to_string()), None),
+        run("inputs/large.txt", Ok(vec!["42".to_string(), "1".to_string()]), Some("1.2s")),
+    ];
+    assert_eq!(batch_table(&runs), "\
+FILE              TIME    PART 1  PART 2
+inputs/day04.txt  12.3ms  185371  984
+bad.txt           -       error: <stdin>: line 3: empty room
+inputs/large.txt  1.2s    42      1
+");
+    assert_eq!(solver_time("warning\ntime: 12.3ms\n"), Some("12.3ms"));
+    assert_eq!(solver_time(""), None);
+    assert_eq!(summary("exit status: 124\ntimeout: no answer after 2s\n"), "timeout: no answer after 2s");
+    assert_eq!(summary("exit status: 1\n"), "exit status: 1");
+    assert_eq!(summary("exit status: 1\n<stdin>: line 2: bad\n  |\n2 | bad\n  | ^\n"), "<stdin>: line 2: bad");
+}
+
+#[test]
+fn serve_command_line() {
+    let args = |s: &str| parse_command(s.split_whitespace().map(String::from));
+    assert_eq!(args("serve"), Ok(Command::Serve(Serve { listen: Serve::LISTEN.parse().unwrap(), timeout: None })));
+    assert_eq!(args("serve --timeout 10 --listen 0.0.0.0:8080"),
+               Ok(Command::Serve(Serve { listen: "0.0.0.0:8080".parse().unwrap(), timeout: Some("10".to_string()) })));
+    assert!(args("serve --listen localhost").is_err());
+    assert!(args("serve --timeout").is_err());
+}
+
+#[test]
+fn serve_replies() {
+    assert_eq!(route("POST", "/solve/4"), Ok(4));
+    assert_eq!(route("GET", "/solve/4"), Err(StatusCode::METHOD_NOT_ALLOWED));
+    assert_eq!(route("POST", "/solve/26"), Err(StatusCode::NOT_FOUND));
+    assert_eq!(route("POST", "/solve/"), Err(StatusCode::NOT_FOUND));
+    assert_eq!(route("POST", "/"), Err(StatusCode::NOT_FOUND));
+    let solved = Reply::Solved { day: 4, answers: vec!["278221".to_string(), "267".to_string()],
+                                 time: Some("2.5ms".to_string()) };
+    assert_eq!(serde_json::to_string(&solved).unwrap(), r#"{"day":4,"answers":["278221","267"],"time":"2.5ms"}"#);
+    let failed = reply(StatusCode::UNPROCESSABLE_ENTITY, &Reply::Failed { error: "empty room".to_string() });
+    assert_eq!(failed.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(failed.headers()[hyper::header::CONTENT_TYPE], "application/json");
+}
+
+#[test]
+fn dash_command_line() {
+    let args = |s: &str| parse_command(s.split_whitespace().map(String::from));
+    assert_eq!(args("dash"), Ok(Command::Dash(Dash::default())));
+    assert_eq!(args("dash 9 --timeout 5 4 --inputs /tmp/aoc"),
+               Ok(Command::Dash(Dash { days: vec![4, 9], inputs: Some(PathBuf::from("/tmp/aoc")),
+                                       timeout: Some("5".to_string()) })));
+    assert!(args("dash 26").is_err());
+    assert!(args("dash --timeout 0").is_err());
+}
+
+#[test]
+fn dashboard_view() {
+    use dashboard::*;
+    use ratatui::crossterm::event::KeyCode;
+
+    let mut dashboard = Dashboard::new(&[1, 4, 8]);
+    dashboard.update(Update::Running(1));
+    dashboard.update(Update::Solved(1, Ok(vec!["242".to_string(), "150".to_string()]), Some("1.2ms".to_string())));
+    dashboard.update(Update::Solved(4, Err("<stdin>: line 3: empty room".to_string()), None));
+    dashboard.update(Update::Running(8));
+    let screen = |dashboard: &Dashboard| {
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().chunks(80).map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+    };
+    let lines = screen(&dashboard);
+    assert!(lines.iter().any(|line| line.contains("Day 01") && line.contains("solved") && line.contains("1.2ms")
+                                        && line.contains("242") && line.contains("150")));
+    assert!(lines.iter().any(|line| line.contains("Day 04") && line.contains("failed") && line.contains("empty room")));
+    assert!(lines.iter().any(|line| line.contains("Day 08") && line.contains("running")));
+
+    // opening a day runs its visualization once.
+    assert_eq!(dashboard.key(KeyCode::Enter), Some(Job::Visualize(1)));
+    dashboard.update(Update::Visualized(1, Ok("Easter Bunny Headquarters distance: 5\n(0, 0)\n(2, 0)\n(2, 3)\n".to_string())));
+    assert!(screen(&dashboard).iter().any(|line| line.contains("distance: 5")));
+    assert_eq!(dashboard.key(KeyCode::Esc), None);
+    assert_eq!(dashboard.key(KeyCode::Enter), None);
+    assert_eq!(dashboard.key(KeyCode::Esc), None);
+    assert_eq!(dashboard.key(KeyCode::Down), None);
+    assert_eq!(dashboard.key(KeyCode::Char('r')), Some(Job::Solve(4)));
+    assert_eq!(dashboard.days[1].status, Status::Pending);
+    assert_eq!(dashboard.key(KeyCode::Char('q')), None);
+    assert!(dashboard.quit);
+    let mut empty = Dashboard::new(&[]);
+    assert_eq!(empty.key(KeyCode::Down), None);
+    assert_eq!(empty.key(KeyCode::Esc), None);
+    assert!(empty.quit);
+    assert_eq!(path_corners("distance: 5\n(0, 0)\n(2, -3)\n(x, 1)\n"), vec![(0.0, 0.0), (2.0, -3.0)]);
+}
+
+#[test]
+fn manifests() {
+    let workspace = "[workspace]\nmembers = [\"search\"]\n\n[workspace.dependencies]\n# shared.\n\
+                     aoc-core = { path = \"aoc-core\" }\nsearch = { path = \"search\" }\nrand = \"^0.7.3\"\n";
+    let crates = workspace_crates(workspace);
+    assert_eq!(crates.len(), 2);
+    assert_eq!((crates["aoc-core"], crates["search"]), ("aoc-core", "search"));
+    let day = "[package]\nname = \"day\"\n\n[dependencies]\nsearch = { workspace = true }\n\
+               rand = { workspace = true, optional = true }\nregex = \"^1.3.7\"\n\n\
+               [dev-dependencies]\nsnapshot = { workspace = true }\n";
+    assert_eq!(workspace_dependencies(day), vec!["search", "rand"]);
+    assert_eq!(hex(&[0, 15, 255]), "000fff");
+}
+
+#[test]
+fn solver_versions() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let (day12, day23) = (golden::day_dir(root, 12).unwrap(), golden::day_dir(root, 23).unwrap());
+    let version = solver_version(root, &day12).unwrap();
+    assert_eq!(version.len(), 64);
+    assert_eq!(solver_version(root, &day12), Ok(version.clone()));
+    assert_ne!(solver_version(root, &day23), Ok(version));
+    let key = Cache::key(root, 12, &day12, b"cpy 41 a\n").unwrap();
+    assert!(key.starts_with("day12-"));
+    assert_ne!(Cache::key(root, 12, &day12, b"cpy 42 a\n"), Ok(key));
+}
+
+#[test]
+fn cached_answers() {
+    let dir = std::env::temp_dir().join(format!("aoc2016-cache-{}", std::process::id()));
+    let mut cache = Cache { dir: dir.clone(), lookup: true };
+    assert_eq!(cache.get("day04-key"), None);
+    cache.put("day04-key", "278221\n267\n").unwrap();
+    assert_eq!(cache.get("day04-key").as_deref(), Some("278221\n267\n"));
+    cache.lookup = false;
+    assert_eq!(cache.get("day04-key"), None);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn repository_days() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let days = available_days(root);
+    assert_eq!(days.first(), Some(&1));
+    assert!(days.contains(&25));
+    assert_eq!(input_url(4), "https://adventofcode.com/2016/day/4/input");
+}
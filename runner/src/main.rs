@@ -0,0 +1,107 @@
+use runner::*;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Returns the repository root, holding the day directories.
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+/// Returns the cargo binary running us, so that the days use the same toolchain.
+fn cargo() -> PathBuf {
+    env::var_os("CARGO").map_or_else(|| PathBuf::from("cargo"), PathBuf::from)
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| report::exit(e))
+}
+
+async fn run_all(options: Options) -> usize {
+    let root = root();
+    let days = if options.all { available_days(&root) } else { options.days };
+    let client = reqwest::Client::builder()
+        .user_agent("github.com/kAworu/adventofcode-2016 runner")
+        .build()
+        .unwrap_or_else(|e| report::exit(e));
+    // the solvers are CPU-bound, so don't run more of them than there are cores.
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    // the solvers have to run to measure them.
+    let cache = (!options.memory).then(|| Cache { dir: root.join(Cache::DIR), lookup: !options.no_cache });
+    let context = Context {
+        client,
+        session: env::var(SESSION_VAR).ok(),
+        cargo: cargo(),
+        root,
+        inputs: options.inputs,
+        solvers: Arc::new(Semaphore::new(cores)),
+        memory: options.memory,
+        cache,
+    };
+
+    // every day is fetched and solved concurrently, the results are printed as they come.
+    let mut runs = JoinSet::new();
+    for day in days {
+        runs.spawn(run(context.clone(), day));
+    }
+    let mut failures = 0;
+    while let Some(joined) = runs.join_next().await {
+        let run = joined.unwrap_or_else(|e| report::exit(e));
+        match run.peak {
+            _ if run.cached => println!("== Day {:02} (input {:.1?}, cached)", run.day, run.download),
+            Some(peak) => println!("== Day {:02} (input {:.1?}, solve {:.1?}, peak heap {})",
+                                   run.day, run.download, run.solve, memory::Bytes(peak)),
+            None => println!("== Day {:02} (input {:.1?}, solve {:.1?})", run.day, run.download, run.solve),
+        }
+        match run.output {
+            Ok(output) => print!("{}", output),
+            Err(e) => {
+                failures += 1;
+                eprintln!("day {}: {}", run.day, e);
+            },
+        }
+    }
+    failures
+}
+
+fn main() {
+    let options = match parse_command(env::args().skip(1)).unwrap_or_else(|e| report::exit(e)) {
+        Command::Run(options) => options,
+        Command::Generate(generate) => {
+            runner::generate(&cargo(), &root(), &generate).unwrap_or_else(|e| report::exit(e));
+            return;
+        },
+        Command::Batch(batch) => {
+            let runs = runner::batch(&cargo(), &root(), &batch).unwrap_or_else(|e| report::exit(e));
+            print!("{}", batch_table(&runs));
+            let failures = runs.iter().filter(|run| run.answers.is_err()).count();
+            if failures > 0 {
+                report::exit(format!("{} file(s) failed", failures));
+            }
+            return;
+        },
+        Command::Dash(mut dash) => {
+            let root = root();
+            if dash.days.is_empty() {
+                dash.days = available_days(&root);
+            }
+            dashboard::run(cargo(), root, dash).unwrap_or_else(|e| report::exit(e));
+            return;
+        },
+        Command::Serve(serve) => {
+            eprintln!("listening on http://{}, POST /solve/{{day}} with the puzzle input", serve.listen);
+            runtime().block_on(runner::serve(cargo(), root(), serve)).unwrap_or_else(|e| report::exit(e));
+            return;
+        },
+    };
+
+    let failures = runtime().block_on(run_all(options));
+    if failures > 0 {
+        report::exit(format!("{} day(s) failed", failures));
+    }
+}
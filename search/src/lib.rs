@@ -0,0 +1,547 @@
+//! Memoized state-space searches (breadth-first, depth-first, A* and IDA*) reporting
+//! statistics, shared by the days exploring puzzle states so that search tuning happens in
+//! one place.
+//!
+//! A search can be stopped early (e.g. on a `--timeout`) through `Space::cancelled()`, its result
+//! is then marked as `cancelled`.
+
+#[macro_use]
+extern crate tracing;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+/// The cost of a move, or of a path.
+pub type Cost = u64;
+
+/// A state space to search.
+pub trait Space {
+    /// A state of the space.
+    type State: Clone;
+    /// The canonical form of a state, see `key()`.
+    type Key: Eq + Hash;
+
+    /// Returns the states reachable in one move from `state`, along with the move cost.
+    fn successors(&self, state: &Self::State) -> Vec<(Self::State, Cost)>;
+
+    /// Returns `true` if `state` is a goal, `false` otherwise.
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// Returns the canonical form of `state`. States sharing the same key are considered
+    /// equivalent and only one of them is expanded, so that e.g. symmetric states can be
+    /// pruned.
+    fn key(&self, state: &Self::State) -> Self::Key;
+
+    /// Returns a lower bound of the cost from `state` to the closest goal, used by A* and
+    /// IDA*. It must never overestimate for the path found to be the cheapest.
+    fn heuristic(&self, _state: &Self::State) -> Cost {
+        0
+    }
+
+    /// Returns `true` when the search should stop, checked before expanding each state. The
+    /// states found so far are then returned as if the space was exhausted, see
+    /// `Outcome::cancelled`.
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Statistics about a search.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Stats {
+    /// The number of states whose successors were computed.
+    pub expanded: usize,
+    /// The number of successors computed.
+    pub generated: usize,
+    /// The number of distinct keys seen. For IDA* this is the longest path explored instead.
+    pub visited: usize,
+    /// The largest frontier size during the search. For IDA* this is the deepest path
+    /// explored instead.
+    pub max_frontier: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} expanded, {} generated, {} visited, max frontier {}",
+               self.expanded, self.generated, self.visited, self.max_frontier)
+    }
+}
+
+/// A path from the start state to a goal.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Path<S> {
+    /// The states along the path, from the start to the goal (both included).
+    pub states: Vec<S>,
+    /// The total cost of the moves along the path.
+    pub cost: Cost,
+}
+
+impl<S> Path<S> {
+    /// Returns the number of moves along the path.
+    pub fn moves(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    /// Returns the goal reached.
+    pub fn goal(&self) -> &S {
+        self.states.last().unwrap()
+    }
+}
+
+/// The result of a search.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Outcome<S> {
+    /// The path found, if any.
+    pub path: Option<Path<S>>,
+    /// Statistics about the search.
+    pub stats: Stats,
+    /// `true` when the search was stopped by `Space::cancelled()`, a missing path then doesn't
+    /// mean that there is none.
+    pub cancelled: bool,
+}
+
+/// The states reached by `explore()`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Exploration<S> {
+    /// Every state reached along with its depth (i.e. its number of moves from the start), in
+    /// breadth-first order. Only one state per key is kept.
+    pub states: Vec<(S, usize)>,
+    /// Statistics about the exploration.
+    pub stats: Stats,
+    /// `true` when the exploration was stopped by `Space::cancelled()`, some states may be
+    /// missing.
+    pub cancelled: bool,
+}
+
+// the states reached by a search, linked to their parent so that paths can be rebuilt.
+struct Tree<S> {
+    nodes: Vec<(S, Option<usize>, Cost)>,
+}
+
+impl<S: Clone> Tree<S> {
+    fn add(&mut self, state: S, parent: Option<usize>, cost: Cost) -> usize {
+        self.nodes.push((state, parent, cost));
+        self.nodes.len() - 1
+    }
+
+    fn path(&self, mut node: usize) -> Path<S> {
+        let cost = self.nodes[node].2;
+        let mut states = vec![self.nodes[node].0.clone()];
+        while let Some(parent) = self.nodes[node].1 {
+            states.push(self.nodes[parent].0.clone());
+            node = parent;
+        }
+        states.reverse();
+        Path { states, cost }
+    }
+}
+
+// Shared by the breadth-first and depth-first searches, which only differ by the end of the
+// frontier they take states from. States are marked as visited when they enter the frontier.
+fn uninformed<P: Space>(space: &P, start: P::State, lifo: bool) -> Outcome<P::State> {
+    let mut stats = Stats::default();
+    let mut tree = Tree { nodes: Vec::new() };
+    let mut visited = HashSet::new();
+    visited.insert(space.key(&start));
+    let mut frontier = VecDeque::new();
+    frontier.push_back(tree.add(start, None, 0));
+    let (mut path, mut cancelled) = (None, false);
+    // breadth-first, the tree nodes are added level by level and so the current level ends
+    // with the last node added before it started.
+    let (mut level, mut depth, mut level_end) = (None, 0, 0);
+    loop {
+        stats.max_frontier = stats.max_frontier.max(frontier.len());
+        let node = match if lifo { frontier.pop_back() } else { frontier.pop_front() } {
+            Some(node) => node,
+            None => break,
+        };
+        if !lifo && node >= level_end {
+            // NOTE: the previous level span has to be exited before entering the next one.
+            level.take();
+            level = Some(info_span!("bfs_level", depth, states = tree.nodes.len() - node).entered());
+            depth += 1;
+            level_end = tree.nodes.len();
+        }
+        if space.is_goal(&tree.nodes[node].0) {
+            path = Some(tree.path(node));
+            break;
+        }
+        if space.cancelled() {
+            cancelled = true;
+            break;
+        }
+        stats.expanded += 1;
+        let cost = tree.nodes[node].2;
+        for (next, step) in space.successors(&tree.nodes[node].0) {
+            stats.generated += 1;
+            if visited.insert(space.key(&next)) {
+                frontier.push_back(tree.add(next, Some(node), cost + step));
+            }
+        }
+    }
+    stats.visited = visited.len();
+    Outcome { path, stats, cancelled }
+}
+
+/// Search a goal breadth-first from `start`. The path found has the fewest moves, but not
+/// necessarily the lowest cost when moves have different costs, see `astar()`.
+pub fn bfs<P: Space>(space: &P, start: P::State) -> Outcome<P::State> {
+    uninformed(space, start, false)
+}
+
+/// Search a goal depth-first from `start`. The path found is not necessarily the shortest.
+pub fn dfs<P: Space>(space: &P, start: P::State) -> Outcome<P::State> {
+    uninformed(space, start, true)
+}
+
+/// Search the cheapest path from `start` to a goal using A*, guided by the space heuristic.
+pub fn astar<P: Space>(space: &P, start: P::State) -> Outcome<P::State> {
+    let mut stats = Stats::default();
+    let mut tree = Tree { nodes: Vec::new() };
+    // the lowest cost found so far for each key, and the keys already expanded.
+    let mut best: HashMap<P::Key, Cost> = HashMap::new();
+    let mut closed: HashSet<P::Key> = HashSet::new();
+    let mut frontier = BinaryHeap::new();
+    best.insert(space.key(&start), 0);
+    let estimate = space.heuristic(&start);
+    frontier.push(Reverse((estimate, 0, tree.add(start, None, 0))));
+    let (mut path, mut cancelled) = (None, false);
+    while let Some(Reverse((_, cost, node))) = frontier.pop() {
+        stats.max_frontier = stats.max_frontier.max(frontier.len() + 1);
+        let key = space.key(&tree.nodes[node].0);
+        // NOTE: a key may be pushed several times as cheaper paths are found, only its first
+        // pop is relevant.
+        if !closed.insert(key) {
+            continue;
+        }
+        if space.is_goal(&tree.nodes[node].0) {
+            path = Some(tree.path(node));
+            break;
+        }
+        if space.cancelled() {
+            cancelled = true;
+            break;
+        }
+        stats.expanded += 1;
+        for (next, step) in space.successors(&tree.nodes[node].0) {
+            stats.generated += 1;
+            let key = space.key(&next);
+            let next_cost = cost + step;
+            if closed.contains(&key) || best.get(&key).is_some_and(|&known| known <= next_cost) {
+                continue;
+            }
+            best.insert(key, next_cost);
+            let estimate = next_cost + space.heuristic(&next);
+            frontier.push(Reverse((estimate, next_cost, tree.add(next, Some(node), next_cost))));
+        }
+    }
+    stats.visited = best.len();
+    Outcome { path, stats, cancelled }
+}
+
+/// Search the cheapest path from `start` to a goal using IDA*, i.e. successive depth-first
+/// searches bounded by the estimated total cost. It uses far less memory than `astar()` but
+/// only avoid cycles along the current path, so it may expand the same state many times.
+pub fn idastar<P: Space>(space: &P, start: P::State) -> Outcome<P::State> {
+    // Returns the path cost when a goal has been found (and left at the end of `path`),
+    // otherwise the lowest estimate that exceeded `bound` if any. A cancelled search returns no
+    // bound, as if the space was exhausted.
+    fn bounded<P: Space>(space: &P, path: &mut Vec<(P::State, P::Key)>, cost: Cost, bound: Cost,
+                         stats: &mut Stats) -> Result<Cost, Option<Cost>> {
+        stats.max_frontier = stats.max_frontier.max(path.len());
+        let state = path[path.len() - 1].0.clone();
+        let estimate = cost + space.heuristic(&state);
+        if estimate > bound {
+            return Err(Some(estimate));
+        }
+        if space.is_goal(&state) {
+            return Ok(cost);
+        }
+        if space.cancelled() {
+            return Err(None);
+        }
+        stats.expanded += 1;
+        let mut next_bound = None;
+        for (next, step) in space.successors(&state) {
+            stats.generated += 1;
+            let key = space.key(&next);
+            if path.iter().any(|(_, seen)| *seen == key) {
+                continue;
+            }
+            path.push((next, key));
+            match bounded(space, path, cost + step, bound, stats) {
+                Ok(total) => return Ok(total),
+                Err(Some(exceeded)) => next_bound = Some(next_bound.map_or(exceeded, |b: Cost| b.min(exceeded))),
+                Err(None) if space.cancelled() => return Err(None),
+                Err(None) => (),
+            }
+            path.pop();
+        }
+        Err(next_bound)
+    }
+
+    let mut stats = Stats::default();
+    let mut bound = space.heuristic(&start);
+    let key = space.key(&start);
+    let mut path = vec![(start, key)];
+    let found = loop {
+        match bounded(space, &mut path, 0, bound, &mut stats) {
+            Ok(cost) => break Some(cost),
+            Err(Some(next_bound)) => bound = next_bound,
+            Err(None) => break None,
+        }
+    };
+    stats.visited = stats.max_frontier;
+    let cancelled = found.is_none() && space.cancelled();
+    let path = found.map(|cost| Path { states: path.into_iter().map(|(state, _)| state).collect(), cost });
+    Outcome { path, stats, cancelled }
+}
+
+/// Visit every state reachable from `start` breadth-first, up to `max_depth` moves away when
+/// given. Goals are not special and don't stop the exploration.
+pub fn explore<P: Space>(space: &P, start: P::State, max_depth: Option<usize>) -> Exploration<P::State> {
+    let mut stats = Stats::default();
+    let mut visited = HashSet::new();
+    visited.insert(space.key(&start));
+    let mut states = vec![(start, 0)];
+    let mut next = 0;
+    let (mut level, mut cancelled) = (None, false);
+    while next < states.len() {
+        if space.cancelled() {
+            cancelled = true;
+            break;
+        }
+        stats.max_frontier = stats.max_frontier.max(states.len() - next);
+        let depth = states[next].1;
+        if next == 0 || states[next - 1].1 != depth {
+            // NOTE: the previous level span has to be exited before entering the next one.
+            level.take();
+            level = Some(info_span!("bfs_level", depth, states = states.len() - next).entered());
+        }
+        if max_depth.is_none_or(|max| depth < max) {
+            stats.expanded += 1;
+            for (successor, _) in space.successors(&states[next].0) {
+                stats.generated += 1;
+                if visited.insert(space.key(&successor)) {
+                    states.push((successor, depth + 1));
+                }
+            }
+        }
+        next += 1;
+    }
+    stats.visited = visited.len();
+    Exploration { states, stats, cancelled }
+}
+
+
+// the Day 13 puzzle example: a maze of cubicles where (x, y) is open when the number of bits
+// set in x*x + 3*x + 2*x*y + y + y*y + 10 is even.
+#[cfg(test)]
+struct Cubicles {
+    goal: (u64, u64),
+}
+
+#[cfg(test)]
+impl Space for Cubicles {
+    type State = (u64, u64);
+    type Key = (u64, u64);
+
+    fn successors(&self, &(x, y): &(u64, u64)) -> Vec<((u64, u64), Cost)> {
+        let open = |x: u64, y: u64| (x * x + 3 * x + 2 * x * y + y + y * y + 10).count_ones().is_multiple_of(2);
+        let mut moves = vec![(x + 1, y), (x, y + 1)];
+        if x > 0 {
+            moves.push((x - 1, y));
+        }
+        if y > 0 {
+            moves.push((x, y - 1));
+        }
+        moves.into_iter().filter(|&(x, y)| open(x, y)).map(|state| (state, 1)).collect()
+    }
+
+    fn is_goal(&self, state: &(u64, u64)) -> bool {
+        *state == self.goal
+    }
+
+    fn key(&self, state: &(u64, u64)) -> (u64, u64) {
+        *state
+    }
+
+    fn heuristic(&self, &(x, y): &(u64, u64)) -> Cost {
+        x.abs_diff(self.goal.0) + y.abs_diff(self.goal.1)
+    }
+}
+
+#[test]
+fn cubicles() {
+    let space = Cubicles { goal: (7, 4) };
+    let breadth = bfs(&space, (1, 1));
+    let guided = astar(&space, (1, 1));
+    let iterative = idastar(&space, (1, 1));
+    for outcome in [&breadth, &guided, &iterative].iter() {
+        let path = outcome.path.as_ref().unwrap();
+        assert_eq!((path.moves(), path.cost), (11, 11));
+        assert_eq!((path.states[0], *path.goal()), ((1, 1), (7, 4)));
+    }
+    // the heuristic pays off.
+    assert!(guided.stats.expanded < breadth.stats.expanded);
+    // any path will do depth-first, but it has to be a valid one.
+    let depth = dfs(&space, (1, 1)).path.unwrap();
+    assert!(depth.moves() >= 11);
+    for pair in depth.states.windows(2) {
+        assert!(space.successors(&pair[0]).iter().any(|&(state, _)| state == pair[1]));
+    }
+    // unreachable (it's a wall), this terminates because (1, 1) is in a closed area.
+    let wall = Cubicles { goal: (1, 0) };
+    assert_eq!(bfs(&wall, (1, 1)).path, None);
+    assert_eq!(astar(&wall, (1, 1)).path, None);
+}
+
+#[test]
+fn explore_depth() {
+    let space = Cubicles { goal: (7, 4) };
+    let exploration = explore(&space, (1, 1), Some(2));
+    let mut states: Vec<_> = exploration.states.clone();
+    states.sort();
+    assert_eq!(states, vec![((0, 0), 2), ((0, 1), 1), ((1, 1), 0), ((1, 2), 1), ((2, 2), 2)]);
+    assert_eq!(exploration.stats.visited, 5);
+    assert_eq!(exploration.stats.expanded, 3);
+    // without a depth limit, the whole closed area around the start is explored.
+    assert_eq!(explore(&space, (1, 1), None).states.len(), 173);
+}
+
+// the Day 13 maze, cancelled once `budget` states have been expanded.
+#[cfg(test)]
+struct Budget {
+    maze: Cubicles,
+    budget: ::std::cell::Cell<usize>,
+}
+
+#[cfg(test)]
+impl Space for Budget {
+    type State = (u64, u64);
+    type Key = (u64, u64);
+
+    fn successors(&self, state: &(u64, u64)) -> Vec<((u64, u64), Cost)> {
+        self.budget.set(self.budget.get().saturating_sub(1));
+        self.maze.successors(state)
+    }
+
+    fn is_goal(&self, state: &(u64, u64)) -> bool {
+        self.maze.is_goal(state)
+    }
+
+    fn key(&self, state: &(u64, u64)) -> (u64, u64) {
+        *state
+    }
+
+    fn cancelled(&self) -> bool {
+        self.budget.get() == 0
+    }
+}
+
+#[test]
+fn cancellation() {
+    let space = |budget| Budget { maze: Cubicles { goal: (7, 4) }, budget: ::std::cell::Cell::new(budget) };
+    type Search = fn(&Budget, (u64, u64)) -> Outcome<(u64, u64)>;
+    for search in [bfs as Search, dfs, astar, idastar].iter() {
+        let outcome = search(&space(5), (1, 1));
+        assert!(outcome.cancelled && outcome.path.is_none());
+        assert_eq!(outcome.stats.expanded, 5);
+        let outcome = search(&space(usize::MAX), (1, 1));
+        assert!(!outcome.cancelled && outcome.path.is_some());
+    }
+    let exploration = explore(&space(3), (1, 1), None);
+    assert!(exploration.cancelled);
+    assert_eq!(exploration.stats.expanded, 3);
+    assert!(!explore(&space(usize::MAX), (1, 1), None).cancelled);
+    // exhausting the space is not a cancellation.
+    let wall = Cubicles { goal: (1, 0) };
+    assert!(!bfs(&wall, (1, 1)).cancelled && !astar(&wall, (1, 1)).cancelled);
+}
+
+// a weighted graph where the path with the fewest moves is not the cheapest one.
+#[cfg(test)]
+struct Graph(Vec<Vec<(usize, Cost)>>);
+
+#[cfg(test)]
+impl Space for Graph {
+    type State = usize;
+    type Key = usize;
+
+    fn successors(&self, state: &usize) -> Vec<(usize, Cost)> {
+        self.0[*state].clone()
+    }
+
+    fn is_goal(&self, state: &usize) -> bool {
+        *state == self.0.len() - 1
+    }
+
+    fn key(&self, state: &usize) -> usize {
+        *state
+    }
+}
+
+#[test]
+fn weighted() {
+    let graph = Graph(vec![
+        vec![(1, 10), (2, 1)],
+        vec![(4, 10)],
+        vec![(3, 1), (0, 1)],
+        vec![(1, 1), (4, 20)],
+        vec![],
+    ]);
+    let fewest_moves = bfs(&graph, 0).path.unwrap();
+    assert_eq!((fewest_moves.states, fewest_moves.cost), (vec![0, 1, 4], 20));
+    for path in [astar(&graph, 0).path.unwrap(), idastar(&graph, 0).path.unwrap()].iter() {
+        assert_eq!((&path.states, path.cost), (&vec![0, 2, 3, 1, 4], 13));
+    }
+}
+
+// moving one of two identical tokens along a line until they are both at `end`, where
+// (a, b) and (b, a) are the same state.
+#[cfg(test)]
+struct Tokens {
+    end: u8,
+    canonical: bool,
+}
+
+#[cfg(test)]
+impl Space for Tokens {
+    type State = (u8, u8);
+    type Key = (u8, u8);
+
+    fn successors(&self, &(a, b): &(u8, u8)) -> Vec<((u8, u8), Cost)> {
+        let mut moves = Vec::new();
+        if a < self.end {
+            moves.push(((a + 1, b), 1));
+        }
+        if b < self.end {
+            moves.push(((a, b + 1), 1));
+        }
+        moves
+    }
+
+    fn is_goal(&self, &(a, b): &(u8, u8)) -> bool {
+        a == self.end && b == self.end
+    }
+
+    fn key(&self, &(a, b): &(u8, u8)) -> (u8, u8) {
+        if self.canonical { (a.min(b), a.max(b)) } else { (a, b) }
+    }
+}
+
+#[test]
+fn canonicalization() {
+    let plain = bfs(&Tokens { end: 10, canonical: false }, (0, 0));
+    let canonical = bfs(&Tokens { end: 10, canonical: true }, (0, 0));
+    assert_eq!(plain.path.unwrap().moves(), 20);
+    assert_eq!(canonical.path.unwrap().moves(), 20);
+    assert_eq!(plain.stats.visited, 11 * 11);
+    assert_eq!(canonical.stats.visited, 11 * 12 / 2);
+    assert!(canonical.stats.expanded < plain.stats.expanded);
+    assert_eq!(canonical.stats.to_string(),
+               format!("{} expanded, {} generated, 66 visited, max frontier {}",
+                       canonical.stats.expanded, canonical.stats.generated, canonical.stats.max_frontier));
+}
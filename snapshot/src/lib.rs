@@ -0,0 +1,85 @@
+//! Snapshot testing of the user-facing text outputs (screen renderings, rooms, trees, JSON…).
+//!
+//! `assert_snapshot!(name, actual)` compares `actual` with the "tests/snapshots/NAME.snap" file
+//! of the crate under test. With `UPDATE_SNAPSHOTS=1` in the environment the files are written
+//! instead, so that a deliberate formatting change is accepted by running the tests again and
+//! reviewing the snapshots diff.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// The environment variable making `check()` write the snapshots instead of comparing them.
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Returns `true` if the snapshots should be written, `false` otherwise.
+fn updating() -> bool {
+    env::var_os(UPDATE_VAR).is_some_and(|value| value != "0")
+}
+
+/// Compare `actual` with the snapshot `name` in `dir`, returns a description of the difference
+/// if any. The snapshot is written instead when updating.
+pub fn check(dir: &Path, name: &str, actual: &str) -> Result<(), String> {
+    let path = dir.join(format!("{}.snap", name));
+    if updating() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        return fs::write(&path, actual).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+    let expected = fs::read_to_string(&path)
+        .map_err(|e| format!("{}: {} (run with {}=1 to create it)", path.display(), e, UPDATE_VAR))?;
+    if expected == actual {
+        return Ok(());
+    }
+    Err(format!("snapshot {} changed (run with {}=1 to accept it):\n{}", path.display(), UPDATE_VAR,
+                diff(&expected, actual)))
+}
+
+/// Returns a line by line comparison of `expected` and `actual`, the unchanged lines being
+/// prefixed by two spaces, the expected ones by `-` and the actual ones by `+`.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let (expected, actual): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+    let mut out = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => out += &format!("  {}\n", e),
+            (e, a) => {
+                if let Some(e) = e {
+                    out += &format!("- {}\n", e);
+                }
+                if let Some(a) = a {
+                    out += &format!("+ {}\n", a);
+                }
+            },
+        }
+    }
+    out
+}
+
+/// Assert that the given text matches the named snapshot of the crate under test, see
+/// `check()`.
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($name:expr, $actual:expr) => {{
+        let dir = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots");
+        if let Err(e) = $crate::check(&dir, $name, &$actual) {
+            panic!("{}", e);
+        }
+    }};
+}
+
+
+#[test]
+fn line_diff() {
+    assert_eq!(diff("a\nb\nc\n", "a\nb\nc\n"), "  a\n  b\n  c\n");
+    assert_eq!(diff("a\nb\n", "a\nB\nc\n"), "  a\n- b\n+ B\n+ c\n");
+}
+
+#[test]
+fn check_snapshots() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots");
+    assert!(check(&dir, "greeting", "hello\nworld\n").is_ok());
+    let error = check(&dir, "greeting", "hello\nthere\n").unwrap_err();
+    assert!(error.ends_with("  hello\n- world\n+ there\n"), "{}", error);
+    assert!(check(&dir, "missing", "").is_err());
+    assert_snapshot!("greeting", "hello\nworld\n");
+}
@@ -0,0 +1,98 @@
+//! The thread pool shared by the days parallel features.
+//!
+//! Rather than each solver building its own, every parallel solver runs on the rayon global
+//! pool, sized once by the binary from the command line or the environment. In order:
+//!
+//! 1. `--threads N` on the command line,
+//! 2. the solver own variable, e.g. `AOC_THREADS_DAY14=N`, to opt a single solver out,
+//! 3. `AOC_THREADS=N` for every solver,
+//! 4. one thread per core otherwise.
+//!
+//! `N` is the thread count, 1 meaning that the solver runs on a single thread.
+
+extern crate rayon;
+extern crate report;
+
+use std::env;
+
+/// The command line option setting the thread count.
+pub const OPTION: &str = "--threads";
+
+/// The environment variable setting the thread count of every solver.
+pub const VAR: &str = "AOC_THREADS";
+
+/// Returns the environment variable setting the thread count of `solver` only, e.g.
+/// `AOC_THREADS_DAY14` for "day14".
+pub fn solver_var(solver: &str) -> String {
+    format!("{}_{}", VAR, solver.to_uppercase())
+}
+
+/// Returns the thread count given after `--threads` in `args`, if any.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<usize>, String> {
+    let mut args = args.into_iter().skip_while(|arg| arg != OPTION);
+    if args.next().is_none() {
+        return Ok(None);
+    }
+    match args.next() {
+        Some(n) => parse_count(OPTION, &n).map(Some),
+        None => Err(format!("usage: {} N", OPTION)),
+    }
+}
+
+/// Parse a thread count, `what` being where it comes from.
+fn parse_count(what: &str, s: &str) -> Result<usize, String> {
+    s.trim().parse().ok().filter(|&n| n > 0).ok_or_else(|| format!("{}: bad thread count: {}", what, s))
+}
+
+/// Returns the thread count to use from the `--threads` option, the solver variable and the
+/// shared variable values in that order of precedence, `None` meaning one thread per core.
+pub fn resolve(option: Option<usize>, solver: Option<(&str, &str)>, shared: Option<&str>)
+    -> Result<Option<usize>, String>
+{
+    if option.is_some() {
+        return Ok(option);
+    }
+    if let Some((var, value)) = solver {
+        return parse_count(var, value).map(Some);
+    }
+    shared.map(|value| parse_count(VAR, value)).transpose()
+}
+
+/// Size the shared pool of `solver` (e.g. "day14") from the command line and the environment.
+/// Exit on error.
+pub fn configure(solver: &str) {
+    let option = parse_args(env::args().skip(1)).unwrap_or_else(|e| report::exit(e));
+    let var = solver_var(solver);
+    let (solver_value, shared) = (env::var(&var).ok(), env::var(VAR).ok());
+    let solver_value = solver_value.as_deref().map(|value| (var.as_str(), value));
+    let threads = resolve(option, solver_value, shared.as_deref()).unwrap_or_else(|e| report::exit(e));
+    if let Some(threads) = threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .unwrap_or_else(|e| report::exit(e));
+    }
+}
+
+
+#[test]
+fn command_line() {
+    let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+    assert_eq!(parse_args(args("input.txt")), Ok(None));
+    assert_eq!(parse_args(args("--threads 4 input.txt")), Ok(Some(4)));
+    assert!(parse_args(args("--threads")).is_err());
+    assert!(parse_args(args("--threads 0")).is_err());
+    assert!(parse_args(args("--threads many")).is_err());
+}
+
+#[test]
+fn precedence() {
+    let solver = Some(("AOC_THREADS_DAY14", "1"));
+    assert_eq!(resolve(Some(8), solver, Some("4")), Ok(Some(8)));
+    assert_eq!(resolve(None, solver, Some("4")), Ok(Some(1)));
+    assert_eq!(resolve(None, None, Some("4")), Ok(Some(4)));
+    assert_eq!(resolve(None, None, None), Ok(None));
+    assert_eq!(resolve(None, Some(("AOC_THREADS_DAY14", "x")), None),
+               Err("AOC_THREADS_DAY14: bad thread count: x".to_string()));
+    assert_eq!(solver_var("day14"), "AOC_THREADS_DAY14");
+}